@@ -0,0 +1,186 @@
+use bench::utils::{
+    generate_key_entries, proof, proof_with_storage, publish_with_storage, KEY_ENTRY_BATCH_SIZES,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use futures::executor::block_on;
+use messages::{
+    publish::{CertificateKind, PublishCertificate, PublishNotification, PublishVote},
+    Root,
+};
+use storage::vkd_storage::AkdStorage;
+use test_utils::{certificate, committee, keys, notification, votes};
+use vkd::storage::memory::AsyncInMemoryDatabase;
+use vkd::{AkdLabel, AkdValue};
+
+const AKD_STORAGE_PATH: &str = ".criterion_micro_benchmark_vkd_storage";
+
+/// The number of key-values pairs in the state tree used by the single-tree
+/// benchmarks (creation/verification of notifications, votes, certificates).
+const DEFAULT_NUM_TREE_ENTRIES: u64 = 1_000;
+
+/// Benchmark the creation of a publish notification.
+///
+/// Setup (tree construction + audit proof) runs once per iteration, outside the
+/// timed region, via `iter_batched`, so only `PublishNotification::new` itself is timed.
+fn create_notification(c: &mut Criterion) {
+    let (_, keypair) = keys().pop().unwrap();
+
+    c.bench_function("create_notification", |b| {
+        b.iter_batched(
+            || {
+                let _ = std::fs::remove_dir_all(AKD_STORAGE_PATH);
+                let db = AkdStorage::new(AKD_STORAGE_PATH);
+                block_on(proof_with_storage(DEFAULT_NUM_TREE_ENTRIES, db))
+            },
+            |(_, root, proof)| PublishNotification::new(root, proof, 1, 0, &keypair),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let _ = std::fs::remove_dir_all(AKD_STORAGE_PATH);
+}
+
+/// Benchmark the verification of a publish notification.
+fn verify_notification(c: &mut Criterion) {
+    let (_, keypair) = keys().pop().unwrap();
+    let committee = committee(0);
+
+    c.bench_function("verify_notification", |b| {
+        b.iter_batched(
+            || {
+                let (_, root, proof) = block_on(proof(DEFAULT_NUM_TREE_ENTRIES));
+                PublishNotification::new(root, proof, 1, 0, &keypair)
+            },
+            |notification| block_on(notification.verify(&committee, &Root::default())),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmark the creation of a publish vote.
+fn create_vote(c: &mut Criterion) {
+    let (_, keypair) = keys().pop().unwrap();
+
+    c.bench_function("create_vote", |b| {
+        b.iter_batched(
+            || block_on(notification()),
+            |notification| PublishVote::new(&notification, &keypair),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmark the verification of a publish vote.
+fn verify_vote(c: &mut Criterion) {
+    let committee = committee(0);
+
+    c.bench_function("verify_vote", |b| {
+        b.iter_batched(
+            || block_on(votes()).pop().unwrap(),
+            |vote| vote.verify(&committee),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmark the aggregation of a quorum of votes into a certificate.
+fn aggregate_certificate(c: &mut Criterion) {
+    let threshold = committee(0).quorum_threshold() as usize;
+
+    c.bench_function("aggregate_certificate", |b| {
+        b.iter_batched(
+            || {
+                let mut votes = block_on(votes());
+                votes.truncate(threshold);
+                (block_on(notification()), votes)
+            },
+            |(notification, votes)| PublishCertificate {
+                root: notification.root,
+                sequence_number: notification.sequence_number,
+                round: notification.round,
+                kind: CertificateKind::Votes(
+                    votes
+                        .iter()
+                        .map(|x| (x.author, x.signature.clone()))
+                        .collect(),
+                ),
+                timestamps: Vec::new(),
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmark the verification of a certificate.
+fn verify_certificate(c: &mut Criterion) {
+    let threshold = committee(0).quorum_threshold() as usize;
+    let committee = committee(0);
+
+    c.bench_function("verify_certificate", |b| {
+        b.iter_batched(
+            || {
+                let mut certificate = block_on(certificate());
+                if let CertificateKind::Votes(votes) = &mut certificate.kind {
+                    votes.truncate(threshold);
+                }
+                certificate
+            },
+            |certificate| certificate.verify(&committee),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmark the publish operation for every [`KEY_ENTRY_BATCH_SIZES`], over
+/// both the in-memory and the persistent (`AkdStorage`) backends. Key entry
+/// generation is excluded from the timed region, since it's an artifact of
+/// the benchmark setup rather than something `publish` itself does.
+fn publish(c: &mut Criterion) {
+    let mut group = c.benchmark_group("publish");
+
+    for batch_size in KEY_ENTRY_BATCH_SIZES {
+        let key_entries = generate_key_entries(*batch_size);
+
+        group.bench_with_input(
+            format!("in_memory_batch_size_{}", batch_size),
+            &key_entries,
+            |b, key_entries| {
+                b.iter_batched(
+                    || (key_entries.clone(), AsyncInMemoryDatabase::new()),
+                    |(key_entries, db)| block_on(publish_with_storage(key_entries, db)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            format!("persistent_batch_size_{}", batch_size),
+            &key_entries,
+            |b, key_entries| {
+                b.iter_batched(
+                    || {
+                        let _ = std::fs::remove_dir_all(AKD_STORAGE_PATH);
+                        (key_entries.clone(), AkdStorage::new(AKD_STORAGE_PATH))
+                    },
+                    |(key_entries, db)| block_on(publish_with_storage(key_entries, db)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+    let _ = std::fs::remove_dir_all(AKD_STORAGE_PATH);
+}
+
+criterion_group!(
+    benches,
+    create_notification,
+    verify_notification,
+    create_vote,
+    verify_vote,
+    aggregate_certificate,
+    verify_certificate,
+    publish,
+);
+criterion_main!(benches);
@@ -4,8 +4,16 @@ use bytes::{BufMut, Bytes, BytesMut};
 use clap::{arg, crate_name, crate_version, Arg, Command};
 use config::{Committee, Import};
 use futures::stream::{futures_unordered::FuturesUnordered, StreamExt};
-use log::{info, warn};
+use human_repr::HumanDuration;
+use log::{debug, info, warn};
+use messages::{
+    sync::PublishCertificateQuery, ClientToIdPMessage, IdPToWitnessMessage, SequenceNumber,
+    WitnessToIdPMessage,
+};
 use network::reliable_sender::ReliableSender;
+use rand::thread_rng;
+use rand_distr::{Distribution, Exp};
+use std::collections::VecDeque;
 use tokio::{
     net::TcpStream,
     time::{interval, sleep, Duration, Instant},
@@ -14,6 +22,9 @@ use tokio::{
 /// The default size of an update request (key + value).
 const DEFAULT_UPDATE_SIZE: usize = 64;
 
+/// How often the closed-loop benchmark polls a witness for the next certificate, in ms.
+const POLL_INTERVAL: u64 = 50;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Read the cli parameters.
@@ -26,6 +37,8 @@ async fn main() -> Result<()> {
             arg!(--rate <INT> "The rate (txs/s) at which to send the transactions"),
             arg!(--size [INT] "The size (B) of an update key + value"),
             arg!(--faults [INT] "The number of crash-faults"),
+            arg!(--closed_loop "Measure closed-loop end-to-end commit latency instead of open-loop throughput"),
+            arg!(--batch_size [INT] "The IdP's configured batch size, used to attribute closed-loop commits to sample transactions"),
         ])
         .arg_required_else_help(true)
         .get_matches();
@@ -68,18 +81,33 @@ async fn main() -> Result<()> {
         anyhow!("The number of faults should be less than the committee size")
     );
 
+    let closed_loop = matches.is_present("closed_loop");
+
+    let batch_size = matches
+        .value_of("batch_size")
+        .unwrap_or("1")
+        .parse::<usize>()
+        .context("The batch size must be a non-negative integer")?;
+
     // Make a benchmark client.
-    let client = BenchmarkClient::new(committee, rate, size, faults);
+    let client = BenchmarkClient::new(committee, rate, size, faults, closed_loop, batch_size);
     client.print_parameters();
 
     // Wait for all nodes to be online and synchronized.
     client.wait().await;
 
     // Start the benchmark.
-    client
-        .benchmark()
-        .await
-        .context("Failed to submit transactions")
+    if closed_loop {
+        client
+            .benchmark_closed_loop()
+            .await
+            .context("Failed to submit transactions")
+    } else {
+        client
+            .benchmark()
+            .await
+            .context("Failed to submit transactions")
+    }
 }
 
 /// A client only useful for benchmarks.
@@ -92,16 +120,32 @@ pub struct BenchmarkClient {
     size: usize,
     /// The number of crash-faults.
     faults: usize,
+    /// When set, measure closed-loop end-to-end commit latency (Poisson arrivals, polled
+    /// commit certificates) instead of firing open-loop bursts and sinking acknowledgements.
+    closed_loop: bool,
+    /// The IdP's configured batch size. Only meaningful in closed-loop mode, where it tells
+    /// the client how many of the oldest in-flight sample transactions a newly-sealed
+    /// certificate commits.
+    batch_size: usize,
 }
 
 impl BenchmarkClient {
     /// Creates a new benchmark client.
-    pub fn new(committee: Committee, rate: u64, size: usize, faults: usize) -> Self {
+    pub fn new(
+        committee: Committee,
+        rate: u64,
+        size: usize,
+        faults: usize,
+        closed_loop: bool,
+        batch_size: usize,
+    ) -> Self {
         Self {
             committee,
             rate,
             size,
             faults,
+            closed_loop,
+            batch_size,
         }
     }
 
@@ -110,6 +154,9 @@ impl BenchmarkClient {
         // NOTE: These log entries are used to compute performance.
         info!("Transactions rate: {} tx/s", self.rate);
         info!("Target idp address: {}", self.committee.idp.address);
+        if self.closed_loop {
+            info!("Closed-loop mode: batch size {}", self.batch_size);
+        }
     }
 
     /// Wait for all authorities to be online.
@@ -169,7 +216,7 @@ impl BenchmarkClient {
                         key.resize(self.size, 0u8);
                         let label = AkdLabel(key.split().freeze().to_vec());
 
-                        let update = (label, value.clone());
+                        let update = ClientToIdPMessage::Update((label, value.clone()));
                         let bytes = Bytes::from(bincode::serialize(&update).unwrap());
 
                         let handle = network.send(address, bytes).await;
@@ -193,4 +240,109 @@ impl BenchmarkClient {
         }
         Ok(())
     }
+
+    /// Run a closed-loop benchmark: schedule sends with Poisson (memoryless) inter-arrival
+    /// times instead of fixed bursts, and measure end-to-end commit latency by polling a
+    /// witness for the certificate sequence number expected to cover each sample transaction.
+    pub async fn benchmark_closed_loop(&self) -> Result<()> {
+        let witness = self
+            .committee
+            .witnesses_addresses()
+            .into_iter()
+            .next()
+            .map(|(_, address)| address)
+            .ok_or_else(|| anyhow!("Committee has no witnesses to poll for commit certificates"))?;
+
+        let mut network = ReliableSender::new();
+        let address = self.committee.idp.address;
+        let mut key = BytesMut::with_capacity(self.size);
+        let value = AkdValue(vec![0; self.size]);
+        let mut pending_acks = FuturesUnordered::new();
+
+        // Inter-arrival times drawn from an exponential distribution approximate a Poisson
+        // arrival process at the target rate.
+        let arrivals = Exp::new(self.rate as f64).context("The rate must be positive")?;
+        let mut rng = thread_rng();
+
+        let mut counter = 0u64;
+        // Send time of every sample transaction still awaiting commit, oldest first; a newly
+        // sealed certificate is assumed (this being the benchmark's only writer) to commit
+        // exactly `self.batch_size` of the oldest outstanding transactions.
+        let mut in_flight: VecDeque<(u64, Instant)> = VecDeque::new();
+        let mut latencies = Vec::new();
+        let mut next_sequence_number: SequenceNumber = 1;
+
+        let poll = interval(Duration::from_millis(POLL_INTERVAL));
+        tokio::pin!(poll);
+
+        // NOTE: This log entry is used to compute performance.
+        info!("Start sending transactions (closed loop)");
+        loop {
+            let gap = Duration::from_secs_f64(arrivals.sample(&mut rng));
+            tokio::select! {
+                _ = sleep(gap) => {
+                    counter += 1;
+                    let id = counter;
+                    key.put_u64(id);
+                    key.resize(self.size, 0u8);
+                    let label = AkdLabel(key.split().freeze().to_vec());
+
+                    let update = ClientToIdPMessage::Update((label, value.clone()));
+                    let bytes = Bytes::from(bincode::serialize(&update).unwrap());
+
+                    in_flight.push_back((id, Instant::now()));
+                    pending_acks.push(network.send(address, bytes).await);
+
+                    // NOTE: This log entry is used to compute performance.
+                    info!("Sending sample transaction {}", id);
+                }
+                Some(_) = pending_acks.next() => {
+                    // Sink acknowledgements; commit latency is measured via certificate polling.
+                },
+                _ = poll.tick() => {
+                    if in_flight.is_empty() {
+                        continue;
+                    }
+
+                    let query = PublishCertificateQuery { sequence_number: next_sequence_number };
+                    let message = IdPToWitnessMessage::PublishCertificateQuery(query);
+                    let bytes = Bytes::from(bincode::serialize(&message).unwrap());
+                    let reply = network.send(witness, bytes).await.await?;
+
+                    if let WitnessToIdPMessage::PublishCertificateResponse(_) = bincode::deserialize(&reply)? {
+                        let now = Instant::now();
+                        next_sequence_number += 1;
+                        for _ in 0..self.batch_size.min(in_flight.len()) {
+                            let (id, sent_at) = in_flight.pop_front().unwrap();
+                            let latency = now.duration_since(sent_at);
+                            debug!("Sample transaction {} committed in {}", id, latency.human_duration());
+                            latencies.push(latency);
+                        }
+                        Self::log_percentiles(&mut latencies);
+                    }
+                    // Otherwise the certificate isn't sealed yet; retry the same sequence
+                    // number on the next tick.
+                },
+                else => break
+            }
+        }
+        Ok(())
+    }
+
+    /// Log the p50/p90/p99 latency over every commit observed so far.
+    fn log_percentiles(latencies: &mut [Duration]) {
+        latencies.sort_unstable();
+        let percentile = |p: f64| -> Duration {
+            let index = ((latencies.len() - 1) as f64 * p).round() as usize;
+            latencies[index]
+        };
+        // NOTE: This log entry is used to compute performance.
+        info!(
+            "Latency (n={}): p50 {}, p90 {}, p99 {}",
+            latencies.len(),
+            percentile(0.50).human_duration(),
+            percentile(0.90).human_duration(),
+            percentile(0.99).human_duration(),
+        );
+    }
 }
@@ -0,0 +1,7 @@
+//! Shared helpers behind the crate's benchmark targets: proof/notification/vote
+//! generation, key-entry fixtures, and multi-epoch publish soak testing. Split
+//! out into a library so both `src/micro_benchmark.rs` (the multi-epoch soak
+//! test binary) and `benches/micro_benchmark.rs` (the Criterion suite) can
+//! share the same setup code instead of duplicating it.
+
+pub mod utils;
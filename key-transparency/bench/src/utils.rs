@@ -1,6 +1,14 @@
 #![allow(dead_code)]
 
-use crate::AkdStorage;
+use bytes::{BufMut, Bytes, BytesMut};
+use config::Committee;
+use crypto::KeyPair;
+use messages::{
+    publish::{CertificateKind, Proof, PublishCertificate, PublishNotification, PublishVote},
+    Blake3, IdPToWitnessMessage, Root,
+};
+use std::time::Instant;
+use storage::vkd_storage::AkdStorage;
 use vkd::{
     directory::Directory,
     ecvrf::HardCodedAkdVRF,
@@ -10,20 +18,16 @@ use vkd::{
         Storage,
     },
 };
-use bytes::{BufMut, Bytes, BytesMut};
-use config::Committee;
-use crypto::KeyPair;
-use messages::{
-    publish::{Proof, PublishCertificate, PublishNotification, PublishVote},
-    Blake3, IdPToWitnessMessage, Root,
-};
-use std::time::Instant;
 
 const MULTI_EPOCH_PUBLISH_STORAGE_DIR: &str = ".multi_epoch_publish_vkd_storage";
 
 // The size of the AkdLabel and AkdValue
 const LABEL_VALUE_SIZE_BYTES: usize = 32;
 
+/// Key-entry batch sizes the micro-benchmarks in `benches/micro_benchmark.rs`
+/// sweep over for the `publish` operation.
+pub const KEY_ENTRY_BATCH_SIZES: &[u64] = &[2_u64.pow(5), 2_u64.pow(7), 2_u64.pow(10), 2_u64.pow(15)];
+
 /// Create a publish proof from a tree with the specified number of key-value pairs and an in-memory storage.
 pub async fn proof(entries: u64) -> (Root, Root, Proof) {
     let db = AsyncInMemoryDatabase::new();
@@ -40,7 +44,7 @@ where
 
     // Create a test tree with the specified number of key-values.
     let vrf = HardCodedAkdVRF {};
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
 
     // Compute the start root (at sequence 0) and end root (at sequence 1).
     let current_azks = vkd.retrieve_current_azks().await.unwrap();
@@ -73,7 +77,7 @@ pub async fn publish_with_storage<AkdStorage>(
     AkdStorage: vkd::storage::Storage + Sync + Send,
 {
     let vrf = HardCodedAkdVRF {};
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
 
     vkd.publish::<Blake3>(key_entries).await.unwrap();
 }
@@ -86,7 +90,7 @@ where
 {
     // Setup
     let vrf = HardCodedAkdVRF {};
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
 
     // Generate keys and publish.
     // It is okay to include key generation here since this function
@@ -103,7 +107,7 @@ pub async fn publish_multi_epoch(batch_size: u64, num_epoch: u64) {
     // AKD Setup
     let vrf = HardCodedAkdVRF {};
     let db = AkdStorage::new(MULTI_EPOCH_PUBLISH_STORAGE_DIR);
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
 
     // Generate necessary keys
     let key_entries = generate_key_entries(batch_size * num_epoch);
@@ -201,18 +205,29 @@ impl<'a> NotificationGenerator<'a> {
 
     /// Make a dummy (but valid) publish notification.
     pub fn make_notification(&self, sequence_number: u64) -> Bytes {
-        let notification =
-            PublishNotification::new(self.root, self.proof.clone(), sequence_number, self.keypair);
+        let notification = PublishNotification::new(
+            self.root,
+            self.proof.clone(),
+            sequence_number,
+            /* round */ 0,
+            self.keypair,
+        );
         let message = IdPToWitnessMessage::PublishNotification(notification);
         let serialized = bincode::serialize(&message).unwrap();
         Bytes::from(serialized)
     }
 }
 
-/// Make dumb (but valid) publish certificates.
+/// Assembles a quorum of votes into a certificate. Mirrors the checks performed by the
+/// production `idp::aggregator::Aggregator` (stake-weighted quorum, per-author dedup and
+/// signature verification) so that benchmark throughput reflects the real commit path.
 pub struct CertificateGenerator {
     /// The committee information.
     pub committee: Committee,
+    /// The current voting power accumulated for the votes gathered so far.
+    weight: config::VotingPower,
+    /// The set of witnesses that already voted.
+    used: std::collections::HashSet<crypto::PublicKey>,
     votes: Vec<PublishVote>,
 }
 
@@ -220,28 +235,49 @@ impl CertificateGenerator {
     pub fn new(committee: Committee) -> Self {
         Self {
             committee,
+            weight: 0,
+            used: std::collections::HashSet::new(),
             votes: Vec::new(),
         }
     }
 
     /// Reset the certificate generator.
     pub fn clear(&mut self) {
+        self.weight = 0;
+        self.used.clear();
         self.votes.clear();
     }
 
-    /// Try to assemble a certificate from votes.
+    /// Try to assemble a certificate from votes. Duplicate or unsigned votes are ignored
+    /// rather than stalling the benchmark.
     pub fn try_make_certificate(&mut self, vote: PublishVote) -> Option<Bytes> {
+        let voting_power = self.committee.voting_power(&vote.author);
+        if voting_power == 0
+            || !self.used.insert(vote.author)
+            || vote.verify(&self.committee).is_err()
+        {
+            return None;
+        }
+
+        self.weight += voting_power;
         self.votes.push(vote);
-        (self.votes.len() >= self.committee.quorum_threshold() as usize).then(|| {
+
+        (self.weight >= self.committee.quorum_threshold()).then(|| {
+            let timestamps = self.votes.iter().map(|v| (v.author, v.timestamp)).collect();
             let certificate = PublishCertificate {
                 root: self.votes[0].root,
                 sequence_number: self.votes[0].sequence_number,
-                votes: self
-                    .votes
-                    .drain(..)
-                    .map(|v| (v.author, v.signature))
-                    .collect(),
+                round: self.votes[0].round,
+                kind: CertificateKind::Votes(
+                    self.votes
+                        .drain(..)
+                        .map(|v| (v.author, v.signature))
+                        .collect(),
+                ),
+                timestamps,
             };
+            self.weight = 0;
+            self.used.clear();
             let message = IdPToWitnessMessage::PublishCertificate(certificate);
             let serialized = bincode::serialize(&message).unwrap();
             Bytes::from(serialized)
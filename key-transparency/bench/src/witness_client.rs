@@ -1,6 +1,5 @@
-mod utils;
-
 use anyhow::{anyhow, ensure, Context, Result};
+use bench::utils::{CertificateGenerator, NotificationGenerator};
 use clap::{arg, crate_name, crate_version, Arg, Command};
 use config::{Committee, Import, PrivateConfig};
 use crypto::KeyPair;
@@ -13,7 +12,6 @@ use tokio::{
     net::TcpStream,
     time::{interval, sleep, Duration, Instant},
 };
-use utils::{CertificateGenerator, NotificationGenerator};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -1,4 +1,4 @@
-use crypto::{KeyPair, PublicKey};
+use crypto::{kzg_da::Srs, BlsPublicKey, KeyPair, PublicKey, ThresholdKeySet, ThresholdKeyShare};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -68,6 +68,13 @@ pub struct Witness {
     pub voting_power: VotingPower,
     /// The network addresses of the witness.
     pub address: SocketAddr,
+    /// This witness's BLS public key, if the committee aggregates votes with
+    /// `messages::publish::CertificateKind::BlsAggregate` rather than (or in addition to)
+    /// per-witness `Votes` or `Threshold` shares. Only ever set on a key whose proof of
+    /// possession was checked with `crypto::BlsPublicKey::verify_possession` when this witness
+    /// was registered, since summing unvetted public keys is vulnerable to a rogue-key attack.
+    #[serde(default)]
+    pub bls_public_key: Option<BlsPublicKey>,
 }
 
 /// The (public) committee information.
@@ -75,6 +82,35 @@ pub struct Witness {
 pub struct Committee {
     pub idp: Idp,
     pub witnesses: BTreeMap<PublicKey, Witness>,
+    /// The committee's threshold signature setup, if witnesses certify with aggregate
+    /// signatures rather than (or in addition to) individual votes. `None` for committees
+    /// that only ever produce vote-based certificates.
+    #[serde(default)]
+    pub threshold_keys: Option<ThresholdKeySet>,
+    /// The committee's KZG structured reference string, if publish notifications carry a
+    /// data-availability commitment for their audit proof. `None` for committees that do not
+    /// run the data-availability layer.
+    #[serde(default)]
+    pub data_availability_srs: Option<Srs>,
+    /// The maximum size, in bytes, of a single serialized wire message (a publish notification
+    /// or certificate) the IdP will broadcast and a witness will accept, checked before the
+    /// message is even handed to `codec::decode`. Bounds how much a misconfigured or malicious
+    /// peer can make either side buffer.
+    #[serde(default = "default_max_payload_size")]
+    pub max_payload_size: usize,
+    /// Identifies which fork (in the sense of a hard fork restarting sequence numbers with a
+    /// new witness set) this committee configuration belongs to. A notification or vote
+    /// carrying a different `fork_id` is for a committee this node does not consider current,
+    /// and is rejected during the publish handshake rather than processed. `0` for a committee
+    /// that has never been forked.
+    #[serde(default)]
+    pub fork_id: u64,
+}
+
+/// Default for [`Committee::max_payload_size`]: generous enough for any notification or
+/// certificate this codebase produces today, small enough to bound a misbehaving peer.
+pub fn default_max_payload_size() -> usize {
+    16 * 1024 * 1024
 }
 
 impl Import for Committee {}
@@ -120,6 +156,130 @@ impl Committee {
             .map(|(name, witness)| (*name, witness.address))
             .collect()
     }
+
+    /// Returns `name`'s index in this committee's deterministic witness ordering (`witnesses`'s
+    /// `BTreeMap` order), or `None` if it is not a member. Lets a certificate encode its
+    /// contributing witnesses as a compact bitmap over this ordering instead of a full list of
+    /// public keys, since every holder of the same `Committee` derives the same indices.
+    pub fn witness_index(&self, name: &PublicKey) -> Option<usize> {
+        self.witnesses.keys().position(|key| key == name)
+    }
+
+    /// Returns the witness at `index` in this committee's deterministic ordering, the inverse
+    /// of `witness_index`, or `None` if out of range.
+    pub fn witness_at(&self, index: usize) -> Option<PublicKey> {
+        self.witnesses.keys().nth(index).copied()
+    }
+
+    /// Penalize `offender` for proven misbehavior (e.g. a verified equivocation, see
+    /// `messages::publish::ConflictingVote`) by zeroing its voting power, so every later
+    /// `PublishVote::verify`/`PublishCertificate::verify` against this committee rejects it as
+    /// an unknown witness instead of counting its signature toward quorum. Does not remove the
+    /// entry outright, so `witness_index`/`witness_at` (and so any bitmap already computed
+    /// against this committee) keep the same ordering. Returns `false` if `offender` is not a
+    /// member of this committee.
+    pub fn penalize(&mut self, offender: &PublicKey) -> bool {
+        match self.witnesses.get_mut(offender) {
+            Some(witness) => {
+                witness.voting_power = 0;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deterministically selects the leader for a view-change round, weighted by voting power:
+    /// each witness owns a contiguous slice of `[0, total_votes)` proportional to its
+    /// `voting_power` (in `witnesses`'s deterministic `BTreeMap` order), and `round` picks a
+    /// point in that range by wrapping modulo the total. Every witness computes this
+    /// independently, with no need to communicate, to agree on who assembles the next round's
+    /// certificate when the IdP stalls. `round` takes `messages::Round` (a bare `u64`); `config`
+    /// cannot depend on `messages` (which already depends on `config`), so it is spelled out here.
+    pub fn leader(&self, round: u64) -> PublicKey {
+        let total_votes: VotingPower = self.witnesses.values().map(|x| x.voting_power).sum();
+        let point = (round % total_votes as u64) as VotingPower;
+        let mut cumulative = 0;
+        for (name, witness) in &self.witnesses {
+            cumulative += witness.voting_power;
+            if point < cumulative {
+                return *name;
+            }
+        }
+        unreachable!("point is always strictly less than total_votes")
+    }
+}
+
+/// Tracks how the `Committee` has evolved over the sequence-number space, so that a certificate
+/// signed back when an older witness set (or voting-power distribution) was in force stays
+/// verifiable after the committee is reconfigured. `committee_at(sequence_number)` resolves via a
+/// range lookup -- the committee registered at the largest key not exceeding `sequence_number` --
+/// rather than assuming a single `Committee` is current forever. Takes `u64` rather than
+/// `messages::SequenceNumber`, for the same layering reason as `Committee::leader`: `config`
+/// cannot depend on `messages` (which already depends on `config`).
+#[derive(Clone)]
+pub struct CommitteeHistory {
+    by_sequence: BTreeMap<u64, Committee>,
+}
+
+impl CommitteeHistory {
+    /// Start a new history with `genesis` in force from sequence number 0 onward.
+    pub fn new(genesis: Committee) -> Self {
+        let mut by_sequence = BTreeMap::new();
+        by_sequence.insert(0, genesis);
+        Self { by_sequence }
+    }
+
+    /// Register `committee` as effective from `sequence_number` onward. Replaces whatever
+    /// committee was already registered at that exact sequence number, if any.
+    pub fn reconfigure(&mut self, sequence_number: u64, committee: Committee) {
+        self.by_sequence.insert(sequence_number, committee);
+    }
+
+    /// The committee in force at `sequence_number`: the one registered at the largest key not
+    /// exceeding it. Falls back to the earliest registered committee if `sequence_number`
+    /// precedes every reconfiguration on record (which should only happen for a malformed
+    /// history, since `new` always seeds an entry at sequence number 0).
+    pub fn committee_at(&self, sequence_number: u64) -> &Committee {
+        self.by_sequence
+            .range(..=sequence_number)
+            .next_back()
+            .or_else(|| self.by_sequence.iter().next())
+            .map(|(_, committee)| committee)
+            .expect("CommitteeHistory always has at least one registered committee")
+    }
+
+    /// The most recently registered committee, i.e. the one in force for the next sequence
+    /// number to be proposed.
+    pub fn latest(&self) -> &Committee {
+        self.by_sequence
+            .values()
+            .next_back()
+            .expect("CommitteeHistory always has at least one registered committee")
+    }
+
+    /// The sequence numbers at which the committee reconfigures, restricted to the open-closed
+    /// range `(from, to]`. This is every rotation a light client must cross while walking from a
+    /// certificate it trusts at `from` up to one for `to`, so it can verify each intermediate
+    /// certificate against the committee actually in force for it instead of jumping straight
+    /// to `to` and implicitly trusting every committee change along the way.
+    pub fn reconfigurations_between(&self, from: u64, to: u64) -> Vec<u64> {
+        self.by_sequence
+            .range((std::ops::Bound::Excluded(from), std::ops::Bound::Included(to)))
+            .map(|(sequence_number, _)| *sequence_number)
+            .collect()
+    }
+
+    /// Apply `Committee::penalize` for `offender` in every registered committee, current and
+    /// future alike, so a witness caught equivocating under one registered window can't keep
+    /// voting under any other window it is also a member of. Already-certified sequence
+    /// numbers are unaffected by this -- their certificates were produced and verified before
+    /// the penalty existed -- only `committee_at` lookups made from now on see the zeroed
+    /// voting power. Returns whether `offender` was found in at least one committee.
+    pub fn penalize(&mut self, offender: &PublicKey) -> bool {
+        self.by_sequence
+            .values_mut()
+            .fold(false, |found, committee| committee.penalize(offender) || found)
+    }
 }
 
 /// The private configuration of the identity provider and witnesses.
@@ -129,6 +289,35 @@ pub struct PrivateConfig {
     pub name: PublicKey,
     /// The private key of this entity.
     pub secret: KeyPair,
+    /// This witness's share of the committee's threshold key set, if any. Handed out by
+    /// whoever runs threshold key generation for the committee (see `crypto::threshold_setup`);
+    /// `PrivateConfig::new` cannot produce one on its own since a share only makes sense in
+    /// the context of the whole committee's key set.
+    #[serde(default)]
+    pub threshold_share: Option<ThresholdKeyShare>,
+    /// Where this entity's vkd storage durably persists its records. Each entity picks its
+    /// own backend (it is not part of the public committee configuration) since it only
+    /// affects how that entity runs, not the protocol.
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+}
+
+/// Selects which `storage::Backend` an entity's `AkdStorage` should use.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum StorageBackend {
+    /// The default: a local, on-disk `Storage` rooted at `path`.
+    Local { path: String },
+    /// An S3-compatible object store, namespacing every key under `prefix`.
+    S3 { bucket: String, prefix: String },
+}
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        Self::Local {
+            path: "vkd_storage".to_string(),
+        }
+    }
 }
 
 impl Default for PrivateConfig {
@@ -141,7 +330,12 @@ impl PrivateConfig {
     /// Creates a new private configuration.
     pub fn new() -> Self {
         let (name, secret) = KeyPair::generate_production_keypair();
-        Self { name, secret }
+        Self {
+            name,
+            secret,
+            threshold_share: None,
+            storage_backend: StorageBackend::default(),
+        }
     }
 }
 
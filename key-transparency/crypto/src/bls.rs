@@ -0,0 +1,182 @@
+//! BLS12-381 aggregate signatures (via the `bls_signatures` crate) for constant-size,
+//! O(1)-verification quorum certificates.
+//!
+//! Unlike [`crate::threshold`], where a single dealer hands every witness a *share* of one
+//! group key, here each witness generates its own independent BLS keypair -- there is no
+//! dealer and no key-generation ceremony. Signatures from a subset of witnesses are combined
+//! by simple point addition into one aggregate, and the aggregate verifies against the sum of
+//! the contributing witnesses' public keys in a single pairing check. The tradeoff: summing
+//! arbitrary public keys is vulnerable to a rogue-key attack (a malicious witness can register
+//! a public key chosen as a function of everyone else's, letting it forge an aggregate that
+//! looks like it includes honest signers who never signed), so every public key accepted into
+//! a committee must first be validated with [`BlsKeyPair::prove_possession`] /
+//! [`BlsPublicKey::verify_possession`] at registration time.
+use bls_signatures::{
+    aggregate as raw_aggregate, PrivateKey, PublicKey as RawPublicKey, Serialize as BlsSerialize,
+    Signature as RawSignature,
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Digest;
+
+/// Errors returned while generating, signing with, or verifying a BLS aggregate scheme.
+#[derive(Debug, Error)]
+pub enum BlsError {
+    #[error("BLS proof of possession does not verify for this public key")]
+    InvalidPossessionProof,
+
+    #[error("No signature shares to aggregate")]
+    EmptyAggregate,
+
+    #[error("Aggregate signature does not verify against the contributing public keys")]
+    InvalidAggregate,
+
+    #[error("Malformed BLS key or signature encoding")]
+    MalformedEncoding,
+}
+
+/// A witness's BLS public key, registered once as part of the committee configuration
+/// (alongside, e.g., `config::Committee::threshold_keys`). Only meaningful once its
+/// proof-of-possession has been checked with [`Self::verify_possession`] at registration time;
+/// nothing checks it again afterwards, since re-deriving and re-checking it on every
+/// certificate would defeat the point of a constant-size aggregate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlsPublicKey(#[serde(with = "serde_bytes")] Vec<u8>);
+
+impl BlsPublicKey {
+    fn raw(&self) -> Result<RawPublicKey, BlsError> {
+        RawPublicKey::from_bytes(&self.0).map_err(|_| BlsError::MalformedEncoding)
+    }
+
+    /// Verify `proof` is a proof of possession of the secret key backing this public key,
+    /// i.e. that whoever registered this key actually holds the secret half. Run once, when a
+    /// new witness is added to the committee; guards the aggregate scheme's `verify_aggregate`
+    /// against rogue-key attacks, where a malicious registrant picks a public key as a function
+    /// of the honest keys to forge an aggregate that looks like it includes them.
+    pub fn verify_possession(&self, proof: &BlsPopProof) -> Result<(), BlsError> {
+        let public_key = self.raw()?;
+        let signature = RawSignature::from_bytes(&proof.0).map_err(|_| BlsError::MalformedEncoding)?;
+        if public_key.verify(signature, &self.0) {
+            Ok(())
+        } else {
+            Err(BlsError::InvalidPossessionProof)
+        }
+    }
+}
+
+/// A proof that the registrant of a [`BlsPublicKey`] holds the corresponding secret key: a
+/// signature by that key over its own encoded bytes (the message-augmentation trick, applied
+/// once at registration instead of on every vote).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlsPopProof(#[serde(with = "serde_bytes")] Vec<u8>);
+
+/// A witness's BLS keypair.
+pub struct BlsKeyPair {
+    secret: PrivateKey,
+}
+
+impl BlsKeyPair {
+    /// Generate a new BLS keypair from the given RNG.
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        Self {
+            secret: PrivateKey::generate(rng),
+        }
+    }
+
+    /// This keypair's public half, to be registered in the committee configuration alongside a
+    /// [`BlsPopProof`] from [`Self::prove_possession`].
+    pub fn public_key(&self) -> BlsPublicKey {
+        BlsPublicKey(self.secret.public_key().as_bytes())
+    }
+
+    /// Prove possession of this keypair's secret key, for the committee to check with
+    /// [`BlsPublicKey::verify_possession`] before accepting it.
+    pub fn prove_possession(&self) -> BlsPopProof {
+        let message = self.secret.public_key().as_bytes();
+        BlsPopProof(self.secret.sign(&message).as_bytes())
+    }
+
+    /// Produce this witness's signature share over `digest`, to be combined with a quorum of
+    /// others with [`aggregate`].
+    pub fn sign(&self, digest: &Digest) -> BlsSignatureShare {
+        BlsSignatureShare(self.secret.sign(digest.as_ref()).as_bytes())
+    }
+}
+
+/// A witness's individual BLS signature over a digest, to be combined with a quorum of others
+/// into a [`BlsAggregateSignature`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlsSignatureShare(#[serde(with = "serde_bytes")] Vec<u8>);
+
+impl BlsSignatureShare {
+    fn raw(&self) -> Result<RawSignature, BlsError> {
+        RawSignature::from_bytes(&self.0).map_err(|_| BlsError::MalformedEncoding)
+    }
+}
+
+/// The constant-size sum of a quorum of [`BlsSignatureShare`]s (each over the same digest),
+/// verifying against the sum of the contributing witnesses' public keys in a single pairing
+/// check regardless of quorum size.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlsAggregateSignature(#[serde(with = "serde_bytes")] Vec<u8>);
+
+impl std::fmt::Debug for BlsAggregateSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", base64::encode(&self.0))
+    }
+}
+
+// Useful for tests.
+impl PartialEq for BlsAggregateSignature {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+/// Combine `shares` (each over the same `digest`, from distinct witnesses) into a single
+/// constant-size aggregate signature by point addition.
+pub fn aggregate(shares: &[BlsSignatureShare]) -> Result<BlsAggregateSignature, BlsError> {
+    if shares.is_empty() {
+        return Err(BlsError::EmptyAggregate);
+    }
+    let raw_shares = shares
+        .iter()
+        .map(BlsSignatureShare::raw)
+        .collect::<Result<Vec<_>, _>>()?;
+    let combined = raw_aggregate(&raw_shares).map_err(|_| BlsError::InvalidAggregate)?;
+    Ok(BlsAggregateSignature(combined.as_bytes()))
+}
+
+/// Verify that `signature` is the aggregate of valid signatures by every key in `signers` (and
+/// no one else) over `digest`. Reconstructs the aggregate public key by summing `signers`
+/// (equivalently, since every signer signed the same `digest`: passing `digest` once per
+/// signer to the underlying multi-message pairing check) and performs a single pairing
+/// operation. Callers are responsible for having already checked each signer's proof of
+/// possession (at committee-registration time, not here) and for ensuring `signers` has no
+/// duplicates (`PublishCertificate::verify_contributors` rejects duplicates; it does not check
+/// proofs of possession).
+pub fn verify_aggregate(
+    signature: &BlsAggregateSignature,
+    digest: &Digest,
+    signers: &[BlsPublicKey],
+) -> Result<(), BlsError> {
+    if signers.is_empty() {
+        return Err(BlsError::EmptyAggregate);
+    }
+    let raw_signature = RawSignature::from_bytes(&signature.0).map_err(|_| BlsError::MalformedEncoding)?;
+    let raw_signers = signers
+        .iter()
+        .map(BlsPublicKey::raw)
+        .collect::<Result<Vec<_>, _>>()?;
+    let hashes: Vec<&[u8]> = signers.iter().map(|_| digest.as_ref()).collect();
+    if bls_signatures::verify(&raw_signature, &hashes, &raw_signers) {
+        Ok(())
+    } else {
+        Err(BlsError::InvalidAggregate)
+    }
+}
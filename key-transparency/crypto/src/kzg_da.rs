@@ -0,0 +1,496 @@
+//! KZG-committed, Reed-Solomon-encoded data availability for audit epochs.
+//!
+//! A blob (the serialized audit proof/root for an epoch) is chunked into field elements,
+//! treated as the evaluations of a degree-`< n` polynomial over the `n`-th roots of unity,
+//! and committed to with a single constant-size KZG commitment. The polynomial is then
+//! evaluated over a `2n`-th-root-of-unity domain (Reed-Solomon encoding), and each witness
+//! is handed one evaluation plus an opening proof that it is consistent with the commitment.
+//! Any `n` valid shares are enough to reconstruct the whole blob, so an auditor never has to
+//! trust the IdP to serve it: it only has to find `n` honest witnesses.
+//!
+//! The trusted setup (`Srs::setup`) uses a single dealer who knows `tau` and must forget it
+//! afterwards; this mirrors [`crate::threshold`]'s single-dealer threshold key generation,
+//! which makes the same tradeoff for the same reason (the dealer is already trusted with the
+//! committee configuration).
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Gt, Scalar};
+use ff::{Field, PrimeField};
+use group::Group;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The number of payload bytes packed into one field element: 31 bytes always fit under the
+/// BLS12-381 scalar field's 255-bit modulus, so every chunk decodes back unambiguously.
+const BYTES_PER_CHUNK: usize = 31;
+
+/// Errors returned while encoding, verifying, or reconstructing a data-availability blob.
+#[derive(Debug, Error)]
+pub enum DaError {
+    #[error("domain size must be a power of two, got {0}")]
+    NotPowerOfTwo(usize),
+
+    #[error("share's opening proof does not verify against the commitment")]
+    InvalidShare,
+
+    #[error("only {have} of the {required} shares needed for reconstruction were supplied")]
+    InsufficientShares { have: usize, required: usize },
+
+    #[error("shares do not all commit to the same polynomial")]
+    MismatchedShares,
+
+    #[error("blob needs {required} chunks but the SRS only supports {available}")]
+    SrsTooSmall { required: usize, available: usize },
+}
+
+/// The powers-of-`tau` structured reference string, in both groups (G1 for commitments and
+/// openings, G2 for the single `tau` point the pairing check needs). Public committee
+/// information, so it lives alongside [`crate::ThresholdKeySet`] (e.g. in `config::Committee`).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Srs {
+    #[serde(with = "g1_affine_vec_bytes")]
+    g1_powers: Vec<G1Affine>,
+    #[serde(with = "g2_affine_vec_bytes")]
+    g2_powers: Vec<G2Affine>,
+}
+
+impl Srs {
+    /// Run a single-dealer trusted setup supporting blobs chunked into up to `max_chunks`
+    /// field elements (see [`BYTES_PER_CHUNK`]). The dealer must discard its randomness (the
+    /// toxic waste) after this returns; a real deployment would run this as an MPC ceremony
+    /// instead of generating `tau` in one process.
+    pub fn setup<R: CryptoRng + RngCore>(max_chunks: usize, rng: &mut R) -> Self {
+        // The extended (Reed-Solomon) domain is twice the data domain, and commitments/proofs
+        // are taken over polynomials of degree `< 2 * max_chunks`.
+        let max_degree = 2 * max_chunks.next_power_of_two();
+        let tau = Scalar::random(rng);
+        let mut g1_powers = Vec::with_capacity(max_degree + 1);
+        let mut power = Scalar::one();
+        for _ in 0..=max_degree {
+            g1_powers.push(G1Affine::from(G1Projective::generator() * power));
+            power *= tau;
+        }
+        let g2_powers = vec![
+            G2Affine::from(G2Projective::generator()),
+            G2Affine::from(G2Projective::generator() * tau),
+        ];
+        Self {
+            g1_powers,
+            g2_powers,
+        }
+    }
+
+    /// The largest number of chunks (see [`BYTES_PER_CHUNK`]) this SRS can commit to.
+    pub fn max_chunks(&self) -> usize {
+        (self.g1_powers.len() - 1) / 2
+    }
+
+    fn commit(&self, coefficients: &[Scalar]) -> G1Projective {
+        coefficients
+            .iter()
+            .zip(self.g1_powers.iter())
+            .map(|(c, power)| G1Projective::from(power) * c)
+            .fold(G1Projective::identity(), |acc, term| acc + term)
+    }
+}
+
+/// (De)serialize a `Vec<G1Affine>` as a sequence of canonical 48-byte compressed encodings.
+mod g1_affine_vec_bytes {
+    use super::G1Affine;
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[G1Affine], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(serde_bytes::Bytes::new(&value.to_compressed()))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<G1Affine>, D::Error> {
+        let raw: Vec<serde_bytes::ByteBuf> = Deserialize::deserialize(deserializer)?;
+        raw.into_iter()
+            .map(|bytes| {
+                let array: [u8; 48] = bytes
+                    .into_vec()
+                    .try_into()
+                    .map_err(|_| serde::de::Error::custom("Malformed point"))?;
+                let point = G1Affine::from_compressed(&array);
+                if point.is_some().into() {
+                    Ok(point.unwrap())
+                } else {
+                    Err(serde::de::Error::custom("Malformed point"))
+                }
+            })
+            .collect()
+    }
+}
+
+/// (De)serialize a `Vec<G2Affine>` as a sequence of canonical 96-byte compressed encodings.
+mod g2_affine_vec_bytes {
+    use super::G2Affine;
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[G2Affine], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(serde_bytes::Bytes::new(&value.to_compressed()))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<G2Affine>, D::Error> {
+        let raw: Vec<serde_bytes::ByteBuf> = Deserialize::deserialize(deserializer)?;
+        raw.into_iter()
+            .map(|bytes| {
+                let array: [u8; 96] = bytes
+                    .into_vec()
+                    .try_into()
+                    .map_err(|_| serde::de::Error::custom("Malformed point"))?;
+                let point = G2Affine::from_compressed(&array);
+                if point.is_some().into() {
+                    Ok(point.unwrap())
+                } else {
+                    Err(serde::de::Error::custom("Malformed point"))
+                }
+            })
+            .collect()
+    }
+}
+
+/// A KZG commitment to the polynomial encoding a blob, constant-size regardless of blob length.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Commitment(G1Affine);
+
+impl std::fmt::Debug for Commitment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", base64::encode(self.0.to_compressed()))
+    }
+}
+
+impl Serialize for Commitment {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serde_bytes::serialize(&self.0.to_compressed()[..], serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Commitment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
+        let array: [u8; 48] =
+            bytes.try_into().map_err(|_| serde::de::Error::custom("Malformed commitment"))?;
+        let point = G1Affine::from_compressed(&array);
+        if point.is_some().into() {
+            Ok(Self(point.unwrap()))
+        } else {
+            Err(serde::de::Error::custom("Malformed commitment"))
+        }
+    }
+}
+
+/// One witness's share of an encoded blob: the evaluation point, the polynomial's value there,
+/// and an opening proof tying that value to a [`Commitment`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Share {
+    /// This share's index into the `2n`-point extended (Reed-Solomon) domain.
+    pub index: usize,
+    /// The evaluation point `omega_j`.
+    #[serde(with = "scalar_bytes")]
+    pub point: Scalar,
+    /// The polynomial's value at `point`.
+    #[serde(with = "scalar_bytes")]
+    pub value: Scalar,
+    /// The opening proof `pi_j`.
+    #[serde(with = "g1_affine_bytes")]
+    proof: G1Affine,
+}
+
+impl std::fmt::Debug for Share {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "DaShare({})", self.index)
+    }
+}
+
+/// (De)serialize a [`Scalar`] as its canonical 32-byte little-endian encoding.
+mod scalar_bytes {
+    use super::Scalar;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::serialize(&value.to_bytes()[..], serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("Malformed scalar"))?;
+        let scalar = Scalar::from_bytes(&array);
+        if scalar.is_some().into() {
+            Ok(scalar.unwrap())
+        } else {
+            Err(serde::de::Error::custom("Malformed scalar"))
+        }
+    }
+}
+
+/// (De)serialize a [`G1Affine`] as its canonical 48-byte compressed encoding.
+mod g1_affine_bytes {
+    use super::G1Affine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &G1Affine, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::serialize(&value.to_compressed()[..], serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<G1Affine, D::Error> {
+        let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
+        let array: [u8; 48] = bytes
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("Malformed point"))?;
+        let point = G1Affine::from_compressed(&array);
+        if point.is_some().into() {
+            Ok(point.unwrap())
+        } else {
+            Err(serde::de::Error::custom("Malformed point"))
+        }
+    }
+}
+
+/// Find an `n`-th root of unity in the BLS12-381 scalar field, `n` a power of two.
+fn domain_generator(n: usize) -> Result<Scalar, DaError> {
+    if !n.is_power_of_two() {
+        return Err(DaError::NotPowerOfTwo(n));
+    }
+    // `Scalar::ROOT_OF_UNITY` is a primitive `2^S`-th root of unity; raising it to the power
+    // `2^S / n` yields a primitive `n`-th root.
+    let log_n = n.trailing_zeros();
+    assert!(
+        log_n <= Scalar::S,
+        "domain size exceeds the scalar field's two-adicity"
+    );
+    let mut root = Scalar::ROOT_OF_UNITY;
+    for _ in 0..(Scalar::S - log_n) {
+        root = root.square();
+    }
+    Ok(root)
+}
+
+/// In-place radix-2 Cooley-Tukey FFT over a domain generated by `omega` (see [`ifft`] for the
+/// inverse transform). `values` must have a power-of-two length.
+fn fft(values: &mut [Scalar], omega: Scalar) {
+    let n = values.len();
+    if n == 1 {
+        return;
+    }
+    debug_assert!(n.is_power_of_two());
+
+    // Bit-reversal permutation.
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= n {
+        let half = size / 2;
+        let step = n / size;
+        for start in (0..n).step_by(size) {
+            let mut w = Scalar::one();
+            for offset in 0..half {
+                let even = values[start + offset];
+                let odd = values[start + offset + half] * w;
+                values[start + offset] = even + odd;
+                values[start + offset + half] = even - odd;
+                w *= omega.pow(&[step as u64, 0, 0, 0]);
+            }
+        }
+        size *= 2;
+    }
+}
+
+fn ifft(values: &mut [Scalar], omega: Scalar) {
+    let n = values.len();
+    fft(values, omega.invert().unwrap());
+    let n_inv = Scalar::from(n as u64).invert().unwrap();
+    for value in values.iter_mut() {
+        *value *= n_inv;
+    }
+}
+
+/// Pack `blob` into `BYTES_PER_CHUNK`-byte field elements, zero-padded up to the next power of
+/// two (so it can sit on an FFT domain).
+fn chunk_into_scalars(blob: &[u8]) -> Vec<Scalar> {
+    let chunk_count = blob.chunks(BYTES_PER_CHUNK).count().max(1);
+    let domain_size = chunk_count.next_power_of_two();
+
+    let mut scalars = Vec::with_capacity(domain_size);
+    for chunk in blob.chunks(BYTES_PER_CHUNK) {
+        let mut buffer = [0u8; 32];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        scalars.push(Scalar::from_bytes(&buffer).unwrap());
+    }
+    scalars.resize(domain_size, Scalar::zero());
+    scalars
+}
+
+fn scalar_to_bytes(scalar: &Scalar) -> [u8; BYTES_PER_CHUNK] {
+    let full = scalar.to_bytes();
+    full[..BYTES_PER_CHUNK].try_into().unwrap()
+}
+
+/// Divide `(p(x) - value)` by `(x - point)`, assuming `point` is a root of the numerator (i.e.
+/// `p(point) == value`). Used to compute each share's opening proof.
+fn divide_by_linear(coefficients: &[Scalar], point: Scalar, value: Scalar) -> Vec<Scalar> {
+    let mut numerator = coefficients.to_vec();
+    numerator[0] -= value;
+
+    let mut quotient = vec![Scalar::zero(); numerator.len() - 1];
+    let mut remainder = Scalar::zero();
+    for i in (0..numerator.len()).rev() {
+        let coefficient = numerator[i] + remainder * point;
+        if i > 0 {
+            quotient[i - 1] = coefficient;
+        }
+        remainder = coefficient;
+    }
+    quotient
+}
+
+/// KZG-commit to `blob` and Reed-Solomon-encode it into `2n` shares, `n` the next power of two
+/// at or above `ceil(len(blob) / BYTES_PER_CHUNK)`.
+pub fn encode(srs: &Srs, blob: &[u8]) -> Result<(Commitment, Vec<Share>), DaError> {
+    let evaluations = chunk_into_scalars(blob);
+    let n = evaluations.len();
+    if n > srs.max_chunks() {
+        return Err(DaError::SrsTooSmall {
+            required: n,
+            available: srs.max_chunks(),
+        });
+    }
+    let omega_n = domain_generator(n)?;
+
+    // Interpolate the `n` evaluations into coefficients, then commit.
+    let mut coefficients = evaluations;
+    ifft(&mut coefficients, omega_n);
+    let commitment = Commitment(G1Affine::from(srs.commit(&coefficients)));
+
+    // Reed-Solomon-encode: evaluate the same polynomial over the extended `2n`-point domain.
+    let extended_n = 2 * n;
+    let omega_2n = domain_generator(extended_n)?;
+    let mut extended = coefficients.clone();
+    extended.resize(extended_n, Scalar::zero());
+    fft(&mut extended, omega_2n);
+
+    let mut shares = Vec::with_capacity(extended_n);
+    let mut point = Scalar::one();
+    for (index, value) in extended.into_iter().enumerate() {
+        let quotient = divide_by_linear(&coefficients, point, value);
+        let proof = G1Affine::from(srs.commit(&quotient));
+        shares.push(Share {
+            index,
+            point,
+            value,
+            proof,
+        });
+        point *= omega_2n;
+    }
+
+    Ok((commitment, shares))
+}
+
+/// Verify that `share` is consistent with `commitment`, via the pairing check
+/// `e(C - [value]G1, G2) == e(proof, [tau]G2 - [point]G2)`.
+pub fn verify_share(srs: &Srs, commitment: &Commitment, share: &Share) -> Result<(), DaError> {
+    let lhs_g1 = G1Affine::from(
+        G1Projective::from(commitment.0) - G1Projective::generator() * share.value,
+    );
+    let rhs_g2 = G2Affine::from(
+        G2Projective::from(srs.g2_powers[1]) - G2Projective::generator() * share.point,
+    );
+
+    let lhs: Gt = pairing(&lhs_g1, &srs.g2_powers[0]);
+    let rhs: Gt = pairing(&share.proof, &rhs_g2);
+
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(DaError::InvalidShare)
+    }
+}
+
+/// Reconstruct the original blob from any `n` distinct, valid shares (out of the `2n` a
+/// [`encode`]'d blob produces). Returns the full zero-padded blob (`domain_size *
+/// BYTES_PER_CHUNK` bytes); the original length is not recovered, so callers that know the
+/// true blob length (e.g. from the certified epoch metadata) truncate to it themselves.
+pub fn reconstruct(shares: &[Share], domain_size: usize) -> Result<Vec<u8>, DaError> {
+    if shares.len() < domain_size {
+        return Err(DaError::InsufficientShares {
+            have: shares.len(),
+            required: domain_size,
+        });
+    }
+    let used = &shares[..domain_size];
+
+    // Lagrange-interpolate the polynomial's coefficients from these `domain_size` (point,
+    // value) pairs, then evaluate it back over the original `n`-point domain to recover the
+    // chunk evaluations.
+    let coefficients = lagrange_interpolate(used)?;
+    let omega_n = domain_generator(domain_size)?;
+    let mut evaluations = coefficients;
+    evaluations.resize(domain_size, Scalar::zero());
+    fft(&mut evaluations, omega_n);
+
+    let mut blob = Vec::with_capacity(domain_size * BYTES_PER_CHUNK);
+    for scalar in &evaluations {
+        blob.extend_from_slice(&scalar_to_bytes(scalar));
+    }
+    Ok(blob)
+}
+
+/// Naive O(m^2) Lagrange interpolation of `points` into coefficients of the unique polynomial
+/// of degree `< points.len()` passing through them all.
+fn lagrange_interpolate(points: &[Share]) -> Result<Vec<Scalar>, DaError> {
+    let m = points.len();
+    let mut coefficients = vec![Scalar::zero(); m];
+
+    for i in 0..m {
+        // The i-th Lagrange basis polynomial, as dense coefficients, built up by repeated
+        // multiplication by `(x - points[j].point)` for every `j != i`.
+        let mut basis = vec![Scalar::one()];
+        let mut denominator = Scalar::one();
+        for (j, other) in points.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            denominator *= points[i].point - other.point;
+
+            let mut next = vec![Scalar::zero(); basis.len() + 1];
+            for (degree, coefficient) in basis.iter().enumerate() {
+                next[degree + 1] += *coefficient;
+                next[degree] -= *coefficient * other.point;
+            }
+            basis = next;
+        }
+
+        let inverse =
+            Option::<Scalar>::from(denominator.invert()).ok_or(DaError::MismatchedShares)?;
+        let scale = points[i].value * inverse;
+        for (degree, coefficient) in basis.into_iter().enumerate() {
+            coefficients[degree] += coefficient * scale;
+        }
+    }
+    Ok(coefficients)
+}
@@ -1,18 +1,89 @@
 use ed25519_dalek as dalek;
-use ed25519_dalek::{Signer, Verifier};
+use ed25519_dalek::{Digest as _, Sha512, Signer, Verifier};
+use p256::ecdsa::signature::{Signer as _, Verifier as _};
 use rand::{rngs::OsRng, CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::{
     array::TryFromSliceError,
+    collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
 };
+use thiserror::Error;
 
 #[cfg(test)]
 #[path = "tests/crypto_tests.rs"]
 pub mod crypto_tests;
 
-/// Convenient name for Dalek's signature error.
-pub type CryptoError = dalek::SignatureError;
+mod threshold;
+pub use threshold::{
+    setup as threshold_setup, SignatureShare, ThresholdError, ThresholdKeySet, ThresholdKeyShare,
+    ThresholdSignature,
+};
+
+mod bls;
+pub use bls::{
+    aggregate as bls_aggregate, verify_aggregate as bls_verify_aggregate, BlsAggregateSignature,
+    BlsError, BlsKeyPair, BlsPopProof, BlsPublicKey, BlsSignatureShare,
+};
+
+pub mod kzg_da;
+
+/// Errors returned by the signing and verification routines of this module.
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    #[error(transparent)]
+    Ed25519(#[from] dalek::SignatureError),
+
+    #[error(transparent)]
+    EcdsaP256(#[from] p256::ecdsa::Error),
+
+    #[error(transparent)]
+    EcdsaSecp256k1(#[from] k256::ecdsa::Error),
+
+    #[error("Unknown signature scheme tag {0}")]
+    UnknownScheme(u8),
+
+    #[error("Malformed key or signature encoding")]
+    MalformedEncoding,
+
+    #[error("Signature uses scheme {signature:?} but key uses scheme {key:?}")]
+    SchemeMismatch {
+        key: SignatureScheme,
+        signature: SignatureScheme,
+    },
+
+    #[error("Batch verification failed for entries at indices {0:?}")]
+    BatchVerificationFailed(Vec<usize>),
+}
+
+/// Which signature algorithm a [`PublicKey`]/[`KeyPair`]/[`Signature`] uses. The default
+/// scheme (and the only one understood by the legacy, untagged wire encoding) is `Ed25519`.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum SignatureScheme {
+    Ed25519,
+    EcdsaP256,
+    EcdsaSecp256k1,
+}
+
+impl SignatureScheme {
+    /// The one-byte tag prefixed to the scheme-specific bytes on the wire.
+    fn tag(self) -> u8 {
+        match self {
+            SignatureScheme::Ed25519 => 0,
+            SignatureScheme::EcdsaP256 => 1,
+            SignatureScheme::EcdsaSecp256k1 => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, CryptoError> {
+        match tag {
+            0 => Ok(SignatureScheme::Ed25519),
+            1 => Ok(SignatureScheme::EcdsaP256),
+            2 => Ok(SignatureScheme::EcdsaSecp256k1),
+            _ => Err(CryptoError::UnknownScheme(tag)),
+        }
+    }
+}
 
 /// Represents a hash digest (32 bytes).
 #[derive(Hash, PartialEq, Default, Eq, Clone, Deserialize, Serialize, Ord, PartialOrd)]
@@ -55,9 +126,17 @@ impl TryFrom<&[u8]> for Digest {
     }
 }
 
+/// The longest encoded public key across all supported schemes (a compressed P-256 or
+/// secp256k1 point; Ed25519 keys only use the first 32 bytes).
+const MAX_PUBLIC_KEY_LENGTH: usize = 33;
+
 /// Represents the public key (and identity) of the IdP or witness.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
-pub struct PublicKey(pub [u8; dalek::PUBLIC_KEY_LENGTH]);
+pub struct PublicKey {
+    scheme: SignatureScheme,
+    len: u8,
+    bytes: [u8; MAX_PUBLIC_KEY_LENGTH],
+}
 
 impl Serialize for PublicKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -93,36 +172,80 @@ impl std::fmt::Display for PublicKey {
 
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
-        &self.0
+        &self.bytes[..self.len as usize]
     }
 }
 
 impl PublicKey {
-    /// Encode a public key in base64 (human-readable).
+    /// Wrap the scheme-specific encoded bytes of a public key. Fails if `bytes` is longer
+    /// than `MAX_PUBLIC_KEY_LENGTH`.
+    fn from_scheme_bytes(scheme: SignatureScheme, bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() > MAX_PUBLIC_KEY_LENGTH {
+            return Err(CryptoError::MalformedEncoding);
+        }
+        let mut buffer = [0u8; MAX_PUBLIC_KEY_LENGTH];
+        buffer[..bytes.len()].copy_from_slice(bytes);
+        Ok(Self {
+            scheme,
+            len: bytes.len() as u8,
+            bytes: buffer,
+        })
+    }
+
+    /// Encode a public key in base64 (human-readable): a one-byte scheme tag followed by the
+    /// scheme-specific key bytes.
     pub fn encode_base64(&self) -> String {
-        base64::encode(&self.0[..])
+        let mut encoded = Vec::with_capacity(1 + self.len as usize);
+        encoded.push(self.scheme.tag());
+        encoded.extend_from_slice(self.as_ref());
+        base64::encode(&encoded)
     }
 
-    /// Decode a base64-encoded public key.
-    pub fn decode_base64(s: &str) -> Result<Self, base64::DecodeError> {
-        let bytes = base64::decode(s)?;
-        let array = bytes[..32]
-            .try_into()
-            .map_err(|_| base64::DecodeError::InvalidLength)?;
-        Ok(Self(array))
+    /// Decode a base64-encoded public key. A bare 32-byte payload is accepted as a legacy,
+    /// untagged Ed25519 key so keys serialized before scheme-tagging still round-trip.
+    pub fn decode_base64(s: &str) -> Result<Self, CryptoError> {
+        let raw = base64::decode(s).map_err(|_| CryptoError::MalformedEncoding)?;
+        if raw.len() == dalek::PUBLIC_KEY_LENGTH {
+            return Self::from_scheme_bytes(SignatureScheme::Ed25519, &raw);
+        }
+        let (tag, bytes) = raw.split_first().ok_or(CryptoError::MalformedEncoding)?;
+        let scheme = SignatureScheme::from_tag(*tag)?;
+        Self::from_scheme_bytes(scheme, bytes)
     }
 }
 
+/// The per-scheme secret key material backing a [`KeyPair`].
+enum KeyPairInner {
+    Ed25519(dalek::Keypair),
+    EcdsaP256(p256::ecdsa::SigningKey),
+    EcdsaSecp256k1(k256::ecdsa::SigningKey),
+}
+
 /// Represents a public and secret key pair.
 /// TODO: Make sure secrets are not copyable and movable to control where they are in memory
-pub struct KeyPair(dalek::Keypair);
+pub struct KeyPair(KeyPairInner);
 
 impl Serialize for KeyPair {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(&base64::encode(&self.0.to_bytes()))
+        let (scheme, secret_bytes) = match &self.0 {
+            KeyPairInner::Ed25519(keypair) => {
+                (SignatureScheme::Ed25519, keypair.to_bytes().to_vec())
+            }
+            KeyPairInner::EcdsaP256(signing_key) => {
+                (SignatureScheme::EcdsaP256, signing_key.to_bytes().to_vec())
+            }
+            KeyPairInner::EcdsaSecp256k1(signing_key) => (
+                SignatureScheme::EcdsaSecp256k1,
+                signing_key.to_bytes().to_vec(),
+            ),
+        };
+        let mut encoded = Vec::with_capacity(1 + secret_bytes.len());
+        encoded.push(scheme.tag());
+        encoded.extend(secret_bytes);
+        serializer.serialize_str(&base64::encode(&encoded))
     }
 }
 
@@ -132,65 +255,200 @@ impl<'de> Deserialize<'de> for KeyPair {
         D: serde::de::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let value = base64::decode(&s).map_err(|err| serde::de::Error::custom(err.to_string()))?;
-        let key = dalek::Keypair::from_bytes(&value)
-            .map_err(|err| serde::de::Error::custom(err.to_string()))?;
-        Ok(KeyPair(key))
+        let raw = base64::decode(&s).map_err(|err| serde::de::Error::custom(err.to_string()))?;
+
+        // Legacy, untagged encoding: a bare Ed25519 keypair.
+        if raw.len() == dalek::KEYPAIR_LENGTH {
+            let keypair = dalek::Keypair::from_bytes(&raw)
+                .map_err(|err| serde::de::Error::custom(err.to_string()))?;
+            return Ok(KeyPair(KeyPairInner::Ed25519(keypair)));
+        }
+
+        let (tag, bytes) = raw
+            .split_first()
+            .ok_or_else(|| serde::de::Error::custom("Empty keypair"))?;
+        let scheme =
+            SignatureScheme::from_tag(*tag).map_err(|e| serde::de::Error::custom(e.to_string()))?;
+        let inner = match scheme {
+            SignatureScheme::Ed25519 => KeyPairInner::Ed25519(
+                dalek::Keypair::from_bytes(bytes)
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))?,
+            ),
+            SignatureScheme::EcdsaP256 => KeyPairInner::EcdsaP256(
+                p256::ecdsa::SigningKey::from_bytes(bytes)
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))?,
+            ),
+            SignatureScheme::EcdsaSecp256k1 => KeyPairInner::EcdsaSecp256k1(
+                k256::ecdsa::SigningKey::from_bytes(bytes)
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))?,
+            ),
+        };
+        Ok(KeyPair(inner))
     }
 }
 
 impl KeyPair {
     /// Returns the public key part of the keypair.
     pub fn public(&self) -> PublicKey {
-        PublicKey(self.0.public.to_bytes())
+        match &self.0 {
+            KeyPairInner::Ed25519(keypair) => {
+                PublicKey::from_scheme_bytes(SignatureScheme::Ed25519, &keypair.public.to_bytes())
+            }
+            KeyPairInner::EcdsaP256(signing_key) => PublicKey::from_scheme_bytes(
+                SignatureScheme::EcdsaP256,
+                signing_key
+                    .verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes(),
+            ),
+            KeyPairInner::EcdsaSecp256k1(signing_key) => PublicKey::from_scheme_bytes(
+                SignatureScheme::EcdsaSecp256k1,
+                signing_key
+                    .verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes(),
+            ),
+        }
+        .expect("Scheme-specific public keys always fit MAX_PUBLIC_KEY_LENGTH")
     }
 
-    /// Generate a new keypair.
+    /// Generate a new, production (Ed25519) keypair.
     pub fn generate_production_keypair() -> (PublicKey, KeyPair) {
-        Self::generate_keypair(&mut OsRng)
+        Self::generate_keypair(SignatureScheme::Ed25519, &mut OsRng)
     }
 
-    /// Generate a keypair from the specified RNG (useful for testing).
-    pub fn generate_keypair<R>(csprng: &mut R) -> (PublicKey, KeyPair)
+    /// Generate a keypair of the given scheme from the specified RNG (useful for testing).
+    pub fn generate_keypair<R>(scheme: SignatureScheme, csprng: &mut R) -> (PublicKey, KeyPair)
     where
         R: CryptoRng + RngCore,
     {
-        let keypair = dalek::Keypair::generate(csprng);
-        (PublicKey(keypair.public.to_bytes()), KeyPair(keypair))
+        let inner = match scheme {
+            SignatureScheme::Ed25519 => KeyPairInner::Ed25519(dalek::Keypair::generate(csprng)),
+            SignatureScheme::EcdsaP256 => {
+                KeyPairInner::EcdsaP256(p256::ecdsa::SigningKey::random(csprng))
+            }
+            SignatureScheme::EcdsaSecp256k1 => {
+                KeyPairInner::EcdsaSecp256k1(k256::ecdsa::SigningKey::random(csprng))
+            }
+        };
+        let keypair = KeyPair(inner);
+        let public = keypair.public();
+        (public, keypair)
+    }
+
+    /// Sign a digest with this keypair's secret key.
+    fn sign(&self, value: &Digest) -> Signature {
+        match &self.0 {
+            KeyPairInner::Ed25519(keypair) => Signature::from_scheme_bytes(
+                SignatureScheme::Ed25519,
+                &keypair.sign(value.as_ref()).to_bytes(),
+            ),
+            KeyPairInner::EcdsaP256(signing_key) => {
+                let signature: p256::ecdsa::Signature = signing_key.sign(value.as_ref());
+                Signature::from_scheme_bytes(SignatureScheme::EcdsaP256, &signature.to_bytes())
+            }
+            KeyPairInner::EcdsaSecp256k1(signing_key) => {
+                let signature: k256::ecdsa::Signature = signing_key.sign(value.as_ref());
+                Signature::from_scheme_bytes(SignatureScheme::EcdsaSecp256k1, &signature.to_bytes())
+            }
+        }
     }
 }
 
-/// A signature over a digest.
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Signature(dalek::Signature);
+/// A signature over a digest, tagged with the scheme used to produce it.
+#[derive(Clone)]
+pub struct Signature {
+    scheme: SignatureScheme,
+    bytes: Vec<u8>,
+}
 
 impl Default for Signature {
     fn default() -> Self {
-        Self(dalek::Signature::from_bytes(&[0; dalek::SIGNATURE_LENGTH]).unwrap())
+        Self::from_scheme_bytes(SignatureScheme::Ed25519, &[0; dalek::SIGNATURE_LENGTH])
     }
 }
 
 impl std::fmt::Debug for Signature {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let s = base64::encode(&self.0);
-        write!(f, "{}", s)?;
-        Ok(())
+        write!(f, "{}", base64::encode(&self.bytes))
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let mut encoded = Vec::with_capacity(1 + self.bytes.len());
+        encoded.push(self.scheme.tag());
+        encoded.extend_from_slice(&self.bytes);
+        serializer.serialize_bytes(&encoded)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let raw = Vec::<u8>::deserialize(deserializer)?;
+
+        // Legacy, untagged encoding: a bare Ed25519 signature.
+        if raw.len() == dalek::SIGNATURE_LENGTH {
+            return Ok(Self::from_scheme_bytes(SignatureScheme::Ed25519, &raw));
+        }
+
+        let (tag, bytes) = raw
+            .split_first()
+            .ok_or_else(|| serde::de::Error::custom("Empty signature"))?;
+        let scheme =
+            SignatureScheme::from_tag(*tag).map_err(|e| serde::de::Error::custom(e.to_string()))?;
+        Ok(Self::from_scheme_bytes(scheme, bytes))
     }
 }
 
 impl Signature {
+    fn from_scheme_bytes(scheme: SignatureScheme, bytes: &[u8]) -> Self {
+        Self {
+            scheme,
+            bytes: bytes.to_vec(),
+        }
+    }
+
     /// Sign a digest with the specified private key.
     pub fn new(value: &Digest, secret: &KeyPair) -> Self {
-        Signature(secret.0.sign(value.as_ref()))
+        secret.sign(value)
     }
 
     /// Verify a (single) signature over a digest.
     pub fn verify(&self, value: &Digest, author: &PublicKey) -> Result<(), CryptoError> {
-        let public_key = dalek::PublicKey::from_bytes(author.as_ref())?;
-        public_key.verify(value.as_ref(), &self.0)
+        if self.scheme != author.scheme {
+            return Err(CryptoError::SchemeMismatch {
+                key: author.scheme,
+                signature: self.scheme,
+            });
+        }
+        match self.scheme {
+            SignatureScheme::Ed25519 => {
+                let public_key = dalek::PublicKey::from_bytes(author.as_ref())?;
+                let signature = dalek::Signature::try_from(self.bytes.as_slice())?;
+                Ok(public_key.verify(value.as_ref(), &signature)?)
+            }
+            SignatureScheme::EcdsaP256 => {
+                let verifying_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(author.as_ref())?;
+                let signature = p256::ecdsa::Signature::try_from(self.bytes.as_slice())?;
+                Ok(verifying_key.verify(value.as_ref(), &signature)?)
+            }
+            SignatureScheme::EcdsaSecp256k1 => {
+                let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(author.as_ref())?;
+                let signature = k256::ecdsa::Signature::try_from(self.bytes.as_slice())?;
+                Ok(verifying_key.verify(value.as_ref(), &signature)?)
+            }
+        }
     }
 
-    /// Batch-verify many signature4d over the same digest.
+    /// Batch-verify many signatures over the same digest. Dalek's batch verifier only
+    /// supports Ed25519, so any non-Ed25519 signer is verified one at a time instead.
     pub fn verify_batch<'a, I>(value: &'a Digest, votes: I) -> Result<(), CryptoError>
     where
         I: IntoIterator<Item = &'a (PublicKey, Signature)>,
@@ -198,11 +456,170 @@ impl Signature {
         let mut messages: Vec<&[u8]> = Vec::new();
         let mut signatures: Vec<dalek::Signature> = Vec::new();
         let mut public_keys: Vec<dalek::PublicKey> = Vec::new();
-        for (addr, sig) in votes.into_iter() {
+        for (author, signature) in votes.into_iter() {
+            if author.scheme != SignatureScheme::Ed25519
+                || signature.scheme != SignatureScheme::Ed25519
+            {
+                signature.verify(value, author)?;
+                continue;
+            }
             messages.push(value.as_ref());
-            signatures.push(sig.0);
-            public_keys.push(dalek::PublicKey::from_bytes(&addr.0)?);
+            signatures.push(dalek::Signature::try_from(signature.bytes.as_slice())?);
+            public_keys.push(dalek::PublicKey::from_bytes(author.as_ref())?);
+        }
+        if messages.is_empty() {
+            return Ok(());
+        }
+        dalek::verify_batch(&messages[..], &signatures[..], &public_keys[..])
+            .map_err(CryptoError::from)
+    }
+
+    /// Batch-verify many signatures, each potentially over a *different* digest (e.g. one
+    /// signature per lookup proof in a bundle spanning distinct labels/epochs). Dalek's
+    /// `verify_batch` already supports heterogeneous messages, so this amortizes the whole
+    /// bundle into a single verification instead of falling back to one `verify` per triple.
+    pub fn verify_batch_multi<'a, I>(votes: I) -> Result<(), CryptoError>
+    where
+        I: IntoIterator<Item = &'a (Digest, PublicKey, Signature)>,
+    {
+        let mut messages: Vec<&[u8]> = Vec::new();
+        let mut signatures: Vec<dalek::Signature> = Vec::new();
+        let mut public_keys: Vec<dalek::PublicKey> = Vec::new();
+        for (digest, author, signature) in votes.into_iter() {
+            if author.scheme != SignatureScheme::Ed25519
+                || signature.scheme != SignatureScheme::Ed25519
+            {
+                signature.verify(digest, author)?;
+                continue;
+            }
+            messages.push(digest.as_ref());
+            signatures.push(dalek::Signature::try_from(signature.bytes.as_slice())?);
+            public_keys.push(dalek::PublicKey::from_bytes(author.as_ref())?);
+        }
+        if messages.is_empty() {
+            return Ok(());
         }
         dalek::verify_batch(&messages[..], &signatures[..], &public_keys[..])
+            .map_err(CryptoError::from)
+    }
+
+    /// Like `verify_batch_multi`, but on failure reports the indices (in iteration order) of
+    /// every triple that didn't verify, instead of just that the bundle as a whole is bad.
+    /// Verifies one triple at a time, so prefer `verify_batch_multi` on the (expected) happy
+    /// path and only fall back to this to pinpoint the offender(s).
+    pub fn verify_batch_multi_report<'a, I>(votes: I) -> Result<(), CryptoError>
+    where
+        I: IntoIterator<Item = &'a (Digest, PublicKey, Signature)>,
+    {
+        let failed: Vec<usize> = votes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (digest, author, signature))| {
+                signature.verify(digest, author).err().map(|_| i)
+            })
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(CryptoError::BatchVerificationFailed(failed))
+        }
+    }
+}
+
+/// Errors returned while verifying a [`Signed`] message against a [`KeySet`].
+#[derive(Debug, Error)]
+pub enum QuorumError {
+    #[error("Signature from {0} does not verify")]
+    InvalidSignature(PublicKey),
+
+    #[error("Quorum not reached: {distinct} distinct valid signer(s), {threshold} required")]
+    QuorumNotReached { distinct: usize, threshold: usize },
+}
+
+/// The set of keys authorized to co-sign a [`Signed`] message, and the minimum number of
+/// distinct members of that set whose signatures are required to consider it certified.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeySet {
+    keys: BTreeSet<PublicKey>,
+    threshold: usize,
+}
+
+impl KeySet {
+    /// Create a new `KeySet`. Panics unless `1 <= threshold <= keys.len()`.
+    pub fn new(keys: BTreeSet<PublicKey>, threshold: usize) -> Self {
+        assert!(
+            threshold >= 1 && threshold <= keys.len(),
+            "Threshold must be between 1 and the number of keys"
+        );
+        Self { keys, threshold }
+    }
+
+    /// Whether `key` is a member of this set.
+    pub fn contains(&self, key: &PublicKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// The minimum number of distinct members required to certify a message.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+/// A message co-signed by a subset of a [`KeySet`], e.g. an epoch root hash gossiped among
+/// and certified by a committee of auditors/witnesses.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Signed<T> {
+    /// The message being certified.
+    pub message: T,
+    /// The collected signatures, keyed by signer (at most one signature per signer).
+    pub signatures: BTreeMap<PublicKey, Signature>,
+}
+
+impl<T: Serialize> Signed<T> {
+    /// Wrap `message` with no signatures yet collected.
+    pub fn new(message: T) -> Self {
+        Self {
+            message,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Sign the message and record the resulting signature, replacing any previous
+    /// signature from the same keypair.
+    pub fn add_signature(&mut self, keypair: &KeyPair) {
+        let signature = Signature::new(&self.digest(), keypair);
+        self.signatures.insert(keypair.public(), signature);
+    }
+
+    /// Canonically hash the wrapped message.
+    fn digest(&self) -> Digest {
+        let bytes = bincode::serialize(&self.message).expect("Failed to serialize message");
+        Digest(Sha512::digest(&bytes).as_slice()[..32].try_into().unwrap())
+    }
+
+    /// Verify that at least `keys.threshold()` distinct members of `keys` validly signed this
+    /// message. Signatures from keys outside `keys` are ignored (not counted either way), and
+    /// repeated signatures from the same signer count only once.
+    pub fn verify(&self, keys: &KeySet) -> Result<(), QuorumError> {
+        let digest = self.digest();
+        let mut distinct = BTreeSet::new();
+        for (author, signature) in &self.signatures {
+            if !keys.contains(author) {
+                continue;
+            }
+            signature
+                .verify(&digest, author)
+                .map_err(|_| QuorumError::InvalidSignature(*author))?;
+            distinct.insert(*author);
+        }
+
+        if distinct.len() >= keys.threshold() {
+            Ok(())
+        } else {
+            Err(QuorumError::QuorumNotReached {
+                distinct: distinct.len(),
+                threshold: keys.threshold(),
+            })
+        }
     }
 }
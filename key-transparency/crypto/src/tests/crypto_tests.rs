@@ -20,7 +20,7 @@ impl Message {
 pub fn keys() -> Vec<(PublicKey, KeyPair)> {
     let mut rng = StdRng::from_seed([0; 32]);
     (0..4)
-        .map(|_| KeyPair::generate_keypair(&mut rng))
+        .map(|_| KeyPair::generate_keypair(SignatureScheme::Ed25519, &mut rng))
         .collect()
 }
 
@@ -98,3 +98,248 @@ fn verify_invalid_batch() {
     // Verify the batch.
     assert!(Signature::verify_batch(&message.digest(), &signatures).is_err());
 }
+
+#[test]
+fn verify_valid_batch_multi() {
+    // Sign 3 distinct messages (simulating 3 lookup proofs over different epoch roots).
+    let mut keys = keys();
+    let votes: Vec<_> = (0..3)
+        .map(|i| {
+            let (public_key, secret_key) = keys.pop().unwrap();
+            let message = Message {
+                content: format!("Epoch root {}", i),
+            };
+            let digest = message.digest();
+            (
+                digest.clone(),
+                public_key,
+                Signature::new(&digest, &secret_key),
+            )
+        })
+        .collect();
+
+    assert!(Signature::verify_batch_multi(&votes).is_ok());
+}
+
+#[test]
+fn verify_invalid_batch_multi_reports_offending_index() {
+    let mut keys = keys();
+    let mut votes: Vec<_> = (0..3)
+        .map(|i| {
+            let (public_key, secret_key) = keys.pop().unwrap();
+            let message = Message {
+                content: format!("Epoch root {}", i),
+            };
+            let digest = message.digest();
+            (
+                digest.clone(),
+                public_key,
+                Signature::new(&digest, &secret_key),
+            )
+        })
+        .collect();
+
+    // Corrupt the signature at index 1.
+    votes[1].2 = Signature::default();
+
+    assert!(Signature::verify_batch_multi(&votes).is_err());
+    assert!(matches!(
+        Signature::verify_batch_multi_report(&votes),
+        Err(CryptoError::BatchVerificationFailed(indices)) if indices == vec![1]
+    ));
+}
+
+#[test]
+fn quorum_reached() {
+    let keypairs = keys();
+    let key_set = KeySet::new(keypairs.iter().map(|(name, _)| *name).collect(), 3);
+
+    let mut signed = Signed::new(Message {
+        content: "Hello, world!".to_string(),
+    });
+    for (_, keypair) in keypairs.iter().take(3) {
+        signed.add_signature(keypair);
+    }
+
+    assert!(signed.verify(&key_set).is_ok());
+}
+
+#[test]
+fn quorum_not_reached() {
+    let keypairs = keys();
+    let key_set = KeySet::new(keypairs.iter().map(|(name, _)| *name).collect(), 3);
+
+    let mut signed = Signed::new(Message {
+        content: "Hello, world!".to_string(),
+    });
+    for (_, keypair) in keypairs.iter().take(2) {
+        signed.add_signature(keypair);
+    }
+
+    assert!(matches!(
+        signed.verify(&key_set),
+        Err(QuorumError::QuorumNotReached {
+            distinct: 2,
+            threshold: 3
+        })
+    ));
+}
+
+#[test]
+fn quorum_ignores_signers_outside_key_set() {
+    let keypairs = keys();
+    let key_set = KeySet::new(keypairs.iter().take(3).map(|(name, _)| *name).collect(), 2);
+
+    let mut signed = Signed::new(Message {
+        content: "Hello, world!".to_string(),
+    });
+    // Sign with the two first (in-set) keys and the fourth (out-of-set) key.
+    signed.add_signature(&keypairs[0].1);
+    signed.add_signature(&keypairs[1].1);
+    signed.add_signature(&keypairs[3].1);
+
+    // Only 2 of the 3 signatures count towards the threshold, but that's enough.
+    assert!(signed.verify(&key_set).is_ok());
+}
+
+#[test]
+fn quorum_repeated_signer_counts_once() {
+    let keypairs = keys();
+    let key_set = KeySet::new(keypairs.iter().map(|(name, _)| *name).collect(), 2);
+
+    let mut signed = Signed::new(Message {
+        content: "Hello, world!".to_string(),
+    });
+    signed.add_signature(&keypairs[0].1);
+    signed.add_signature(&keypairs[0].1); // Re-signing with the same key is a no-op.
+
+    assert!(matches!(
+        signed.verify(&key_set),
+        Err(QuorumError::QuorumNotReached {
+            distinct: 1,
+            threshold: 2
+        })
+    ));
+}
+
+#[test]
+#[should_panic]
+fn key_set_rejects_invalid_threshold() {
+    let keypairs = keys();
+    let _ = KeySet::new(keypairs.iter().map(|(name, _)| *name).collect(), 0);
+}
+
+#[test]
+fn threshold_combine_verifies() {
+    let mut rng = StdRng::from_seed([0; 32]);
+    let (keys, shares) = threshold_setup(4, 3, &mut rng);
+    let digest = Message {
+        content: "Epoch root".to_string(),
+    }
+    .digest();
+
+    let collected: Vec<_> = shares
+        .iter()
+        .take(3)
+        .map(|share| share.sign(&digest))
+        .collect();
+    let aggregate = keys.combine(&digest, &collected).unwrap();
+    assert!(aggregate.verify(&digest, &keys).is_ok());
+}
+
+#[test]
+fn threshold_combine_rejects_insufficient_shares() {
+    let mut rng = StdRng::from_seed([0; 32]);
+    let (keys, shares) = threshold_setup(4, 3, &mut rng);
+    let digest = Message {
+        content: "Epoch root".to_string(),
+    }
+    .digest();
+
+    let collected: Vec<_> = shares
+        .iter()
+        .take(2)
+        .map(|share| share.sign(&digest))
+        .collect();
+    assert!(matches!(
+        keys.combine(&digest, &collected),
+        Err(ThresholdError::InsufficientShares {
+            have: 2,
+            required: 3
+        })
+    ));
+}
+
+#[test]
+fn threshold_rejects_invalid_share() {
+    let mut rng = StdRng::from_seed([0; 32]);
+    let (keys, shares) = threshold_setup(4, 3, &mut rng);
+    let digest = Message {
+        content: "Epoch root".to_string(),
+    }
+    .digest();
+    let other_digest = Message {
+        content: "Different epoch root".to_string(),
+    }
+    .digest();
+
+    // A share signed over the wrong digest does not verify against that digest.
+    let bad_share = shares[0].sign(&other_digest);
+    assert!(matches!(
+        keys.verify_share(&digest, &bad_share),
+        Err(ThresholdError::InvalidShare(0))
+    ));
+}
+
+#[test]
+fn kzg_da_round_trip_reconstructs_blob() {
+    use crate::kzg_da::{encode, reconstruct, verify_share, Srs};
+
+    let mut rng = StdRng::from_seed([0; 32]);
+    let srs = Srs::setup(/* max_chunks */ 4, &mut rng);
+    let blob = b"the quick brown fox jumps over the lazy dog".to_vec();
+
+    let (commitment, shares) = encode(&srs, &blob).unwrap();
+    for share in &shares {
+        assert!(verify_share(&srs, &commitment, share).is_ok());
+    }
+
+    // Any `n` (the data domain size) of the `2n` shares reconstruct the original, zero-padded
+    // blob, regardless of which ones are picked.
+    let domain_size = shares.len() / 2;
+    let mut subset = shares[domain_size..].to_vec();
+    subset.truncate(domain_size);
+    let reconstructed = reconstruct(&subset, domain_size).unwrap();
+    assert_eq!(&reconstructed[..blob.len()], blob.as_slice());
+}
+
+#[test]
+fn kzg_da_rejects_tampered_share() {
+    use crate::kzg_da::{encode, verify_share, Srs};
+    use bls12_381::Scalar;
+    use ff::Field;
+
+    let mut rng = StdRng::from_seed([0; 32]);
+    let srs = Srs::setup(/* max_chunks */ 4, &mut rng);
+    let (commitment, mut shares) = encode(&srs, b"audit blob").unwrap();
+
+    shares[0].value += Scalar::one();
+    assert!(verify_share(&srs, &commitment, &shares[0]).is_err());
+}
+
+#[test]
+fn kzg_da_rejects_blob_too_large_for_srs() {
+    use crate::kzg_da::{encode, DaError, Srs};
+
+    let mut rng = StdRng::from_seed([0; 32]);
+    let srs = Srs::setup(/* max_chunks */ 1, &mut rng);
+    let blob = vec![0u8; 31 * 4];
+
+    assert!(matches!(
+        encode(&srs, &blob),
+        Err(DaError::SrsTooSmall {
+            required: 4,
+            available: 1
+        })
+    ));
+}
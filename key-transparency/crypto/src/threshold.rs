@@ -0,0 +1,171 @@
+//! Threshold BLS signatures (via the `threshold_crypto` crate) for constant-size quorum
+//! certificates.
+//!
+//! Unlike the rest of this module, where a quorum certificate carries one signature per
+//! signer, a threshold scheme lets a committee combine a quorum of per-member signature
+//! *shares* into a single aggregate signature that verifies against one group public key in
+//! a single pairing check, regardless of how large the committee is. The tradeoff is a
+//! trusted (or DKG-run) key-generation step: [`setup`] below uses a single dealer, which is
+//! adequate for this codebase's threat model (the dealer is the same party that already
+//! hands out the committee configuration).
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use threshold_crypto::{
+    PublicKeySet, SecretKeySet, SecretKeyShare, Signature, SignatureShare as RawSignatureShare,
+};
+
+use crate::Digest;
+
+/// Errors returned while setting up, signing with, or verifying a threshold scheme.
+#[derive(Debug, Error)]
+pub enum ThresholdError {
+    #[error("Signature share from witness {0} does not verify")]
+    InvalidShare(usize),
+
+    #[error("Only {have} of the {required} required shares were supplied")]
+    InsufficientShares { have: usize, required: usize },
+
+    #[error("Combined signature does not verify against the group public key")]
+    InvalidAggregate,
+}
+
+/// The public half of a threshold key set: the group public key plus enough commitment data
+/// to verify individual shares and combine them into an aggregate. Public committee
+/// information, so it lives alongside [`config::Committee`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThresholdKeySet {
+    /// The number of shares required to produce a valid aggregate signature.
+    threshold: usize,
+    keys: PublicKeySet,
+}
+
+impl ThresholdKeySet {
+    /// The number of shares required to produce a valid aggregate signature.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Verify a single witness's signature share over `digest`.
+    pub fn verify_share(
+        &self,
+        digest: &Digest,
+        share: &SignatureShare,
+    ) -> Result<(), ThresholdError> {
+        let public_share = self.keys.public_key_share(share.index);
+        if public_share.verify(&share.share, digest.as_ref()) {
+            Ok(())
+        } else {
+            Err(ThresholdError::InvalidShare(share.index))
+        }
+    }
+
+    /// Combine a quorum of signature shares into a single constant-size aggregate, verifying
+    /// the result against the group public key before returning it.
+    pub fn combine(
+        &self,
+        digest: &Digest,
+        shares: &[SignatureShare],
+    ) -> Result<ThresholdSignature, ThresholdError> {
+        if shares.len() < self.threshold {
+            return Err(ThresholdError::InsufficientShares {
+                have: shares.len(),
+                required: self.threshold,
+            });
+        }
+        for share in shares {
+            self.verify_share(digest, share)?;
+        }
+
+        let combined = self
+            .keys
+            .combine_signatures(shares.iter().map(|share| (share.index, &share.share)))
+            .map_err(|_| ThresholdError::InvalidAggregate)?;
+        let signature = ThresholdSignature(combined);
+        signature.verify(digest, self)?;
+        Ok(signature)
+    }
+}
+
+/// One witness's secret share of a [`ThresholdKeySet`]. Distributed only to that witness, as
+/// part of its `config::PrivateConfig`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThresholdKeyShare {
+    index: usize,
+    share: SecretKeyShare,
+}
+
+impl ThresholdKeyShare {
+    /// Produce this witness's signature share over `digest`.
+    pub fn sign(&self, digest: &Digest) -> SignatureShare {
+        SignatureShare {
+            index: self.index,
+            share: self.share.sign(digest.as_ref()),
+        }
+    }
+}
+
+/// Run a single-dealer threshold key generation for a committee of `count` witnesses,
+/// requiring `threshold` shares (out of `count`) to produce an aggregate signature.
+///
+/// Returns the public [`ThresholdKeySet`] (to be embedded in `config::Committee`) together
+/// with one [`ThresholdKeyShare`] per witness, in committee order.
+pub fn setup<R>(
+    count: usize,
+    threshold: usize,
+    rng: &mut R,
+) -> (ThresholdKeySet, Vec<ThresholdKeyShare>)
+where
+    R: CryptoRng + RngCore,
+{
+    assert!(
+        threshold >= 1 && threshold <= count,
+        "Threshold must be between 1 and the number of witnesses"
+    );
+    let secret_keys = SecretKeySet::random(threshold - 1, rng);
+    let keys = secret_keys.public_keys();
+    let shares = (0..count)
+        .map(|index| ThresholdKeyShare {
+            index,
+            share: secret_keys.secret_key_share(index),
+        })
+        .collect();
+    (ThresholdKeySet { threshold, keys }, shares)
+}
+
+/// A witness's partial signature over a digest, to be combined with a quorum of others into
+/// a [`ThresholdSignature`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignatureShare {
+    index: usize,
+    share: RawSignatureShare,
+}
+
+/// The constant-size aggregate of a quorum of [`SignatureShare`]s, verifying against the
+/// group public key of a [`ThresholdKeySet`] in a single operation regardless of quorum size.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThresholdSignature(Signature);
+
+impl ThresholdSignature {
+    /// Verify this aggregate against the group public key of `keys`.
+    pub fn verify(&self, digest: &Digest, keys: &ThresholdKeySet) -> Result<(), ThresholdError> {
+        if keys.keys.public_key().verify(&self.0, digest.as_ref()) {
+            Ok(())
+        } else {
+            Err(ThresholdError::InvalidAggregate)
+        }
+    }
+}
+
+impl std::fmt::Debug for ThresholdSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", base64::encode(self.0.to_bytes()))
+    }
+}
+
+// Useful for tests.
+impl PartialEq for ThresholdSignature {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bytes() == other.0.to_bytes()
+    }
+}
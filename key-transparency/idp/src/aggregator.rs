@@ -1,60 +1,135 @@
-use config::{Committee, VotingPower};
-use crypto::{PublicKey, Signature};
+use crate::peer_score::{Impoliteness, PeerScore};
+use config::{Committee, CommitteeHistory, VotingPower};
+use crypto::{BlsSignatureShare, Digest, PublicKey, Signature, SignatureShare};
 use messages::{
     ensure,
     error::{IdpError, IdpResult, MessageError},
-    publish::{PublishCertificate, PublishVote},
-    Root,
+    publish::{CertificateKind, ConflictingVote, PublishCertificate, PublishMessage, PublishVote},
+    Root, Round, SequenceNumber,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// Aggregates votes into a certificate.
 pub struct Aggregator {
-    /// The committee information.
-    committee: Committee,
+    /// The committee(s) in force over time. Votes are checked against whichever committee was
+    /// registered for their own `sequence_number` (via a range lookup), so the IdP can rotate
+    /// witnesses with `reconfigure` while votes cast under an older committee keep aggregating
+    /// correctly until that sequence number is fully behind us.
+    history: CommitteeHistory,
     /// The root to certify.
     root: Root,
+    /// The sequence number being certified.
+    sequence_number: SequenceNumber,
+    /// The voting round being certified.
+    round: Round,
     /// The current voting power accumulated for this root.
     weight: VotingPower,
     /// The list of votes' signatures.
     votes: Vec<(PublicKey, Signature)>,
+    /// Each contributing witness's authenticated `PublishVote::timestamp`, in the order its
+    /// vote was appended. Carried over into the resulting certificate's own `timestamps` field
+    /// so `PublishCertificate::confirmation_time` can be computed downstream.
+    timestamps: Vec<(PublicKey, u64)>,
+    /// The threshold signature shares collected so far for the current root, alongside the
+    /// witness each share came from. Every share a counted vote carries is kept, unchecked;
+    /// `Self::assemble_kind` only uses them when `committee.threshold_keys` is set.
+    threshold_shares: Vec<(PublicKey, SignatureShare)>,
+    /// The BLS signature shares collected so far for the current root, alongside the witness
+    /// each share came from. Every share a counted vote carries is kept, unchecked, whether or
+    /// not its author has a registered `bls_public_key`.
+    bls_shares: Vec<(PublicKey, BlsSignatureShare)>,
     /// The set of witness that already voted.
     used: HashSet<PublicKey>,
+    /// Every vote seen so far for the current sequence number, keyed by author. Kept across
+    /// resets of the same sequence number so a witness cannot equivocate across re-broadcasts
+    /// (e.g. after a round change) without getting caught.
+    seen: HashMap<PublicKey, PublishVote>,
+    /// Soft, non-cryptographic misbehavior counts for witnesses that are merely wasting work
+    /// (resending a vote we already have, voting for content we are not tracking), as opposed
+    /// to the hard [`ConflictingVote`] proof produced for genuine equivocation. Never reset by
+    /// [`Self::reset`]/[`Self::reconfigure`]: it tracks a peer's behavior across rounds and
+    /// sequence numbers, not just the one currently being aggregated.
+    peer_score: PeerScore,
 }
 
 impl Aggregator {
-    /// Initialize a new aggregator.
+    /// Initialize a new aggregator with `committee` in force from sequence number 0 onward.
+    /// Use [`Self::reconfigure`] to register a later committee once the IdP decides to rotate
+    /// witnesses or re-weight voting power.
     pub fn new(committee: Committee, root: Root) -> Self {
         Self {
-            committee,
+            history: CommitteeHistory::new(committee),
             root,
+            sequence_number: SequenceNumber::default(),
+            round: Round::default(),
             weight: VotingPower::default(),
             votes: Vec::new(),
+            timestamps: Vec::new(),
+            threshold_shares: Vec::new(),
+            bls_shares: Vec::new(),
             used: HashSet::new(),
+            seen: HashMap::new(),
+            peer_score: PeerScore::new(),
         }
     }
 
-    /// Reset the aggregator.
-    pub fn reset(&mut self, root: Root) {
+    /// Reset the aggregator to certify a new root at the given sequence number and round.
+    pub fn reset(&mut self, root: Root, sequence_number: SequenceNumber, round: Round) {
+        // Only forget previously-seen votes once we move past the sequence number they
+        // were cast for; this is what lets us catch a witness equivocating across resets.
+        if self.sequence_number != sequence_number {
+            self.seen.clear();
+        }
         self.root = root;
+        self.sequence_number = sequence_number;
+        self.round = round;
         self.weight = 0;
         self.votes.clear();
+        self.timestamps.clear();
+        self.threshold_shares.clear();
+        self.bls_shares.clear();
         self.used.clear();
     }
 
+    /// Register `committee` as effective from `sequence_number` onward, so subsequently
+    /// appended votes for that sequence number (and any later one not itself reconfigured) are
+    /// checked against it instead of whatever committee used to be current.
+    pub fn reconfigure(&mut self, sequence_number: SequenceNumber, committee: Committee) {
+        self.history.reconfigure(sequence_number, committee);
+    }
+
+    /// The soft misbehavior score accumulated for `witness` so far (see [`PeerScore`]).
+    pub fn misbehavior_score(&self, witness: &PublicKey) -> usize {
+        self.peer_score.total(witness)
+    }
+
+    /// Whether `witness` has crossed `threshold` soft misbehavior events and its connection
+    /// should be dropped, independent of whether it has ever produced a hard equivocation proof.
+    pub fn should_disconnect(&self, witness: &PublicKey, threshold: usize) -> bool {
+        self.peer_score.should_disconnect(witness, threshold)
+    }
+
+    /// The committee that was in force for `sequence_number`, so a caller holding a misbehavior
+    /// proof tied to that sequence number (e.g. a `ConflictingVote`) can verify it before calling
+    /// `Self::penalize`.
+    pub fn committee_for(&self, sequence_number: SequenceNumber) -> Committee {
+        self.history.committee_at(sequence_number).clone()
+    }
+
+    /// Zero `offender`'s voting power in every committee registered in `self.history`, so every
+    /// future `Self::append` call rejects its votes as coming from an unknown witness (see
+    /// `Committee::penalize`). Intended to be called once proven misbehavior (e.g. the
+    /// `IdpError::EquivocatingWitness` proof `Self::append` returns) has been verified by the
+    /// caller. Returns whether `offender` was found in at least one committee.
+    pub fn penalize(&mut self, offender: &PublicKey) -> bool {
+        self.history.penalize(offender)
+    }
+
     /// Append a vote to the aggregator.
     pub fn append(&mut self, vote: PublishVote) -> IdpResult<Option<PublishCertificate>> {
         let author = vote.author;
-        let voting_power = self.committee.voting_power(&author);
-
-        // Ensure the vote is for the correct root.
-        ensure!(
-            self.root == vote.root,
-            IdpError::UnexpectedVote {
-                expected: self.root,
-                received: vote.root
-            }
-        );
+        let committee = self.history.committee_at(vote.sequence_number).clone();
+        let voting_power = committee.voting_power(&author);
 
         // Ensure the witness is in the committee.
         ensure!(
@@ -62,28 +137,141 @@ impl Aggregator {
             IdpError::MessageError(MessageError::UnknownWitness(author))
         );
 
-        // Ensure it is the first time this authority votes.
+        // Reject a vote cast for a fork this committee configuration doesn't recognize, rather
+        // than folding it into the current quorum.
         ensure!(
-            self.used.insert(author),
-            IdpError::MessageError(MessageError::WitnessReuse(author))
+            vote.fork_id == committee.fork_id,
+            IdpError::MessageError(MessageError::ForkMismatch {
+                expected: committee.fork_id,
+                received: vote.fork_id,
+            })
         );
 
-        // Verify the vote.
-        vote.verify(&self.committee)?;
+        // Verify the vote's signature before trusting anything about it -- including caching
+        // it in `self.seen` below. `author` is just a self-declared field on an otherwise
+        // unauthenticated message at this point: caching an unverified vote would let a
+        // forged message (claiming to be from some other witness) poison equivocation
+        // detection for that witness's real, validly-signed vote when it later arrives, and
+        // get the real vote rejected as equivocation -- and dropped from the quorum -- instead
+        // of counted.
+        vote.verify(&committee)?;
+
+        // Detect equivocation: the same witness voting for two different roots at the same
+        // round. Produce a proof that a third party can verify independently. A witness
+        // legitimately re-voting for a different root at a *later* round (having unlocked
+        // via a justification) is not equivocation. `previous` was itself verified before
+        // being cached, so this produces a proof backed by two validly-signed, conflicting
+        // votes.
+        match self.seen.get(&author) {
+            Some(previous) if previous.round == vote.round && previous.root != vote.root => {
+                return Err(IdpError::EquivocatingWitness(Box::new(ConflictingVote {
+                    vote_1: previous.clone(),
+                    vote_2: vote,
+                })));
+            }
+            _ => {
+                self.seen.insert(author, vote.clone());
+            }
+        }
+
+        // Ensure the vote is for the current round: votes from a round we have already
+        // moved past cannot be combined with current-round votes into one valid certificate
+        // (they sign different digests).
+        if self.round != vote.round {
+            self.peer_score.record(author, Impoliteness::UnknownSequenceNumber);
+            return Err(IdpError::UnexpectedRound {
+                expected: self.round,
+                received: vote.round,
+            });
+        }
+
+        // Ensure the vote is for the correct root.
+        if self.root != vote.root {
+            self.peer_score.record(author, Impoliteness::UnknownSequenceNumber);
+            return Err(IdpError::UnexpectedVote {
+                expected: self.root,
+                received: vote.root,
+            });
+        }
+
+        // Ensure it is the first time this authority votes.
+        if !self.used.insert(author) {
+            self.peer_score.record(author, Impoliteness::DuplicateVote);
+            return Err(IdpError::MessageError(MessageError::WitnessReuse(author)));
+        }
 
         // Check if we have a quorum.
         self.votes.push((author, vote.signature));
+        self.timestamps.push((author, vote.timestamp));
+        if let Some(share) = vote.threshold_share.clone() {
+            self.threshold_shares.push((author, share));
+        }
+        if let Some(share) = vote.bls_share.clone() {
+            self.bls_shares.push((author, share));
+        }
         self.weight += voting_power;
-        if self.weight >= self.committee.quorum_threshold() {
+        if self.weight >= committee.quorum_threshold() {
             self.weight = 0; // Ensures quorum is only reached once.
             return Ok(Some(PublishCertificate {
                 root: vote.root,
                 sequence_number: vote.sequence_number,
-                votes: self.votes.clone(),
+                round: vote.round,
+                kind: self.assemble_kind(&committee, &vote.digest()),
+                timestamps: self.timestamps.clone(),
             }));
         }
         Ok(None)
     }
+
+    /// Assemble the cheapest certificate kind the collected votes support: a single
+    /// constant-size `Threshold` aggregate if `committee` has threshold keys configured and
+    /// enough witnesses attached a share; failing that, a `BlsAggregate` if enough witnesses
+    /// with a registered BLS key attached one; otherwise the per-witness `Votes` form. Takes
+    /// `committee` explicitly (the one resolved for the vote that completed the quorum) rather
+    /// than a fixed field, so a certificate assembled right after a reconfiguration is packed
+    /// against the committee its votes were actually checked against.
+    fn assemble_kind(&self, committee: &Committee, digest: &Digest) -> CertificateKind {
+        if let Some(threshold_keys) = committee.threshold_keys.as_ref() {
+            if self.threshold_shares.len() >= threshold_keys.threshold() {
+                let shares: Vec<_> = self
+                    .threshold_shares
+                    .iter()
+                    .map(|(_, share)| share.clone())
+                    .collect();
+                if let Ok(signature) = threshold_keys.combine(digest, &shares) {
+                    let names: Vec<_> = self
+                        .threshold_shares
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect();
+                    let contributors = CertificateKind::pack_contributors(committee, &names);
+                    return CertificateKind::Threshold {
+                        signature,
+                        contributors,
+                    };
+                }
+            }
+        }
+
+        let bls_weight: VotingPower = self
+            .bls_shares
+            .iter()
+            .map(|(name, _)| committee.voting_power(name))
+            .sum();
+        if bls_weight >= committee.quorum_threshold() {
+            let shares: Vec<_> = self.bls_shares.iter().map(|(_, share)| share.clone()).collect();
+            if let Ok(signature) = crypto::bls_aggregate(&shares) {
+                let names: Vec<_> = self.bls_shares.iter().map(|(name, _)| *name).collect();
+                let contributors = CertificateKind::pack_contributors(committee, &names);
+                return CertificateKind::BlsAggregate {
+                    signature,
+                    contributors,
+                };
+            }
+        }
+
+        CertificateKind::Votes(self.votes.clone())
+    }
 }
 
 #[cfg(test)]
@@ -119,4 +307,253 @@ mod tests {
         assert_eq!(certificate.root, root);
         assert_eq!(certificate.sequence_number, sequence_number);
     }
+
+    #[tokio::test]
+    async fn make_threshold_certificate() {
+        let (committee, shares) = test_utils::threshold_committee(0);
+        let notification = test_utils::notification().await;
+        let sequence_number = notification.sequence_number;
+        let mut aggregator = Aggregator::new(committee.clone(), notification.root);
+
+        // Each witness attaches its threshold signature share alongside its ordinary vote
+        // signature, exactly as `witness::publish_handler` does when `PrivateConfig` carries
+        // a share (see `PublishVote::with_threshold_share`).
+        let mut votes: Vec<_> = test_utils::keys()
+            .iter()
+            .zip(shares.iter())
+            .map(|((_, keypair), share)| {
+                PublishVote::new(&notification, keypair).with_threshold_share(share)
+            })
+            .collect();
+
+        let mut certificate = None;
+        while let Some(vote) = votes.pop() {
+            if let Some(result) = aggregator.append(vote).unwrap() {
+                certificate = Some(result);
+                break;
+            }
+        }
+
+        // A quorum of shares combines into a single constant-size `Threshold` certificate
+        // rather than the per-witness `Votes` form.
+        let certificate = certificate.unwrap();
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate.sequence_number, sequence_number);
+        assert!(matches!(
+            certificate.kind,
+            CertificateKind::Threshold { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn make_bls_aggregate_certificate() {
+        let (committee, bls_keys) = test_utils::bls_committee(0);
+        let notification = test_utils::notification().await;
+        let sequence_number = notification.sequence_number;
+        let mut aggregator = Aggregator::new(committee.clone(), notification.root);
+
+        // Each witness attaches its BLS signature share alongside its ordinary vote signature,
+        // exactly as `witness::publish_handler` does when `PrivateConfig` carries a BLS
+        // keypair (see `PublishVote::with_bls_share`).
+        let mut votes: Vec<_> = test_utils::keys()
+            .iter()
+            .zip(bls_keys.iter())
+            .map(|((_, keypair), bls_keypair)| {
+                PublishVote::new(&notification, keypair).with_bls_share(bls_keypair)
+            })
+            .collect();
+
+        let mut certificate = None;
+        while let Some(vote) = votes.pop() {
+            if let Some(result) = aggregator.append(vote).unwrap() {
+                certificate = Some(result);
+                break;
+            }
+        }
+
+        // A quorum of BLS shares combines into a single constant-size `BlsAggregate`
+        // certificate rather than the per-witness `Votes` form.
+        let certificate = certificate.unwrap();
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate.sequence_number, sequence_number);
+        assert!(matches!(
+            certificate.kind,
+            CertificateKind::BlsAggregate { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn reconfigure_resolves_committee_by_sequence_number() {
+        use messages::publish::PublishNotification;
+
+        let (_, idp_keypair) = test_utils::keys().pop().unwrap();
+        let (_, root, proof) = test_utils::proof().await;
+
+        let genesis = committee(0);
+        let mut aggregator = Aggregator::new(genesis.clone(), root);
+
+        // From sequence number 10 onward, one witness's voting power is revoked.
+        let removed = *genesis.witnesses.keys().next().unwrap();
+        let mut reconfigured = genesis.clone();
+        reconfigured
+            .witnesses
+            .get_mut(&removed)
+            .unwrap()
+            .voting_power = 0;
+        aggregator.reconfigure(10, reconfigured);
+
+        let removed_keypair = test_utils::keys()
+            .into_iter()
+            .find(|(name, _)| *name == removed)
+            .unwrap()
+            .1;
+
+        // Before sequence number 10, the removed witness's vote is still resolved against the
+        // genesis committee (where it still has voting power) and accepted.
+        let early_notification = PublishNotification::new(
+            root,
+            proof.clone(),
+            /* sequence_number */ 5,
+            /* round */ 0,
+            &idp_keypair,
+        );
+        let early_vote = PublishVote::new(&early_notification, &removed_keypair);
+        aggregator.reset(root, 5, 0);
+        assert!(aggregator.append(early_vote).is_ok());
+
+        // From sequence number 10 onward, the same witness is rejected as unknown, because the
+        // committee resolved for that sequence number has zeroed its voting power.
+        let late_notification = PublishNotification::new(
+            root,
+            proof,
+            /* sequence_number */ 10,
+            /* round */ 0,
+            &idp_keypair,
+        );
+        let late_vote = PublishVote::new(&late_notification, &removed_keypair);
+        aggregator.reset(root, 10, 0);
+        match aggregator.append(late_vote) {
+            Err(IdpError::MessageError(MessageError::UnknownWitness(name))) => {
+                assert_eq!(name, removed);
+            }
+            other => panic!("Expected UnknownWitness, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn detect_equivocation() {
+        use messages::publish::PublishNotification;
+
+        let mut votes = votes().await;
+        let root = votes[0].root;
+        let sequence_number = votes[0].sequence_number;
+        let mut aggregator = Aggregator::new(committee(0), root);
+        aggregator.reset(root, sequence_number, /* round */ 0);
+
+        let vote_0 = votes.pop().unwrap();
+        let author = vote_0.author;
+        assert!(aggregator.append(vote_0).is_ok());
+
+        // Craft a second vote, from the same witness, committing to a different root at the
+        // same sequence number and round.
+        let witness_keypair = test_utils::keys()
+            .into_iter()
+            .find(|(name, _)| *name == author)
+            .unwrap()
+            .1;
+        let (_, idp_keypair) = test_utils::keys().pop().unwrap();
+        let (conflicting_root, _, proof) = test_utils::proof().await;
+        let conflicting_notification = PublishNotification::new(
+            conflicting_root,
+            proof,
+            sequence_number,
+            /* round */ 0,
+            &idp_keypair,
+        );
+        let conflicting_vote = PublishVote::new(&conflicting_notification, &witness_keypair);
+
+        match aggregator.append(conflicting_vote) {
+            Err(IdpError::EquivocatingWitness(proof)) => {
+                assert_eq!(proof.vote_1.author, author);
+                assert_eq!(proof.vote_2.author, author);
+                assert!(proof.verify(&committee(0)).is_ok());
+            }
+            _ => panic!("Expected an equivocation proof"),
+        }
+    }
+
+    #[tokio::test]
+    async fn penalize_rejects_future_votes_from_equivocator() {
+        use messages::publish::PublishNotification;
+
+        let mut votes = votes().await;
+        let root = votes[0].root;
+        let sequence_number = votes[0].sequence_number;
+        let mut aggregator = Aggregator::new(committee(0), root);
+        aggregator.reset(root, sequence_number, /* round */ 0);
+
+        let vote_0 = votes.pop().unwrap();
+        let author = vote_0.author;
+        assert!(aggregator.append(vote_0).is_ok());
+
+        let witness_keypair = test_utils::keys()
+            .into_iter()
+            .find(|(name, _)| *name == author)
+            .unwrap()
+            .1;
+        let (_, idp_keypair) = test_utils::keys().pop().unwrap();
+        let (conflicting_root, _, proof) = test_utils::proof().await;
+        let conflicting_notification = PublishNotification::new(
+            conflicting_root,
+            proof,
+            sequence_number,
+            /* round */ 0,
+            &idp_keypair,
+        );
+        let conflicting_vote = PublishVote::new(&conflicting_notification, &witness_keypair);
+
+        let mut penalized_committee = committee(0);
+        match aggregator.append(conflicting_vote) {
+            Err(IdpError::EquivocatingWitness(proof)) => {
+                assert!(proof.verify(&committee(0)).is_ok());
+                assert!(penalized_committee.penalize(&author));
+            }
+            _ => panic!("Expected an equivocation proof"),
+        }
+
+        // A later vote from the penalized witness is rejected as unknown, since its voting
+        // power was zeroed rather than its key being removed from the committee outright.
+        let notification = test_utils::notification().await;
+        let later_vote = PublishVote::new(&notification, &witness_keypair);
+        let mut later_aggregator = Aggregator::new(penalized_committee, notification.root);
+        later_aggregator.reset(notification.root, notification.sequence_number, 0);
+        match later_aggregator.append(later_vote) {
+            Err(IdpError::MessageError(MessageError::UnknownWitness(name))) => {
+                assert_eq!(name, author);
+            }
+            other => panic!("Expected UnknownWitness, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_soft_misbehavior_independent_of_equivocation() {
+        let mut votes = votes().await;
+        let root = votes[0].root;
+        let sequence_number = votes[0].sequence_number;
+        let mut aggregator = Aggregator::new(committee(0), root);
+        aggregator.reset(root, sequence_number, /* round */ 0);
+
+        let vote = votes.pop().unwrap();
+        let author = vote.author;
+        assert_eq!(aggregator.misbehavior_score(&author), 0);
+        assert!(aggregator.append(vote.clone()).is_ok());
+
+        // Resending the exact same vote is impolite (wasted work) but not cryptographic
+        // evidence of misbehavior, so it is tracked by `PeerScore` rather than rejected with
+        // an `EquivocatingWitness` proof.
+        assert!(aggregator.append(vote).is_err());
+        assert_eq!(aggregator.misbehavior_score(&author), 1);
+        assert!(!aggregator.should_disconnect(&author, 2));
+        assert!(aggregator.should_disconnect(&author, 1));
+    }
 }
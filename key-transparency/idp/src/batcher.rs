@@ -1,6 +1,9 @@
-use bytes::Bytes;
+use crate::metrics;
 use log::{debug, warn};
-use messages::update::Batch;
+use messages::{
+    merkle::{BatchCommitment, MerkleAccumulator},
+    update::{Batch, SealedBatch, UpdateRequest},
+};
 use tokio::{
     sync::mpsc::{Receiver, Sender},
     task::JoinHandle,
@@ -13,14 +16,18 @@ pub struct Batcher {
     batch_size: usize,
     /// The maximum delay after which to seal the batch (in ms).
     max_batch_delay: u64,
-    /// Channel to receive requests from the network.
-    rx_request: Receiver<Bytes>,
-    /// Output channel to deliver sealed batches to the `NotificationMaker`.
-    tx_batch: Sender<Batch>,
+    /// Channel to receive requests from the network (already deserialized by the `IdpHandler`).
+    rx_request: Receiver<UpdateRequest>,
+    /// Output channel to deliver sealed batches (with their Merkle commitment) to the `Prover`.
+    tx_batch: Sender<SealedBatch>,
     /// Holds the current batch.
     current_batch: Batch,
     /// Holds the size of the current batch (in bytes).
     current_batch_size: usize,
+    /// Accumulates a Merkle tree over the current batch's requests, in the order they are
+    /// received, so the batch can be sealed with a commitment a client can get an inclusion
+    /// proof against.
+    accumulator: MerkleAccumulator,
 }
 
 impl Batcher {
@@ -28,8 +35,8 @@ impl Batcher {
     pub fn spawn(
         batch_size: usize,
         max_batch_delay: u64,
-        rx_request: Receiver<Bytes>,
-        tx_batch: Sender<Batch>,
+        rx_request: Receiver<UpdateRequest>,
+        tx_batch: Sender<SealedBatch>,
     ) -> JoinHandle<()> {
         #[cfg(feature = "benchmark")]
         // NOTE: These log entries are used to compute performance.
@@ -43,6 +50,7 @@ impl Batcher {
                 tx_batch,
                 current_batch: Vec::with_capacity(2 * batch_size),
                 current_batch_size: 0,
+                accumulator: MerkleAccumulator::new(),
             }
             .run()
             .await
@@ -57,16 +65,9 @@ impl Batcher {
         loop {
             tokio::select! {
                 // Assemble client requests into batches of preset size.
-                Some(bytes) = self.rx_request.recv() => {
-                    let update = match bincode::deserialize(&bytes) {
-                        Ok(x) => x,
-                        Err(e) => {
-                            warn!("Failed to deserialize request: {}", e);
-                            continue;
-                        }
-                    };
-
+                Some(update) = self.rx_request.recv() => {
                     self.current_batch_size += 1;
+                    self.accumulator.push(&update);
                     self.current_batch.push(update);
                     if self.current_batch_size >= self.batch_size {
                         self.seal().await;
@@ -93,12 +94,19 @@ impl Batcher {
         }
     }
 
-    /// Seal the current batch.
+    /// Seal the current batch, together with the Merkle commitment over its requests.
     async fn seal(&mut self) {
         self.current_batch_size = 0;
         let batch: Batch = self.current_batch.drain(..).collect();
+        metrics::BATCHES_SEALED.inc();
+        metrics::UPDATES_BATCHED.inc_by(batch.len() as u64);
+        let commitment = BatchCommitment {
+            root: self.accumulator.root(),
+            size: self.accumulator.len(),
+        };
+        self.accumulator = MerkleAccumulator::new();
         self.tx_batch
-            .send(batch)
+            .send((batch, commitment))
             .await
             .expect("Failed to deliver batch");
     }
@@ -1,7 +1,10 @@
 mod aggregator;
 mod batcher;
+pub(crate) mod metrics;
+mod peer_score;
 mod prover;
 mod publisher;
+mod query_server;
 mod synchronizer;
 
 use async_trait::async_trait;
@@ -10,14 +13,19 @@ use bytes::Bytes;
 use config::Committee;
 use crypto::KeyPair;
 use futures::{future::join_all, SinkExt};
-use log::info;
+use log::{info, warn};
+use messages::{codec, update::UpdateRequest, ClientToIdPMessage, SequenceNumber};
 use network::receiver::{MessageHandler, Receiver as NetworkReceiver, Writer};
 use prover::Prover;
 use publisher::Publisher;
-use std::error::Error;
+use query_server::{ClientQuery, QueryServer};
+use std::{error::Error, net::SocketAddr};
 use storage::Storage;
 use synchronizer::Synchronizer;
-use tokio::sync::mpsc::{channel, Sender};
+use tokio::sync::{
+    mpsc::{channel, Sender},
+    oneshot, watch,
+};
 
 /// Storage address of the sequence number.
 pub(crate) const STORE_LAST_NOTIFICATION_ADDR: [u8; 32] = [255; 32];
@@ -25,30 +33,51 @@ pub(crate) const STORE_LAST_NOTIFICATION_ADDR: [u8; 32] = [255; 32];
 /// The default size of inter-tasks channels.
 pub(crate) const DEFAULT_CHANNEL_SIZE: usize = 1_000;
 
+/// A one-shot channel used by the `IdpHandler` to receive a `QueryServer`'s reply.
+pub(crate) type Replier = oneshot::Sender<messages::IdPToClientMessage>;
+
 /// Spawn a new IdP.
 pub async fn spawn_idp<AkdStorage>(
-    // The keypair of the IdP.
+    // The keypair of the IdP, used by the `Prover` to sign fresh publish notifications.
     keypair: KeyPair,
+    // A second, independently-loaded copy of the same keypair, used by the `Publisher` to
+    // re-sign a proposal when it bumps the round on a timeout (`KeyPair` deliberately does not
+    // implement `Clone`, so each consumer loads its own copy of the secret material).
+    publisher_keypair: KeyPair,
     // The committee information.
     committee: Committee,
     // The secure storage containing the last publish notification.
     secure_storage: Storage,
     // The storage containing all past certificates.
     sync_storage: Storage,
+    // The storage containing each sealed batch's Merkle commitment, keyed by sequence number.
+    merkle_storage: Storage,
     // The big storage containing all key-values.
     vkd_storage: AkdStorage,
     // The number of updates to batch into a single proof.
     batch_size: usize,
     // The maximum delay before sealing a batch of requests.
     max_batch_delay: u64,
+    // The base timeout (ms) before the publisher retransmits a notification to witnesses
+    // that have not yet voted.
+    timeout_delay: u64,
+    // The maximum number of certificates pushed to a lagging witness in a single round, bounding
+    // the work a single anti-entropy round can trigger.
+    max_range_span: SequenceNumber,
+    // The address the Prometheus `/metrics` endpoint is served on.
+    metrics_address: SocketAddr,
 ) where
-    AkdStorage: vkd::storage::Storage + Sync + Send + 'static,
+    AkdStorage: vkd::storage::Storage + Clone + Sync + Send + 'static,
 {
     let (tx_request, rx_request) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_query, rx_query) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_batch, rx_batch) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_notification, rx_notification) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_trigger, rx_trigger) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_certificate, rx_certificate) = channel(DEFAULT_CHANNEL_SIZE);
+    // Tracks the sequence number of the latest witness-certified root; the `QueryServer`
+    // refuses to answer with proofs more recent than this.
+    let (tx_certified_epoch, rx_certified_epoch) = watch::channel(SequenceNumber::default());
 
     // The `Batcher` receives clients update requests and batch them together.
     let batcher_handle = Batcher::spawn(batch_size, max_batch_delay, rx_request, tx_batch);
@@ -56,30 +85,51 @@ pub async fn spawn_idp<AkdStorage>(
     // The `Prover` persists batches of updates and generate a commit (audit) proof.
     let prover_handle = Prover::spawn(
         keypair,
+        committee.clone(),
         &secure_storage,
-        vkd_storage,
+        merkle_storage,
+        vkd_storage.clone(),
         rx_batch,
         tx_notification,
     );
 
-    // The `Publisher` broadcasts publish notifications to the witnesses.
+    // The `Publisher` broadcasts publish notifications to the witnesses, re-signing a
+    // proposal itself when it needs to bump the round on a timeout.
     let publisher_handle = Publisher::spawn(
+        publisher_keypair,
         committee.clone(),
         secure_storage,
         rx_notification,
         tx_trigger,
         tx_certificate,
+        tx_certified_epoch,
+        timeout_delay,
     );
 
     // The `Synchronizer` helps the witnesses to remain up to date.
-    let synchronizer_handle =
-        Synchronizer::spawn(committee.clone(), sync_storage, rx_trigger, rx_certificate);
+    let synchronizer_handle = Synchronizer::spawn(
+        committee.clone(),
+        sync_storage,
+        rx_trigger,
+        rx_certificate,
+        max_range_span,
+    );
+
+    // The `QueryServer` answers client lookup and key-history queries against the latest
+    // witness-certified root.
+    let query_server_handle = QueryServer::spawn(vkd_storage, rx_query, rx_certified_epoch);
+
+    // Serve the Prometheus `/metrics` endpoint.
+    let metrics_handle = ::metrics::spawn(metrics_address);
 
     // Spawn a network receiver.
     let name = committee.idp.name;
     let mut address = committee.idp.address;
     address.set_ip("0.0.0.0".parse().unwrap());
-    let handler = IdpHandler { tx_request };
+    let handler = IdpHandler {
+        tx_request,
+        tx_query,
+    };
     NetworkReceiver::spawn(address, handler);
 
     // Prevent the function from returning.
@@ -93,6 +143,8 @@ pub async fn spawn_idp<AkdStorage>(
         prover_handle,
         publisher_handle,
         synchronizer_handle,
+        query_server_handle,
+        metrics_handle,
     ])
     .await;
 }
@@ -100,20 +152,53 @@ pub async fn spawn_idp<AkdStorage>(
 /// Defines how the network receiver handles incoming messages.
 #[derive(Clone)]
 struct IdpHandler {
-    tx_request: Sender<Bytes>,
+    tx_request: Sender<UpdateRequest>,
+    tx_query: Sender<(ClientQuery, Replier)>,
 }
 
 #[async_trait]
 impl MessageHandler for IdpHandler {
     async fn dispatch(&self, writer: &mut Writer, serialized: Bytes) -> Result<(), Box<dyn Error>> {
-        // Reply with an ACK.
-        let _ = writer.send(Bytes::from("Ack")).await;
-
-        // Forward the request to the `Batcher`.
-        self.tx_request
-            .send(serialized)
-            .await
-            .expect("Failed to deliver request");
+        let message: ClientToIdPMessage = match codec::decode(&serialized) {
+            Ok(x) => x,
+            Err(e) => {
+                warn!("Failed to deserialize client message: {}", e);
+                return Ok(());
+            }
+        };
+
+        match message {
+            ClientToIdPMessage::Update(update) => {
+                // Reply with an ACK and forward the request to the `Batcher`.
+                let reply = codec::encode(&messages::IdPToClientMessage::Ack)
+                    .expect("Failed to serialize ack");
+                let _ = writer.send(Bytes::from(reply)).await;
+                self.tx_request
+                    .send(update)
+                    .await
+                    .expect("Failed to deliver request");
+            }
+            ClientToIdPMessage::LookupQuery(query) => {
+                let (sender, receiver) = oneshot::channel();
+                self.tx_query
+                    .send((ClientQuery::Lookup(query.label), sender))
+                    .await
+                    .expect("Failed to deliver query");
+                let reply = receiver.await.expect("Failed to receive query reply");
+                let serialized = codec::encode(&reply).expect("Failed to serialize reply");
+                let _ = writer.send(Bytes::from(serialized)).await;
+            }
+            ClientToIdPMessage::HistoryQuery(query) => {
+                let (sender, receiver) = oneshot::channel();
+                self.tx_query
+                    .send((ClientQuery::History(query.label), sender))
+                    .await
+                    .expect("Failed to deliver query");
+                let reply = receiver.await.expect("Failed to receive query reply");
+                let serialized = codec::encode(&reply).expect("Failed to serialize reply");
+                let _ = writer.send(Bytes::from(serialized)).await;
+            }
+        }
         Ok(())
     }
 }
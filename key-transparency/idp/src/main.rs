@@ -1,12 +1,23 @@
 use anyhow::{Context, Result};
 use clap::{arg, crate_name, crate_version, Arg, Command};
-use config::{Committee, Import, PrivateConfig};
+use config::{Committee, Import, PrivateConfig, StorageBackend};
 use idp::spawn_idp;
-use storage::{vkd_storage::AkdStorage, Storage};
+use storage::{
+    backend, backend::DynBackend, s3_backend::S3Backend, vkd_storage::AkdStorage, Storage,
+};
 
 /// The default maximum delay before sealing a batch (in ms).
 const DEFAULT_MAX_BATCH_DELAY: u64 = 5_000;
 
+/// The default base timeout before the publisher retransmits a notification (in ms).
+const DEFAULT_TIMEOUT_DELAY: u64 = 5_000;
+
+/// The default maximum number of certificates pushed to a lagging witness in a single round.
+const DEFAULT_MAX_RANGE_SPAN: u64 = 1_000;
+
+/// The default bind address for the Prometheus `/metrics` endpoint.
+const DEFAULT_METRICS_ADDRESS: &str = "127.0.0.1:9100";
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Read the cli parameters.
@@ -19,9 +30,13 @@ async fn main() -> Result<()> {
             arg!(--committee <FILE> "The path to the committee file"),
             arg!(--secure_storage <FILE> "The directory to hold the secure storage"),
             arg!(--sync_storage <FILE> "The directory to hold the sync storage"),
+            arg!(--merkle_storage <FILE> "The directory to hold the Merkle commitment storage"),
             arg!(--vkd_storage <FILE> "The directory to hold the big vkd database"),
             arg!(--batch_size <INT> "The number of client update requests to batch into a proof"),
             arg!(--max_batch_delay [INT] "The maximum delay (ms) before sealing a batch"),
+            arg!(--timeout_delay [INT] "The base timeout (ms) before retransmitting a notification"),
+            arg!(--max_range_span [INT] "The maximum number of certificates pushed to a lagging witness in a single round"),
+            arg!(--metrics_address [ADDR] "The address to serve the Prometheus /metrics endpoint on"),
         ])
         .arg_required_else_help(true)
         .get_matches();
@@ -45,6 +60,11 @@ async fn main() -> Result<()> {
     let private_config =
         PrivateConfig::import(private_config_file).context("Failed to load keypair")?;
 
+    // `KeyPair` deliberately does not implement `Clone`, so the `Publisher` (which re-signs
+    // proposals on a round timeout) gets its own copy loaded independently from the same file.
+    let publisher_private_config: PrivateConfig =
+        PrivateConfig::import(private_config_file).context("Failed to load keypair")?;
+
     let committee_file = matches.value_of("committee").unwrap();
     let committee = Committee::import(committee_file).context("Failed to load committee")?;
 
@@ -55,8 +75,27 @@ async fn main() -> Result<()> {
     let sync_storage_file = matches.value_of("sync_storage").unwrap();
     let sync_storage = Storage::new(sync_storage_file).context("Failed to create sync storage")?;
 
-    let vkd_storage_file = matches.value_of("vkd_storage").unwrap();
-    let vkd_storage = AkdStorage::new(vkd_storage_file);
+    let merkle_storage_file = matches.value_of("merkle_storage").unwrap();
+    let merkle_storage =
+        Storage::new(merkle_storage_file).context("Failed to create Merkle commitment storage")?;
+
+    // The backend choice lives in the private config rather than on the command line, since
+    // it is this entity's own deployment concern and not the protocol's.
+    let vkd_storage_backend: DynBackend = match &private_config.storage_backend {
+        StorageBackend::Local { .. } => {
+            let vkd_storage_file = matches.value_of("vkd_storage").unwrap();
+            let storage =
+                Storage::new(vkd_storage_file).context("Failed to create vkd storage")?;
+            // `AkdStorage::with_backend` (unlike `AkdStorage::new`) skips journal recovery, so
+            // this is the one place left to replay a batch a previous run crashed in the
+            // middle of before anything can observe it half-applied.
+            backend::recover_batch_journal(&storage)
+                .context("Failed to recover an incomplete batch-write journal")?;
+            Box::new(storage)
+        }
+        StorageBackend::S3 { bucket, prefix } => Box::new(S3Backend::new(bucket, prefix).await),
+    };
+    let vkd_storage = AkdStorage::with_backend(vkd_storage_backend);
 
     let batch_size = matches
         .value_of("batch_size")
@@ -71,15 +110,40 @@ async fn main() -> Result<()> {
         None => DEFAULT_MAX_BATCH_DELAY,
     };
 
+    let timeout_delay = match matches.value_of("timeout_delay") {
+        Some(x) => x
+            .parse::<u64>()
+            .context("The timeout delay must be a non-negative integer")?,
+        None => DEFAULT_TIMEOUT_DELAY,
+    };
+
+    let max_range_span = match matches.value_of("max_range_span") {
+        Some(x) => x
+            .parse::<u64>()
+            .context("The maximum range span must be a non-negative integer")?,
+        None => DEFAULT_MAX_RANGE_SPAN,
+    };
+
+    let metrics_address = matches
+        .value_of("metrics_address")
+        .unwrap_or(DEFAULT_METRICS_ADDRESS)
+        .parse()
+        .context("The metrics address must be a valid socket address")?;
+
     // Spawn the IdP.
     spawn_idp(
         /* keypair */ private_config.secret,
+        /* publisher_keypair */ publisher_private_config.secret,
         committee,
         secure_storage,
         sync_storage,
+        merkle_storage,
         vkd_storage,
         batch_size,
         max_batch_delay,
+        timeout_delay,
+        max_range_span,
+        metrics_address,
     )
     .await;
 
@@ -0,0 +1,34 @@
+//! Process-wide counters and gauges for the IdP, scraped via the `metrics` crate's HTTP
+//! server. Kept in one module (rather than spread per-file) so the set of exported metric
+//! names is easy to audit at a glance.
+
+use lazy_static::lazy_static;
+use prometheus::{IntCounter, IntGauge};
+
+lazy_static! {
+    /// Total number of batches sealed by the `Batcher`, whether triggered by reaching
+    /// `batch_size` or by the max-delay timer firing early.
+    pub static ref BATCHES_SEALED: IntCounter = metrics::register_int_counter(
+        "idp_batches_sealed_total",
+        "Total number of batches sealed"
+    );
+
+    /// Total number of client update requests folded into a sealed batch.
+    pub static ref UPDATES_BATCHED: IntCounter = metrics::register_int_counter(
+        "idp_updates_batched_total",
+        "Total number of client update requests folded into a sealed batch"
+    );
+
+    /// The sequence number of the latest certificate persisted by the `Synchronizer`.
+    pub static ref SEQUENCE_NUMBER: IntGauge = metrics::register_int_gauge(
+        "idp_sequence_number",
+        "The sequence number of the latest certificate persisted by the IdP"
+    );
+
+    /// The number of witnesses the `Synchronizer` currently has an outstanding catch-up
+    /// retry armed for.
+    pub static ref PENDING_SYNC_REQUESTS: IntGauge = metrics::register_int_gauge(
+        "idp_pending_sync_requests",
+        "Number of witnesses with an outstanding anti-entropy retry armed"
+    );
+}
@@ -0,0 +1,95 @@
+use crypto::PublicKey;
+use std::collections::HashMap;
+
+/// A kind of "impolite but not independently provable" peer behavior: something that wastes
+/// work without rising to the level of an `EquivocatingWitness`-style proof. Counted per kind
+/// rather than as one undifferentiated tally, so a peer doing a lot of one relatively benign
+/// thing does not trip the same threshold as a peer doing a little of everything.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Impoliteness {
+    /// Resent a vote identical to one this node already holds for the same sequence number.
+    DuplicateVote,
+    /// Voted for a round or a root other than the one this node is currently aggregating.
+    UnknownSequenceNumber,
+}
+
+/// Tracks, per peer, how many times each kind of `Impoliteness` has been observed. Borrows the
+/// "costly vs. beneficial message" accounting idea from gossip protocols: unlike
+/// `messages::publish::ConflictingVote`, a `PeerScore` is not cryptographic evidence of
+/// misbehavior, just a signal that a peer's per-message cost is no longer worth paying, so
+/// callers can disconnect it well before it ever produces hard proof.
+#[derive(Default)]
+pub struct PeerScore {
+    counts: HashMap<PublicKey, HashMap<Impoliteness, usize>>,
+}
+
+impl PeerScore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one more instance of `kind` from `peer`, returning the updated count for that
+    /// (peer, kind) pair.
+    pub fn record(&mut self, peer: PublicKey, kind: Impoliteness) -> usize {
+        let count = self
+            .counts
+            .entry(peer)
+            .or_insert_with(HashMap::new)
+            .entry(kind)
+            .or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// The total count, across every kind of impoliteness, recorded for `peer`.
+    pub fn total(&self, peer: &PublicKey) -> usize {
+        self.counts
+            .get(peer)
+            .map_or(0, |kinds| kinds.values().sum())
+    }
+
+    /// Whether `peer` has crossed `threshold` total impolite events and should be disconnected.
+    pub fn should_disconnect(&self, peer: &PublicKey, threshold: usize) -> bool {
+        self.total(peer) >= threshold
+    }
+
+    /// Forget every count recorded for `peer` (e.g. once it has been disconnected, so a later
+    /// reconnection starts with a clean slate).
+    pub fn forget(&mut self, peer: &PublicKey) {
+        self.counts.remove(peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{KeyPair, SignatureScheme};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn accumulates_and_disconnects_per_peer() {
+        let mut rng = StdRng::from_seed([4; 32]);
+        let (peer, _) = KeyPair::generate_keypair(SignatureScheme::Ed25519, &mut rng);
+        let (other, _) = KeyPair::generate_keypair(SignatureScheme::Ed25519, &mut rng);
+
+        let mut score = PeerScore::new();
+        assert_eq!(score.total(&peer), 0);
+        assert!(!score.should_disconnect(&peer, 3));
+
+        score.record(peer, Impoliteness::DuplicateVote);
+        score.record(peer, Impoliteness::UnknownSequenceNumber);
+        assert_eq!(score.total(&peer), 2);
+        assert!(!score.should_disconnect(&peer, 3));
+
+        score.record(peer, Impoliteness::DuplicateVote);
+        assert_eq!(score.total(&peer), 3);
+        assert!(score.should_disconnect(&peer, 3));
+
+        // Unrelated peers are tracked independently.
+        assert_eq!(score.total(&other), 0);
+
+        score.forget(&peer);
+        assert_eq!(score.total(&peer), 0);
+    }
+}
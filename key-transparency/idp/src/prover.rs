@@ -1,10 +1,13 @@
 use crate::STORE_LAST_NOTIFICATION_ADDR;
 use vkd::{directory::Directory, ecvrf::HardCodedAkdVRF};
-use crypto::KeyPair;
+use config::Committee;
+use crypto::{kzg_da, KeyPair, PublicKey};
 use futures::executor::block_on;
+use log::warn;
 use messages::{
+    merkle::BatchCommitment,
     publish::{Proof, PublishNotification},
-    update::Batch,
+    update::{Batch, SealedBatch},
     Blake3, Root, SequenceNumber,
 };
 use storage::Storage;
@@ -17,14 +20,20 @@ use tokio::{
 pub struct Prover<AkdStorage> {
     /// The private key material of the IdP.
     keypair: KeyPair,
-    /// Receive batches of clients' requests.
-    rx_batch: Receiver<Batch>,
+    /// The committee information, used to attach a data-availability commitment and per-witness
+    /// shares to each notification when the committee runs that layer.
+    committee: Committee,
+    /// Receive sealed batches of clients' requests, with their Merkle commitment.
+    rx_batch: Receiver<SealedBatch>,
     /// Outputs handles waiting to receive witnesses' votes.
     tx_notification: Sender<PublishNotification>,
     /// The sequence number of the last notification created by the IdP.
     sequence_number: SequenceNumber,
     /// The `vkd` key directory.
     vkd: Directory<AkdStorage, HardCodedAkdVRF>,
+    /// Persists each batch's Merkle commitment keyed by sequence number, so a client's
+    /// inclusion proof request remains serveable after the IdP restarts.
+    merkle_storage: Storage,
 }
 
 impl<AkdStorage> Prover<AkdStorage>
@@ -34,9 +43,11 @@ where
     /// Spawn a new `Prover`.
     pub fn spawn(
         keypair: KeyPair,
+        committee: Committee,
         secure_storage: &Storage,
+        merkle_storage: Storage,
         vkd_storage: AkdStorage,
-        rx_batch: Receiver<Batch>,
+        rx_batch: Receiver<SealedBatch>,
         tx_notification: Sender<PublishNotification>,
     ) -> JoinHandle<()> {
         // Load the last sequence number and perform initialization steps.
@@ -47,17 +58,19 @@ where
             // Make or load the vkd directory.
             let db = vkd_storage;
             let vrf = HardCodedAkdVRF {};
-            let vkd = Directory::new::<Blake3>(&db, &vrf, false)
+            let vkd = Directory::new::<Blake3>(&db, &vrf)
                 .await
                 .expect("Failed to create vkd");
 
             // Run a new `NotificationMaker`.
             Self {
                 keypair,
+                committee,
                 rx_batch,
                 tx_notification,
                 sequence_number,
                 vkd,
+                merkle_storage,
             }
             .run()
             .await;
@@ -123,9 +136,62 @@ where
         (root, proof)
     }
 
+    /// KZG-commit to and Reed-Solomon-encode `proof`, so witnesses can serve an auditor the
+    /// means to reconstruct it without trusting the IdP. `None` if the committee does not run
+    /// the data-availability layer, or if `proof` needs more chunks than fit within the
+    /// committee's `validity_threshold()` (so that any `validity_threshold()` honest witnesses
+    /// are always enough to reconstruct it) — in that case the notification is sent without a
+    /// commitment rather than blocking liveness.
+    fn data_availability(
+        &self,
+        proof: &Proof,
+    ) -> Option<(kzg_da::Commitment, Vec<(PublicKey, kzg_da::Share)>)> {
+        let srs = self.committee.data_availability_srs.as_ref()?;
+
+        let blob = bincode::serialize(proof).expect("Failed to serialize proof");
+        let (commitment, shares) = match kzg_da::encode(srs, &blob) {
+            Ok(result) => result,
+            Err(kzg_da::DaError::SrsTooSmall { required, available }) => {
+                warn!(
+                    "Audit proof needs {} data-availability chunks but the committee's SRS only \
+                     supports {}; omitting the commitment for this notification",
+                    required, available
+                );
+                return None;
+            }
+            Err(e) => panic!("Failed to encode data-availability shares: {}", e),
+        };
+        // `shares` is the full `2n`-point Reed-Solomon codeword, but `kzg_da::reconstruct` only
+        // needs `n` (`shares.len() / 2`) of them back -- that's the number that must fit within
+        // `validity_threshold()` for any `validity_threshold()` honest witnesses to always be
+        // enough to reconstruct the blob.
+        let reconstruction_threshold = shares.len() / 2;
+        if reconstruction_threshold > self.committee.validity_threshold() as usize {
+            warn!(
+                "Audit proof needs {} data-availability shares to reconstruct, which exceeds \
+                 the committee's validity threshold of {}; omitting the commitment for this \
+                 notification",
+                reconstruction_threshold,
+                self.committee.validity_threshold()
+            );
+            return None;
+        }
+
+        // Round-robin the shares over the committee so every witness gets one, even if there
+        // are more witnesses than shares.
+        let data_shares = self
+            .committee
+            .witnesses
+            .keys()
+            .enumerate()
+            .map(|(i, name)| (*name, shares[i % shares.len()].clone()))
+            .collect();
+        Some((commitment, data_shares))
+    }
+
     /// Main loop receiving batches of client requests.
     async fn run(&mut self) {
-        while let Some(batch) = self.rx_batch.recv().await {
+        while let Some((batch, commitment)) = self.rx_batch.recv().await {
             #[cfg(feature = "benchmark")]
             Self::link_requests_and_notifications(self.sequence_number + 1, &batch);
 
@@ -135,9 +201,28 @@ where
             // Increment the sequence number.
             self.sequence_number += 1;
 
-            // Make a new publish notification.
-            let notification =
-                PublishNotification::new(root, proof, self.sequence_number, &self.keypair);
+            // Persist the batch's Merkle commitment keyed by sequence number, so an inclusion
+            // proof for this batch remains serveable even after the IdP restarts.
+            let serialized_commitment =
+                bincode::serialize(&commitment).expect("Failed to serialize commitment");
+            self.merkle_storage
+                .write(&self.sequence_number.to_le_bytes(), &serialized_commitment)
+                .expect("Failed to persist Merkle commitment");
+
+            // Make a new publish notification, proposed at round 0. The `Publisher` bumps the
+            // round (and re-signs) if this proposal doesn't reach quorum in time.
+            let data_availability = self.data_availability(&proof);
+            let mut notification = PublishNotification::new(
+                root,
+                proof,
+                self.sequence_number,
+                /* round */ 0,
+                &self.keypair,
+            )
+            .with_fork_id(self.committee.fork_id);
+            if let Some((commitment, shares)) = data_availability {
+                notification = notification.with_data_availability(commitment, shares);
+            }
 
             // Send the notification to the broadcaster.
             self.tx_notification
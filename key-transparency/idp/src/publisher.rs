@@ -5,27 +5,94 @@ use crate::{
 };
 use bytes::Bytes;
 use config::Committee;
-use crypto::PublicKey;
+use crypto::{KeyPair, PublicKey};
 use futures::stream::{futures_unordered::FuturesUnordered, StreamExt};
 use log::{debug, info, warn};
 use messages::{
+    codec, ensure,
     error::{IdpError, IdpResult, WitnessError},
     publish::{PublishNotification, PublishVote},
-    IdPToWitnessMessage, Root, SequenceNumber, WitnessToIdPMessage,
+    IdPToWitnessMessage, Root, Round, SequenceNumber, WitnessToIdPMessage,
 };
 use network::reliable_sender::{CancelHandler, ReliableSender};
-use std::net::SocketAddr;
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+};
 use storage::Storage;
 use tokio::{
     sync::{
         mpsc::{Receiver, Sender},
-        oneshot,
+        oneshot, watch,
     },
     task::JoinHandle,
+    time::{sleep, Duration, Instant, Sleep},
 };
+use tokio_util::time::{delay_queue, DelayQueue};
+
+/// The cap on the retransmission timeout's backoff: the timeout doubles every time it fires
+/// without gathering a quorum, up to `MAX_TIMEOUT_MULTIPLIER * base_timeout`.
+const MAX_TIMEOUT_MULTIPLIER: u32 = 8;
+
+/// The maximum number of times the round timer may fire for a single sequence number before
+/// `publish` gives up and surfaces a recoverable `QuorumTimeout` error, rather than bumping the
+/// round forever against witnesses that may simply be unreachable.
+const MAX_ROUND_ATTEMPTS: u32 = 10;
+
+/// How long `run` waits for a witness to ack a certificate before giving up on that waiter.
+const STATE_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The maximum number of certificate-ack waiters `run` tracks for a single witness before it
+/// stops enqueuing new ones for that witness, so a witness that never acks its certificates
+/// can't grow the Publisher's memory usage without bound.
+const MAX_PENDING_STATE_RESPONSES_PER_WITNESS: usize = 100;
+
+/// The total number of soft `Impoliteness` events (see `crate::peer_score`), as counted by
+/// `Aggregator::misbehavior_score` over the `Publisher`'s lifetime, at which a witness is
+/// excluded from every later notification broadcast.
+const MISBEHAVIOR_DISCONNECT_THRESHOLD: usize = 5;
+
+/// A cancelable, exponentially-backed-off countdown: fires when a quorum of votes hasn't been
+/// gathered in time, prompting the publisher to bump the round and re-propose to every witness.
+/// Modeled after the round-change timers of Tendermint-style consensus protocols.
+struct Timer {
+    /// The initial delay (ms) the timer is reset to after a quorum is reached.
+    base_delay: u64,
+    /// The current delay (ms), possibly backed off from `base_delay`.
+    current_delay: u64,
+    /// The underlying sleep future.
+    sleep: std::pin::Pin<Box<Sleep>>,
+}
+
+impl Timer {
+    /// Create a new timer with the given base delay (ms), armed to fire `base_delay` ms from now.
+    fn new(base_delay: u64) -> Self {
+        Self {
+            base_delay,
+            current_delay: base_delay,
+            sleep: Box::pin(sleep(Duration::from_millis(base_delay))),
+        }
+    }
+
+    /// Re-arm the timer with a doubled delay, capped at `MAX_TIMEOUT_MULTIPLIER * base_delay`.
+    fn backoff(&mut self) {
+        let max_delay = self.base_delay * MAX_TIMEOUT_MULTIPLIER as u64;
+        self.current_delay = (self.current_delay * 2).min(max_delay);
+        self.arm();
+    }
+
+    /// Reset the underlying sleep future to fire `current_delay` ms from now.
+    fn arm(&mut self) {
+        self.sleep
+            .as_mut()
+            .reset(Instant::now() + Duration::from_millis(self.current_delay));
+    }
+}
 
 /// Broadcast publish notifications to the witnesses, gather votes and broadcast certificates.
 pub struct Publisher {
+    /// The private key material of the IdP, used to re-sign a proposal when bumping its round.
+    keypair: KeyPair,
     /// The persistent storage.
     storage: Storage,
     /// Receive serialized publish notifications to broadcast.
@@ -34,6 +101,9 @@ pub struct Publisher {
     tx_trigger: Sender<SyncTrigger>,
     /// Deliver newly created certificates.
     tx_certificate: Sender<NewCertificate>,
+    /// Broadcasts the sequence number of the latest witness-certified root, so the
+    /// `QueryServer` knows which epochs it may safely serve proofs for.
+    tx_certified_epoch: watch::Sender<SequenceNumber>,
     /// A reliable network sender.
     network: ReliableSender,
     /// The public keys of the witnesses (in the same order as the `addresses` field).
@@ -42,30 +112,49 @@ pub struct Publisher {
     addresses: Vec<SocketAddr>,
     /// A votes aggregator to assemble a quorum of votes into a certificate.
     aggregator: Aggregator,
+    /// Witnesses excluded from future notification broadcasts after crossing
+    /// `MISBEHAVIOR_DISCONNECT_THRESHOLD` (see [`Self::disconnect_if_misbehaving`]). Never
+    /// cleared: a witness this misbehaved stays disconnected for the lifetime of this
+    /// `Publisher`.
+    disconnected: HashSet<PublicKey>,
+    /// The base retransmission timeout (ms).
+    timeout_delay: u64,
+    /// The committee's configured ceiling on a single serialized wire message, checked before
+    /// broadcasting a notification or certificate.
+    max_payload_size: usize,
 }
 
 impl Publisher {
     /// Spawn a new broadcaster.
     pub fn spawn(
+        keypair: KeyPair,
         committee: Committee,
         storage: Storage,
         rx_notification: Receiver<PublishNotification>,
         tx_trigger: Sender<SyncTrigger>,
         tx_certificate: Sender<NewCertificate>,
+        tx_certified_epoch: watch::Sender<SequenceNumber>,
+        timeout_delay: u64,
     ) -> JoinHandle<()> {
         tokio::spawn(async move {
             let (names, addresses) = committee.witnesses_addresses().into_iter().unzip();
+            let max_payload_size = committee.max_payload_size;
             Self {
+                keypair,
                 storage,
                 rx_notification,
                 tx_trigger,
                 tx_certificate,
+                tx_certified_epoch,
                 network: ReliableSender::new(),
                 names,
                 addresses,
                 // The aggregator will be reset with the correct root hash upon receiving the
                 // first publish notification.
                 aggregator: Aggregator::new(committee, Root::default()),
+                disconnected: HashSet::new(),
+                timeout_delay,
+                max_payload_size,
             }
             .run()
             .await;
@@ -92,6 +181,26 @@ impl Publisher {
         receiver
     }
 
+    /// If `witness` has crossed `MISBEHAVIOR_DISCONNECT_THRESHOLD` soft misbehavior events (see
+    /// `Aggregator::should_disconnect`), stop sending it future notifications: it is added to
+    /// `self.disconnected`, which `Self::broadcast_notification` excludes from here on.
+    fn disconnect_if_misbehaving(&mut self, witness: PublicKey) {
+        if self.disconnected.contains(&witness) {
+            return;
+        }
+        if self
+            .aggregator
+            .should_disconnect(&witness, MISBEHAVIOR_DISCONNECT_THRESHOLD)
+        {
+            warn!(
+                "Disconnecting witness {} after {} misbehavior events",
+                witness,
+                self.aggregator.misbehavior_score(&witness)
+            );
+            self.disconnected.insert(witness);
+        }
+    }
+
     /// Parse the witnesses' reply to a IdP publish notification.
     fn parse_notification_reply(message: WitnessToIdPMessage) -> IdpResult<PublishVote> {
         match message {
@@ -108,124 +217,272 @@ impl Publisher {
         (reply, author)
     }
 
-    /// Publish a new update to the witnesses.
-    async fn publish(
-        &mut self,
-        notification: PublishNotification,
-    ) -> Vec<(CancelHandler, PublicKey)> {
-        let sequence_number = notification.sequence_number;
+    /// Like `waiter`, but also carries the `delay_queue::Key` of this waiter's entry in
+    /// `run`'s `state_response_deadlines`, so the caller can cancel the matching deadline once
+    /// the witness actually acks the certificate.
+    async fn state_waiter(
+        wait_for: CancelHandler,
+        author: PublicKey,
+        deadline: delay_queue::Key,
+    ) -> (Bytes, PublicKey, delay_queue::Key) {
+        let reply = wait_for
+            .await
+            .expect("Failed to receive response from network");
+        (reply, author, deadline)
+    }
 
-        // Reset the aggregator to hold the votes for ths notification.
-        self.aggregator.reset(notification.root);
+    /// Re-propose the same root at a higher round, re-signed by the IdP. Used when a round
+    /// times out without reaching quorum: the digest (and thus every witness's previous
+    /// signature) is bound to the round, so the proposal must be re-signed rather than merely
+    /// retransmitted.
+    fn bump_round(&self, notification: &PublishNotification, round: Round) -> PublishNotification {
+        let bumped = PublishNotification::new(
+            notification.root,
+            notification.proof.clone(),
+            notification.sequence_number,
+            round,
+            &self.keypair,
+        );
+        let bumped = match &notification.data_commitment {
+            Some(commitment) => {
+                bumped.with_data_availability(commitment.clone(), notification.data_shares.clone())
+            }
+            None => bumped,
+        };
+        bumped.with_fork_id(notification.fork_id)
+    }
 
-        // Serialize the notification.
-        let message = IdPToWitnessMessage::PublishNotification(notification);
-        let serialized_notification =
-            bincode::serialize(&message).expect("Failed to serialize notification");
+    /// Serialize, persist, and broadcast `notification` to every witness, returning the
+    /// serialized bytes (kept around for the anti-entropy retry path) and a handle per witness
+    /// to await its reply. Fails with `IdpError::PayloadTooLarge` rather than broadcasting a
+    /// message larger than the committee's configured `max_payload_size`.
+    async fn broadcast_notification(
+        &mut self,
+        notification: &PublishNotification,
+    ) -> IdpResult<(Bytes, Vec<(CancelHandler, PublicKey)>)> {
+        let message = IdPToWitnessMessage::PublishNotification(notification.clone());
+        let serialized = codec::encode(&message).expect("Failed to serialize notification");
+        ensure!(
+            serialized.len() <= self.max_payload_size,
+            IdpError::PayloadTooLarge {
+                length: serialized.len(),
+                max_length: self.max_payload_size,
+            }
+        );
 
         // Persist the last notification to storage.
         self.storage
-            .write(&STORE_LAST_NOTIFICATION_ADDR, &serialized_notification)
+            .write(&STORE_LAST_NOTIFICATION_ADDR, &serialized)
             .expect("Failed to persist notification");
 
-        // Broadcast the publish notification to the witnesses.
-        let bytes_notification = Bytes::from(serialized_notification);
-        let addresses = self.addresses.clone();
-        let mut wait_for_quorum: FuturesUnordered<_> = self
+        let bytes = Bytes::from(serialized);
+        // Skip witnesses `Self::disconnect_if_misbehaving` has already given up on.
+        let (names, addresses): (Vec<_>, Vec<_>) = self
+            .names
+            .iter()
+            .cloned()
+            .zip(self.addresses.iter().cloned())
+            .filter(|(name, _)| !self.disconnected.contains(name))
+            .unzip();
+        let handles = self
             .network
-            .broadcast(addresses, bytes_notification.clone())
+            .broadcast(addresses, bytes.clone())
             .await
             .into_iter()
-            .zip(self.names.iter().cloned())
+            .zip(names)
+            .collect();
+        Ok((bytes, handles))
+    }
+
+    /// Publish a new update to the witnesses. Gives up and returns a recoverable
+    /// `IdpError::QuorumTimeout` if `MAX_ROUND_ATTEMPTS` round timeouts elapse without a
+    /// quorum, rather than bumping the round forever.
+    async fn publish(
+        &mut self,
+        notification: PublishNotification,
+    ) -> IdpResult<Vec<(CancelHandler, PublicKey)>> {
+        let sequence_number = notification.sequence_number;
+        let mut round = notification.round;
+        let mut notification = notification;
+        let mut attempts = 0;
+
+        // Reset the aggregator to hold the votes for this notification.
+        self.aggregator.reset(notification.root, sequence_number, round);
+
+        // Broadcast the publish notification to the witnesses.
+        let (mut bytes_notification, handles) = self.broadcast_notification(&notification).await?;
+        let mut wait_for_quorum: FuturesUnordered<_> = handles
+            .into_iter()
             .map(|(handle, name)| Self::waiter(handle, name))
             .collect();
 
+        let mut timer = Timer::new(self.timeout_delay);
+
         // Collect the votes and assemble a certificate.
-        while let Some((reply, author)) = wait_for_quorum.next().await {
-            // Deserialize the reply.
-            let message: WitnessToIdPMessage = match bincode::deserialize(&reply) {
-                Ok(x) => x,
-                Err(e) => {
-                    warn!("{:?}", e);
-                    continue;
-                }
-            };
-
-            // Check if the witness is out of date. If that is the case, update it.
-            if let Some(status) = message.sequence_number() {
-                if status < sequence_number {
-                    debug!("{} is outdated ({} < {})", author, status, sequence_number);
-                    let last_notification = bytes_notification.clone();
-                    let handle = self.sync_and_retry(author, status, last_notification).await;
-                    wait_for_quorum.push(Self::waiter(handle, author));
-                    continue;
-                }
-            }
+        loop {
+            tokio::select! {
+                Some((reply, author)) = wait_for_quorum.next() => {
+                    // Deserialize the reply.
+                    let message: WitnessToIdPMessage = match codec::decode(&reply) {
+                        Ok(x) => x,
+                        Err(e) => {
+                            warn!("{:?}", e);
+                            continue;
+                        }
+                    };
 
-            // Finally parse the publish vote.
-            let vote = match Self::parse_notification_reply(message) {
-                Ok(vote) => {
-                    debug!("Received {:?}", vote);
-                    vote
-                }
-                Err(e) => {
-                    warn!("{:?}", e);
-                    continue;
-                }
-            };
-
-            // Check if we got enough votes to make a certificate.
-            let potential_certificate = match self.aggregator.append(vote) {
-                Ok(x) => x,
-                Err(e) => {
-                    warn!("{}", e);
-                    continue;
-                }
-            };
-            if let Some(certificate) = potential_certificate {
-                debug!("Commit {:?}", certificate);
-                // NOTE: This log entry is used to compute performance.
-                info!("Commit {}", certificate);
-
-                // Serialize the certificate.
-                let message = IdPToWitnessMessage::PublishCertificate(certificate);
-                let serialized =
-                    bincode::serialize(&message).expect("Failed to serialize certificate");
-
-                // Send it to the synchronizer and ensure it is correctly stored.
-                let (sender, receiver) = oneshot::channel();
-                let message = NewCertificate {
-                    sequence_number,
-                    certificate: serialized.clone(),
-                    ack: sender,
-                };
-                self.tx_certificate
-                    .send(message)
-                    .await
-                    .expect("Failed to deliver certificate");
-                receiver.await.expect("Failed to ack new certificate");
-
-                // Broadcast the certificate to the witnesses.
-                let bytes = Bytes::from(serialized);
-                let handles = self
-                    .network
-                    .broadcast(self.addresses.clone(), bytes)
-                    .await
-                    .into_iter()
-                    .zip(self.names.iter().cloned())
-                    .collect();
+                    // Check if the witness is out of date. If that is the case, update it.
+                    if let Some(status) = message.sequence_number() {
+                        if status < sequence_number {
+                            debug!("{} is outdated ({} < {})", author, status, sequence_number);
+                            let last_notification = bytes_notification.clone();
+                            let handle = self.sync_and_retry(author, status, last_notification).await;
+                            wait_for_quorum.push(Self::waiter(handle, author));
+                            continue;
+                        }
+                    }
+
+                    // Finally parse the publish vote.
+                    let vote = match Self::parse_notification_reply(message) {
+                        Ok(vote) => {
+                            debug!("Received {:?}", vote);
+                            vote
+                        }
+                        Err(e) => {
+                            warn!("{:?}", e);
+                            continue;
+                        }
+                    };
+
+                    // Check if we got enough votes to make a certificate.
+                    let potential_certificate = match self.aggregator.append(vote) {
+                        Ok(x) => x,
+                        Err(IdpError::EquivocatingWitness(proof)) => {
+                            // `Aggregator::penalize` trusts its caller to have verified the
+                            // misbehavior proof first (see its doc comment): re-check both votes
+                            // are genuinely signed by the same witness and genuinely conflict
+                            // before acting on it, rather than taking `Aggregator::append`'s word
+                            // for it.
+                            let committee =
+                                self.aggregator.committee_for(proof.vote_1.sequence_number);
+                            if let Err(e) = proof.verify(&committee) {
+                                warn!("Rejected invalid equivocation proof: {:?}", e);
+                                continue;
+                            }
+
+                            // A witness double-voted: zero its voting power so it cannot keep
+                            // forming quorums with a third, conflicting vote, and log the proof
+                            // so an operator (or anyone else) can hold it accountable.
+                            let offender = proof.vote_1.author;
+                            warn!("Detected equivocation by {}: {:?}", offender, proof);
+                            self.aggregator.penalize(&offender);
+                            self.disconnect_if_misbehaving(offender);
+                            continue;
+                        }
+                        Err(IdpError::UnexpectedRound { .. }) => {
+                            // A stale vote for a round we have already moved past; harmless
+                            // unless it keeps happening (checked below). `append` already
+                            // recorded this as soft `Impoliteness` against `author`.
+                            self.disconnect_if_misbehaving(author);
+                            continue;
+                        }
+                        Err(e) => {
+                            warn!("{}", e);
+                            // `append` records `Impoliteness` against `author` for every soft
+                            // error path it can attribute to a specific witness (unexpected
+                            // vote, duplicate vote); harmless one-offs, but worth disconnecting
+                            // a witness that keeps triggering them.
+                            self.disconnect_if_misbehaving(author);
+                            continue;
+                        }
+                    };
+                    if let Some(certificate) = potential_certificate {
+                        debug!("Commit {:?}", certificate);
+                        // NOTE: This log entry is used to compute performance.
+                        info!("Commit {}", certificate);
 
-                // Stop waiting for votes.
-                return handles;
+                        // Serialize the certificate.
+                        let message = IdPToWitnessMessage::PublishCertificate(certificate);
+                        let serialized =
+                            codec::encode(&message).expect("Failed to serialize certificate");
+                        ensure!(
+                            serialized.len() <= self.max_payload_size,
+                            IdpError::PayloadTooLarge {
+                                length: serialized.len(),
+                                max_length: self.max_payload_size,
+                            }
+                        );
+
+                        // Send it to the synchronizer and ensure it is correctly stored.
+                        let (sender, receiver) = oneshot::channel();
+                        let message = NewCertificate {
+                            sequence_number,
+                            certificate: serialized.clone(),
+                            ack: sender,
+                        };
+                        self.tx_certificate
+                            .send(message)
+                            .await
+                            .expect("Failed to deliver certificate");
+                        receiver.await.expect("Failed to ack new certificate");
+
+                        // Let the `QueryServer` know it may now serve proofs up to this epoch.
+                        let _ = self.tx_certified_epoch.send(sequence_number);
+
+                        // Broadcast the certificate to the witnesses.
+                        let bytes = Bytes::from(serialized);
+                        let handles = self
+                            .network
+                            .broadcast(self.addresses.clone(), bytes)
+                            .await
+                            .into_iter()
+                            .zip(self.names.iter().cloned())
+                            .collect();
+
+                        // Stop waiting for votes.
+                        return Ok(handles);
+                    }
+                },
+
+                () = &mut timer.sleep => {
+                    attempts += 1;
+                    if attempts >= MAX_ROUND_ATTEMPTS {
+                        warn!(
+                            "Giving up on sequence {} after {} round timeouts",
+                            sequence_number, attempts
+                        );
+                        return Err(IdpError::QuorumTimeout { sequence_number, attempts });
+                    }
+
+                    // No quorum yet: this round's digest is now considered dead, so bump the
+                    // round (re-signing the same root) and have every witness vote again, not
+                    // just the ones that hadn't replied yet — their old-round votes no longer
+                    // count towards the new round's quorum.
+                    round += 1;
+                    warn!(
+                        "No quorum for sequence {} at round {}, advancing to round {} (attempt {}/{})",
+                        sequence_number, round - 1, round, attempts, MAX_ROUND_ATTEMPTS
+                    );
+                    notification = self.bump_round(&notification, round);
+                    self.aggregator.reset(notification.root, sequence_number, round);
+
+                    let (new_bytes, handles) = self.broadcast_notification(&notification).await?;
+                    bytes_notification = new_bytes;
+                    wait_for_quorum = handles
+                        .into_iter()
+                        .map(|(handle, name)| Self::waiter(handle, name))
+                        .collect();
+
+                    timer.backoff();
+                },
             }
         }
-        panic!("Failed to gather quorum of votes");
     }
 
     /// Analyses the witnesses response to IdP's publishes certificates.
     async fn analyze_state_response(&mut self, reply: Bytes, author: PublicKey) {
         // Deserialize the reply.
-        let message: WitnessToIdPMessage = match bincode::deserialize(&reply) {
+        let message: WitnessToIdPMessage = match codec::decode(&reply) {
             Ok(x) => x,
             Err(e) => {
                 warn!("{:?}", e);
@@ -255,25 +512,97 @@ impl Publisher {
 
     /// Main loop receiving new notifications to publish.
     async fn run(&mut self) {
-        // Gather certificates handles to receive state ack.
-        // TODO: Make this memory-bound (like the synchronizer). A bad witness can make us run out
-        // of memory by never replying to our certificates.
+        // Gather certificates handles to receive state ack. Bounded per witness by
+        // `pending_state_responses`: a witness that never acks a certificate stops getting new
+        // waiters tracked once it hits `MAX_PENDING_STATE_RESPONSES_PER_WITNESS`, and
+        // `state_response_deadlines` reclaims a waiter's slot in that budget once it has waited
+        // longer than `STATE_RESPONSE_TIMEOUT`, regardless of whether the witness ever replies.
         let mut state_responses = FuturesUnordered::new();
+        let mut state_response_deadlines: DelayQueue<PublicKey> = DelayQueue::new();
+        // Deadlines not yet expired or acked; an ack arriving for a key already removed from
+        // here (because its deadline already fired) must not touch `state_response_deadlines`
+        // again, since `DelayQueue` drops an entry as soon as it is yielded by `next`.
+        let mut live_deadlines: HashSet<delay_queue::Key> = HashSet::new();
+        let mut pending_state_responses: HashMap<PublicKey, usize> = HashMap::new();
 
         loop {
             tokio::select! {
                 // Receive serialized publish notifications.
-                Some(notification) = self.rx_notification.recv() => self
-                    .publish(notification)
-                    .await
-                    .into_iter()
-                    .for_each(|(handle, author)| state_responses.push(Self::waiter(handle, author))),
+                Some(notification) = self.rx_notification.recv() => match self.publish(notification).await {
+                    Ok(handles) => {
+                        for (handle, author) in handles {
+                            let pending = pending_state_responses.entry(author).or_insert(0);
+                            if *pending >= MAX_PENDING_STATE_RESPONSES_PER_WITNESS {
+                                warn!(
+                                    "Dropping certificate-ack waiter for {}: already tracking {} pending",
+                                    author, pending
+                                );
+                                continue;
+                            }
+                            *pending += 1;
+
+                            let deadline_key = state_response_deadlines.insert(author, STATE_RESPONSE_TIMEOUT);
+                            live_deadlines.insert(deadline_key);
+                            state_responses.push(Self::state_waiter(handle, author, deadline_key));
+                        }
+                    }
+                    // Recoverable: the witnesses that failed to reach quorum for this update
+                    // are presumably unreachable or malfunctioning; log and move on to the
+                    // next notification rather than crashing the whole IdP task.
+                    Err(e) => warn!("{}", e),
+                },
 
                 // Receive state ack from the witnesses.
-                Some((reply, author)) = state_responses.next() => self
-                    .analyze_state_response(reply,author)
-                    .await,
+                Some((reply, author, deadline_key)) = state_responses.next() => {
+                    if live_deadlines.remove(&deadline_key) {
+                        state_response_deadlines.remove(&deadline_key);
+                    }
+                    if let Some(pending) = pending_state_responses.get_mut(&author) {
+                        *pending = pending.saturating_sub(1);
+                    }
+                    self.analyze_state_response(reply, author).await;
+                },
+
+                // A witness hasn't acked its certificate in time: give up on that waiter's slot
+                // in the witness's budget, rather than holding it (and the memory it implies)
+                // forever. The underlying network waiter may still resolve later and is simply
+                // ignored at that point (its deadline key is no longer in `live_deadlines`).
+                Some(expired) = state_response_deadlines.next() => {
+                    live_deadlines.remove(&expired.key());
+                    let author = expired.into_inner();
+                    warn!("Timed out waiting for {} to ack a certificate", author);
+                    if let Some(pending) = pending_state_responses.get_mut(&author) {
+                        *pending = pending.saturating_sub(1);
+                    }
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_backoff_doubles_and_caps() {
+        let mut timer = Timer::new(100);
+        assert_eq!(timer.current_delay, 100);
+
+        timer.backoff();
+        assert_eq!(timer.current_delay, 200);
+
+        timer.backoff();
+        assert_eq!(timer.current_delay, 400);
+
+        timer.backoff();
+        assert_eq!(timer.current_delay, 800);
+
+        // Capped at `MAX_TIMEOUT_MULTIPLIER` (8) times the base delay: further backoffs stop
+        // growing the delay instead of escalating it without bound.
+        timer.backoff();
+        assert_eq!(timer.current_delay, 800);
+        timer.backoff();
+        assert_eq!(timer.current_delay, 800);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::Replier;
+use vkd::{directory::Directory, ecvrf::HardCodedAkdVRF, storage::types::AkdLabel};
+use messages::{
+    error::MessageError,
+    query::{HistoryResponse, LookupResponse},
+    Blake3, IdPToClientMessage, SequenceNumber,
+};
+use tokio::sync::{mpsc::Receiver, watch};
+
+/// A client query routed to the `QueryServer`.
+#[derive(Debug)]
+pub enum ClientQuery {
+    /// Look up the latest certified value of a label.
+    Lookup(AkdLabel),
+    /// Fetch the full certified version history of a label.
+    History(AkdLabel),
+}
+
+/// Answers clients' lookup and key-history queries against the latest *certified* root,
+/// i.e. the one backed by a `PublishCertificate` that a quorum of witnesses signed. This
+/// lets clients verify inclusion (or history) without trusting the IdP.
+pub struct QueryServer<AkdStorage> {
+    /// The `vkd` key directory (shared storage with the `Prover`).
+    vkd: Directory<AkdStorage, HardCodedAkdVRF>,
+    /// Receive client queries.
+    rx_query: Receiver<(ClientQuery, Replier)>,
+    /// Tracks the sequence number of the latest witness-certified root.
+    certified_epoch: watch::Receiver<SequenceNumber>,
+}
+
+impl<AkdStorage> QueryServer<AkdStorage>
+where
+    AkdStorage: vkd::storage::Storage + Sync + Send + 'static,
+{
+    /// Spawn a new `QueryServer` task.
+    pub fn spawn(
+        vkd_storage: AkdStorage,
+        rx_query: Receiver<(ClientQuery, Replier)>,
+        certified_epoch: watch::Receiver<SequenceNumber>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let vrf = HardCodedAkdVRF {};
+            let vkd = Directory::new::<Blake3>(&vkd_storage, &vrf)
+                .await
+                .expect("Failed to open vkd directory for queries");
+
+            Self {
+                vkd,
+                rx_query,
+                certified_epoch,
+            }
+            .run()
+            .await;
+        })
+    }
+
+    /// Main loop answering client queries.
+    async fn run(&mut self) {
+        while let Some((query, replier)) = self.rx_query.recv().await {
+            let reply = self.answer(query).await;
+            let _ = replier.send(reply);
+        }
+    }
+
+    /// Produce a reply for a single query, refusing to serve proofs for epochs that are not
+    /// yet backed by a witness quorum.
+    async fn answer(&self, query: ClientQuery) -> IdPToClientMessage {
+        let certified = *self.certified_epoch.borrow();
+        let current_azks = match self.vkd.retrieve_current_azks().await {
+            Ok(azks) => azks,
+            Err(e) => {
+                let error = MessageError::SerializationError(e.to_string());
+                return Self::wrap(query, Err(error));
+            }
+        };
+
+        // Refuse to serve a proof over state more recent than what a witness quorum certified;
+        // a client could otherwise be fed a root the IdP alone vouches for.
+        if current_azks.get_latest_epoch() > certified {
+            return Self::wrap(query, Err(MessageError::EpochNotCertified(certified)));
+        }
+
+        match query {
+            ClientQuery::Lookup(label) => {
+                let result = self
+                    .vkd
+                    .lookup::<Blake3>(label)
+                    .await
+                    .map(|proof| LookupResponse {
+                        sequence_number: certified,
+                        proof,
+                    })
+                    .map_err(|e| MessageError::PoofVerificationFailed(e.to_string()));
+                IdPToClientMessage::LookupResponse(result)
+            }
+            ClientQuery::History(label) => {
+                let result = self
+                    .vkd
+                    .key_history::<Blake3>(&label)
+                    .await
+                    .map(|proof| HistoryResponse {
+                        sequence_number: certified,
+                        proof,
+                    })
+                    .map_err(|e| MessageError::PoofVerificationFailed(e.to_string()));
+                IdPToClientMessage::HistoryResponse(result)
+            }
+        }
+    }
+
+    /// Wrap an error into the reply variant matching the original query.
+    fn wrap(query: ClientQuery, error: Result<(), MessageError>) -> IdPToClientMessage {
+        let error = error.unwrap_err();
+        match query {
+            ClientQuery::Lookup(_) => IdPToClientMessage::LookupResponse(Err(error)),
+            ClientQuery::History(_) => IdPToClientMessage::HistoryResponse(Err(error)),
+        }
+    }
+}
@@ -1,8 +1,9 @@
+use crate::metrics;
 use bytes::Bytes;
 use config::Committee;
 use crypto::PublicKey;
 use futures::stream::{futures_unordered::FuturesUnordered, StreamExt};
-use log::debug;
+use log::{debug, warn};
 use messages::SequenceNumber;
 use network::reliable_sender::{CancelHandler, ReliableSender};
 use std::collections::HashMap;
@@ -10,11 +11,42 @@ use storage::Storage;
 use tokio::{
     sync::{mpsc::Receiver, oneshot},
     task::JoinHandle,
+    time::Duration,
 };
+use tokio_util::time::{delay_queue, DelayQueue};
 
 /// The maximum number of pending updates per witness.
 const MAX_PENDING_UPDATES: usize = 100;
 
+/// How long to wait for a witness to show progress before re-sending its missing certificates,
+/// the first time a retry is needed. Doubled on every further attempt that makes no progress
+/// (see `retry_delay`), up to `SYNC_RETRY_MAX_TIMEOUT`, so a lagging or offline witness is not
+/// hammered at a fixed rate forever.
+const SYNC_RETRY_BASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The retry delay never grows past this, no matter how many consecutive attempts a witness
+/// has failed to make progress on.
+const SYNC_RETRY_MAX_TIMEOUT: Duration = Duration::from_secs(64);
+
+/// The number of times to retry synchronizing a witness before giving up on it.
+const MAX_SYNC_RETRIES: usize = 5;
+
+/// A one-byte prefix for the keys under which per-witness sync cursors are persisted, so they
+/// cannot collide with the (8-byte, little-endian sequence number) keys certificates are stored
+/// under.
+const SYNC_CURSOR_KEY_PREFIX: u8 = 0xfe;
+
+/// Tracks a witness' outstanding synchronization retry.
+struct PendingSync {
+    /// The key identifying this witness' entry in the `pending_syncs` delay queue.
+    key: delay_queue::Key,
+    /// The sequence number this witness is expected to reach once it has applied the
+    /// certificates last sent to it (one past the highest one sent).
+    progress: SequenceNumber,
+    /// The number of retries already attempted.
+    attempts: usize,
+}
+
 /// Signal to the synchronizer to update a specific witness.
 #[derive(Debug)]
 pub struct SyncTrigger {
@@ -54,6 +86,21 @@ pub struct Synchronizer {
     /// Keep track of the progress of witnesses' updates. It ensures the IdP runs in
     /// finite memory (no bad witness can exhaust the IdP's resources).
     updates_in_progress: HashMap<PublicKey, usize>,
+    /// Expires an entry when a witness hasn't shown progress catching up in time, so we can
+    /// re-send its missing certificates instead of leaving it permanently stuck.
+    pending_syncs: DelayQueue<PublicKey>,
+    /// Per-witness bookkeeping for the retries tracked in `pending_syncs`.
+    retries: HashMap<PublicKey, PendingSync>,
+    /// One past the highest sequence number each witness is known to have acknowledged, durably mirrored
+    /// in `storage` under `witness_cursor_key` so a restart does not forget a witness's progress
+    /// and re-send certificates it already has. Advanced only by `advance_cursor`, and also used
+    /// as a lower bound in `update` so a stale (e.g. just-restarted) `witness_sequence_number`
+    /// reported by the witness itself can never walk a cursor backwards.
+    cursors: HashMap<PublicKey, SequenceNumber>,
+    /// The maximum number of certificates pushed to a witness in a single round. Bounds the
+    /// work a single round of catch-up can trigger; a witness further behind than this is
+    /// brought up to date gradually over several rounds, driven by `pending_syncs`.
+    max_range_span: SequenceNumber,
 }
 
 impl Synchronizer {
@@ -63,8 +110,26 @@ impl Synchronizer {
         storage: Storage,
         rx_trigger: Receiver<SyncTrigger>,
         rx_certificate: Receiver<NewCertificate>,
+        max_range_span: SequenceNumber,
     ) -> JoinHandle<()> {
         tokio::spawn(async move {
+            // Recover every witness' last-acknowledged sequence number from the previous run,
+            // so a restart resumes synchronizing from where it left off instead of re-sending
+            // certificates a witness already has.
+            let cursors = committee
+                .witnesses_addresses()
+                .into_iter()
+                .filter_map(|(name, _)| {
+                    let cursor = storage
+                        .read(&Self::witness_cursor_key(&name))
+                        .expect("Failed to read witness sync cursor")?;
+                    let sequence_number = SequenceNumber::from_le_bytes(
+                        cursor.try_into().expect("Malformed witness sync cursor"),
+                    );
+                    Some((name, sequence_number))
+                })
+                .collect();
+
             Self {
                 committee,
                 storage,
@@ -75,27 +140,145 @@ impl Synchronizer {
                 sequence_number: SequenceNumber::default(),
                 network: ReliableSender::new(),
                 updates_in_progress: HashMap::new(),
+                pending_syncs: DelayQueue::new(),
+                retries: HashMap::new(),
+                cursors,
+                max_range_span,
             }
             .run()
             .await;
         })
     }
 
-    /// Updates a specific witness with any certificate it may have missed.
+    /// The storage key a witness' persisted sync cursor is written under.
+    fn witness_cursor_key(name: &PublicKey) -> Vec<u8> {
+        let mut key = vec![SYNC_CURSOR_KEY_PREFIX];
+        key.extend_from_slice(name.as_ref());
+        key
+    }
+
+    /// The backoff delay before the `attempts`-th consecutive retry of a stalled witness:
+    /// doubles every attempt starting from `SYNC_RETRY_BASE_TIMEOUT`, capped at
+    /// `SYNC_RETRY_MAX_TIMEOUT`.
+    fn retry_delay(attempts: usize) -> Duration {
+        let millis = (SYNC_RETRY_BASE_TIMEOUT.as_millis() as u64)
+            .saturating_mul(1u64 << attempts.min(16));
+        Duration::from_millis(millis).min(SYNC_RETRY_MAX_TIMEOUT)
+    }
+
+    /// Record that `target` has acknowledged delivery up to (but not including)
+    /// `sequence_number`, persisting the new cursor. A no-op if `sequence_number` does not
+    /// advance what we already believed, since acks can arrive out of order.
+    fn advance_cursor(&mut self, target: PublicKey, sequence_number: SequenceNumber) {
+        let cursor = self.cursors.entry(target).or_insert(0);
+        if sequence_number <= *cursor {
+            return;
+        }
+        *cursor = sequence_number;
+        self.storage
+            .write(&Self::witness_cursor_key(&target), &sequence_number.to_le_bytes())
+            .expect("Failed to persist witness sync cursor");
+    }
+
+    /// (Re-)arm the retry timer for `target`, now known to be at `sequence_number`. Drops the
+    /// bookkeeping entirely once the witness has caught up to our own sequence number.
+    fn arm_retry(&mut self, target: PublicKey, sequence_number: SequenceNumber) {
+        if sequence_number >= self.sequence_number {
+            if let Some(pending) = self.retries.remove(&target) {
+                self.pending_syncs.remove(&pending.key);
+                metrics::PENDING_SYNC_REQUESTS.dec();
+            }
+            return;
+        }
+
+        match self.retries.get_mut(&target) {
+            // The witness reported more progress than last time: the previous retry worked,
+            // so reset the attempt counter and the backoff delay.
+            Some(pending) if sequence_number > pending.progress => {
+                pending.progress = sequence_number;
+                pending.attempts = 0;
+                self.pending_syncs.reset(&pending.key, Self::retry_delay(0));
+            }
+            // No progress since the last attempt: back off instead of retrying at a fixed rate.
+            Some(pending) => {
+                self.pending_syncs
+                    .reset(&pending.key, Self::retry_delay(pending.attempts));
+            }
+            None => {
+                let key = self.pending_syncs.insert(target, Self::retry_delay(0));
+                self.retries.insert(
+                    target,
+                    PendingSync {
+                        key,
+                        progress: sequence_number,
+                        attempts: 0,
+                    },
+                );
+                metrics::PENDING_SYNC_REQUESTS.inc();
+            }
+        }
+    }
+
+    /// Called when a witness' retry timer expires without further progress: re-send its
+    /// missing certificates, giving up after `MAX_SYNC_RETRIES` attempts.
+    async fn retry_sync(&mut self, target: PublicKey) -> Vec<(CancelHandler, SequenceNumber)> {
+        let progress = match self.retries.get_mut(&target) {
+            Some(pending) => {
+                pending.attempts += 1;
+                if pending.attempts > MAX_SYNC_RETRIES {
+                    warn!(
+                        "Giving up synchronizing {} after {} failed attempts",
+                        target, MAX_SYNC_RETRIES
+                    );
+                    self.retries.remove(&target);
+                    metrics::PENDING_SYNC_REQUESTS.dec();
+                    return Vec::new();
+                }
+                pending.progress
+            }
+            // The entry was already cleaned up (e.g. the witness caught up in the meantime).
+            None => return Vec::new(),
+        };
+
+        debug!("Retrying synchronization of {} from sequence {}", target, progress);
+        let attempts = self.retries.get(&target).unwrap().attempts;
+        let key = self.pending_syncs.insert(target, Self::retry_delay(attempts));
+        self.retries.get_mut(&target).unwrap().key = key;
+        let (handles, progress) = self.update(target, progress).await;
+        self.arm_retry(target, progress);
+        handles
+    }
+
+    /// Updates a specific witness with any certificate it may have missed, capped to at most
+    /// `max_range_span` certificates so a single round bounds the work done. Returns the
+    /// network handles for the certificates sent, along with the sequence number the witness
+    /// is expected to reach once it has applied them all.
     async fn update(
         &mut self,
         target: PublicKey,
         witness_sequence_number: SequenceNumber,
-    ) -> Vec<CancelHandler> {
+    ) -> (Vec<(CancelHandler, SequenceNumber)>, SequenceNumber) {
         debug!("Updating {}", target);
         let address = self
             .committee
             .witness_address(&target)
             .unwrap_or_else(|| panic!("Tried to update unknown witness {}", target));
 
+        // Never start behind what we already persisted as acknowledged: a stale
+        // `witness_sequence_number` (e.g. reported right after the witness itself restarted)
+        // must not make us re-send certificates we already know it has.
+        let start = witness_sequence_number.max(self.cursors.get(&target).copied().unwrap_or(0));
+
+        // Bound the round to at most `max_range_span` certificates, so a witness that is far
+        // behind is brought up to date gradually instead of in one unbounded burst.
+        let range_end = self
+            .sequence_number
+            .min(start.saturating_add(self.max_range_span.saturating_sub(1)));
+
         // Try to send all missing certificates to the witness.
         let mut handles = Vec::new();
-        for s in witness_sequence_number..=self.sequence_number {
+        let mut progress = start;
+        for s in start..=range_end {
             // Ensure we didn't already reached the maximum pending updates for this witness.
             if let Some(counter) = self.updates_in_progress.get_mut(&target) {
                 if *counter >= MAX_PENDING_UPDATES {
@@ -114,9 +297,10 @@ impl Synchronizer {
 
             let bytes = Bytes::from(certificate);
             let handle = self.network.send(address, bytes).await;
-            handles.push(handle);
+            handles.push((handle, s));
+            progress = s + 1;
         }
-        handles
+        (handles, progress)
     }
 
     /// Helper function. It waits for a future to complete and then forwards it result through the sender.
@@ -129,10 +313,16 @@ impl Synchronizer {
             .expect("Failed to deliver retried message");
     }
 
-    /// Helper function. It waits for a future to complete and then delivers a value.
-    async fn updates_waiter(wait_for: CancelHandler, name: PublicKey) -> PublicKey {
+    /// Helper function. It waits for a future to complete and then delivers a value, alongside
+    /// the sequence number of the certificate that was sent, so `run` can advance that
+    /// witness' persisted sync cursor once the send succeeds.
+    async fn updates_waiter(
+        wait_for: CancelHandler,
+        name: PublicKey,
+        sequence_number: SequenceNumber,
+    ) -> (PublicKey, SequenceNumber) {
         let _ = wait_for.await;
-        name
+        (name, sequence_number)
     }
 
     /// Main loop receiving signals to update a specific witness and newly created IdP's certificates.
@@ -147,11 +337,17 @@ impl Synchronizer {
                     // Update the target node.
                     let target = trigger.target;
                     let sequence_number = trigger.sequence_number;
-                    let handles = self.update(target, sequence_number).await;
-                    for handle in handles {
-                        pending_updates.push(Self::updates_waiter(handle, target));
+                    let (handles, progress) = self.update(target, sequence_number).await;
+                    for (handle, s) in handles {
+                        pending_updates.push(Self::updates_waiter(handle, target, s));
                     }
 
+                    // Track the witness' progress, (re-)arming its retry timer. `progress` is
+                    // the sequence number the witness should reach once it applies this round's
+                    // certificates, which may still be behind `self.sequence_number` if the
+                    // round was capped by `max_range_span`.
+                    self.arm_retry(target, progress);
+
                     // Retry to submit the last message (if any).
                     if let Some((message, sender)) = trigger.retry {
                         let address = self
@@ -167,6 +363,7 @@ impl Synchronizer {
                 Some(message) = self.rx_certificate.recv() => {
                     // Update the sequence number.
                     self.sequence_number = message.sequence_number;
+                    metrics::SEQUENCE_NUMBER.set(self.sequence_number as i64);
 
                     // Persist the new certificate.
                     self.storage
@@ -178,15 +375,43 @@ impl Synchronizer {
                 },
 
                 // Pulls the futures.
-                Some(name) = pending_updates.next() => {
+                Some((name, sequence_number)) = pending_updates.next() => {
                     if let Some(counter) = self.updates_in_progress.get_mut(&name) {
                         *counter -= 1;
                     }
+                    self.advance_cursor(name, sequence_number + 1);
                 }
                 Some(()) = pending_retrials.next() => {
                     // Nothing to do.
                 }
+
+                // A witness hasn't shown progress in time: re-send its missing certificates.
+                Some(expired) = self.pending_syncs.next() => {
+                    let target = expired.into_inner();
+                    let handles = self.retry_sync(target).await;
+                    for (handle, s) in handles {
+                        pending_updates.push(Self::updates_waiter(handle, target, s));
+                    }
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        assert_eq!(Synchronizer::retry_delay(0), SYNC_RETRY_BASE_TIMEOUT);
+        assert_eq!(Synchronizer::retry_delay(1), Duration::from_secs(4));
+        assert_eq!(Synchronizer::retry_delay(2), Duration::from_secs(8));
+        assert_eq!(Synchronizer::retry_delay(3), Duration::from_secs(16));
+        assert_eq!(Synchronizer::retry_delay(4), Duration::from_secs(32));
+
+        // Capped at `SYNC_RETRY_MAX_TIMEOUT`: further attempts stop growing the delay.
+        assert_eq!(Synchronizer::retry_delay(5), SYNC_RETRY_MAX_TIMEOUT);
+        assert_eq!(Synchronizer::retry_delay(100), SYNC_RETRY_MAX_TIMEOUT);
+    }
+}
@@ -2,9 +2,10 @@ use function_name::named;
 use futures::future::try_join_all;
 use network::reliable_sender::ReliableSender;
 use test_utils::{
-    certificate, committee, delete_storage, keys, listener, notification, proof,
-    serialized_updates, spawn_test_idp,
+    certificate, committee, delayed_listener, delete_storage, keys, listener, notification, proof,
+    serialized_updates, spawn_fake_witness, spawn_test_idp, WitnessBehavior,
 };
+use tokio::time::Duration;
 
 #[tokio::test]
 #[named]
@@ -49,6 +50,277 @@ async fn correct_update() {
     delete_storage(&test_id);
 }
 
+#[tokio::test]
+#[named]
+async fn retransmission_after_timeout() {
+    let base_port = 9_050;
+    let committee = committee(base_port);
+    let address = committee.idp.address;
+    let test_id = function_name!();
+
+    // Spawn the IdP. Its retransmission timeout (100ms, see `spawn_test_idp`) is shorter than
+    // the slow witness' reply delay below, so a quorum can only form after a retransmit.
+    spawn_test_idp(&test_id, committee.clone());
+    tokio::task::yield_now().await;
+
+    // Spawn one silent (crashed) witness, one slow witness, and the rest as regular
+    // listeners. With 4 witnesses and a quorum of 3, the certificate can only form once the
+    // slow witness replies, so the publisher is forced to retransmit at least once.
+    let mut keys = keys().into_iter();
+    keys.next(); // Leave this witness silent (no listener spawned for it).
+    let (slow_name, slow_key) = keys.next().unwrap();
+    let slow_address = committee.witness_address(&slow_name).unwrap();
+    let slow = delayed_listener(slow_address, slow_key, Duration::from_millis(300));
+
+    let received: Vec<_> = keys
+        .map(|(name, key)| {
+            let address = committee.witness_address(&name).unwrap();
+            listener(address, key)
+        })
+        .chain(std::iter::once(slow))
+        .collect();
+
+    // Send enough correct updates to create a batch.
+    let mut network = ReliableSender::new();
+    for update in serialized_updates() {
+        let handle = network.send(address, update).await;
+        handle.await.unwrap();
+    }
+
+    // Ensure every witness (including the slow one) eventually sees the expected messages.
+    let (start_root, _, _) = proof().await;
+    let expected_notification = notification().await;
+    let expected_certificate = certificate().await;
+    for (notification, certificate) in try_join_all(received).await.unwrap() {
+        assert!(notification.verify(&committee, &start_root).await.is_ok());
+        assert_eq!(notification, expected_notification);
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate, expected_certificate);
+    }
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn quorum_despite_forged_signature() {
+    let base_port = 9_150;
+    let committee = committee(base_port);
+    let address = committee.idp.address;
+    let test_id = function_name!();
+
+    // Spawn the IdP.
+    spawn_test_idp(&test_id, committee.clone());
+    tokio::task::yield_now().await;
+
+    // Spawn one witness that signs its vote with the wrong keypair, and the rest as honest
+    // listeners. A quorum of 3 out of 4 is still reachable without the forger's vote.
+    let mut keys = keys().into_iter();
+    let (forger_name, forger_key) = keys.next().unwrap();
+    let forger_address = committee.witness_address(&forger_name).unwrap();
+    let forger =
+        spawn_fake_witness(forger_address, forger_key, WitnessBehavior::WrongSignature).unwrap();
+
+    let received: Vec<_> = keys
+        .map(|(name, key)| {
+            let address = committee.witness_address(&name).unwrap();
+            listener(address, key)
+        })
+        .collect();
+
+    // Send enough correct updates to create a batch.
+    let mut network = ReliableSender::new();
+    for update in serialized_updates() {
+        let handle = network.send(address, update).await;
+        handle.await.unwrap();
+    }
+
+    // Ensure the honest witnesses still receive a valid certificate despite the forged vote.
+    let (start_root, _, _) = proof().await;
+    let expected_notification = notification().await;
+    let expected_certificate = certificate().await;
+    for (notification, certificate) in try_join_all(received).await.unwrap() {
+        assert!(notification.verify(&committee, &start_root).await.is_ok());
+        assert_eq!(notification, expected_notification);
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate, expected_certificate);
+    }
+
+    // The forger still gets the certificate once the quorum settles (the publisher
+    // broadcasts it to every witness, not just the ones who voted for it).
+    let (forged_notification, forged_certificate) = forger.await.unwrap();
+    assert_eq!(forged_notification, expected_notification);
+    assert!(forged_certificate.verify(&committee).is_ok());
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn quorum_despite_equivocating_witness() {
+    let base_port = 9_200;
+    let committee = committee(base_port);
+    let address = committee.idp.address;
+    let test_id = function_name!();
+
+    // Spawn the IdP.
+    spawn_test_idp(&test_id, committee.clone());
+    tokio::task::yield_now().await;
+
+    // Spawn one witness that votes for a different root than the one proposed, and the rest
+    // as honest listeners. A quorum of 3 out of 4 is still reachable without its vote.
+    let mut keys = keys().into_iter();
+    let (forger_name, forger_key) = keys.next().unwrap();
+    let forger_address = committee.witness_address(&forger_name).unwrap();
+    let forger =
+        spawn_fake_witness(forger_address, forger_key, WitnessBehavior::ForgedRoot).unwrap();
+
+    let received: Vec<_> = keys
+        .map(|(name, key)| {
+            let address = committee.witness_address(&name).unwrap();
+            listener(address, key)
+        })
+        .collect();
+
+    // Send enough correct updates to create a batch.
+    let mut network = ReliableSender::new();
+    for update in serialized_updates() {
+        let handle = network.send(address, update).await;
+        handle.await.unwrap();
+    }
+
+    // Ensure the honest witnesses still receive a valid certificate despite the equivocation.
+    let (start_root, _, _) = proof().await;
+    let expected_notification = notification().await;
+    let expected_certificate = certificate().await;
+    for (notification, certificate) in try_join_all(received).await.unwrap() {
+        assert!(notification.verify(&committee, &start_root).await.is_ok());
+        assert_eq!(notification, expected_notification);
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate, expected_certificate);
+    }
+
+    // The equivocating witness still gets the genuine certificate once the quorum settles.
+    let (forged_notification, forged_certificate) = forger.await.unwrap();
+    assert_eq!(forged_notification, expected_notification);
+    assert!(forged_certificate.verify(&committee).is_ok());
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn quorum_despite_refusing_witness() {
+    let base_port = 9_210;
+    let committee = committee(base_port);
+    let address = committee.idp.address;
+    let test_id = function_name!();
+
+    // Spawn the IdP.
+    spawn_test_idp(&test_id, committee.clone());
+    tokio::task::yield_now().await;
+
+    // Spawn one witness that refuses to vote at all, and the rest as honest listeners. A
+    // quorum of 3 out of 4 is still reachable without its vote.
+    let mut keys = keys().into_iter();
+    let (refuser_name, refuser_key) = keys.next().unwrap();
+    let refuser_address = committee.witness_address(&refuser_name).unwrap();
+    let refuser =
+        spawn_fake_witness(refuser_address, refuser_key, WitnessBehavior::Refuse).unwrap();
+
+    let received: Vec<_> = keys
+        .map(|(name, key)| {
+            let address = committee.witness_address(&name).unwrap();
+            listener(address, key)
+        })
+        .collect();
+
+    // Send enough correct updates to create a batch.
+    let mut network = ReliableSender::new();
+    for update in serialized_updates() {
+        let handle = network.send(address, update).await;
+        handle.await.unwrap();
+    }
+
+    // Ensure the honest witnesses still receive a valid certificate despite the refusal.
+    let (start_root, _, _) = proof().await;
+    let expected_notification = notification().await;
+    let expected_certificate = certificate().await;
+    for (notification, certificate) in try_join_all(received).await.unwrap() {
+        assert!(notification.verify(&committee, &start_root).await.is_ok());
+        assert_eq!(notification, expected_notification);
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate, expected_certificate);
+    }
+
+    // The refusing witness still gets the genuine certificate once the quorum settles.
+    let (refuser_notification, refuser_certificate) = refuser.await.unwrap();
+    assert_eq!(refuser_notification, expected_notification);
+    assert!(refuser_certificate.verify(&committee).is_ok());
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn quorum_despite_dropped_connection() {
+    let base_port = 9_220;
+    let committee = committee(base_port);
+    let address = committee.idp.address;
+    let test_id = function_name!();
+
+    // Spawn the IdP.
+    spawn_test_idp(&test_id, committee.clone());
+    tokio::task::yield_now().await;
+
+    // Spawn one witness whose connection is severed right after it receives the notification,
+    // and the rest as honest listeners. A quorum of 3 out of 4 is still reachable without it.
+    let mut keys = keys().into_iter();
+    let (dropped_name, dropped_key) = keys.next().unwrap();
+    let dropped_address = committee.witness_address(&dropped_name).unwrap();
+    let dropped =
+        spawn_fake_witness(dropped_address, dropped_key, WitnessBehavior::DropConnection)
+            .unwrap();
+
+    let received: Vec<_> = keys
+        .map(|(name, key)| {
+            let address = committee.witness_address(&name).unwrap();
+            listener(address, key)
+        })
+        .collect();
+
+    // Send enough correct updates to create a batch.
+    let mut network = ReliableSender::new();
+    for update in serialized_updates() {
+        let handle = network.send(address, update).await;
+        handle.await.unwrap();
+    }
+
+    // Ensure the honest witnesses still receive a valid certificate despite the drop.
+    let (start_root, _, _) = proof().await;
+    let expected_notification = notification().await;
+    let expected_certificate = certificate().await;
+    for (notification, certificate) in try_join_all(received).await.unwrap() {
+        assert!(notification.verify(&committee, &start_root).await.is_ok());
+        assert_eq!(notification, expected_notification);
+        assert!(certificate.verify(&committee).is_ok());
+        assert_eq!(certificate, expected_certificate);
+    }
+
+    // The dropped witness still gets the genuine certificate on its reconnected link, once
+    // the quorum settles.
+    let (dropped_notification, dropped_certificate) = dropped.await.unwrap();
+    assert_eq!(dropped_notification, expected_notification);
+    assert!(dropped_certificate.verify(&committee).is_ok());
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
 #[tokio::test]
 #[named]
 async fn faulty_witness() {
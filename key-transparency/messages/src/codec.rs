@@ -0,0 +1,117 @@
+//! Wire-serialization for protocol messages.
+//!
+//! Every `*Message` type in this crate crosses the wire through [`encode`] and
+//! [`decode`], which dispatch to whichever backend is selected at compile
+//! time via cargo features:
+//!
+//! - `codec-bincode` (default): [`bincode`], the original on-the-wire format.
+//! - `codec-rmp`: [`rmp_serde`] (MessagePack), a compact, widely interoperable format.
+//! - `codec-postcard`: [`postcard`], a `no_std`-friendly, size-optimized format; useful
+//!   for bandwidth-constrained witnesses.
+//! - `codec-json`: [`serde_json`], a human-readable format useful for wire debugging.
+//!
+//! The features are mutually exclusive: enabling more than one is a compile error, since
+//! all parties on the wire must agree on a single format.
+
+use crate::error::{MessageError, MessageResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(all(feature = "codec-bincode", feature = "codec-rmp"))]
+compile_error!("features \"codec-bincode\" and \"codec-rmp\" are mutually exclusive");
+#[cfg(all(feature = "codec-bincode", feature = "codec-postcard"))]
+compile_error!("features \"codec-bincode\" and \"codec-postcard\" are mutually exclusive");
+#[cfg(all(feature = "codec-bincode", feature = "codec-json"))]
+compile_error!("features \"codec-bincode\" and \"codec-json\" are mutually exclusive");
+#[cfg(all(feature = "codec-rmp", feature = "codec-postcard"))]
+compile_error!("features \"codec-rmp\" and \"codec-postcard\" are mutually exclusive");
+#[cfg(all(feature = "codec-rmp", feature = "codec-json"))]
+compile_error!("features \"codec-rmp\" and \"codec-json\" are mutually exclusive");
+#[cfg(all(feature = "codec-postcard", feature = "codec-json"))]
+compile_error!("features \"codec-postcard\" and \"codec-json\" are mutually exclusive");
+
+/// Serializes `value` with the active codec.
+pub fn encode<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serialize_active(value)
+}
+
+/// Deserializes `bytes` with the active codec.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    deserialize_active(bytes)
+}
+
+#[cfg(feature = "codec-rmp")]
+fn serialize_active<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serialize_rmp(value)
+}
+#[cfg(feature = "codec-rmp")]
+fn deserialize_active<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    deserialize_rmp(bytes)
+}
+
+#[cfg(feature = "codec-postcard")]
+fn serialize_active<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serialize_postcard(value)
+}
+#[cfg(feature = "codec-postcard")]
+fn deserialize_active<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    deserialize_postcard(bytes)
+}
+
+#[cfg(feature = "codec-json")]
+fn serialize_active<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serialize_json(value)
+}
+#[cfg(feature = "codec-json")]
+fn deserialize_active<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    deserialize_json(bytes)
+}
+
+// bincode is the default: active whenever none of the other backends were selected.
+#[cfg(not(any(feature = "codec-rmp", feature = "codec-postcard", feature = "codec-json")))]
+fn serialize_active<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serialize_bincode(value)
+}
+#[cfg(not(any(feature = "codec-rmp", feature = "codec-postcard", feature = "codec-json")))]
+fn deserialize_active<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    deserialize_bincode(bytes)
+}
+
+/// Serializes `value` with `bincode`, regardless of the active codec.
+pub fn serialize_bincode<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    Ok(bincode::serialize(value)?)
+}
+
+/// Deserializes `bytes` with `bincode`, regardless of the active codec.
+pub fn deserialize_bincode<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    Ok(bincode::deserialize(bytes)?)
+}
+
+/// Serializes `value` with `rmp-serde` (MessagePack), regardless of the active codec.
+pub fn serialize_rmp<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    rmp_serde::to_vec(value).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
+
+/// Deserializes `bytes` with `rmp-serde` (MessagePack), regardless of the active codec.
+pub fn deserialize_rmp<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    rmp_serde::from_slice(bytes).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
+
+/// Serializes `value` with `postcard`, regardless of the active codec.
+pub fn serialize_postcard<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    postcard::to_allocvec(value).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
+
+/// Deserializes `bytes` with `postcard`, regardless of the active codec.
+pub fn deserialize_postcard<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    postcard::from_bytes(bytes).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
+
+/// Serializes `value` with `serde_json`, regardless of the active codec.
+pub fn serialize_json<T: Serialize>(value: &T) -> MessageResult<Vec<u8>> {
+    serde_json::to_vec(value).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
+
+/// Deserializes `bytes` with `serde_json`, regardless of the active codec.
+pub fn deserialize_json<T: DeserializeOwned>(bytes: &[u8]) -> MessageResult<T> {
+    serde_json::from_slice(bytes).map_err(|e| MessageError::SerializationError(e.to_string()))
+}
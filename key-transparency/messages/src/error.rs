@@ -1,8 +1,8 @@
-use crate::{deserialize_root, serialize_root, Root, SequenceNumber};
-use vkd::errors::AkdError;
-use crypto::{CryptoError, Digest, PublicKey};
+use crate::{deserialize_root, serialize_root, Root, Round, SequenceNumber};
+use crypto::{kzg_da::DaError, BlsError, CryptoError, Digest, PublicKey, ThresholdError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use vkd::errors::AkdError;
 
 #[macro_export]
 macro_rules! bail {
@@ -24,6 +24,31 @@ macro_rules! ensure {
 pub type MessageResult<T> = Result<T, MessageError>;
 pub type WitnessResult<T> = Result<T, WitnessError>;
 pub type IdpResult<T> = Result<T, IdpError>;
+pub type FrameResult<T> = Result<T, FrameError>;
+pub type EncodeResult<T> = Result<T, EncodeError>;
+
+/// Errors triggered by [`crate::framing::FrameCodec`] while decoding a length-prefixed frame off
+/// the wire, before the buffered bytes are even handed to [`crate::codec::decode`]. Kept distinct
+/// from `MessageError` so a malformed or oversized frame is never mistaken for a
+/// valid-but-unparseable message.
+#[derive(Debug, Error)]
+pub enum FrameError {
+    #[error("Length prefix did not terminate within 5 bytes")]
+    MalformedLengthPrefix,
+
+    #[error("Frame length {length} exceeds the maximum of {max_length}")]
+    FrameTooLarge { length: usize, max_length: usize },
+
+    #[error("Frame announces {expected} bytes but only {available} are buffered")]
+    BytesMissing { expected: usize, available: usize },
+}
+
+/// Errors triggered while framing an outbound message for the wire.
+#[derive(Debug, Error)]
+pub enum EncodeError {
+    #[error("Frame length {length} exceeds the maximum of {max_length}")]
+    FrameTooLarge { length: usize, max_length: usize },
+}
 
 /// Errors triggered when parsing and verifying protocol messages.
 #[derive(Debug, Error, Serialize, Deserialize)]
@@ -43,6 +68,15 @@ pub enum MessageError {
     #[error("Received certificate without a quorum")]
     CertificateRequiresQuorum,
 
+    #[error("Equivocation proof votes are from different authors")]
+    EquivocationProofAuthorMismatch,
+
+    #[error("Equivocation proof votes are for different sequence numbers")]
+    EquivocationProofSequenceMismatch,
+
+    #[error("Equivocation proof votes commit to the same root (not a conflict)")]
+    EquivocationProofNotConflicting,
+
     #[error("Failed to deserialize message ({0})")]
     SerializationError(String),
 
@@ -51,6 +85,67 @@ pub enum MessageError {
 
     #[error("The update request is too short (min 2 bytes)")]
     UpdateRequestTooShort,
+
+    #[error("Cannot serve a proof: epoch {0} is not yet backed by a witness-certified root")]
+    EpochNotCertified(SequenceNumber),
+
+    #[error("Only {have} of the {required} required threshold signature shares were supplied")]
+    InsufficientShares { have: usize, required: usize },
+
+    #[error("Threshold signature share from witness {0} does not verify")]
+    InvalidShare(usize),
+
+    #[error("Certificate uses a threshold scheme the committee was not set up with")]
+    ThresholdSetupMismatch,
+
+    #[error("Certificate names contributor {0} but that witness has no registered BLS key")]
+    MissingBlsKey(PublicKey),
+
+    #[error("AggregatedCommitments batch has no transitions")]
+    EmptyBatch,
+
+    #[error("BLS aggregate signature does not verify: {0}")]
+    InvalidBlsAggregate(String),
+
+    #[error("Notification carries a data-availability commitment but no share for witness {0}")]
+    MissingDataShare(PublicKey),
+
+    #[error("Data-availability share does not verify: {0}")]
+    InvalidDataShare(String),
+
+    #[error("Notification carries a data-availability commitment but the committee has no SRS configured")]
+    DataAvailabilitySetupMismatch,
+
+    #[error("Justification certifies {justified:?} but was attached to a notification for {attached:?}")]
+    JustificationMismatch {
+        #[serde(serialize_with = "serialize_root")]
+        #[serde(deserialize_with = "deserialize_root")]
+        justified: Root,
+        #[serde(serialize_with = "serialize_root")]
+        #[serde(deserialize_with = "deserialize_root")]
+        attached: Root,
+    },
+
+    #[error("View-change is for sequence {sequence_number} but its lock is for {lock_sequence_number}")]
+    ViewChangeLockSequenceMismatch {
+        sequence_number: SequenceNumber,
+        lock_sequence_number: SequenceNumber,
+    },
+
+    #[error("View-change proposes round {new_round} but its lock is already at round {lock_round}")]
+    ViewChangeLockRoundTooHigh { new_round: Round, lock_round: Round },
+
+    #[error("New-view signed by {got}, but {expected} is this round's leader")]
+    UnexpectedViewChangeLeader { expected: PublicKey, got: PublicKey },
+
+    #[error("Message is for fork {received}, but this committee is on fork {expected}")]
+    ForkMismatch { expected: u64, received: u64 },
+
+    #[error("Light client already trusts sequence {trusted}, which is not before {received}")]
+    NonMonotonicSequenceNumber {
+        trusted: SequenceNumber,
+        received: SequenceNumber,
+    },
 }
 
 impl From<CryptoError> for MessageError {
@@ -59,6 +154,32 @@ impl From<CryptoError> for MessageError {
     }
 }
 
+impl From<ThresholdError> for MessageError {
+    fn from(error: ThresholdError) -> Self {
+        match error {
+            ThresholdError::InsufficientShares { have, required } => {
+                MessageError::InsufficientShares { have, required }
+            }
+            ThresholdError::InvalidShare(index) => MessageError::InvalidShare(index),
+            ThresholdError::InvalidAggregate => {
+                MessageError::InvalidSignature("threshold aggregate".to_string())
+            }
+        }
+    }
+}
+
+impl From<BlsError> for MessageError {
+    fn from(error: BlsError) -> Self {
+        MessageError::InvalidBlsAggregate(error.to_string())
+    }
+}
+
+impl From<DaError> for MessageError {
+    fn from(error: DaError) -> Self {
+        MessageError::InvalidDataShare(error.to_string())
+    }
+}
+
 impl From<Box<bincode::ErrorKind>> for MessageError {
     fn from(error: Box<bincode::ErrorKind>) -> Self {
         MessageError::SerializationError(error.to_string())
@@ -95,6 +216,21 @@ pub enum WitnessError {
 
     #[error("Missing earlier certificates, current sequence number at {0}")]
     MissingEarlierCertificates(SequenceNumber),
+
+    #[error("Requested range spans {requested} certificates, which exceeds the maximum of {max}")]
+    RangeTooLarge {
+        requested: SequenceNumber,
+        max: SequenceNumber,
+    },
+
+    #[error("Cannot unlock round {locked_round}: justification is only for round {justification_round}")]
+    StaleJustification {
+        locked_round: Round,
+        justification_round: Round,
+    },
+
+    #[error("IdP equivocated: {0:?}")]
+    EquivocatingIdp(Box<crate::publish::ConflictingNotifications>),
 }
 
 /// Errors triggered by the IdP.
@@ -118,4 +254,25 @@ pub enum IdpError {
         #[serde(deserialize_with = "deserialize_root")]
         received: Root,
     },
+
+    #[error("Witness {} equivocated: voted for two different roots at the same sequence number", .0.vote_1.author)]
+    EquivocatingWitness(Box<crate::publish::ConflictingVote>),
+
+    #[error("Received a vote for round {received}, current round is {expected}")]
+    UnexpectedRound { expected: Round, received: Round },
+
+    #[error("Certificate stream ended at sequence {got}, expected to reach {expected}")]
+    TruncatedCertificateStream {
+        expected: SequenceNumber,
+        got: SequenceNumber,
+    },
+
+    #[error("Failed to gather a quorum of votes for sequence {sequence_number} after {attempts} rounds")]
+    QuorumTimeout {
+        sequence_number: SequenceNumber,
+        attempts: u32,
+    },
+
+    #[error("Serialized message is {length} bytes, exceeding the committee's configured maximum of {max_length}")]
+    PayloadTooLarge { length: usize, max_length: usize },
 }
@@ -0,0 +1,126 @@
+//! Length-prefixed framing for the wire.
+//!
+//! [`FrameCodec`] is a `tokio_util::codec::{Decoder, Encoder}` that prefixes every frame with a
+//! LEB128-style VarInt length: each prefix byte contributes its low 7 bits to the length, with
+//! the high bit set while another prefix byte follows. Plugged into a `tokio_util::codec::Framed`
+//! transport, it is what should sit between a raw socket and [`crate::codec::decode`] -- unlike a
+//! check performed only after a whole message is already buffered, the length is validated
+//! against `max_length` as soon as the prefix finishes parsing, before a single payload byte is
+//! read off the wire, so a peer cannot force us to buffer an oversized payload just by announcing
+//! one.
+use crate::error::{EncodeError, EncodeResult, FrameError, FrameResult};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The VarInt length prefix is capped at 5 bytes, enough to encode any `u32` length.
+const MAX_PREFIX_LEN: usize = 5;
+
+/// A length-prefixed frame codec: each frame is a LEB128-style VarInt length prefix followed by
+/// that many payload bytes. Frames longer than `max_length` are rejected as soon as the prefix is
+/// known, without buffering their payload.
+pub struct FrameCodec {
+    max_length: usize,
+}
+
+impl FrameCodec {
+    /// Creates a codec that rejects any frame whose announced length exceeds `max_length`.
+    pub fn new(max_length: usize) -> Self {
+        Self { max_length }
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Bytes;
+    type Error = FrameError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> FrameResult<Option<Bytes>> {
+        let mut length: usize = 0;
+        let mut prefix_len = 0;
+        while prefix_len < MAX_PREFIX_LEN {
+            let byte = match src.get(prefix_len) {
+                Some(&byte) => byte,
+                // The prefix itself hasn't fully arrived yet; wait for more input.
+                None => return Ok(None),
+            };
+            length |= ((byte & 0x7f) as usize) << (7 * prefix_len);
+            prefix_len += 1;
+
+            if byte & 0x80 == 0 {
+                // The prefix is complete: check the announced length before buffering any of
+                // the payload it promises, instead of buffering up to `length` bytes first.
+                if length > self.max_length {
+                    src.advance(prefix_len);
+                    return Err(FrameError::FrameTooLarge {
+                        length,
+                        max_length: self.max_length,
+                    });
+                }
+
+                return if src.len() - prefix_len < length {
+                    src.reserve(prefix_len + length - src.len());
+                    Ok(None)
+                } else {
+                    src.advance(prefix_len);
+                    Ok(Some(src.split_to(length).freeze()))
+                };
+            }
+        }
+
+        Err(FrameError::MalformedLengthPrefix)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> FrameResult<Option<Bytes>> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            // The connection closed with a frame only partially buffered. If the prefix at
+            // least finished parsing, report how many payload bytes we were promised and never
+            // got; otherwise the prefix itself was cut short.
+            None => {
+                let mut length = 0usize;
+                for (i, &byte) in src.iter().take(MAX_PREFIX_LEN).enumerate() {
+                    length |= ((byte & 0x7f) as usize) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        return Err(FrameError::BytesMissing {
+                            expected: length,
+                            available: src.len(),
+                        });
+                    }
+                }
+                Err(FrameError::MalformedLengthPrefix)
+            }
+        }
+    }
+}
+
+impl Encoder<Bytes> for FrameCodec {
+    type Error = EncodeError;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> EncodeResult<()> {
+        let length = item.len();
+        if length > self.max_length {
+            return Err(EncodeError::FrameTooLarge {
+                length,
+                max_length: self.max_length,
+            });
+        }
+
+        // LEB128-encode the length: low 7 bits per byte, high bit set while more bytes follow.
+        let mut remaining = length;
+        loop {
+            let mut byte = (remaining & 0x7f) as u8;
+            remaining >>= 7;
+            if remaining > 0 {
+                byte |= 0x80;
+            }
+            dst.put_u8(byte);
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        dst.reserve(length);
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
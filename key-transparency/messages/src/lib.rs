@@ -1,12 +1,19 @@
+pub mod codec;
 pub mod error;
+pub mod framing;
+pub mod light_client;
+pub mod merkle;
 pub mod publish;
+pub mod query;
 pub mod sync;
 pub mod update;
 
 use error::{WitnessError, WitnessResult};
-use publish::{PublishCertificate, PublishNotification, PublishVote};
+use publish::{NewView, PublishCertificate, PublishNotification, PublishVote, ViewChange};
+use query::{HistoryQuery, HistoryResult, LookupQuery, LookupResult};
 use serde::{Deserialize, Serialize};
-use sync::{PublishCertificateQuery, State};
+use sync::{CertificateRangeQuery, PublishCertificateQuery, RootChain, RootChainQuery, State};
+use update::UpdateRequest;
 use winter_crypto::{hashers::Blake3_256, Digest as _, Hasher};
 use winter_math::fields::f128::BaseElement;
 use winter_utils::{Deserializable, SliceReader};
@@ -14,21 +21,81 @@ use winter_utils::{Deserializable, SliceReader};
 /// Alias for serialized publish certificates.
 pub type SerializedPublishCertificateMessage = Vec<u8>;
 
+/// Messages sent by clients to the IdP.
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ClientToIdPMessage {
+    /// Add or update a key-value pair; batched and eventually certified.
+    Update(UpdateRequest),
+    /// Look up the latest certified value (and proof) for a label.
+    LookupQuery(LookupQuery),
+    /// Fetch the full certified version history of a label.
+    HistoryQuery(HistoryQuery),
+}
+
+/// Replies sent by the IdP to clients.
+#[derive(Serialize, Deserialize, Debug)]
+pub enum IdPToClientMessage {
+    /// Acknowledges that an update request was enqueued for batching.
+    Ack,
+    /// Reply to a `LookupQuery`.
+    LookupResponse(LookupResult),
+    /// Reply to a `HistoryQuery`.
+    HistoryResponse(HistoryResult),
+}
+
 /// Messages sent by the IdP to the witnesses.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum IdPToWitnessMessage {
     PublishNotification(PublishNotification),
+    /// A batch of publish notifications sent as a single wire message, so an IdP with several
+    /// notifications queued up only pays one network round trip to gather every vote instead
+    /// of one round trip per notification. Each notification is still voted on (and certified)
+    /// independently; see `WitnessToIdPMessage::PublishVoteBatch`.
+    PublishNotificationBatch(Vec<PublishNotification>),
     PublishCertificate(PublishCertificate),
     StateQuery,
+    /// Subscribe to a long-lived stream of `State` pushes, sent every time the witness installs
+    /// a new certificate or its root otherwise advances, instead of repeatedly polling with
+    /// `StateQuery`. The witness replies with the current `State` immediately so late joiners
+    /// start consistent, then again on every subsequent change; the subscriber unsubscribes by
+    /// dropping its connection.
+    SubscribeState,
     PublishCertificateQuery(PublishCertificateQuery),
+    /// Anti-entropy catch-up request: fetch every certificate in an inclusive range, rather
+    /// than one sequence number at a time.
+    CertificateRangeQuery(CertificateRangeQuery),
+    /// Request for the ordered, witness-signed sequence of committed roots in an inclusive
+    /// range, giving an external auditor a lightweight `hashes` vector to feed into
+    /// `vkd::auditor::audit_verify` without fetching a full quorum certificate per epoch.
+    RootChainQuery(RootChainQuery),
+    /// Sent witness-to-witness (reusing this enum, exactly like `PublishCertificateQuery` already
+    /// is by the `SyncRequester`) when a witness's round timer expires without a certificate, to
+    /// report its lock to the round's leader.
+    ViewChange(ViewChange),
+    /// Sent witness-to-witness by a view's leader once it has resolved the view, either with a
+    /// recovered certificate or with notice that none could be recovered.
+    NewView(NewView),
 }
 
 /// Replies sent by the witnesses to the IdP.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum WitnessToIdPMessage {
     PublishVote(WitnessResult<PublishVote>),
+    /// Reply to a `PublishNotificationBatch`: one result per notification, in the same order.
+    PublishVoteBatch(Vec<WitnessResult<PublishVote>>),
     State(WitnessResult<State>),
     PublishCertificateResponse(SerializedPublishCertificateMessage),
+    /// One certificate in a `CertificateRangeQuery` stream, sent in increasing sequence-number
+    /// order so the requester can start applying certificates before the range completes.
+    CertificateStreamFrame(SerializedPublishCertificateMessage),
+    /// Terminates a `CertificateRangeQuery` stream: the highest sequence number actually sent,
+    /// or an error if the range was rejected before any frame was sent.
+    CertificateStreamEnd(WitnessResult<SequenceNumber>),
+    /// Acknowledges a `ViewChange` or `NewView` message; carries no information of its own, it
+    /// only satisfies the request/reply shape every `IdPToWitnessMessage` gets a reply to.
+    ViewChangeAck,
+    /// Reply to a `RootChainQuery`.
+    RootChainResponse(WitnessResult<RootChain>),
 }
 
 impl WitnessToIdPMessage {
@@ -53,6 +120,10 @@ impl WitnessToIdPMessage {
 /// The sequence number of consistent (or reliable) broadcast.
 pub type SequenceNumber = u64;
 
+/// The voting round within a single sequence number, incremented whenever a proposal fails to
+/// reach quorum in time so a stalled round does not stall the protocol forever.
+pub type Round = u64;
+
 // The hasher for the state tree.
 pub type Blake3 = Blake3_256<BaseElement>;
 
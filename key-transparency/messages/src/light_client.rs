@@ -0,0 +1,105 @@
+//! A light-client verification path for resource-constrained auditors, modeled on the
+//! trusting-period/"skipping" verification used by blockchain light clients. Full verification
+//! (`PublishNotification::verify`) is documented as "very CPU-intensive" because it re-runs
+//! `vkd::auditor::audit_verify` over the append-only proof of every state transition. A
+//! [`LightClient`] never does that: given a trusted certificate at sequence number `s`, it
+//! accepts a newer certificate at `s'` purely because its votes form a valid quorum under the
+//! (possibly rotated) committee, trusting that the witnesses who signed it already ran the
+//! expensive audit themselves before voting.
+use crate::{
+    ensure,
+    error::{MessageError, MessageResult},
+    publish::{PublishCertificate, PublishMessage},
+    Root, SequenceNumber,
+};
+use config::{Committee, CommitteeHistory};
+use std::future::Future;
+
+/// Holds the last state a light client has accepted: a sequence number and root it trusts
+/// without ever having re-audited the proof that produced it.
+pub struct LightClient {
+    sequence_number: SequenceNumber,
+    root: Root,
+}
+
+impl LightClient {
+    /// Start trusting `(sequence_number, root)` -- typically a certificate verified once in
+    /// full (e.g. via [`PublishCertificate::verify_with_history`]) or obtained out of band.
+    pub fn new(sequence_number: SequenceNumber, root: Root) -> Self {
+        Self {
+            sequence_number,
+            root,
+        }
+    }
+
+    /// The sequence number and root this light client currently trusts.
+    pub fn trusted(&self) -> (SequenceNumber, Root) {
+        (self.sequence_number, self.root)
+    }
+
+    /// Accept `certificate` as the new trusted state, checked against `committee` -- the
+    /// committee this light client already trusts for that sequence number, not one re-derived
+    /// from the certificate itself. Enforces a strictly increasing sequence number and a valid
+    /// quorum of votes; never re-runs the append-only proof audit.
+    pub fn verify_update(
+        &mut self,
+        certificate: &PublishCertificate,
+        committee: &Committee,
+    ) -> MessageResult<()> {
+        ensure!(
+            certificate.sequence_number() > self.sequence_number,
+            MessageError::NonMonotonicSequenceNumber {
+                trusted: self.sequence_number,
+                received: certificate.sequence_number(),
+            }
+        );
+        certificate.verify(committee)?;
+        self.sequence_number = certificate.sequence_number();
+        self.root = *certificate.root();
+        Ok(())
+    }
+
+    /// Like [`Self::verify_update`], but resolves the committee for `certificate`'s sequence
+    /// number from `history` instead of a single fixed `Committee`, so the light client keeps
+    /// working across a reconfiguration it has already recorded.
+    pub fn verify_update_with_history(
+        &mut self,
+        certificate: &PublishCertificate,
+        history: &CommitteeHistory,
+    ) -> MessageResult<()> {
+        let committee = history.committee_at(certificate.sequence_number());
+        self.verify_update(certificate, committee)
+    }
+
+    /// Bisection mode: when the committee may have rotated one or more times between the
+    /// trusted sequence number and `target`'s, walk every intermediate reconfiguration recorded
+    /// in `history` instead of jumping straight to `target`, so each hop's quorum is checked
+    /// against the committee actually in force for it, maintaining an unbroken chain of
+    /// verified quorums rather than trusting every committee change in between for free.
+    /// `fetch` resolves a certificate for a given sequence number (e.g. a network round trip to
+    /// a full witness); it is only ever called for the reconfiguration boundaries strictly
+    /// between the trusted sequence number and `target`'s.
+    pub async fn bisect_update<F, Fut>(
+        &mut self,
+        target: PublishCertificate,
+        history: &CommitteeHistory,
+        fetch: F,
+    ) -> MessageResult<()>
+    where
+        F: Fn(SequenceNumber) -> Fut,
+        Fut: Future<Output = MessageResult<PublishCertificate>>,
+    {
+        let boundaries =
+            history.reconfigurations_between(self.sequence_number, target.sequence_number());
+        for boundary in boundaries {
+            // `target` itself may already be at (or past) a later boundary; no need to fetch a
+            // redundant intermediate certificate for it.
+            if boundary >= target.sequence_number() {
+                break;
+            }
+            let intermediate = fetch(boundary).await?;
+            self.verify_update_with_history(&intermediate, history)?;
+        }
+        self.verify_update_with_history(&target, history)
+    }
+}
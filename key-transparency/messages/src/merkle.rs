@@ -0,0 +1,176 @@
+use crate::{
+    codec::serialize_bincode, deserialize_root, serialize_root, update::UpdateRequest, Blake3, Root,
+};
+use serde::{Deserialize, Serialize};
+use winter_crypto::{Digest as _, Hasher};
+
+/// An append-only binary Merkle tree over the raw update requests of a single batch, giving a
+/// client an inclusion proof for their own update that is much cheaper to verify than the full
+/// `vkd` audit proof covering the directory transition.
+///
+/// Kept as a vector of layers (leaves at index 0, root as the single entry of the last layer).
+/// Each [`push`](Self::push) only recomputes the rightmost path from the new leaf to the root,
+/// rather than rebuilding the tree from scratch. When a layer has an odd trailing node, that
+/// node is provisionally duplicated to stand in as its own sibling when computing its parent;
+/// the duplicate is transparently replaced once a true sibling is pushed alongside it.
+#[derive(Default)]
+pub struct MerkleAccumulator {
+    layers: Vec<Vec<Root>>,
+}
+
+impl MerkleAccumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of leaves pushed so far.
+    pub fn len(&self) -> usize {
+        self.layers.first().map_or(0, Vec::len)
+    }
+
+    /// Whether no leaf has been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash and append `request` as the next leaf, updating every node on its path to the root.
+    pub fn push(&mut self, request: &UpdateRequest) {
+        let bytes = serialize_bincode(request).expect("Failed to serialize update request");
+        let leaf = Blake3::hash(&bytes);
+        if self.layers.is_empty() {
+            self.layers.push(Vec::new());
+        }
+        self.layers[0].push(leaf);
+
+        let mut index = self.layers[0].len() - 1;
+        let mut level = 0;
+        while self.layers[level].len() > 1 {
+            let nodes = &self.layers[level];
+            let (left, right) = if index % 2 == 0 {
+                (nodes[index], nodes[index])
+            } else {
+                (nodes[index - 1], nodes[index])
+            };
+            let parent = Self::combine(left, right);
+
+            if self.layers.len() == level + 1 {
+                self.layers.push(Vec::new());
+            }
+            let parent_index = index / 2;
+            match self.layers[level + 1].get_mut(parent_index) {
+                Some(slot) => *slot = parent,
+                None => self.layers[level + 1].push(parent),
+            }
+
+            index = parent_index;
+            level += 1;
+        }
+    }
+
+    /// The current root, i.e. the commitment to every leaf pushed so far.
+    pub fn root(&self) -> Root {
+        match self.layers.last() {
+            Some(top) => top[0],
+            None => Blake3::hash(&[]),
+        }
+    }
+
+    /// Build an inclusion proof for the leaf at `index`. Returns `None` if out of bounds.
+    ///
+    /// Meant to be called once the batch is sealed (no further `push`); a proof taken mid-batch
+    /// would embed provisional duplicate siblings that a later push could still replace.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let leaf_index = index;
+        let mut index = index;
+        let mut siblings = Vec::new();
+        for nodes in self.layers.iter().take(self.layers.len() - 1) {
+            let sibling = if index % 2 == 0 {
+                nodes.get(index + 1).copied().unwrap_or(nodes[index])
+            } else {
+                nodes[index - 1]
+            };
+            siblings.push(sibling);
+            index /= 2;
+        }
+
+        Some(MerkleProof {
+            leaf_index,
+            siblings,
+        })
+    }
+
+    /// Hash together a node's two children.
+    fn combine(left: Root, right: Root) -> Root {
+        Blake3::hash(&[left.as_bytes(), right.as_bytes()].concat())
+    }
+}
+
+/// An inclusion proof that a specific leaf is part of a [`BatchCommitment`]'s root.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MerkleProof {
+    /// The index of the leaf this proof covers.
+    pub leaf_index: usize,
+    /// The sibling hashes on the path from the leaf to the root, ordered bottom-up.
+    #[serde(serialize_with = "serialize_commitments")]
+    #[serde(deserialize_with = "deserialize_commitments")]
+    pub siblings: Vec<Root>,
+}
+
+impl MerkleProof {
+    /// Recompute the root implied by this proof for `leaf` and check it matches `root`.
+    pub fn verify(&self, leaf: Root, root: Root) -> bool {
+        let mut index = self.leaf_index;
+        let mut current = leaf;
+        for sibling in &self.siblings {
+            current = if index % 2 == 0 {
+                MerkleAccumulator::combine(current, *sibling)
+            } else {
+                MerkleAccumulator::combine(*sibling, current)
+            };
+            index /= 2;
+        }
+        current == root
+    }
+}
+
+/// The durable commitment produced when a `Batcher` seals a batch: the Merkle root over its
+/// raw update requests, and how many of them it covers.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct BatchCommitment {
+    /// The root of the Merkle tree over the sealed batch's update requests.
+    #[serde(serialize_with = "serialize_root")]
+    #[serde(deserialize_with = "deserialize_root")]
+    pub root: Root,
+    /// The number of update requests committed to by `root`.
+    pub size: usize,
+}
+
+/// A serde serializer for a `Vec` of `winter_crypto::Digest`, mirroring [`crate::serialize_root`].
+pub fn serialize_commitments<S>(x: &[Root], s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeSeq;
+    let mut seq = s.serialize_seq(Some(x.len()))?;
+    for root in x {
+        seq.serialize_element(&root.as_bytes())?;
+    }
+    seq.end()
+}
+
+/// A serde deserializer for a `Vec` of `winter_crypto::Digest`, mirroring [`crate::deserialize_root`].
+pub fn deserialize_commitments<'de, D>(deserializer: D) -> Result<Vec<Root>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use winter_utils::{Deserializable, SliceReader};
+    let bufs = Vec::<[u8; 32]>::deserialize(deserializer)?;
+    bufs.iter()
+        .map(|buf| Root::read_from(&mut SliceReader::new(buf)).map_err(serde::de::Error::custom))
+        .collect()
+}
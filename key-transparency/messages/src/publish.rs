@@ -1,19 +1,33 @@
 use crate::{
     deserialize_root, ensure,
     error::{MessageError, MessageResult},
-    serialize_root, Blake3, Root, SequenceNumber,
+    serialize_root, Blake3, Root, Round, SequenceNumber,
+};
+use config::{Committee, CommitteeHistory, VotingPower};
+use crypto::{
+    kzg_da::{Commitment, Share as DaShare},
+    BlsAggregateSignature, BlsKeyPair, BlsSignatureShare, Digest, KeyPair, PublicKey, Signature,
+    SignatureShare, ThresholdKeyShare, ThresholdSignature,
 };
-use vkd::proof_structs::AppendOnlyProof;
-use config::Committee;
-use crypto::{Digest, KeyPair, PublicKey, Signature};
 use ed25519_dalek::{Digest as _, Sha512};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, convert::TryInto};
+use vkd::proof_structs::AppendOnlyProof;
 use winter_crypto::Digest as _;
 
 /// Represents a state proof.
 pub type Proof = AppendOnlyProof<Blake3>;
 
+/// The current wall-clock time, in milliseconds since the Unix epoch, for stamping a freshly
+/// created `PublishVote`. Saturates to zero instead of panicking on a clock set before 1970,
+/// since a vote's `timestamp` is only ever used for observability, never for protocol safety.
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 /// A message that can be hashed.
 pub trait PublishMessage {
     /// Return a reference to the root commitment.
@@ -22,13 +36,24 @@ pub trait PublishMessage {
     /// Return the sequence number of the message.
     fn sequence_number(&self) -> SequenceNumber;
 
+    /// Return the voting round of the message.
+    fn round(&self) -> Round;
+
     /// Compute the hash of the message.
     fn digest(&self) -> Digest {
         let mut hasher = Sha512::new();
         hasher.update(&self.root().as_bytes());
         hasher.update(self.sequence_number().to_le_bytes());
+        hasher.update(self.round().to_le_bytes());
         Digest(hasher.finalize().as_slice()[..32].try_into().unwrap())
     }
+
+    /// The (first, last) roots this message commits to. Defaults to `(self.root(), self.root())`
+    /// for messages that only ever commit a single root; [`AggregatedCommitments`] overrides
+    /// this to span every root in its batch.
+    fn root_range(&self) -> (Root, Root) {
+        (*self.root(), *self.root())
+    }
 }
 
 /// An publish notification sent by the IdP to the witnesses to request votes.
@@ -42,10 +67,35 @@ pub struct PublishNotification {
     pub proof: Proof,
     /// The sequence number unique to this publish notification.
     pub sequence_number: SequenceNumber,
+    /// The voting round of this proposal. Bumped by the IdP (and re-signed) whenever a round
+    /// times out without reaching quorum, so a silent or equivocating round does not stall
+    /// the protocol forever.
+    pub round: Round,
+    /// A quorum certificate justifying an unlock: proof that 2f+1 witnesses have already moved
+    /// on from whatever they were locked on, allowing a witness locked on a conflicting root to
+    /// safely vote for this proposal instead. `None` for an ordinary (non-conflicting) proposal.
+    pub justification: Option<Box<PublishCertificate>>,
     /// The hash of the previous fields of this publish.
     pub id: Digest,
     /// A signature from the IdP authenticating the publish.
     pub signature: Signature,
+    /// A KZG commitment to the erasure-coded `proof`, letting an auditor reconstruct it from a
+    /// subset of honest witnesses instead of trusting the IdP to serve it. `None` for a
+    /// committee that does not run the data-availability layer (see
+    /// `config::Committee::data_availability_srs`).
+    #[serde(default)]
+    pub data_commitment: Option<Commitment>,
+    /// Every witness's data-availability share against `data_commitment`, keyed by witness.
+    /// Broadcast to every witness alongside the commitment (each only needs, and only checks,
+    /// its own entry) rather than addressed point-to-point.
+    #[serde(default)]
+    pub data_shares: Vec<(PublicKey, DaShare)>,
+    /// Which fork this notification was proposed under (see `config::Committee::fork_id`). A
+    /// witness on a different fork rejects it during the publish handshake rather than voting
+    /// on it. Like `justification`, not part of the signed digest: a witness checks it before
+    /// ever looking at the signature, so it does not need re-signing across a round bump.
+    #[serde(default)]
+    pub fork_id: u64,
 }
 
 impl std::fmt::Debug for PublishNotification {
@@ -75,22 +125,34 @@ impl PublishMessage for PublishNotification {
     fn sequence_number(&self) -> SequenceNumber {
         self.sequence_number
     }
+
+    fn round(&self) -> Round {
+        self.round
+    }
 }
 
 impl PublishNotification {
-    /// Create a new PublishNotification signed by the IdP.
+    /// Create a new PublishNotification signed by the IdP, proposing `root` at `round` with no
+    /// justification. Use [`Self::with_justification`] to attach a quorum certificate when
+    /// re-proposing a different root than the one witnesses may already be locked on.
     pub fn new(
         root: Root,
         proof: Proof,
         sequence_number: SequenceNumber,
+        round: Round,
         keypair: &KeyPair,
     ) -> Self {
         let notification = Self {
             root,
             proof,
             sequence_number,
+            round,
+            justification: None,
             id: Digest::default(),
             signature: Signature::default(),
+            data_commitment: None,
+            data_shares: Vec::new(),
+            fork_id: 0,
         };
         let id = notification.digest();
         let signature = Signature::new(&id, keypair);
@@ -101,8 +163,59 @@ impl PublishNotification {
         }
     }
 
-    /// Verify a publish notification (very CPU-intensive).
-    pub async fn verify(&self, committee: &Committee, previous_root: &Root) -> MessageResult<()> {
+    /// Attach a quorum certificate justifying this proposal, so a witness locked on a
+    /// conflicting root can verify that quorum has already moved on and safely unlock. Does
+    /// not require re-signing: the justification is not part of the signed digest (mirroring
+    /// how `proof` itself is excluded from `digest`), only the fields that identify what the
+    /// IdP is proposing are.
+    pub fn with_justification(mut self, justification: PublishCertificate) -> Self {
+        self.justification = Some(Box::new(justification));
+        self
+    }
+
+    /// Attach a data-availability commitment and per-witness shares for `proof`, computed by
+    /// `crypto::kzg_da::encode`. Like `justification`, this is not part of the signed digest
+    /// (mirroring how `proof` itself is excluded), so it does not require re-signing.
+    pub fn with_data_availability(
+        mut self,
+        commitment: Commitment,
+        shares: Vec<(PublicKey, DaShare)>,
+    ) -> Self {
+        self.data_commitment = Some(commitment);
+        self.data_shares = shares;
+        self
+    }
+
+    /// Attach the fork this notification is proposed under (see `config::Committee::fork_id`).
+    /// Like `with_data_availability`, this does not require re-signing.
+    pub fn with_fork_id(mut self, fork_id: u64) -> Self {
+        self.fork_id = fork_id;
+        self
+    }
+
+    /// This witness's data-availability share, if one was attached.
+    pub fn data_share_for(&self, witness: &PublicKey) -> Option<&DaShare> {
+        self.data_shares
+            .iter()
+            .find(|(name, _)| name == witness)
+            .map(|(_, share)| share)
+    }
+
+    /// Verify the fork, id, and IdP signature on this notification, without touching the
+    /// (much more expensive, and `previous_root`-dependent) commit proof. This is all a
+    /// [`ConflictingNotifications`] proof needs in order to be checked independently of
+    /// any particular witness's view of the chain.
+    fn verify_signature(&self, committee: &Committee) -> MessageResult<()> {
+        // Reject a notification for a fork this committee configuration doesn't recognize
+        // before doing any of the more expensive checks below.
+        ensure!(
+            self.fork_id == committee.fork_id,
+            MessageError::ForkMismatch {
+                expected: committee.fork_id,
+                received: self.fork_id,
+            }
+        );
+
         // Ensure the id is well formed.
         ensure!(
             self.digest() == self.id,
@@ -112,6 +225,13 @@ impl PublishNotification {
         // Verify the signature on the publish notification
         self.signature.verify(&self.id, &committee.idp.name)?;
 
+        Ok(())
+    }
+
+    /// Verify a publish notification (very CPU-intensive).
+    pub async fn verify(&self, committee: &Committee, previous_root: &Root) -> MessageResult<()> {
+        self.verify_signature(committee)?;
+
         // Verify the commit proof.
         let hashes = vec![*previous_root, self.root];
         vkd::auditor::audit_verify::<Blake3>(hashes, self.proof.clone()).await?;
@@ -120,6 +240,208 @@ impl PublishNotification {
     }
 }
 
+/// Sent by the IdP in place of a [`PublishNotification`] to propose several epochs' worth of
+/// roots at once: an ordered run of `(sequence_number, root)` transitions, each one epoch later
+/// than the last, backed by a single append-only proof spanning the whole range instead of one
+/// proof (and one notification/vote/certificate round) per epoch. `vkd::auditor::audit_verify`
+/// already takes a `Vec` of hashes, so auditing `[previous_root, r1, ..., rn]` in one call gives
+/// the same append-only guarantee as `n` individual one-epoch proofs. Like
+/// `PublishNotification`, only the final `(sequence_number, root)` is part of the signed digest
+/// and of what a `PublishCertificate` certifies; the intermediate transitions are not
+/// independently authenticated by the signature, only by `proof` (mirroring how
+/// `PublishNotification::proof` itself is excluded from its digest).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AggregatedCommitments {
+    /// The ordered `(sequence_number, root)` transitions this batch proposes, one per epoch,
+    /// each one sequence number later than the last. Never empty.
+    #[serde(serialize_with = "serialize_transitions")]
+    #[serde(deserialize_with = "deserialize_transitions")]
+    pub transitions: Vec<(SequenceNumber, Root)>,
+    /// The append-only proof spanning every transition in the batch, from the root preceding
+    /// `transitions[0]` through `transitions.last()`.
+    pub proof: Proof,
+    /// The voting round of this proposal (see `PublishNotification::round`).
+    pub round: Round,
+    /// The hash of the previous fields of this batch.
+    pub id: Digest,
+    /// A signature from the IdP authenticating the batch.
+    pub signature: Signature,
+    /// Which fork this batch was proposed under (see `config::Committee::fork_id`).
+    #[serde(default)]
+    pub fork_id: u64,
+}
+
+fn serialize_transitions<S>(
+    transitions: &[(SequenceNumber, Root)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    use serde::ser::SerializeSeq;
+    let mut seq = serializer.serialize_seq(Some(transitions.len()))?;
+    for (sequence_number, root) in transitions {
+        seq.serialize_element(&(*sequence_number, RootWrapper(*root)))?;
+    }
+    seq.end()
+}
+
+fn deserialize_transitions<'de, D>(deserializer: D) -> Result<Vec<(SequenceNumber, Root)>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let raw: Vec<(SequenceNumber, RootWrapper)> = Deserialize::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .map(|(sequence_number, RootWrapper(root))| (sequence_number, root))
+        .collect())
+}
+
+/// A newtype so `(SequenceNumber, Root)` can derive `Serialize`/`Deserialize` via `Root`'s
+/// existing free functions (`serialize_root`/`deserialize_root`), which are shaped for a single
+/// named field rather than a tuple element.
+struct RootWrapper(Root);
+
+impl Serialize for RootWrapper {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serialize_root(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RootWrapper {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserialize_root(deserializer).map(RootWrapper)
+    }
+}
+
+impl std::fmt::Debug for AggregatedCommitments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}: B{}..{}({})",
+            self.id,
+            self.transitions.first().map_or(0, |(s, _)| *s),
+            self.sequence_number(),
+            base64::encode(self.root().as_bytes())
+        )
+    }
+}
+
+// Useful for tests.
+impl PartialEq for AggregatedCommitments {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PublishMessage for AggregatedCommitments {
+    fn root(&self) -> &Root {
+        &self
+            .transitions
+            .last()
+            .expect("AggregatedCommitments always has at least one transition")
+            .1
+    }
+
+    fn sequence_number(&self) -> SequenceNumber {
+        self.transitions
+            .last()
+            .expect("AggregatedCommitments always has at least one transition")
+            .0
+    }
+
+    fn round(&self) -> Round {
+        self.round
+    }
+
+    fn root_range(&self) -> (Root, Root) {
+        let first = self
+            .transitions
+            .first()
+            .expect("AggregatedCommitments always has at least one transition")
+            .1;
+        (first, *self.root())
+    }
+}
+
+impl AggregatedCommitments {
+    /// Create a new batch proposal signed by the IdP, proposing `transitions` (at least one)
+    /// with a single proof spanning the whole range. Panics if `transitions` is empty.
+    pub fn new(
+        transitions: Vec<(SequenceNumber, Root)>,
+        proof: Proof,
+        round: Round,
+        keypair: &KeyPair,
+    ) -> Self {
+        assert!(
+            !transitions.is_empty(),
+            "A batch must commit at least one transition"
+        );
+        let batch = Self {
+            transitions,
+            proof,
+            round,
+            id: Digest::default(),
+            signature: Signature::default(),
+            fork_id: 0,
+        };
+        let id = batch.digest();
+        let signature = Signature::new(&id, keypair);
+        Self {
+            id,
+            signature,
+            ..batch
+        }
+    }
+
+    /// Attach the fork this batch is proposed under (see `PublishNotification::with_fork_id`).
+    pub fn with_fork_id(mut self, fork_id: u64) -> Self {
+        self.fork_id = fork_id;
+        self
+    }
+
+    fn verify_signature(&self, committee: &Committee) -> MessageResult<()> {
+        // Checked first: `root()`/`sequence_number()` (and so `digest()`) panic on an empty
+        // `transitions`, which a malformed or malicious wire message could otherwise smuggle in.
+        ensure!(!self.transitions.is_empty(), MessageError::EmptyBatch);
+
+        ensure!(
+            self.fork_id == committee.fork_id,
+            MessageError::ForkMismatch {
+                expected: committee.fork_id,
+                received: self.fork_id,
+            }
+        );
+        ensure!(
+            self.digest() == self.id,
+            MessageError::MalformedNotificationId(self.id.clone())
+        );
+        self.signature.verify(&self.id, &committee.idp.name)?;
+        Ok(())
+    }
+
+    /// Verify this batch (very CPU-intensive): the IdP's signature over the final transition,
+    /// and that `proof` is a single valid append-only extension from `previous_root` through
+    /// every intermediate transition to the last one -- the same guarantee as verifying each
+    /// transition's one-epoch proof individually, at the cost of one audit call instead of
+    /// `self.transitions.len()`.
+    pub async fn verify(&self, committee: &Committee, previous_root: &Root) -> MessageResult<()> {
+        self.verify_signature(committee)?;
+
+        let mut hashes = vec![*previous_root];
+        hashes.extend(self.transitions.iter().map(|(_, root)| *root));
+        vkd::auditor::audit_verify::<Blake3>(hashes, self.proof.clone()).await?;
+
+        Ok(())
+    }
+}
+
 /// A vote for a publish notification.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PublishVote {
@@ -129,10 +451,34 @@ pub struct PublishVote {
     pub root: Root,
     /// The sequence number of the publish notification.
     pub sequence_number: SequenceNumber,
+    /// The voting round of the publish notification this vote is for.
+    pub round: Round,
     /// The witness creating the vote.
     pub author: PublicKey,
     /// A signature authenticating the vote.
     pub signature: Signature,
+    /// This witness's threshold signature share over the same digest as `signature`, if the
+    /// committee has a `ThresholdKeySet` configured and this witness holds a share of it.
+    /// Collected by the `Aggregator` to assemble a `CertificateKind::Threshold` once a
+    /// quorum of shares comes in, instead of (or alongside) the per-witness `Votes` form.
+    #[serde(default)]
+    pub threshold_share: Option<SignatureShare>,
+    /// This witness's BLS signature share over the same digest as `signature`, if this witness
+    /// holds a BLS keypair registered in the committee. Collected by the `Aggregator` to
+    /// combine into a `CertificateKind::BlsAggregate` once a quorum of shares comes in.
+    #[serde(default)]
+    pub bls_share: Option<BlsSignatureShare>,
+    /// The fork of the notification this vote is for (copied from
+    /// `PublishNotification::fork_id`), so the IdP's `Aggregator` can reject a vote cast under a
+    /// fork it does not recognize instead of folding it into the current quorum.
+    #[serde(default)]
+    pub fork_id: u64,
+    /// This witness's local clock reading (milliseconds since the Unix epoch) when it cast this
+    /// vote, authenticated by `signature` (see `Self::authenticated_digest`) so a relay cannot
+    /// alter it without detection. Lets downstream observers reconstruct when a quorum formed
+    /// and how long certification took, via `PublishCertificate::confirmation_time`.
+    #[serde(default)]
+    pub timestamp: u64,
 }
 
 impl std::fmt::Debug for PublishVote {
@@ -165,6 +511,10 @@ impl PublishMessage for PublishVote {
     fn sequence_number(&self) -> SequenceNumber {
         self.sequence_number
     }
+
+    fn round(&self) -> Round {
+        self.round
+    }
 }
 
 impl PublishVote {
@@ -173,15 +523,70 @@ impl PublishVote {
         let vote = Self {
             root: notification.root,
             sequence_number: notification.sequence_number,
+            round: notification.round,
+            author: keypair.public(),
+            signature: Signature::default(),
+            threshold_share: None,
+            bls_share: None,
+            fork_id: notification.fork_id,
+            timestamp: now_millis(),
+        };
+        Self {
+            signature: Signature::new(&vote.authenticated_digest(), keypair),
+            ..vote
+        }
+    }
+
+    /// Create a new vote for a batch proposal: votes for an `AggregatedCommitments` the same way
+    /// they do for a `PublishNotification`, just over the batch's final `(sequence_number, root)`
+    /// (what `PublishMessage::digest` always signs), certifying every transition in the batch at
+    /// once.
+    pub fn for_batch(batch: &AggregatedCommitments, keypair: &KeyPair) -> Self {
+        let vote = Self {
+            root: *batch.root(),
+            sequence_number: batch.sequence_number(),
+            round: batch.round(),
             author: keypair.public(),
             signature: Signature::default(),
+            threshold_share: None,
+            bls_share: None,
+            fork_id: batch.fork_id,
+            timestamp: now_millis(),
         };
         Self {
-            signature: Signature::new(&vote.digest(), keypair),
+            signature: Signature::new(&vote.authenticated_digest(), keypair),
             ..vote
         }
     }
 
+    /// The digest this vote's own `signature` authenticates: `PublishMessage::digest` (the base
+    /// root/sequence_number/round triple, shared with `PublishCertificate`'s digest) folded with
+    /// this witness's `timestamp`. Kept distinct from `PublishMessage::digest` itself because
+    /// `threshold_share`/`bls_share` (see `Self::with_threshold_share`/`Self::with_bls_share`)
+    /// must sign the exact same message as every other witness's share for them to combine into
+    /// one `CertificateKind::Threshold`/`BlsAggregate`; a witness-specific timestamp could never
+    /// be folded into that shared digest without breaking combination.
+    fn authenticated_digest(&self) -> Digest {
+        let mut hasher = Sha512::new();
+        hasher.update(&self.digest().0);
+        hasher.update(self.timestamp.to_le_bytes());
+        Digest(hasher.finalize().as_slice()[..32].try_into().unwrap())
+    }
+
+    /// Attach this witness's threshold signature share over the vote's digest, so the
+    /// `Aggregator` can combine it with a quorum of others into a `CertificateKind::Threshold`.
+    pub fn with_threshold_share(mut self, share: &ThresholdKeyShare) -> Self {
+        self.threshold_share = Some(share.sign(&self.digest()));
+        self
+    }
+
+    /// Attach this witness's BLS signature share over the vote's digest, so the `Aggregator`
+    /// can combine it with a quorum of others into a `CertificateKind::BlsAggregate`.
+    pub fn with_bls_share(mut self, keypair: &BlsKeyPair) -> Self {
+        self.bls_share = Some(keypair.sign(&self.digest()));
+        self
+    }
+
     /// Verify that the vote is correctly signed.
     pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
         // Ensure the authority has voting rights.
@@ -192,11 +597,339 @@ impl PublishVote {
 
         // Check the signature.
         self.signature
-            .verify(&self.digest(), &self.author)
+            .verify(&self.authenticated_digest(), &self.author)
             .map_err(MessageError::from)
     }
 }
 
+/// A proof that a witness equivocated by signing two votes committing to different roots
+/// at the same sequence number. Anyone holding this proof can independently verify the
+/// misbehavior without trusting whoever reports it: this *is* this codebase's equivocation
+/// proof (`Aggregator::append` is the detection hook that constructs one, triggered by a
+/// witness voting twice for the same sequence number and round with conflicting roots). Once verified,
+/// pair it with `config::Committee::penalize` to zero the offending witness's voting power.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConflictingVote {
+    /// One of the two conflicting votes.
+    pub vote_1: PublishVote,
+    /// The other conflicting vote, from the same author and sequence number but a different root.
+    pub vote_2: PublishVote,
+}
+
+impl std::fmt::Debug for ConflictingVote {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "Equivocation({}, {:?}, {:?})",
+            self.vote_1.author, self.vote_1, self.vote_2
+        )
+    }
+}
+
+impl ConflictingVote {
+    /// Verify that both votes are correctly signed by the same witness and genuinely conflict
+    /// (same author and sequence number, different roots).
+    pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
+        ensure!(
+            self.vote_1.author == self.vote_2.author,
+            MessageError::EquivocationProofAuthorMismatch
+        );
+        ensure!(
+            self.vote_1.sequence_number == self.vote_2.sequence_number,
+            MessageError::EquivocationProofSequenceMismatch
+        );
+        ensure!(
+            self.vote_1.root != self.vote_2.root,
+            MessageError::EquivocationProofNotConflicting
+        );
+        self.vote_1.verify(committee)?;
+        self.vote_2.verify(committee)?;
+        Ok(())
+    }
+}
+
+/// A proof that the IdP equivocated by signing notifications for two different roots at the
+/// same sequence number -- e.g. showing `r1` to half the committee and `r2` to the other half
+/// so each half locks on a different root with no single witness ever seeing the conflict.
+/// Anyone holding this proof can independently verify the misbehavior without trusting whoever
+/// reports it, since [`PublishNotification::verify_signature`] only depends on the committee,
+/// not on any witness's local view of the chain.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConflictingNotifications {
+    /// One of the two conflicting notifications.
+    pub notification_1: PublishNotification,
+    /// The other conflicting notification, same sequence number but a different root.
+    pub notification_2: PublishNotification,
+}
+
+impl std::fmt::Debug for ConflictingNotifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "IdpEquivocation({:?}, {:?})",
+            self.notification_1, self.notification_2
+        )
+    }
+}
+
+impl ConflictingNotifications {
+    /// Verify that both notifications are correctly signed by the committee's IdP and genuinely
+    /// conflict (same sequence number, different roots).
+    pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
+        ensure!(
+            self.notification_1.sequence_number == self.notification_2.sequence_number,
+            MessageError::EquivocationProofSequenceMismatch
+        );
+        ensure!(
+            self.notification_1.root != self.notification_2.root,
+            MessageError::EquivocationProofNotConflicting
+        );
+        self.notification_1.verify_signature(committee)?;
+        self.notification_2.verify_signature(committee)?;
+        Ok(())
+    }
+}
+
+/// Sent by a witness to a round's leader (see `config::Committee::leader`) when its local timer
+/// for a sequence number expires without a certificate arriving, so a stalled or crashed IdP does
+/// not strand an epoch forever. Carries whatever vote this witness is currently locked on (if
+/// any), so the leader can recover an already-quorate root from votes the IdP never got to
+/// aggregate into a certificate, and drive the witnesses to commit without it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ViewChange {
+    /// The sequence number whose round is being changed.
+    pub sequence_number: SequenceNumber,
+    /// The view being proposed, used (together with `sequence_number`) both to pick the leader
+    /// (`committee.leader(new_round)`) and to group this message with the other witnesses'
+    /// view-changes for the same attempt.
+    pub new_round: Round,
+    /// The witness reporting the timeout.
+    pub author: PublicKey,
+    /// This witness's current lock, if any.
+    pub lock: Option<PublishVote>,
+    /// A signature authenticating the view-change message.
+    pub signature: Signature,
+}
+
+impl std::fmt::Debug for ViewChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "ViewChange{}({}, locked={})",
+            self.sequence_number,
+            self.author,
+            self.lock.is_some()
+        )
+    }
+}
+
+impl ViewChange {
+    /// Create a new view-change message, signed by `keypair`.
+    pub fn new(
+        sequence_number: SequenceNumber,
+        new_round: Round,
+        lock: Option<PublishVote>,
+        keypair: &KeyPair,
+    ) -> Self {
+        let view_change = Self {
+            sequence_number,
+            new_round,
+            author: keypair.public(),
+            lock,
+            signature: Signature::default(),
+        };
+        Self {
+            signature: Signature::new(&view_change.digest(), keypair),
+            ..view_change
+        }
+    }
+
+    fn digest(&self) -> Digest {
+        let mut hasher = Sha512::new();
+        hasher.update(self.sequence_number.to_le_bytes());
+        hasher.update(self.new_round.to_le_bytes());
+        hasher.update(self.author.as_ref());
+        if let Some(lock) = &self.lock {
+            hasher.update(lock.digest().as_ref());
+        }
+        Digest(hasher.finalize().as_slice()[..32].try_into().unwrap())
+    }
+
+    /// Verify that the message is well-formed and correctly signed, and that any attached lock
+    /// genuinely predates the proposed view.
+    pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
+        ensure!(
+            committee.voting_power(&self.author) > 0,
+            MessageError::UnknownWitness(self.author)
+        );
+        self.signature
+            .verify(&self.digest(), &self.author)
+            .map_err(MessageError::from)?;
+        if let Some(lock) = &self.lock {
+            ensure!(
+                lock.sequence_number == self.sequence_number,
+                MessageError::ViewChangeLockSequenceMismatch {
+                    sequence_number: self.sequence_number,
+                    lock_sequence_number: lock.sequence_number,
+                }
+            );
+            ensure!(
+                lock.round < self.new_round,
+                MessageError::ViewChangeLockRoundTooHigh {
+                    new_round: self.new_round,
+                    lock_round: lock.round,
+                }
+            );
+            lock.verify(committee)?;
+        }
+        Ok(())
+    }
+}
+
+/// Broadcast by a view's leader once it has collected a quorum of [`ViewChange`] messages for
+/// the same `(sequence_number, new_round)`. If the collected locks already reached quorum for
+/// some root and round, `certificate` carries the recovered certificate, which witnesses apply
+/// exactly like one delivered by the IdP. Otherwise `certificate` is `None`: the leader could not
+/// recover a certificate from the votes cast so far (the IdP died before any root ever reached
+/// quorum), which this recovery mechanism cannot safely invent on its own since only the IdP can
+/// produce a new, validly-proved root; witnesses only log this and keep waiting.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NewView {
+    /// The sequence number whose round changed.
+    pub sequence_number: SequenceNumber,
+    /// The view this message concludes.
+    pub new_round: Round,
+    /// The witness that acted as leader for this view.
+    pub leader: PublicKey,
+    /// The certificate recovered from the collected view-changes' locks, if any root/round
+    /// combination among them already reached quorum.
+    pub certificate: Option<PublishCertificate>,
+    /// A signature from the leader authenticating this message.
+    pub signature: Signature,
+}
+
+impl std::fmt::Debug for NewView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "NewView{}(leader={}, recovered={})",
+            self.sequence_number,
+            self.leader,
+            self.certificate.is_some()
+        )
+    }
+}
+
+impl NewView {
+    /// Create a new new-view message, signed by `keypair` (the leader).
+    pub fn new(
+        sequence_number: SequenceNumber,
+        new_round: Round,
+        certificate: Option<PublishCertificate>,
+        keypair: &KeyPair,
+    ) -> Self {
+        let new_view = Self {
+            sequence_number,
+            new_round,
+            leader: keypair.public(),
+            certificate,
+            signature: Signature::default(),
+        };
+        Self {
+            signature: Signature::new(&new_view.digest(), keypair),
+            ..new_view
+        }
+    }
+
+    fn digest(&self) -> Digest {
+        let mut hasher = Sha512::new();
+        hasher.update(self.sequence_number.to_le_bytes());
+        hasher.update(self.new_round.to_le_bytes());
+        hasher.update(self.leader.as_ref());
+        if let Some(certificate) = &self.certificate {
+            hasher.update(certificate.digest().as_ref());
+        }
+        Digest(hasher.finalize().as_slice()[..32].try_into().unwrap())
+    }
+
+    /// Verify that the message is correctly signed by the witness `committee` agrees is the
+    /// leader of `new_round`, and that any attached certificate is itself valid.
+    pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
+        let expected_leader = committee.leader(self.new_round);
+        ensure!(
+            self.leader == expected_leader,
+            MessageError::UnexpectedViewChangeLeader {
+                expected: expected_leader,
+                got: self.leader,
+            }
+        );
+        self.signature
+            .verify(&self.digest(), &self.leader)
+            .map_err(MessageError::from)?;
+        if let Some(certificate) = &self.certificate {
+            certificate.verify(committee)?;
+        }
+        Ok(())
+    }
+}
+
+/// The form taken by a [`PublishCertificate`]'s proof of quorum. Both coexist so a committee
+/// can be migrated from one to the other without a flag day: a certificate's `kind` is
+/// self-describing, and `verify` dispatches on it.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum CertificateKind {
+    /// One signature per witness in the quorum. Grows linearly with the committee and costs
+    /// one verification per witness (batched, but still linear work).
+    Votes(Vec<(PublicKey, Signature)>),
+    /// A single constant-size aggregate combining a quorum of threshold signature shares,
+    /// verifying against the committee's group public key in one operation, plus a bitmap of
+    /// the contributing witnesses (so voting-power-weighted quorum can still be checked: the
+    /// threshold scheme itself only knows about share *count*, not `Committee` weights). The
+    /// bitmap is one bit per witness in `Committee::witness_index` order rather than a
+    /// `Vec<PublicKey>`, so this whole variant stays near-constant size instead of growing
+    /// linearly with the committee.
+    Threshold {
+        signature: ThresholdSignature,
+        contributors: Vec<u8>,
+    },
+    /// A single constant-size BLS aggregate combining a quorum of independently-keyed
+    /// witnesses' signatures, plus a bitmap of the contributors in the same `witness_index`
+    /// order as `Threshold`. Unlike `Threshold`, there is no dealer: each witness signs with
+    /// its own BLS keypair, so `verify` must reconstruct the aggregate public key from
+    /// `contributors` itself rather than checking against one fixed group key.
+    BlsAggregate {
+        signature: BlsAggregateSignature,
+        contributors: Vec<u8>,
+    },
+}
+
+impl CertificateKind {
+    /// Pack `members` into a compact bitmap over `committee`'s deterministic witness ordering,
+    /// for use as a `Threshold` certificate's `contributors` field. Members not in `committee`
+    /// are silently dropped; `verify_contributors` is what actually enforces membership.
+    pub fn pack_contributors(committee: &Committee, members: &[PublicKey]) -> Vec<u8> {
+        let mut bitmap = vec![0u8; (committee.size() + 7) / 8];
+        for name in members {
+            if let Some(index) = committee.witness_index(name) {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+        }
+        bitmap
+    }
+
+    /// The inverse of `pack_contributors`: the committee members marked in `bitmap`.
+    fn unpack_contributors(committee: &Committee, bitmap: &[u8]) -> Vec<PublicKey> {
+        (0..committee.size())
+            .filter(|index| {
+                bitmap
+                    .get(index / 8)
+                    .map_or(false, |byte| byte & (1 << (index % 8)) != 0)
+            })
+            .filter_map(|index| committee.witness_at(index))
+            .collect()
+    }
+}
+
 /// A certificate over a publish notification.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PublishCertificate {
@@ -206,8 +939,18 @@ pub struct PublishCertificate {
     pub root: Root,
     /// The sequence number of the publish notification.
     pub sequence_number: SequenceNumber,
-    /// The quorum of votes making the certificate.
-    pub votes: Vec<(PublicKey, Signature)>,
+    /// The voting round at which the quorum was reached.
+    pub round: Round,
+    /// The proof that a quorum of witnesses certified this root.
+    pub kind: CertificateKind,
+    /// Each contributing witness's authenticated `PublishVote::timestamp`, in the order its vote
+    /// was appended. Purely observational metadata for monitoring (see
+    /// `Self::confirmation_time`/`Self::ordered_timestamps`): unlike `kind`, it is not itself
+    /// part of what makes this certificate valid, and a `Threshold`/`BlsAggregate` certificate
+    /// necessarily reports timestamps for a witness subset, not literally every signer the
+    /// combined signature attests to.
+    #[serde(default)]
+    pub timestamps: Vec<(PublicKey, u64)>,
 }
 
 impl std::fmt::Debug for PublishCertificate {
@@ -243,15 +986,109 @@ impl PublishMessage for PublishCertificate {
     fn sequence_number(&self) -> SequenceNumber {
         self.sequence_number
     }
+
+    fn round(&self) -> Round {
+        self.round
+    }
 }
 
 impl PublishCertificate {
+    /// Verify this certificate against the committee that was in force at its sequence number,
+    /// resolved from `history` by range lookup. Lets an auditor (or anyone else holding a
+    /// `CommitteeHistory` spanning multiple reconfigurations) validate a chain of certificates
+    /// even though the witness set or voting power backing them changed partway through.
+    pub fn verify_with_history(&self, history: &CommitteeHistory) -> MessageResult<()> {
+        let committee = history.committee_at(self.sequence_number);
+        self.verify(committee)
+    }
+
+    /// A representative commit time for this certificate: the voting-power-weighted median of
+    /// `self.timestamps`, or `None` if it carries none. Byzantine-robust as long as the faulty
+    /// share of `committee`'s voting power reflected in `self.timestamps` is under half of it:
+    /// sorting timestamps and walking voting power until at least half the represented weight is
+    /// covered always lands on an honest witness's clock, so a faulty minority cannot skew the
+    /// reported time by withholding or lying about their own timestamp.
+    pub fn confirmation_time(&self, committee: &Committee) -> Option<u64> {
+        if self.timestamps.is_empty() {
+            return None;
+        }
+        let mut weighted: Vec<(u64, VotingPower)> = self
+            .timestamps
+            .iter()
+            .map(|(name, timestamp)| (*timestamp, committee.voting_power(name)))
+            .collect();
+        weighted.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let total_weight: VotingPower = weighted.iter().map(|(_, weight)| weight).sum();
+        let mut cumulative = 0;
+        for (timestamp, weight) in weighted {
+            cumulative += weight;
+            if cumulative * 2 >= total_weight {
+                return Some(timestamp);
+            }
+        }
+        None
+    }
+
+    /// `self.timestamps`, sorted by timestamp, so a monitoring tool can read off the slowest
+    /// (last) witnesses to vote at a glance instead of sorting the raw field itself.
+    pub fn ordered_timestamps(&self) -> Vec<(PublicKey, u64)> {
+        let mut timestamps = self.timestamps.clone();
+        timestamps.sort_by_key(|(_, timestamp)| *timestamp);
+        timestamps
+    }
+
     /// Verify that certificate.
     pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
-        // Ensure the certificate has a quorum.
+        match &self.kind {
+            CertificateKind::Votes(votes) => self.verify_votes(committee, votes),
+            CertificateKind::Threshold {
+                signature,
+                contributors,
+            } => {
+                // The threshold scheme only guarantees that enough *shares* were combined; it
+                // knows nothing of `Committee` voting power. Check that separately, the same
+                // way `verify_votes` does.
+                let contributors = CertificateKind::unpack_contributors(committee, contributors);
+                self.verify_contributors(committee, &contributors)?;
+
+                let threshold_keys = committee
+                    .threshold_keys
+                    .as_ref()
+                    .ok_or(MessageError::ThresholdSetupMismatch)?;
+                signature
+                    .verify(&self.digest(), threshold_keys)
+                    .map_err(MessageError::from)
+            }
+            CertificateKind::BlsAggregate {
+                signature,
+                contributors,
+            } => {
+                let contributors = CertificateKind::unpack_contributors(committee, contributors);
+                self.verify_contributors(committee, &contributors)?;
+
+                let bls_keys = contributors
+                    .iter()
+                    .map(|name| {
+                        committee
+                            .witnesses
+                            .get(name)
+                            .and_then(|witness| witness.bls_public_key.clone())
+                            .ok_or(MessageError::MissingBlsKey(*name))
+                    })
+                    .collect::<MessageResult<Vec<_>>>()?;
+                crypto::bls_verify_aggregate(signature, &self.digest(), &bls_keys)
+                    .map_err(MessageError::from)
+            }
+        }
+    }
+
+    /// Ensure `contributors` lists only known, non-repeated witnesses whose combined voting
+    /// power reaches quorum.
+    fn verify_contributors(&self, committee: &Committee, contributors: &[PublicKey]) -> MessageResult<()> {
         let mut weight = 0;
         let mut used = HashSet::new();
-        for (name, _) in self.votes.iter() {
+        for name in contributors {
             ensure!(!used.contains(name), MessageError::WitnessReuse(*name));
             let voting_power = committee.voting_power(name);
             ensure!(voting_power > 0, MessageError::UnknownWitness(*name));
@@ -262,8 +1099,19 @@ impl PublishCertificate {
             weight >= committee.quorum_threshold(),
             MessageError::CertificateRequiresQuorum
         );
+        Ok(())
+    }
+
+    fn verify_votes(
+        &self,
+        committee: &Committee,
+        votes: &[(PublicKey, Signature)],
+    ) -> MessageResult<()> {
+        // Ensure the certificate has a quorum.
+        let contributors: Vec<_> = votes.iter().map(|(name, _)| *name).collect();
+        self.verify_contributors(committee, &contributors)?;
 
         // Check the signatures.
-        Signature::verify_batch(&self.digest(), &self.votes).map_err(MessageError::from)
+        Signature::verify_batch(&self.digest(), votes).map_err(MessageError::from)
     }
 }
@@ -0,0 +1,68 @@
+use crate::{error::MessageResult, Blake3, SequenceNumber};
+use vkd::{
+    proof_structs::{HistoryProof, LookupProof},
+    storage::types::AkdLabel,
+};
+use serde::{Deserialize, Serialize};
+
+/// A client request for the latest certified value of a label, along with a proof of
+/// (non-)membership that the client can verify against a `PublishCertificate`'s root.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LookupQuery {
+    /// The label to look up.
+    pub label: AkdLabel,
+}
+
+impl std::fmt::Debug for LookupQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "LookupQuery({})", base64::encode(&self.label))
+    }
+}
+
+/// A client request for the full, certified version history of a label.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryQuery {
+    /// The label whose history is requested.
+    pub label: AkdLabel,
+}
+
+impl std::fmt::Debug for HistoryQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "HistoryQuery({})", base64::encode(&self.label))
+    }
+}
+
+/// The IdP's reply to a `LookupQuery`: the sequence number of the `PublishCertificate` the
+/// proof is anchored to, and the `vkd` membership/non-membership proof itself.
+#[derive(Serialize, Deserialize)]
+pub struct LookupResponse {
+    /// The sequence number of the certificate backing `proof`.
+    pub sequence_number: SequenceNumber,
+    /// The lookup proof, to be checked against the certified root with `vkd::client::lookup_verify`.
+    pub proof: LookupProof<Blake3>,
+}
+
+impl std::fmt::Debug for LookupResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "LookupResponse(sequence {})", self.sequence_number)
+    }
+}
+
+/// The IdP's reply to a `HistoryQuery`.
+#[derive(Serialize, Deserialize)]
+pub struct HistoryResponse {
+    /// The sequence number of the certificate backing `proof`.
+    pub sequence_number: SequenceNumber,
+    /// The history proof, to be checked against the certified root with `vkd::client::key_history_verify`.
+    pub proof: HistoryProof<Blake3>,
+}
+
+impl std::fmt::Debug for HistoryResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "HistoryResponse(sequence {})", self.sequence_number)
+    }
+}
+
+/// Result types returned by the `QueryServer`.
+pub type LookupResult = MessageResult<LookupResponse>;
+pub type HistoryResult = MessageResult<HistoryResponse>;
@@ -1,11 +1,21 @@
 use crate::{
-    deserialize_root,
+    codec::{deserialize_bincode, serialize_bincode},
+    deserialize_root, ensure,
+    error::{MessageError, MessageResult},
     publish::{PublishMessage, PublishVote},
     serialize_root, Blake3, Root, SequenceNumber,
 };
-use vkd::{directory::Directory, ecvrf::HardCodedAkdVRF, storage::memory::AsyncInMemoryDatabase};
+use config::Committee;
+use crypto::{Digest, KeyPair, PublicKey, Signature};
+use ed25519_dalek::{Digest as _, Sha512};
 use futures::executor::block_on;
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use storage::Storage;
+use vkd::{directory::Directory, ecvrf::HardCodedAkdVRF, storage::memory::AsyncInMemoryDatabase};
+
+/// Storage address of the durable `State` write-ahead record.
+const STORE_STATE_ADDR: [u8; 32] = [255; 32];
 
 /// The safety-critical state of a witness.
 #[derive(Serialize, Deserialize, Clone)]
@@ -20,11 +30,36 @@ pub struct State {
     pub lock: Option<PublishVote>,
 }
 
+impl State {
+    /// Reconstruct the latest durably-recorded state, or a fresh one if nothing was ever
+    /// persisted. Meant to be called once at startup, before the witness answers any publish
+    /// notification, so it never resumes voting without first recovering whatever lock it may
+    /// have been holding when it crashed.
+    pub fn load(storage: &Storage) -> Self {
+        storage
+            .read(&STORE_STATE_ADDR)
+            .expect("Failed to load state from storage")
+            .map(|bytes| deserialize_bincode(&bytes).expect("Failed to deserialize state"))
+            .unwrap_or_default()
+    }
+
+    /// Atomically write this state as the new write-ahead record. Callers must persist a state
+    /// change (acquiring or releasing `lock`, advancing `sequence_number`) before releasing the
+    /// corresponding vote to the network, so a crash-and-restart can never resume into signing
+    /// something that conflicts with a vote already sent.
+    pub fn persist(&self, storage: &Storage) {
+        let serialized = serialize_bincode(self).expect("Failed to serialize state");
+        storage
+            .write(&STORE_STATE_ADDR, &serialized)
+            .expect("Failed to persist state");
+    }
+}
+
 impl Default for State {
     fn default() -> Self {
         let db = AsyncInMemoryDatabase::new();
         let vrf = HardCodedAkdVRF {};
-        let vkd = block_on(Directory::new::<Blake3>(&db, &vrf, false))
+        let vkd = block_on(Directory::new::<Blake3>(&db, &vrf))
             .expect("Failed to create empty tree directory");
         let current_azks = block_on(vkd.retrieve_current_azks()).expect("Failed to compute azks");
         let root = block_on(vkd.get_root_hash_at_epoch::<Blake3>(&current_azks, 0))
@@ -71,3 +106,143 @@ impl std::fmt::Debug for PublishCertificateQuery {
         write!(f, "CertRequest({})", self.sequence_number)
     }
 }
+
+/// Request for every publish certificate in the inclusive range `[from, to]`, used by a
+/// lagging party to catch up in one round-trip instead of one sequence number at a time.
+#[derive(Serialize, Deserialize)]
+pub struct CertificateRangeQuery {
+    /// The sequence number of the first requested certificate.
+    pub from: SequenceNumber,
+    /// The sequence number of the last requested certificate.
+    pub to: SequenceNumber,
+}
+
+impl CertificateRangeQuery {
+    /// The number of certificates this range spans, or zero if `to < from`.
+    pub fn span(&self) -> SequenceNumber {
+        if self.to < self.from {
+            0
+        } else {
+            self.to - self.from + 1
+        }
+    }
+}
+
+impl std::fmt::Debug for CertificateRangeQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "CertRangeRequest({}..={})", self.from, self.to)
+    }
+}
+
+/// Request for the ordered sequence of committed roots in the inclusive range `[from, to]`,
+/// witness-signed as a single lightweight attestation instead of a full `PublishCertificate`
+/// per sequence number. Meant for an external auditor that already trusts the witness
+/// committee and just needs the `hashes` vector `vkd::auditor::audit_verify` expects, without
+/// fetching and verifying a quorum certificate (proof included) for every epoch in the range.
+#[derive(Serialize, Deserialize)]
+pub struct RootChainQuery {
+    /// The sequence number of the first requested root.
+    pub from: SequenceNumber,
+    /// The sequence number of the last requested root.
+    pub to: SequenceNumber,
+}
+
+impl RootChainQuery {
+    /// The number of roots this range spans, or zero if `to < from`.
+    pub fn span(&self) -> SequenceNumber {
+        if self.to < self.from {
+            0
+        } else {
+            self.to - self.from + 1
+        }
+    }
+}
+
+impl std::fmt::Debug for RootChainQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "RootChainRequest({}..={})", self.from, self.to)
+    }
+}
+
+/// One committed root in a `RootChain`.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct RootChainEntry {
+    /// The sequence number this root was committed at.
+    pub sequence_number: SequenceNumber,
+    /// The committed root.
+    #[serde(serialize_with = "serialize_root")]
+    #[serde(deserialize_with = "deserialize_root")]
+    pub root: Root,
+}
+
+impl std::fmt::Debug for RootChainEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "({}, {})",
+            self.sequence_number,
+            base64::encode(self.root.as_bytes())
+        )
+    }
+}
+
+/// An ordered, witness-signed sequence of committed roots, answering a `RootChainQuery`. Serves
+/// as a lightweight trust anchor for an external auditor: the signature lets it attribute the
+/// chain to a specific witness without needing the full quorum certificate (and its state-
+/// transition proof) for every sequence number in the range.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RootChain {
+    /// The committed roots in increasing sequence-number order. Sequence numbers this witness
+    /// has no stored certificate for are skipped (mirroring `CertificateRangeQuery`'s gap
+    /// handling), so a gap just yields a shorter chain rather than an error.
+    pub entries: Vec<RootChainEntry>,
+    /// The witness attesting to this chain.
+    pub author: PublicKey,
+    /// A signature over `entries`, authenticating that this witness itself observed this exact
+    /// ordered sequence of roots.
+    pub signature: Signature,
+}
+
+impl RootChain {
+    /// Create a new root chain, signed by `keypair`.
+    pub fn new(entries: Vec<RootChainEntry>, keypair: &KeyPair) -> Self {
+        let signature = Signature::new(&Self::digest(&entries), keypair);
+        Self {
+            entries,
+            author: keypair.public(),
+            signature,
+        }
+    }
+
+    fn digest(entries: &[RootChainEntry]) -> Digest {
+        let mut hasher = Sha512::new();
+        for entry in entries {
+            hasher.update(entry.sequence_number.to_le_bytes());
+            hasher.update(entry.root.as_bytes());
+        }
+        Digest(hasher.finalize().as_slice()[..32].try_into().unwrap())
+    }
+
+    /// Verify that the chain is signed by a committee member and the signature matches its
+    /// entries.
+    pub fn verify(&self, committee: &Committee) -> MessageResult<()> {
+        ensure!(
+            committee.voting_power(&self.author) > 0,
+            MessageError::UnknownWitness(self.author)
+        );
+        self.signature
+            .verify(&Self::digest(&self.entries), &self.author)
+            .map_err(MessageError::from)
+    }
+}
+
+impl std::fmt::Debug for RootChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "RootChain({}, {} entries)",
+            self.author,
+            self.entries.len()
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use crate::merkle::BatchCommitment;
 use vkd::storage::types::{AkdLabel, AkdValue};
 
 /// A client request in a format understandable by `vkd`.
@@ -5,3 +6,6 @@ pub type UpdateRequest = (AkdLabel, AkdValue);
 
 /// A batch of requests.
 pub type Batch = Vec<UpdateRequest>;
+
+/// A sealed batch together with the Merkle commitment over its requests.
+pub type SealedBatch = (Batch, BatchCommitment);
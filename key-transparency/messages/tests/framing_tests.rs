@@ -0,0 +1,88 @@
+use bytes::{Bytes, BytesMut};
+use messages::error::{EncodeError, FrameError};
+use messages::framing::FrameCodec;
+use tokio_util::codec::{Decoder, Encoder};
+
+#[test]
+fn round_trips_a_frame() {
+    let mut codec = FrameCodec::new(1_024);
+    let mut buf = BytesMut::new();
+    codec.encode(Bytes::from_static(b"hello"), &mut buf).unwrap();
+    assert_eq!(codec.decode(&mut buf).unwrap(), Some(Bytes::from_static(b"hello")));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn waits_for_a_prefix_split_across_reads() {
+    let mut codec = FrameCodec::new(1_024);
+    let mut buf = BytesMut::new();
+    codec.encode(Bytes::from_static(b"hello"), &mut buf).unwrap();
+
+    // Feed the frame one byte at a time: until the whole prefix and payload have arrived,
+    // `decode` must return `Ok(None)` rather than erroring or returning a short frame.
+    let mut stream = BytesMut::new();
+    let mut frame = None;
+    while frame.is_none() {
+        stream.extend_from_slice(&buf.split_to(1));
+        frame = codec.decode(&mut stream).unwrap();
+    }
+    assert_eq!(frame, Some(Bytes::from_static(b"hello")));
+}
+
+#[test]
+fn rejects_an_oversized_frame_before_buffering_its_payload() {
+    let mut codec = FrameCodec::new(4);
+    let mut buf = BytesMut::new();
+    // Only the VarInt length prefix for a 1,000-byte frame is buffered -- the payload itself
+    // never arrives -- yet the codec must still reject it immediately instead of returning
+    // `Ok(None)` to wait for payload bytes that would blow past `max_length`.
+    let mut announced = BytesMut::new();
+    FrameCodec::new(usize::MAX)
+        .encode(Bytes::from(vec![0u8; 1_000]), &mut announced)
+        .unwrap();
+    buf.extend_from_slice(&announced[..2]); // just the two-byte VarInt prefix for 1,000
+
+    match codec.decode(&mut buf) {
+        Err(FrameError::FrameTooLarge { length, max_length }) => {
+            assert_eq!(length, 1_000);
+            assert_eq!(max_length, 4);
+        }
+        other => panic!("expected FrameTooLarge, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_length_prefix_that_never_terminates() {
+    let mut codec = FrameCodec::new(1_024);
+    let mut buf = BytesMut::from(&[0x80u8, 0x80, 0x80, 0x80, 0x80][..]);
+    assert!(matches!(codec.decode(&mut buf), Err(FrameError::MalformedLengthPrefix)));
+}
+
+#[test]
+fn reports_bytes_missing_on_eof_mid_payload() {
+    let mut codec = FrameCodec::new(1_024);
+    let mut buf = BytesMut::new();
+    codec.encode(Bytes::from_static(b"hello"), &mut buf).unwrap();
+    buf.truncate(buf.len() - 2); // drop the last two payload bytes, as if the peer hung up
+
+    match codec.decode_eof(&mut buf) {
+        Err(FrameError::BytesMissing { expected, available }) => {
+            assert_eq!(expected, 5);
+            assert_eq!(available, 4); // 1-byte prefix + 3 remaining payload bytes
+        }
+        other => panic!("expected BytesMissing, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_an_oversized_frame_on_encode() {
+    let mut codec = FrameCodec::new(4);
+    let mut buf = BytesMut::new();
+    match codec.encode(Bytes::from_static(b"hello"), &mut buf) {
+        Err(EncodeError::FrameTooLarge { length, max_length }) => {
+            assert_eq!(length, 5);
+            assert_eq!(max_length, 4);
+        }
+        other => panic!("expected FrameTooLarge, got {:?}", other),
+    }
+}
@@ -0,0 +1,83 @@
+use config::CommitteeHistory;
+use crypto::PublicKey;
+use messages::error::MessageError;
+use messages::light_client::LightClient;
+use messages::publish::{CertificateKind, PublishCertificate, PublishMessage, PublishNotification, PublishVote};
+use test_utils::{certificate, committee};
+
+/// Build a certificate at `sequence_number`, reusing a fresh proof and every key except
+/// `excluded`, mirroring how `test_utils::certificate` itself assembles one.
+async fn certificate_at(sequence_number: u64, excluded: &[PublicKey]) -> PublishCertificate {
+    let (_, idp_keypair) = test_utils::keys().pop().unwrap();
+    let (_, root, proof) = test_utils::proof().await;
+    let notification =
+        PublishNotification::new(root, proof, sequence_number, /* round */ 0, &idp_keypair);
+    let votes: Vec<_> = test_utils::keys()
+        .iter()
+        .filter(|(name, _)| !excluded.contains(name))
+        .map(|(_, keypair)| PublishVote::new(&notification, keypair))
+        .collect();
+    PublishCertificate {
+        root: notification.root,
+        sequence_number: notification.sequence_number,
+        round: notification.round,
+        timestamps: votes.iter().map(|v| (v.author, v.timestamp)).collect(),
+        kind: CertificateKind::Votes(votes.into_iter().map(|v| (v.author, v.signature)).collect()),
+    }
+}
+
+#[tokio::test]
+async fn accepts_monotonic_quorum_certified_update() {
+    let genesis = certificate().await;
+    let mut light_client = LightClient::new(genesis.sequence_number, genesis.root);
+
+    let next = certificate_at(genesis.sequence_number + 1, &[]).await;
+    assert!(light_client
+        .verify_update(&next, &committee(0))
+        .is_ok());
+    assert_eq!(light_client.trusted(), (next.sequence_number, next.root));
+}
+
+#[tokio::test]
+async fn rejects_non_monotonic_update() {
+    let genesis = certificate().await;
+    let mut light_client = LightClient::new(genesis.sequence_number, genesis.root);
+
+    // A certificate for a sequence number at or before the trusted one is rejected outright,
+    // even though its quorum is otherwise perfectly valid.
+    match light_client.verify_update(&genesis, &committee(0)) {
+        Err(MessageError::NonMonotonicSequenceNumber { trusted, received }) => {
+            assert_eq!(trusted, genesis.sequence_number);
+            assert_eq!(received, genesis.sequence_number);
+        }
+        other => panic!("Expected NonMonotonicSequenceNumber, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn bisects_across_a_committee_reconfiguration() {
+    let genesis = certificate().await;
+    let mut history = CommitteeHistory::new(committee(0));
+
+    // From sequence number 5 onward, one witness's voting power is revoked.
+    let mut reconfigured = committee(0);
+    let removed = *reconfigured.witnesses.keys().next().unwrap();
+    reconfigured.witnesses.get_mut(&removed).unwrap().voting_power = 0;
+    history.reconfigure(5, reconfigured);
+
+    let target = certificate_at(10, &[removed]).await;
+    let mut light_client = LightClient::new(genesis.sequence_number, genesis.root);
+
+    // The only certificate `fetch` is ever asked for is the reconfiguration boundary (5), which
+    // must itself carry a valid quorum under the *new* committee (excluding the removed witness).
+    let boundary = certificate_at(5, &[removed]).await;
+    let result = light_client
+        .bisect_update(target.clone(), &history, |sequence_number| {
+            assert_eq!(sequence_number, 5);
+            let boundary = boundary.clone();
+            async move { Ok::<_, MessageError>(boundary) }
+        })
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(light_client.trusted(), (target.sequence_number, target.root));
+}
@@ -1,4 +1,8 @@
-use test_utils::{certificate, committee, notification, proof, votes};
+use messages::publish::PublishMessage;
+use test_utils::{
+    batch, batch_proof, certificate, committee, notification, proof, threshold_certificate,
+    threshold_committee, votes,
+};
 
 #[tokio::test]
 async fn verify_notification() {
@@ -25,3 +29,107 @@ async fn verify_certificate() {
     let certificate = certificate().await;
     assert!(certificate.verify(&committee(0)).is_ok());
 }
+
+#[tokio::test]
+async fn verify_threshold_certificate() {
+    let (committee, _) = threshold_committee(0);
+    let certificate = threshold_certificate().await;
+    assert!(certificate.verify(&committee).is_ok());
+}
+
+#[tokio::test]
+async fn verify_threshold_certificate_requires_threshold_setup() {
+    // A threshold certificate is rejected by a committee that was never given the matching
+    // group public key, even though both forms compare equal (the compact form is meant to
+    // be a drop-in replacement, so equality only tracks root and sequence number).
+    let certificate = threshold_certificate().await;
+    let other = certificate().await;
+    assert_eq!(certificate, other);
+    assert!(matches!(
+        certificate.verify(&committee(0)),
+        Err(messages::error::MessageError::ThresholdSetupMismatch)
+    ));
+}
+
+#[tokio::test]
+async fn verify_batch() {
+    let (root_0, root_1, root_2, _) = batch_proof().await;
+    let batch = batch().await;
+
+    // The batch's `PublishMessage` accessors report its final transition, not an intermediate
+    // one, and `root_range` spans every transition it carries.
+    assert_eq!(batch.sequence_number(), 2);
+    assert_eq!(*batch.root(), root_2);
+    assert_eq!(batch.root_range(), (root_1, root_2));
+
+    assert!(batch.verify(&committee(0), &root_0).await.is_ok());
+}
+
+#[tokio::test]
+async fn verify_bad_batch() {
+    let (_, _, root_2, _) = batch_proof().await;
+    let batch = batch().await;
+    // Auditing against the wrong starting root must fail even though every transition after it
+    // is genuinely a valid extension of the next.
+    assert!(batch.verify(&committee(0), &root_2).await.is_err());
+}
+
+#[tokio::test]
+async fn certify_batch() {
+    let batch = batch().await;
+    let certificate = messages::publish::PublishCertificate {
+        root: *batch.root(),
+        sequence_number: batch.sequence_number(),
+        round: batch.round(),
+        kind: messages::publish::CertificateKind::Votes(
+            test_utils::keys()
+                .iter()
+                .map(|(_, keypair)| {
+                    let vote = messages::publish::PublishVote::for_batch(&batch, keypair);
+                    (vote.author, vote.signature)
+                })
+                .collect(),
+        ),
+        timestamps: Vec::new(),
+    };
+    assert!(certificate.verify(&committee(0)).is_ok());
+    assert_eq!(certificate.sequence_number, batch.sequence_number());
+}
+
+#[tokio::test]
+async fn vote_timestamp_is_authenticated() {
+    let mut vote = votes().await.pop().unwrap();
+    assert!(vote.verify(&committee(0)).is_ok());
+
+    // A relay tampering with the timestamp after the fact is caught by `verify`, exactly like
+    // tampering with the root or sequence number would be.
+    vote.timestamp += 1;
+    assert!(vote.verify(&committee(0)).is_err());
+}
+
+#[tokio::test]
+async fn confirmation_time_is_the_weighted_median() {
+    let names: Vec<_> = test_utils::keys().into_iter().map(|(name, _)| name).collect();
+    let mut certificate = certificate().await;
+    certificate.timestamps = names
+        .iter()
+        .zip([100, 200, 300, 400])
+        .map(|(name, timestamp)| (*name, timestamp))
+        .collect();
+
+    // Every witness carries equal voting power in the test committee, so the weighted median of
+    // four timestamps is the lower of the two middle values.
+    assert_eq!(certificate.confirmation_time(&committee(0)), Some(200));
+
+    // `ordered_timestamps` reports the same pairs sorted by timestamp, so the slowest witness
+    // (to vote) is always last.
+    let ordered = certificate.ordered_timestamps();
+    assert_eq!(ordered.last().unwrap().1, 400);
+}
+
+#[tokio::test]
+async fn confirmation_time_is_none_without_timestamps() {
+    let mut certificate = certificate().await;
+    certificate.timestamps.clear();
+    assert_eq!(certificate.confirmation_time(&committee(0)), None);
+}
@@ -0,0 +1,98 @@
+//! A minimal metrics wrapper modeled on lighthouse's `metrics` crate: a single process-wide
+//! Prometheus [`Registry`], typed constructors for the metric kinds this codebase actually
+//! uses, and a tiny HTTP server exposing the registry at `/metrics` for scraping. Kept
+//! deliberately small (no histograms, no push gateway) since nothing here needs them yet.
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use prometheus::{Encoder, IntCounter, IntGauge, Opts, Registry, TextEncoder};
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+lazy_static! {
+    /// The registry every metric constructed through this crate is registered against.
+    pub static ref REGISTRY: Registry = Registry::new();
+}
+
+/// Register a new counter under [`REGISTRY`]. Panics if `name` is already taken: metric names
+/// must be unique within a process, and a clash can only come from a programming mistake.
+pub fn register_int_counter(name: &str, help: &str) -> IntCounter {
+    let counter = IntCounter::with_opts(Opts::new(name, help)).expect("Invalid counter metadata");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("Failed to register counter (duplicate metric name?)");
+    counter
+}
+
+/// Register a new gauge under [`REGISTRY`]. Panics if `name` is already taken, for the same
+/// reason as [`register_int_counter`].
+pub fn register_int_gauge(name: &str, help: &str) -> IntGauge {
+    let gauge = IntGauge::with_opts(Opts::new(name, help)).expect("Invalid gauge metadata");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("Failed to register gauge (duplicate metric name?)");
+    gauge
+}
+
+/// Render every metric currently in [`REGISTRY`] in the Prometheus text exposition format.
+fn gather() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+    buffer
+}
+
+/// Spawn a bare-bones HTTP server answering `GET /metrics` with the current registry
+/// snapshot (anything else gets a 404). This is a scrape target, not a general-purpose web
+/// server, so it is intentionally not built on a full HTTP stack.
+pub fn spawn(address: SocketAddr) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind metrics server to {}: {}", address, e);
+                return;
+            }
+        };
+        info!("Metrics server listening on {}", address);
+
+        loop {
+            let (socket, _) = match listener.accept().await {
+                Ok(x) => x,
+                Err(e) => {
+                    error!("Failed to accept metrics connection: {}", e);
+                    continue;
+                }
+            };
+            tokio::spawn(serve(socket));
+        }
+    })
+}
+
+/// Handle a single scrape connection: read (and discard) the request, reply once, close.
+async fn serve(mut socket: tokio::net::TcpStream) {
+    let mut buffer = [0u8; 1024];
+    let n = match socket.read(&mut buffer).await {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+    let request = String::from_utf8_lossy(&buffer[..n]);
+
+    let response = if request.starts_with("GET /metrics") {
+        let body = gather();
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        [header.into_bytes(), body].concat()
+    } else {
+        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+    };
+
+    let _ = socket.write_all(&response).await;
+}
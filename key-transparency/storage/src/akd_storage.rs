@@ -1,4 +1,11 @@
-use crate::Storage;
+use crate::{
+    backend,
+    backend::{Backend, DynBackend},
+    Storage,
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
 use vkd::{
     errors::StorageError as AkdStorageError,
     storage::{
@@ -10,26 +17,38 @@ use vkd::{
         Storable as AkdStorable,
     },
 };
-use async_trait::async_trait;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
 
-pub struct AkdStorage {
-    database: Arc<RwLock<Storage>>,
+/// The vkd storage adapter, generic over the `Backend` that actually durably persists
+/// records. Defaults to `DynBackend` so callers that only know which backend to use at
+/// startup (see `PrivateConfig::storage_backend`) are not forced to monomorphize on it.
+pub struct AkdStorage<B: Backend = DynBackend> {
+    database: Arc<RwLock<B>>,
     transaction: Transaction,
 }
 
-impl AkdStorage {
+impl AkdStorage<Storage> {
+    /// Creates a new `AkdStorage` backed by a local, on-disk `Storage` at `path`. Kept as
+    /// the default constructor since local storage remains the default backend.
     pub fn new(path: &str) -> Self {
         let storage = Storage::new(path).expect("Failed to initialize inner storage");
+        backend::recover_batch_journal(&storage)
+            .expect("Failed to recover an incomplete batch-write journal");
+        Self::with_backend(storage)
+    }
+}
+
+impl<B: Backend> AkdStorage<B> {
+    /// Creates a new `AkdStorage` backed by an already-constructed `backend`, e.g. an
+    /// `S3Backend` for operators who want remote, replicated durable storage.
+    pub fn with_backend(backend: B) -> Self {
         Self {
-            database: Arc::new(RwLock::new(storage)),
+            database: Arc::new(RwLock::new(backend)),
             transaction: Transaction::new(),
         }
     }
 }
 
-impl Clone for AkdStorage {
+impl<B: Backend> Clone for AkdStorage<B> {
     fn clone(&self) -> Self {
         Self {
             database: self.database.clone(),
@@ -39,9 +58,9 @@ impl Clone for AkdStorage {
 }
 
 #[async_trait]
-impl vkd::storage::Storage for AkdStorage {
+impl<B: Backend> vkd::storage::Storage for AkdStorage<B> {
     async fn log_metrics(&self, _level: log::Level) {
-       self.database.read().await.log_metrics();
+        self.database.read().await.log_metrics().await;
     }
 
     async fn begin_transaction(&self) -> bool {
@@ -77,15 +96,26 @@ impl vkd::storage::Storage for AkdStorage {
         let guard = self.database.write().await;
         guard
             .write(&record.get_full_binary_id(), &serialized)
+            .await
             .map_err(|e| AkdStorageError::Other(format!("Failed to persist record: {}", e)))
     }
 
     async fn batch_set(&self, records: Vec<DbRecord>) -> Result<(), AkdStorageError> {
-        // TODO: This is really bad, we may end up with partial writes in case of failure.
-        for record in records {
-            self.set(record).await?;
+        let mut entries = Vec::with_capacity(records.len());
+        for record in &records {
+            let serialized = bincode::serialize(record)
+                .map_err(|e| AkdStorageError::Other(format!("Serialization error: {}", e)))?;
+            entries.push((record.get_full_binary_id(), serialized));
         }
-        Ok(())
+
+        // Goes through the backend's `batch_write`, which `Storage` implements atomically via
+        // a write-ahead journal, so a crash mid-commit can never strand the directory with
+        // only some of a `Transaction`'s records applied.
+        let guard = self.database.write().await;
+        guard
+            .batch_write(entries)
+            .await
+            .map_err(|e| AkdStorageError::Other(format!("Failed to persist batch: {}", e)))
     }
 
     async fn get<St: AkdStorable>(&self, id: &St::Key) -> Result<DbRecord, AkdStorageError> {
@@ -97,7 +127,7 @@ impl vkd::storage::Storage for AkdStorage {
 
         let binary_id = St::get_full_binary_key_id(id);
         let guard = self.database.read().await;
-        match (*guard).read(&binary_id) {
+        match guard.read(&binary_id).await {
             Ok(Some(bytes)) => bincode::deserialize(&bytes)
                 .map_err(|e| AkdStorageError::Other(format!("Serialization error: {}", e))),
             Ok(None) => Err(AkdStorageError::NotFound("Not found".to_string())),
@@ -123,7 +153,7 @@ impl vkd::storage::Storage for AkdStorage {
     }
 
     async fn flush_cache(&self) {
-        self.database.read().await.flush_cache();
+        self.database.read().await.flush_cache().await;
     }
 
     async fn get_user_data(&self, _username: &AkdLabel) -> Result<KeyData, AkdStorageError> {
@@ -0,0 +1,198 @@
+use crate::Storage;
+use async_trait::async_trait;
+use thiserror::Error;
+
+/// An error returned by a `Backend` implementation.
+#[derive(Error, Debug)]
+pub enum BackendError {
+    #[error("backend I/O error: {0}")]
+    Io(String),
+}
+
+/// A durable key-value backend for `AkdStorage`. Abstracting the actual storage medium
+/// behind this trait lets `AkdStorage` run against anything from a local directory to a
+/// remote object store without its own logic changing.
+///
+/// Keys and values are opaque bytes: `AkdStorage` is responsible for serializing records
+/// and deriving their binary identifiers before calling into a `Backend`.
+#[async_trait]
+pub trait Backend: Send + Sync {
+    /// Read the value stored under `key`, or `None` if nothing has ever been written there.
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, BackendError>;
+
+    /// Durably write `value` under `key`, overwriting whatever was there before.
+    async fn write(&self, key: &[u8], value: &[u8]) -> Result<(), BackendError>;
+
+    /// Write every `(key, value)` pair. Implementations should make this atomic where the
+    /// underlying store allows it; the default implementation does not, and simply writes
+    /// one entry at a time.
+    async fn batch_write(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), BackendError> {
+        for (key, value) in entries {
+            self.write(&key, &value).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete whatever is stored under `key`, if anything.
+    async fn delete(&self, key: &[u8]) -> Result<(), BackendError>;
+
+    /// Log this backend's own diagnostics (e.g. cache hit rate), if it has any worth
+    /// logging. A no-op by default, since most backends (e.g. `S3Backend`) have nothing
+    /// beyond what `vkd` already tracks at the `AkdStorage` level.
+    async fn log_metrics(&self) {}
+
+    /// Flush any write-behind cache this backend keeps, if any. A no-op by default.
+    async fn flush_cache(&self) {}
+}
+
+/// A `Backend` as a trait object, so the concrete backend an IdP or witness uses can be
+/// picked at startup from `PrivateConfig` rather than fixed at compile time.
+pub type DynBackend = Box<dyn Backend>;
+
+#[async_trait]
+impl Backend for DynBackend {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, BackendError> {
+        (**self).read(key).await
+    }
+
+    async fn write(&self, key: &[u8], value: &[u8]) -> Result<(), BackendError> {
+        (**self).write(key, value).await
+    }
+
+    async fn batch_write(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), BackendError> {
+        (**self).batch_write(entries).await
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<(), BackendError> {
+        (**self).delete(key).await
+    }
+
+    async fn log_metrics(&self) {
+        (**self).log_metrics().await
+    }
+
+    async fn flush_cache(&self) {
+        (**self).flush_cache().await
+    }
+}
+
+/// Reserved key for the write-ahead journal backing `Storage`'s atomic `batch_write`. Only
+/// ever present while a batch is in flight: it records every `(key, value)` pair the batch
+/// intends to write, so `recover_batch_journal` can always bring a crashed-mid-batch `Storage`
+/// forward to "everything applied" rather than leaving it at "some of it applied".
+const BATCH_JOURNAL_KEY: &[u8] = b"__batch_write_journal__";
+
+/// The default backend: the existing local, on-disk `Storage`.
+#[async_trait]
+impl Backend for Storage {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, BackendError> {
+        Storage::read(self, key).map_err(|e| BackendError::Io(e.to_string()))
+    }
+
+    async fn write(&self, key: &[u8], value: &[u8]) -> Result<(), BackendError> {
+        Storage::write(self, key, value).map_err(|e| BackendError::Io(e.to_string()))
+    }
+
+    async fn batch_write(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), BackendError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // Durably record the whole batch before touching any of its keys. If we crash
+        // anywhere below, `recover_batch_journal` replays this journal on the next open
+        // instead of leaving some keys written and others not.
+        let journal = bincode::serialize(&entries)
+            .map_err(|e| BackendError::Io(format!("Failed to serialize batch journal: {}", e)))?;
+        Storage::write(self, BATCH_JOURNAL_KEY, &journal)
+            .map_err(|e| BackendError::Io(e.to_string()))?;
+
+        for (key, value) in &entries {
+            Storage::write(self, key, value).map_err(|e| BackendError::Io(e.to_string()))?;
+        }
+
+        // The batch is now fully applied: the journal is no longer needed to recover it.
+        Storage::delete(self, BATCH_JOURNAL_KEY).map_err(|e| BackendError::Io(e.to_string()))
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<(), BackendError> {
+        Storage::delete(self, key).map_err(|e| BackendError::Io(e.to_string()))
+    }
+
+    async fn log_metrics(&self) {
+        Storage::log_metrics(self)
+    }
+
+    async fn flush_cache(&self) {
+        Storage::flush_cache(self)
+    }
+}
+
+/// Replay an incomplete `batch_write` journal left behind by a crash mid-commit, so a restart
+/// never observes a half-applied batch; a no-op if no journal is present (the common case: the
+/// previous run either never batched or committed cleanly). This belongs in `Storage::new`
+/// itself, the single choke point every consumer of `Storage` goes through; until that
+/// constructor calls it directly, every call site that opens a `Storage` meant to back
+/// `AkdStorage` must call this immediately afterward and before any other operation can
+/// observe a partially-applied batch -- `AkdStorage::new` does so for its own inner `Storage`,
+/// and `idp::main` does so for the `Storage` it boxes into a `DynBackend` and hands to
+/// `AkdStorage::with_backend`, since `with_backend` has no `Storage` of its own to recover.
+pub fn recover_batch_journal(storage: &Storage) -> Result<(), BackendError> {
+    let journal = match Storage::read(storage, BATCH_JOURNAL_KEY)
+        .map_err(|e| BackendError::Io(e.to_string()))?
+    {
+        Some(bytes) => bytes,
+        None => return Ok(()),
+    };
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = bincode::deserialize(&journal)
+        .map_err(|e| BackendError::Io(format!("Failed to deserialize batch journal: {}", e)))?;
+    for (key, value) in &entries {
+        Storage::write(storage, key, value).map_err(|e| BackendError::Io(e.to_string()))?;
+    }
+    Storage::delete(storage, BATCH_JOURNAL_KEY).map_err(|e| BackendError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn recovers_a_batch_that_crashed_after_the_journal_but_before_the_entries() {
+        let path = ".test_recovers_a_batch_that_crashed_after_the_journal_but_before_the_entries";
+        let _ = std::fs::remove_dir_all(path);
+        let storage = Storage::new(path).unwrap();
+
+        // Reproduce exactly the prefix of `Backend::batch_write` that runs before a crash would
+        // leave the journal durably written but none of the batch's own keys applied yet.
+        let entries = vec![
+            (b"key-1".to_vec(), b"value-1".to_vec()),
+            (b"key-2".to_vec(), b"value-2".to_vec()),
+        ];
+        let journal = bincode::serialize(&entries).unwrap();
+        Storage::write(&storage, BATCH_JOURNAL_KEY, &journal).unwrap();
+        drop(storage);
+
+        // Simulate the restart: open a fresh `Storage` handle onto the same on-disk state and
+        // recover before anything else touches it.
+        let storage = Storage::new(path).unwrap();
+        assert_eq!(Storage::read(&storage, b"key-1").unwrap(), None);
+        recover_batch_journal(&storage).unwrap();
+
+        assert_eq!(Storage::read(&storage, b"key-1").unwrap(), Some(b"value-1".to_vec()));
+        assert_eq!(Storage::read(&storage, b"key-2").unwrap(), Some(b"value-2".to_vec()));
+        assert_eq!(Storage::read(&storage, BATCH_JOURNAL_KEY).unwrap(), None);
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[tokio::test]
+    async fn recovery_is_a_no_op_without_a_pending_journal() {
+        let path = ".test_recovery_is_a_no_op_without_a_pending_journal";
+        let _ = std::fs::remove_dir_all(path);
+        let storage = Storage::new(path).unwrap();
+
+        recover_batch_journal(&storage).unwrap();
+        assert_eq!(Storage::read(&storage, BATCH_JOURNAL_KEY).unwrap(), None);
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
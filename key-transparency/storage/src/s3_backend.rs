@@ -0,0 +1,80 @@
+use crate::backend::{Backend, BackendError};
+use async_trait::async_trait;
+use aws_sdk_s3::{types::ByteStream, Client};
+
+/// A `Backend` storing every record as an object in an S3-compatible bucket, so the IdP
+/// and witnesses can run against remote, replicated storage instead of a local directory.
+pub struct S3Backend {
+    client: Client,
+    bucket: String,
+    /// Prepended to every key, so several logical stores (e.g. secure storage and sync
+    /// storage) can share a single bucket without colliding.
+    prefix: String,
+}
+
+impl S3Backend {
+    /// Connect to `bucket` using the default AWS SDK credential chain (environment,
+    /// profile, or instance metadata), namespacing every key under `prefix`.
+    pub async fn new(bucket: &str, prefix: &str) -> Self {
+        let config = aws_config::load_from_env().await;
+        Self {
+            client: Client::new(&config),
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn object_key(&self, key: &[u8]) -> String {
+        format!("{}/{}", self.prefix, hex::encode(key))
+    }
+}
+
+#[async_trait]
+impl Backend for S3Backend {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, BackendError> {
+        let result = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => {
+                let bytes = output
+                    .body
+                    .collect()
+                    .await
+                    .map_err(|e| BackendError::Io(e.to_string()))?
+                    .into_bytes();
+                Ok(Some(bytes.to_vec()))
+            }
+            Err(e) if e.to_string().contains("NoSuchKey") => Ok(None),
+            Err(e) => Err(BackendError::Io(e.to_string())),
+        }
+    }
+
+    async fn write(&self, key: &[u8], value: &[u8]) -> Result<(), BackendError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .body(ByteStream::from(value.to_vec()))
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| BackendError::Io(e.to_string()))
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<(), BackendError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| BackendError::Io(e.to_string()))
+    }
+}
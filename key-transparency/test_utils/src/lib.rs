@@ -1,30 +1,48 @@
-use vkd::{
-    directory::Directory, ecvrf::HardCodedAkdVRF, storage::memory::AsyncInMemoryDatabase, AkdLabel,
-    AkdValue,
-};
 use bytes::Bytes;
 use config::{Committee, Idp, Witness};
-use crypto::{KeyPair, PublicKey};
+use crypto::{BlsKeyPair, KeyPair, PublicKey, Signature, SignatureScheme, ThresholdKeyShare};
 use futures::{stream::StreamExt, SinkExt};
 use idp::spawn_idp;
 use messages::{
-    publish::{Proof, PublishCertificate, PublishNotification, PublishVote},
+    publish::{
+        AggregatedCommitments, CertificateKind, Proof, PublishCertificate, PublishMessage,
+        PublishNotification, PublishVote,
+    },
     update::UpdateRequest,
-    Blake3, IdPToWitnessMessage, Root, WitnessToIdPMessage,
+    Blake3, ClientToIdPMessage, IdPToWitnessMessage, Root, WitnessToIdPMessage,
 };
 use network::reliable_sender::{CancelHandler, ReliableSender};
 use rand::{rngs::StdRng, SeedableRng};
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr};
 use storage::Storage;
-use tokio::{net::TcpListener, task::JoinHandle};
+use tokio::{
+    net::TcpListener,
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
-use witness::spawn_witness;
+use vkd::{
+    directory::Directory, ecvrf::HardCodedAkdVRF, storage::memory::AsyncInMemoryDatabase, AkdLabel,
+    AkdValue,
+};
+use winter_crypto::Hasher;
+use witness::{
+    spawn_witness,
+    test_witness::{ByzantineBehavior, TestWitness},
+};
+
+// The maximum certificate range span served by test witnesses.
+pub const TEST_MAX_RANGE_SPAN: messages::SequenceNumber = 1_000;
+
+// The view timeout used by test witnesses, short enough that tests exercising the
+// `ViewChangeHandler`'s recovery path do not have to wait out a production-length timeout.
+pub const TEST_VIEW_TIMEOUT: Duration = Duration::from_millis(200);
 
 // Test cryptographic keys.
 pub fn keys() -> Vec<(PublicKey, KeyPair)> {
     let mut rng = StdRng::from_seed([0; 32]);
     (0..4)
-        .map(|_| KeyPair::generate_keypair(&mut rng))
+        .map(|_| KeyPair::generate_keypair(SignatureScheme::Ed25519, &mut rng))
         .collect()
 }
 
@@ -46,10 +64,85 @@ pub fn committee(base_port: u16) -> Committee {
                         address: format!("127.0.0.1:{}", base_port + 1 + i as u16)
                             .parse()
                             .unwrap(),
+                        bls_public_key: None,
                     },
                 )
             })
             .collect(),
+        threshold_keys: None,
+        data_availability_srs: None,
+        max_payload_size: config::default_max_payload_size(),
+        fork_id: 0,
+    }
+}
+
+// A test committee set up for the data-availability layer, sized so that its SRS comfortably
+// covers audit proofs without hitting `validity_threshold()`.
+pub fn data_availability_committee(base_port: u16) -> Committee {
+    let mut committee = committee(base_port);
+    let mut rng = StdRng::from_seed([2; 32]);
+    committee.data_availability_srs = Some(crypto::kzg_da::Srs::setup(
+        committee.validity_threshold() as usize,
+        &mut rng,
+    ));
+    committee
+}
+
+// A test committee set up for threshold-signature certificates, together with the per-witness
+// secret shares (in witness order) a dealer would hand out alongside it.
+pub fn threshold_committee(base_port: u16) -> (Committee, Vec<ThresholdKeyShare>) {
+    let mut committee = committee(base_port);
+    let mut rng = StdRng::from_seed([1; 32]);
+    let (threshold_keys, shares) = crypto::threshold_setup(
+        committee.witnesses.len(),
+        committee.quorum_threshold() as usize,
+        &mut rng,
+    );
+    committee.threshold_keys = Some(threshold_keys);
+    (committee, shares)
+}
+
+// A test committee set up for BLS-aggregate certificates, together with the per-witness
+// keypairs (in witness order) used to sign and (for the committee half) register with.
+pub fn bls_committee(base_port: u16) -> (Committee, Vec<BlsKeyPair>) {
+    let mut committee = committee(base_port);
+    let mut rng = StdRng::from_seed([3; 32]);
+    let bls_keys: Vec<_> = (0..committee.witnesses.len())
+        .map(|_| BlsKeyPair::generate(&mut rng))
+        .collect();
+    for ((_, witness), keypair) in committee.witnesses.iter_mut().zip(bls_keys.iter()) {
+        witness.bls_public_key = Some(keypair.public_key());
+    }
+    (committee, bls_keys)
+}
+
+// A test threshold-signature certificate over the same notification as `certificate()`.
+pub async fn threshold_certificate() -> PublishCertificate {
+    let notification = notification().await;
+    let (committee, shares) = threshold_committee(0);
+    let threshold_keys = committee.threshold_keys.unwrap();
+
+    // A certificate's digest only commits to the root, sequence number and round (see
+    // `PublishMessage::digest`), so it is identical to the notification's.
+    let digest = notification.digest();
+    let names: Vec<_> = committee.witnesses.keys().copied().collect();
+    let contributors = CertificateKind::pack_contributors(&committee, &names);
+    let quorum_shares: Vec<_> = shares
+        .iter()
+        .take(threshold_keys.threshold())
+        .map(|share| share.sign(&digest))
+        .collect();
+    let signature = threshold_keys.combine(&digest, &quorum_shares).unwrap();
+
+    PublishCertificate {
+        root: notification.root,
+        sequence_number: notification.sequence_number,
+        round: notification.round,
+        kind: CertificateKind::Threshold {
+            signature,
+            contributors,
+        },
+        timestamps: Vec::new(),
     }
 }
 
@@ -67,8 +160,11 @@ pub fn updates() -> Vec<UpdateRequest> {
 // Serialized test update requests.
 pub fn serialized_updates() -> Vec<Bytes> {
     updates()
-        .iter()
-        .map(|update| Bytes::from(bincode::serialize(&update).unwrap()))
+        .into_iter()
+        .map(|update| {
+            let message = ClientToIdPMessage::Update(update);
+            Bytes::from(messages::codec::encode(&message).unwrap())
+        })
         .collect()
 }
 
@@ -80,7 +176,7 @@ pub async fn proof() -> (Root, Root, Proof) {
     // Create a test tree with dumb key-values.
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedAkdVRF {};
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
 
     // Compute the start root (at sequence 0) and end root (at sequence 1).
     let current_azks = vkd.retrieve_current_azks().await.unwrap();
@@ -112,6 +208,7 @@ pub async fn notification() -> PublishNotification {
         root,
         proof,
         /* sequence_number */ 1,
+        /* round */ 0,
         /* keypair */ &identity_provider,
     )
 }
@@ -128,28 +225,164 @@ pub async fn votes() -> Vec<PublishVote> {
 // A test certificate.
 pub async fn certificate() -> PublishCertificate {
     let notification = notification().await;
+    let votes = votes().await;
     PublishCertificate {
         root: notification.root,
         sequence_number: notification.sequence_number,
-        votes: votes()
-            .await
-            .into_iter()
-            .map(|x| (x.author, x.signature))
-            .collect(),
+        round: notification.round,
+        timestamps: votes.iter().map(|v| (v.author, v.timestamp)).collect(),
+        kind: CertificateKind::Votes(votes.into_iter().map(|x| (x.author, x.signature)).collect()),
     }
 }
 
+// Test root hashes and a single append-only proof spanning two publishes back to back
+// (sequence numbers 0, 1, and 2), for exercising `AggregatedCommitments`.
+pub async fn batch_proof() -> (Root, Root, Root, Proof) {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedAkdVRF {};
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
+
+    let current_azks = vkd.retrieve_current_azks().await.unwrap();
+    let root_0 = vkd
+        .get_root_hash_at_epoch::<Blake3>(&current_azks, /* sequence number */ 0)
+        .await
+        .unwrap();
+
+    vkd.publish::<Blake3>(updates()).await.unwrap();
+    let current_azks = vkd.retrieve_current_azks().await.unwrap();
+    let root_1 = vkd
+        .get_root_hash_at_epoch::<Blake3>(&current_azks, /* sequence number */ 1)
+        .await
+        .unwrap();
+
+    vkd.publish::<Blake3>(updates()).await.unwrap();
+    let current_azks = vkd.retrieve_current_azks().await.unwrap();
+    let root_2 = vkd
+        .get_root_hash_at_epoch::<Blake3>(&current_azks, /* sequence number */ 2)
+        .await
+        .unwrap();
+
+    let proof = vkd.audit::<Blake3>(0, 2).await.unwrap();
+    (root_0, root_1, root_2, proof)
+}
+
+// A test batch proposal certifying sequence numbers 1 and 2 in one shot.
+pub async fn batch() -> AggregatedCommitments {
+    let (_, identity_provider) = keys().pop().unwrap();
+    let (_, root_1, root_2, proof) = batch_proof().await;
+    AggregatedCommitments::new(
+        vec![(1, root_1), (2, root_2)],
+        proof,
+        /* round */ 0,
+        &identity_provider,
+    )
+}
+
 // Spawn test witnesses.
 pub fn spawn_test_witnesses(test_id: &str, committee: &Committee) {
     delete_storage(test_id);
-    for (i, (_, keypair)) in keys().into_iter().enumerate() {
+    // `keys()` is deterministic (seeded `StdRng`), so calling it again yields further
+    // independently-loaded copies of the same keypairs for the `ViewChangeHandler` and
+    // `SyncHelper` to sign their own messages with (`KeyPair` deliberately does not implement
+    // `Clone`).
+    let view_change_keypairs = keys();
+    let sync_keypairs = keys();
+    for (i, (((name, keypair), (_, view_change_keypair)), (_, sync_keypair))) in keys()
+        .into_iter()
+        .zip(view_change_keypairs.into_iter())
+        .zip(sync_keypairs.into_iter())
+        .enumerate()
+    {
+        let secure_storage_path = format!(".test_secure_storage_{}_{}", test_id, i);
+        let secure_storage = Storage::new(&secure_storage_path).unwrap();
+
+        let audit_storage_path = format!(".test_audit_storage_{}_{}", test_id, i);
+        let audit_storage = Storage::new(&audit_storage_path).unwrap();
+
+        // Derive a metrics port from this witness's own network port (offset well clear of
+        // the handful of ports every test committee spans) so concurrently-running tests
+        // never collide on the same metrics listener.
+        let mut metrics_address = committee.witness_address(&name).unwrap();
+        metrics_address.set_port(metrics_address.port() + 40);
+
+        spawn_witness(
+            keypair,
+            view_change_keypair,
+            sync_keypair,
+            committee.clone(),
+            /* threshold_share */ None,
+            secure_storage,
+            audit_storage,
+            TEST_MAX_RANGE_SPAN,
+            TEST_VIEW_TIMEOUT,
+            metrics_address,
+        );
+    }
+}
+
+// Like `spawn_test_witnesses`, but replaces the witnesses at the given 0-based indices (in
+// committee order) with `TestWitness`es running the given `ByzantineBehavior` instead of the
+// real, honest dispatch, so a test can assert that the rest of the committee stays safe and
+// live despite them.
+pub fn spawn_test_witnesses_with_byzantine(
+    test_id: &str,
+    committee: &Committee,
+    mut byzantine: HashMap<usize, ByzantineBehavior>,
+) {
+    delete_storage(test_id);
+    let view_change_keypairs = keys();
+    let sync_keypairs = keys();
+    let behavior_keypairs = keys();
+    for (
+        i,
+        ((((name, keypair), (_, view_change_keypair)), (_, sync_keypair)), (_, behavior_keypair)),
+    ) in keys()
+        .into_iter()
+        .zip(view_change_keypairs.into_iter())
+        .zip(sync_keypairs.into_iter())
+        .zip(behavior_keypairs.into_iter())
+        .enumerate()
+    {
         let secure_storage_path = format!(".test_secure_storage_{}_{}", test_id, i);
         let secure_storage = Storage::new(&secure_storage_path).unwrap();
 
         let audit_storage_path = format!(".test_audit_storage_{}_{}", test_id, i);
         let audit_storage = Storage::new(&audit_storage_path).unwrap();
 
-        spawn_witness(keypair, committee.clone(), secure_storage, audit_storage);
+        match byzantine.remove(&i) {
+            Some(behavior) => TestWitness::spawn(
+                keypair,
+                view_change_keypair,
+                sync_keypair,
+                behavior_keypair,
+                behavior,
+                committee.clone(),
+                /* threshold_share */ None,
+                secure_storage,
+                audit_storage,
+                TEST_MAX_RANGE_SPAN,
+                TEST_VIEW_TIMEOUT,
+            ),
+            None => {
+                // Derive a metrics port from this witness's own network port (offset well
+                // clear of the handful of ports every test committee spans) so concurrently
+                // running tests never collide on the same metrics listener.
+                let mut metrics_address = committee.witness_address(&name).unwrap();
+                metrics_address.set_port(metrics_address.port() + 40);
+                spawn_witness(
+                    keypair,
+                    view_change_keypair,
+                    sync_keypair,
+                    committee.clone(),
+                    /* threshold_share */ None,
+                    secure_storage,
+                    audit_storage,
+                    TEST_MAX_RANGE_SPAN,
+                    TEST_VIEW_TIMEOUT,
+                    metrics_address,
+                )
+            }
+        }
     }
 }
 
@@ -157,6 +390,9 @@ pub fn spawn_test_witnesses(test_id: &str, committee: &Committee) {
 pub fn spawn_test_idp(test_id: &str, committee: Committee) {
     delete_storage(test_id);
     let (_, keypair) = keys().pop().unwrap();
+    // `KeyPair` deliberately does not implement `Clone`; generate an independent (but, thanks
+    // to the fixed test seed, identical) copy for the `Publisher`.
+    let (_, publisher_keypair) = keys().pop().unwrap();
 
     let secure_storage_path = format!(".test_idp_secure_storage_{}", test_id);
     let secure_storage = Storage::new(&secure_storage_path).unwrap();
@@ -164,18 +400,33 @@ pub fn spawn_test_idp(test_id: &str, committee: Committee) {
     let sync_storage_path = format!(".test_sync_storage_{}", test_id);
     let sync_storage = Storage::new(&sync_storage_path).unwrap();
 
+    let merkle_storage_path = format!(".test_merkle_storage_{}", test_id);
+    let merkle_storage = Storage::new(&merkle_storage_path).unwrap();
+
     let batch_size = serialized_updates().len();
     let max_batch_delay = 200;
+    let timeout_delay = 100;
+
+    // Derive a metrics port from the IdP's own network port, offset well clear of the
+    // witnesses' metrics ports (see `spawn_test_witnesses`), so concurrently-running tests
+    // never collide on the same metrics listener.
+    let mut metrics_address = committee.idp.address;
+    metrics_address.set_port(metrics_address.port() + 30);
 
     tokio::spawn(async move {
         spawn_idp(
             keypair,
+            publisher_keypair,
             committee.clone(),
             secure_storage,
             sync_storage,
+            merkle_storage,
             /* vkd_storage */ AsyncInMemoryDatabase::new(),
             batch_size,
             max_batch_delay,
+            timeout_delay,
+            TEST_MAX_RANGE_SPAN,
+            metrics_address,
         )
         .await;
     });
@@ -193,6 +444,8 @@ pub fn delete_storage(test_id: &str) {
     let _ = std::fs::remove_dir_all(&idp_secure_storage_path);
     let sync_storage_path = format!(".test_sync_storage_{}", test_id);
     let _ = std::fs::remove_dir_all(&sync_storage_path);
+    let merkle_storage_path = format!(".test_merkle_storage_{}", test_id);
+    let _ = std::fs::remove_dir_all(&merkle_storage_path);
 }
 
 // Broadcast a publish notification to the witnesses.
@@ -206,7 +459,7 @@ pub async fn broadcast_notification(
         .map(|(_, address)| address)
         .collect();
     let message = IdPToWitnessMessage::PublishNotification(notification);
-    let serialized = bincode::serialize(&message).unwrap();
+    let serialized = messages::codec::encode(&message).unwrap();
     let bytes = Bytes::from(serialized);
     let mut sender = ReliableSender::new();
     sender.broadcast(addresses, bytes).await
@@ -223,7 +476,55 @@ pub async fn broadcast_certificate(
         .map(|(_, address)| address)
         .collect();
     let message = IdPToWitnessMessage::PublishCertificate(certificate);
-    let serialized = bincode::serialize(&message).unwrap();
+    let serialized = messages::codec::encode(&message).unwrap();
+    let bytes = Bytes::from(serialized);
+    let mut sender = ReliableSender::new();
+    sender.broadcast(addresses, bytes).await
+}
+
+// Broadcast a state query to every witness in the committee.
+pub async fn broadcast_state_query(committee: &Committee) -> Vec<CancelHandler> {
+    let addresses = committee
+        .witnesses_addresses()
+        .into_iter()
+        .map(|(_, address)| address)
+        .collect();
+    let message = IdPToWitnessMessage::StateQuery;
+    let serialized = messages::codec::encode(&message).unwrap();
+    let bytes = Bytes::from(serialized);
+    let mut sender = ReliableSender::new();
+    sender.broadcast(addresses, bytes).await
+}
+
+// Broadcast a root chain query to every witness in the committee.
+pub async fn broadcast_root_chain_query(
+    query: messages::sync::RootChainQuery,
+    committee: &Committee,
+) -> Vec<CancelHandler> {
+    let addresses = committee
+        .witnesses_addresses()
+        .into_iter()
+        .map(|(_, address)| address)
+        .collect();
+    let message = IdPToWitnessMessage::RootChainQuery(query);
+    let serialized = messages::codec::encode(&message).unwrap();
+    let bytes = Bytes::from(serialized);
+    let mut sender = ReliableSender::new();
+    sender.broadcast(addresses, bytes).await
+}
+
+// Broadcast a publish certificate query to every witness in the committee.
+pub async fn broadcast_certificate_query(
+    query: messages::sync::PublishCertificateQuery,
+    committee: &Committee,
+) -> Vec<CancelHandler> {
+    let addresses = committee
+        .witnesses_addresses()
+        .into_iter()
+        .map(|(_, address)| address)
+        .collect();
+    let message = IdPToWitnessMessage::PublishCertificateQuery(query);
+    let serialized = messages::codec::encode(&message).unwrap();
     let bytes = Bytes::from(serialized);
     let mut sender = ReliableSender::new();
     sender.broadcast(addresses, bytes).await
@@ -242,11 +543,11 @@ pub fn listener(
 
         // Wait for a publish notification and reply with a vote.
         let notification = match transport.next().await {
-            Some(Ok(bytes)) => match bincode::deserialize(&bytes).unwrap() {
+            Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
                 IdPToWitnessMessage::PublishNotification(n) => {
                     let vote = PublishVote::new(&n, &keypair);
                     let message = WitnessToIdPMessage::PublishVote(Ok(vote));
-                    let serialized = bincode::serialize(&message).unwrap();
+                    let serialized = messages::codec::encode(&message).unwrap();
                     transport.send(Bytes::from(serialized)).await.unwrap();
                     n
                 }
@@ -257,7 +558,7 @@ pub fn listener(
 
         // Wait for a publish certificate.
         let certificate = match transport.next().await {
-            Some(Ok(bytes)) => match bincode::deserialize(&bytes).unwrap() {
+            Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
                 IdPToWitnessMessage::PublishCertificate(c) => c,
                 _ => panic!("Unexpected protocol message"),
             },
@@ -268,3 +569,158 @@ pub fn listener(
         (notification, certificate)
     })
 }
+
+// A test network listener emulating a slow witness: it only replies to the publish
+// notification after `delay`, to exercise the publisher's retransmission-on-timeout path.
+pub fn delayed_listener(
+    address: SocketAddr,
+    keypair: KeyPair,
+    delay: Duration,
+) -> JoinHandle<(PublishNotification, PublishCertificate)> {
+    tokio::spawn(async move {
+        let listener = TcpListener::bind(&address).await.unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut transport = Framed::new(socket, LengthDelimitedCodec::new());
+
+        // Wait for (possibly several copies of) the publish notification, replying with a
+        // vote only once `delay` has elapsed.
+        let notification = loop {
+            match transport.next().await {
+                Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
+                    IdPToWitnessMessage::PublishNotification(n) => {
+                        sleep(delay).await;
+                        let vote = PublishVote::new(&n, &keypair);
+                        let message = WitnessToIdPMessage::PublishVote(Ok(vote));
+                        let serialized = messages::codec::encode(&message).unwrap();
+                        transport.send(Bytes::from(serialized)).await.unwrap();
+                        break n;
+                    }
+                    _ => panic!("Unexpected protocol message"),
+                },
+                _ => panic!("Failed to receive network message"),
+            }
+        };
+
+        // Wait for a publish certificate.
+        let certificate = match transport.next().await {
+            Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
+                IdPToWitnessMessage::PublishCertificate(c) => c,
+                _ => panic!("Unexpected protocol message"),
+            },
+            _ => panic!("Failed to receive network message"),
+        };
+
+        (notification, certificate)
+    })
+}
+
+/// Scripts how a witness test-double (see `spawn_fake_witness`) reacts to a publish
+/// notification, so tests can deterministically drive the aggregator's and publisher's
+/// fault-handling paths (equivocation detection, retransmission-on-timeout, bad signatures)
+/// without hand-rolling a raw socket in every test.
+#[derive(Clone)]
+pub enum WitnessBehavior {
+    /// Votes correctly and promptly, like `listener`.
+    Honest,
+    /// Never replies at all (crashed, or partitioned away); no listener is even bound.
+    Silent,
+    /// Votes correctly, but only after `Duration` has elapsed, like `delayed_listener`.
+    Delayed(Duration),
+    /// Signs its vote with a keypair other than the one it claims to be, so the signature
+    /// fails to verify.
+    WrongSignature,
+    /// Correctly signs a vote for a different root than the one proposed, emulating a witness
+    /// that equivocates.
+    ForgedRoot,
+    /// Replies with an error instead of a vote, as a witness that refuses to participate
+    /// (e.g. believes it is behind) would.
+    Refuse,
+    /// Accepts the connection and reads the notification, then drops it without replying,
+    /// emulating a witness whose connection is severed mid-round; a fresh connection (as a
+    /// reconnecting publisher would open) is still accepted for the rest of the round.
+    DropConnection,
+}
+
+/// Spawn a test double impersonating a witness, driven by `behavior`. Returns `None` for
+/// `WitnessBehavior::Silent`, since no listener is bound in that case.
+pub fn spawn_fake_witness(
+    address: SocketAddr,
+    keypair: KeyPair,
+    behavior: WitnessBehavior,
+) -> Option<JoinHandle<(PublishNotification, PublishCertificate)>> {
+    if matches!(behavior, WitnessBehavior::Silent) {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let listener = TcpListener::bind(&address).await.unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut transport = Framed::new(socket, LengthDelimitedCodec::new());
+
+        // Wait for the publish notification and vote on it per the scripted behavior.
+        let notification = match transport.next().await {
+            Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
+                IdPToWitnessMessage::PublishNotification(n) => {
+                    if let WitnessBehavior::Delayed(delay) = behavior {
+                        sleep(delay).await;
+                    }
+
+                    if matches!(behavior, WitnessBehavior::DropConnection) {
+                        // Sever the connection without replying, then accept a fresh one
+                        // (as a reconnecting publisher would open) for the certificate.
+                        drop(transport);
+                        let (socket, _) = listener.accept().await.unwrap();
+                        transport = Framed::new(socket, LengthDelimitedCodec::new());
+                        n
+                    } else {
+                        let reply = match behavior {
+                            WitnessBehavior::WrongSignature => {
+                                let mut rng = StdRng::from_entropy();
+                                let (_, wrong_keypair) =
+                                    KeyPair::generate_keypair(SignatureScheme::Ed25519, &mut rng);
+                                let mut vote = PublishVote::new(&n, &keypair);
+                                vote.signature = Signature::new(&vote.digest(), &wrong_keypair);
+                                WitnessToIdPMessage::PublishVote(Ok(vote))
+                            }
+                            WitnessBehavior::ForgedRoot => {
+                                let mut forged = n.clone();
+                                forged.root = Blake3::hash(b"forged root");
+                                WitnessToIdPMessage::PublishVote(Ok(PublishVote::new(
+                                    &forged, &keypair,
+                                )))
+                            }
+                            WitnessBehavior::Refuse => WitnessToIdPMessage::PublishVote(Err(
+                                messages::error::WitnessError::MissingEarlierCertificates(
+                                    n.sequence_number,
+                                ),
+                            )),
+                            WitnessBehavior::Honest | WitnessBehavior::Delayed(_) => {
+                                WitnessToIdPMessage::PublishVote(Ok(PublishVote::new(&n, &keypair)))
+                            }
+                            WitnessBehavior::Silent | WitnessBehavior::DropConnection => {
+                                unreachable!("handled above")
+                            }
+                        };
+
+                        let serialized = messages::codec::encode(&reply).unwrap();
+                        transport.send(Bytes::from(serialized)).await.unwrap();
+                        n
+                    }
+                }
+                _ => panic!("Unexpected protocol message"),
+            },
+            _ => panic!("Failed to receive network message"),
+        };
+
+        // Wait for a publish certificate.
+        let certificate = match transport.next().await {
+            Some(Ok(bytes)) => match messages::codec::decode(&bytes).unwrap() {
+                IdPToWitnessMessage::PublishCertificate(c) => c,
+                _ => panic!("Unexpected protocol message"),
+            },
+            _ => panic!("Failed to receive network message"),
+        };
+
+        (notification, certificate)
+    }))
+}
@@ -1,21 +1,29 @@
+mod metrics;
 mod publish_handler;
 mod sync_helper;
+mod sync_requester;
+pub mod test_witness;
+mod view_change;
 
-use crate::{publish_handler::PublishHandler, sync_helper::SyncHelper};
+use crate::{
+    publish_handler::PublishHandler, sync_helper::SyncHelper, sync_requester::SyncRequester,
+    view_change::ViewChangeHandler,
+};
 use async_trait::async_trait;
 use bytes::Bytes;
 use config::Committee;
-use crypto::KeyPair;
-use futures::sink::SinkExt;
+use crypto::{KeyPair, PublicKey, ThresholdKeyShare};
+use futures::{channel::mpsc, future::join_all, sink::SinkExt, stream::StreamExt};
 use log::info;
 use messages::{
-    error::MessageError,
-    publish::{PublishCertificate, PublishNotification},
-    sync::PublishCertificateQuery,
-    IdPToWitnessMessage, SerializedPublishCertificateMessage, WitnessToIdPMessage,
+    codec,
+    error::FrameError,
+    publish::{NewView, PublishCertificate, PublishNotification, ViewChange},
+    sync::{CertificateRangeQuery, PublishCertificateQuery, RootChainQuery},
+    IdPToWitnessMessage, SequenceNumber, SerializedPublishCertificateMessage, WitnessToIdPMessage,
 };
 use network::receiver::{MessageHandler, Receiver as NetworkReceiver, Writer};
-use std::error::Error;
+use std::{error::Error, net::SocketAddr, time::Duration};
 use storage::Storage;
 use tokio::sync::{
     mpsc::{channel, Sender},
@@ -28,44 +36,147 @@ pub(crate) const DEFAULT_CHANNEL_SIZE: usize = 1_000;
 /// One-shot channel to reply to the IdP.
 pub(crate) type Replier = oneshot::Sender<WitnessToIdPMessage>;
 
+/// Bounded channel to stream a `CertificateRangeQuery`'s reply back one frame at a time,
+/// instead of buffering the whole range before replying.
+pub(crate) type StreamReplier = mpsc::Sender<WitnessToIdPMessage>;
+
 /// Spawn a new witness.
 pub fn spawn_witness(
     // The public and secret keypair of this witness.
     keypair: KeyPair,
+    // A second, independently-loaded copy of the same keypair, used by the `ViewChangeHandler`
+    // to sign view-change and new-view messages (`KeyPair` deliberately does not implement
+    // `Clone`, so each consumer loads its own copy of the secret material).
+    view_change_keypair: KeyPair,
+    // A third, independently-loaded copy of the same keypair, used by the `SyncHelper` to sign
+    // the `RootChain` attestations it serves.
+    sync_keypair: KeyPair,
     // The committee information.
     committee: Committee,
+    // This witness's share of the committee's threshold key set, if the committee has one
+    // configured (see `config::PrivateConfig::threshold_share`).
+    threshold_share: Option<ThresholdKeyShare>,
     // The storage for safety-critical information.
     secure_storage: Storage,
     // The storage for certificates and other self-authenticated information.
     audit_storage: Storage,
+    // The maximum number of certificates this witness will serve in a single anti-entropy
+    // `CertificateRangeQuery`, bounding the work a lagging peer can trigger in one round-trip.
+    max_range_span: SequenceNumber,
+    // How long the `ViewChangeHandler` waits, after casting a vote, for a certificate to commit
+    // before reporting a timeout to the next view's leader.
+    initial_view_timeout: Duration,
+    // The address the Prometheus `/metrics` endpoint is served on.
+    metrics_address: SocketAddr,
 ) {
+    let (name, address, handler) = build_witness_handler(
+        keypair,
+        view_change_keypair,
+        sync_keypair,
+        committee.clone(),
+        threshold_share,
+        secure_storage,
+        audit_storage,
+        max_range_span,
+        initial_view_timeout,
+    );
+
+    // Serve the Prometheus `/metrics` endpoint.
+    ::metrics::spawn(metrics_address);
+
+    // Spawn a network receiver.
+    NetworkReceiver::spawn(address, handler);
+
+    info!(
+        "Witness {} successfully booted on {}",
+        name,
+        committee
+            .witness_address(&name)
+            .expect("Our public key is not in the committee")
+            .ip()
+    );
+    #[cfg(features = "witness-only-benchmark")]
+    log::warn!("Witness booted in witness-benchmark mode (safety/consistency is not guaranteed)");
+}
+
+/// Spawn every internal task (publish handler, sync helper, sync requester, view-change
+/// handler) and assemble the `WitnessHandler` that dispatches incoming network messages to
+/// them, without binding a listener or serving `/metrics`. Factored out of `spawn_witness` so
+/// `test_witness::TestWitness` can wrap the same handler with injected Byzantine faults instead
+/// of running it as-is.
+pub(crate) fn build_witness_handler(
+    keypair: KeyPair,
+    view_change_keypair: KeyPair,
+    sync_keypair: KeyPair,
+    committee: Committee,
+    threshold_share: Option<ThresholdKeyShare>,
+    secure_storage: Storage,
+    audit_storage: Storage,
+    max_range_span: SequenceNumber,
+    initial_view_timeout: Duration,
+) -> (PublicKey, SocketAddr, WitnessHandler) {
     let name = keypair.public();
 
     let (tx_notification, rx_notification) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_certificate, rx_certificate) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_state_query, rx_state_query) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_certificate_request, rx_certificate_request) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_certificate_range_request, rx_certificate_range_request) =
+        channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_root_chain_request, rx_root_chain_request) = channel(DEFAULT_CHANNEL_SIZE);
     let (tx_processed_certificate, rx_processed_certificate) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_subscribe_state, rx_subscribe_state) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_missing, rx_missing) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_vote_cast, rx_vote_cast) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_committed, rx_committed) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_view_change, rx_view_change) = channel(DEFAULT_CHANNEL_SIZE);
+    let (tx_new_view, rx_new_view) = channel(DEFAULT_CHANNEL_SIZE);
 
     // Spawn the publish handler. This task handles all publish-related messages.
     PublishHandler::spawn(
         keypair,
         committee.clone(),
+        threshold_share,
         secure_storage,
         rx_notification,
         rx_certificate,
         rx_state_query,
+        rx_subscribe_state,
         tx_processed_certificate,
+        tx_missing,
+        tx_vote_cast,
+        tx_committed,
     );
 
     // Spawn the sync helper. This task replies to sync request helping other witness to get up to speed.
     SyncHelper::spawn(
+        sync_keypair,
         audit_storage,
+        max_range_span,
         rx_processed_certificate,
         rx_certificate_request,
+        rx_certificate_range_request,
+        rx_root_chain_request,
+    );
+
+    // Spawn the sync requester. This task chases down certificates we are missing from peer
+    // witnesses, complementing the sync helper (which only serves requests, never makes them).
+    SyncRequester::spawn(name, committee.clone(), tx_certificate.clone(), rx_missing);
+
+    // Spawn the view-change handler. This task recovers a stalled sequence number's certificate
+    // from the witnesses' own votes when the IdP stalls or crashes, so an epoch does not stay
+    // stuck waiting for it to come back.
+    ViewChangeHandler::spawn(
+        view_change_keypair,
+        committee.clone(),
+        tx_certificate.clone(),
+        rx_vote_cast,
+        rx_committed,
+        rx_view_change,
+        rx_new_view,
+        initial_view_timeout,
     );
 
-    // Spawn a network receiver.
     let mut address = committee
         .witness_address(&name)
         .expect("Our public key is not in the committee");
@@ -75,24 +186,19 @@ pub fn spawn_witness(
         tx_certificate,
         tx_state_query,
         tx_certificate_request,
+        tx_certificate_range_request,
+        tx_root_chain_request,
+        tx_subscribe_state,
+        tx_view_change,
+        tx_new_view,
+        max_payload_size: committee.max_payload_size,
     };
-    NetworkReceiver::spawn(address, handler);
-
-    info!(
-        "Witness {} successfully booted on {}",
-        name,
-        committee
-            .witness_address(&name)
-            .expect("Our public key is not in the committee")
-            .ip()
-    );
-    #[cfg(features = "witness-only-benchmark")]
-    log::warn!("Witness booted in witness-benchmark mode (safety/consistency is not guaranteed)");
+    (name, address, handler)
 }
 
 /// Defines how the network receiver handles incoming messages.
 #[derive(Clone)]
-struct WitnessHandler {
+pub(crate) struct WitnessHandler {
     tx_notification: Sender<(PublishNotification, Replier)>,
     tx_certificate: Sender<(
         SerializedPublishCertificateMessage,
@@ -101,20 +207,77 @@ struct WitnessHandler {
     )>,
     tx_state_query: Sender<Replier>,
     tx_certificate_request: Sender<(PublishCertificateQuery, Replier)>,
+    tx_certificate_range_request: Sender<(CertificateRangeQuery, StreamReplier)>,
+    tx_root_chain_request: Sender<(RootChainQuery, Replier)>,
+    tx_subscribe_state: Sender<StreamReplier>,
+    tx_view_change: Sender<(ViewChange, Replier)>,
+    tx_new_view: Sender<(NewView, Replier)>,
+    /// The committee's configured ceiling on a single serialized wire message, checked in
+    /// `dispatch` before the message is handed to `codec::decode`.
+    max_payload_size: usize,
 }
 
-#[async_trait]
-impl MessageHandler for WitnessHandler {
-    async fn dispatch(&self, writer: &mut Writer, serialized: Bytes) -> Result<(), Box<dyn Error>> {
-        let (sender, receiver) = oneshot::channel();
+/// Forwards `request` along with a fresh stream's sending half to `tx`, then relays every frame
+/// the producer pushes on that stream to `writer` until the producer closes it (the request
+/// completed) or `writer` itself fails (the peer disconnected).
+async fn stream_reply<T>(
+    writer: &mut Writer,
+    tx: &Sender<(T, StreamReplier)>,
+    request: T,
+) -> Result<(), Box<dyn Error>> {
+    let (reply_tx, mut reply_rx) = mpsc::channel(DEFAULT_CHANNEL_SIZE);
+    tx.send((request, reply_tx))
+        .await
+        .expect("Failed to deliver streaming request to handler");
+    while let Some(frame) = reply_rx.next().await {
+        let bytes = codec::encode(&frame).expect("Failed to serialize reply");
+        writer.send(Bytes::from(bytes)).await?;
+    }
+    Ok(())
+}
 
-        // Deserialize and parse the message.
-        match bincode::deserialize(&serialized).map_err(MessageError::from)? {
+impl WitnessHandler {
+    /// Compute the reply to any message other than the two that stream their reply back
+    /// (`CertificateRangeQuery`, `SubscribeState`), without writing it to the wire. `serialized`
+    /// must be the exact bytes `message` was decoded from: a `PublishCertificate` is re-keyed by
+    /// those bytes in the sync helper's storage, so it must see what was actually received on
+    /// the wire rather than a re-encoding of `message`. Exposed so `test_witness::TestWitness`
+    /// can inspect, delay, or swap out a reply before it goes out, without duplicating the
+    /// channel plumbing that routes each message to its handling task.
+    pub(crate) async fn compute_reply(
+        &self,
+        serialized: &Bytes,
+        message: IdPToWitnessMessage,
+    ) -> WitnessToIdPMessage {
+        let (sender, receiver) = oneshot::channel();
+        match message {
             IdPToWitnessMessage::PublishNotification(notification) => self
                 .tx_notification
                 .send((notification, sender))
                 .await
                 .expect("Failed to send publish notification to publish handler"),
+            // Fan each notification in the batch out through the same per-notification channel
+            // `PublishNotification` uses, concurrently, then fold the individual replies back
+            // into a single `PublishVoteBatch` -- one network round trip for the whole batch,
+            // but every notification is still voted on (and will still be certified)
+            // independently, exactly as if it had arrived on its own.
+            IdPToWitnessMessage::PublishNotificationBatch(notifications) => {
+                let votes = join_all(notifications.into_iter().map(|notification| async move {
+                    let (sender, receiver) = oneshot::channel();
+                    self.tx_notification
+                        .send((notification, sender))
+                        .await
+                        .expect("Failed to send publish notification to publish handler");
+                    match receiver.await.expect("Failed to receive message reply") {
+                        WitnessToIdPMessage::PublishVote(result) => result,
+                        _ => unreachable!(
+                            "publish handler always replies to a notification with a vote"
+                        ),
+                    }
+                }))
+                .await;
+                return WitnessToIdPMessage::PublishVoteBatch(votes);
+            }
             IdPToWitnessMessage::PublishCertificate(certificate) => self
                 .tx_certificate
                 .send((serialized.to_vec(), certificate, sender))
@@ -130,12 +293,64 @@ impl MessageHandler for WitnessHandler {
                 .send((query, sender))
                 .await
                 .expect("Failed to certificate query query to sync helper"),
+            IdPToWitnessMessage::RootChainQuery(query) => self
+                .tx_root_chain_request
+                .send((query, sender))
+                .await
+                .expect("Failed to send root chain query to sync helper"),
+            IdPToWitnessMessage::ViewChange(view_change) => self
+                .tx_view_change
+                .send((view_change, sender))
+                .await
+                .expect("Failed to send view-change to view-change handler"),
+            IdPToWitnessMessage::NewView(new_view) => self
+                .tx_new_view
+                .send((new_view, sender))
+                .await
+                .expect("Failed to send new-view to view-change handler"),
+            IdPToWitnessMessage::CertificateRangeQuery(_) | IdPToWitnessMessage::SubscribeState => {
+                unreachable!("streamed messages are handled by `dispatch` directly")
+            }
         }
+        receiver.await.expect("Failed to receive message reply")
+    }
+}
 
-        // Reply to the IdP.
-        let reply = receiver.await.expect("Failed to receive message reply");
-        let bytes = bincode::serialize(&reply).expect("Failed to serialize reply");
-        writer.send(Bytes::from(bytes)).await?;
+#[async_trait]
+impl MessageHandler for WitnessHandler {
+    async fn dispatch(&self, writer: &mut Writer, serialized: Bytes) -> Result<(), Box<dyn Error>> {
+        // This only rejects an oversized frame *after* `network` has already buffered the whole
+        // thing in `serialized`; it just stops us from also handing it to `codec::decode`. The
+        // real guard against a peer forcing us to buffer an oversized payload in the first place
+        // is `messages::framing::FrameCodec`, which checks the announced length as soon as the
+        // VarInt prefix parses -- `network`'s connection setup is what needs to run frames
+        // through it before they reach `dispatch`.
+        if serialized.len() > self.max_payload_size {
+            return Err(Box::new(FrameError::FrameTooLarge {
+                length: serialized.len(),
+                max_length: self.max_payload_size,
+            }));
+        }
+
+        // Deserialize and parse the message.
+        match codec::decode::<IdPToWitnessMessage>(&serialized)? {
+            // Certificate ranges are streamed back frame-by-frame, so a bulk catch-up does
+            // not have to buffer the whole range in memory on either end.
+            IdPToWitnessMessage::CertificateRangeQuery(query) => {
+                return stream_reply(writer, &self.tx_certificate_range_request, query).await;
+            }
+            // State subscriptions are a long-lived stream: the current `State` is pushed
+            // immediately, then again on every subsequent change, until the subscriber
+            // disconnects.
+            IdPToWitnessMessage::SubscribeState => {
+                return stream_reply(writer, &self.tx_subscribe_state, ()).await;
+            }
+            message => {
+                let reply = self.compute_reply(&serialized, message).await;
+                let bytes = codec::encode(&reply).expect("Failed to serialize reply");
+                writer.send(Bytes::from(bytes)).await?;
+            }
+        }
         Ok(())
     }
 }
@@ -1,9 +1,20 @@
 use anyhow::{Context, Result};
 use clap::{arg, crate_name, crate_version, Arg, ArgMatches, Command};
 use config::{Committee, Export, Import, PrivateConfig};
+use std::time::Duration;
 use storage::Storage;
 use witness::spawn_witness;
 
+/// The default maximum number of certificates served in a single anti-entropy range request.
+const DEFAULT_MAX_RANGE_SPAN: u64 = 1_000;
+
+/// The default time a witness waits, after casting a vote, for a certificate to commit before
+/// reporting a timeout to the next view's leader.
+const DEFAULT_VIEW_TIMEOUT_SECS: u64 = 10;
+
+/// The default bind address for the Prometheus `/metrics` endpoint.
+const DEFAULT_METRICS_ADDRESS: &str = "127.0.0.1:9101";
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Read the cli parameters.
@@ -21,6 +32,9 @@ async fn main() -> Result<()> {
             arg!(--keypair <FILE> "The path to the witness keypair"),
             arg!(--secure_storage <FILE> "The directory to hold the secure storage"),
             arg!(--audit_storage <FILE> "The directory to hold the audit storage"),
+            arg!(--max_range_span [INT] "The maximum number of certificates served per anti-entropy range request"),
+            arg!(--view_timeout [INT] "How long (in seconds) to wait for a certificate to commit before reporting a view timeout"),
+            arg!(--metrics_address [ADDR] "The address to serve the Prometheus /metrics endpoint on"),
         ]))
         .arg_required_else_help(true)
         .get_matches();
@@ -60,6 +74,15 @@ async fn spawn(matches: &ArgMatches) -> Result<()> {
     let keypair_file = matches.value_of("keypair").unwrap();
     let keypair = PrivateConfig::import(keypair_file).context("Failed to load keypair")?;
 
+    // A second, independently-loaded copy of the same keypair for the `ViewChangeHandler`
+    // (`KeyPair` deliberately does not implement `Clone`, so each consumer loads its own copy of
+    // the secret material; mirrors how the IdP loads a separate copy for its `Publisher`).
+    let view_change_keypair =
+        PrivateConfig::import(keypair_file).context("Failed to load keypair")?;
+
+    // A third, independently-loaded copy of the same keypair for the `SyncHelper`.
+    let sync_keypair = PrivateConfig::import(keypair_file).context("Failed to load keypair")?;
+
     let secure_storage_file = matches.value_of("secure_storage").unwrap();
     let secure_storage =
         Storage::new(secure_storage_file).context("Failed to create secure storage")?;
@@ -68,8 +91,40 @@ async fn spawn(matches: &ArgMatches) -> Result<()> {
     let audit_storage =
         Storage::new(audit_storage_file).context("Failed to create audit storage")?;
 
+    let max_range_span = match matches.value_of("max_range_span") {
+        Some(x) => x
+            .parse::<u64>()
+            .context("The maximum range span must be a non-negative integer")?,
+        None => DEFAULT_MAX_RANGE_SPAN,
+    };
+
+    let view_timeout = match matches.value_of("view_timeout") {
+        Some(x) => Duration::from_secs(
+            x.parse::<u64>()
+                .context("The view timeout must be a non-negative integer")?,
+        ),
+        None => Duration::from_secs(DEFAULT_VIEW_TIMEOUT_SECS),
+    };
+
+    let metrics_address = matches
+        .value_of("metrics_address")
+        .unwrap_or(DEFAULT_METRICS_ADDRESS)
+        .parse()
+        .context("The metrics address must be a valid socket address")?;
+
     // Spawn a witness.
-    spawn_witness(keypair.secret, committee, secure_storage, audit_storage);
+    spawn_witness(
+        keypair.secret,
+        view_change_keypair.secret,
+        sync_keypair.secret,
+        committee,
+        keypair.threshold_share,
+        secure_storage,
+        audit_storage,
+        max_range_span,
+        view_timeout,
+        metrics_address,
+    );
 
     // TODO: better way to prevent the program from exiting....
     loop {
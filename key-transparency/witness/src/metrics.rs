@@ -0,0 +1,30 @@
+//! Process-wide counters for the witness, scraped via the `metrics` crate's HTTP server.
+
+use lazy_static::lazy_static;
+use prometheus::IntCounter;
+
+lazy_static! {
+    /// Certificates successfully served in response to a single `PublishCertificateQuery`.
+    pub static ref CERTIFICATES_SERVED: IntCounter = metrics::register_int_counter(
+        "witness_certificates_served_total",
+        "Total number of certificates successfully served to single-certificate sync requests"
+    );
+
+    /// `PublishCertificateQuery`s for a certificate this witness doesn't have in storage.
+    pub static ref CERTIFICATES_MISSED: IntCounter = metrics::register_int_counter(
+        "witness_certificates_missed_total",
+        "Total number of single-certificate sync requests this witness could not satisfy"
+    );
+
+    /// Certificates streamed out in response to anti-entropy `CertificateRangeQuery`s.
+    pub static ref RANGE_CERTIFICATES_SERVED: IntCounter = metrics::register_int_counter(
+        "witness_range_certificates_served_total",
+        "Total number of certificates streamed out in response to range sync requests"
+    );
+
+    /// `RootChainQuery`s successfully answered with a signed `RootChain`.
+    pub static ref ROOT_CHAINS_SERVED: IntCounter = metrics::register_int_counter(
+        "witness_root_chains_served_total",
+        "Total number of root chain requests successfully served to external auditors"
+    );
+}
@@ -1,26 +1,32 @@
-use crate::Replier;
+use crate::{sync_requester::MissingCertificates, Replier, StreamReplier};
 use config::Committee;
-use crypto::KeyPair;
+use crypto::{kzg_da, KeyPair, ThresholdKeyShare};
+use futures::sink::SinkExt;
 use log::{debug, info, warn};
 use messages::{
     ensure,
-    error::{WitnessError, WitnessResult},
-    publish::{PublishCertificate, PublishMessage, PublishNotification, PublishVote},
+    error::{MessageError, WitnessError, WitnessResult},
+    publish::{
+        ConflictingNotifications, PublishCertificate, PublishMessage, PublishNotification,
+        PublishVote,
+    },
     sync::State,
     SequenceNumber, SerializedPublishCertificateMessage, WitnessToIdPMessage,
 };
+use std::collections::BTreeMap;
 use storage::Storage;
 use tokio::sync::mpsc::{Receiver, Sender};
 
-/// Storage address of the state.
-pub const STORE_STATE_ADDR: [u8; 32] = [255; 32];
-
 /// Core logic handing publish notifications and certificates.
 pub struct PublishHandler {
     /// The keypair of this authority.
     keypair: KeyPair,
     /// The committee information.
     committee: Committee,
+    /// This witness's share of the committee's threshold key set, if the committee has one.
+    /// Attached to every vote so the IdP can assemble a constant-size `Threshold` certificate
+    /// instead of the larger per-witness `Votes` form.
+    threshold_share: Option<ThresholdKeyShare>,
     /// The persistent storage.
     storage: Storage,
     /// Receive publish notifications from the IdP.
@@ -33,10 +39,34 @@ pub struct PublishHandler {
     )>,
     /// Receive state queries from the IdP.
     rx_state_query: Receiver<Replier>,
+    /// Receive new state-change subscriptions.
+    rx_subscribe_state: Receiver<StreamReplier>,
     /// Outputs processed (thus verified) publish certificates.
     tx_processed_certificate: Sender<(SerializedPublishCertificateMessage, SequenceNumber)>,
+    /// Signals the `SyncRequester` whenever a gap in the certificate sequence is detected.
+    tx_missing: Sender<MissingCertificates>,
+    /// Forwards every vote this witness casts to the `ViewChangeHandler`, which starts (or
+    /// resets) that sequence number's round timer.
+    tx_vote_cast: Sender<PublishVote>,
+    /// Tells the `ViewChangeHandler` a sequence number committed, so it stops timing it out.
+    tx_committed: Sender<SequenceNumber>,
     /// The state of the witness.
     state: State,
+    /// The notification behind `state.lock`, kept around only so that a later conflicting
+    /// notification for the same sequence number can be turned into a gossipable
+    /// [`ConflictingNotifications`] proof of IdP equivocation rather than a local-only error.
+    /// Not persisted: on restart a witness that must re-learn of a conflict simply falls back
+    /// to rejecting it without a proof, same as before this field existed.
+    locked_notification: Option<PublishNotification>,
+    /// Active state-change subscribers, pruned whenever a push fails (the subscriber
+    /// disconnected).
+    subscribers: Vec<StreamReplier>,
+    /// The last state pushed to subscribers, so identical consecutive states are not re-sent.
+    last_notified: Option<State>,
+    /// Certificates received ahead of `state.sequence_number`, buffered so they are applied
+    /// immediately (in ascending order) once the gap before them closes, instead of being
+    /// dropped and re-fetched from a peer witness.
+    orphans: BTreeMap<SequenceNumber, (SerializedPublishCertificateMessage, PublishCertificate)>,
 }
 
 impl PublishHandler {
@@ -44,6 +74,7 @@ impl PublishHandler {
     pub fn spawn(
         keypair: KeyPair,
         committee: Committee,
+        threshold_share: Option<ThresholdKeyShare>,
         storage: Storage,
         rx_notification: Receiver<(PublishNotification, Replier)>,
         rx_certificate: Receiver<(
@@ -52,38 +83,97 @@ impl PublishHandler {
             Replier,
         )>,
         rx_state_query: Receiver<Replier>,
+        rx_subscribe_state: Receiver<StreamReplier>,
         tx_processed_certificate: Sender<(SerializedPublishCertificateMessage, SequenceNumber)>,
+        tx_missing: Sender<MissingCertificates>,
+        tx_vote_cast: Sender<PublishVote>,
+        tx_committed: Sender<SequenceNumber>,
     ) {
         tokio::spawn(async move {
-            // Try to load the state from storage.
-            let state = storage
-                .read(&STORE_STATE_ADDR)
-                .expect("Failed to load state from storage")
-                .map(|bytes| bincode::deserialize(&bytes).expect("Failed to deserialize state"))
-                .unwrap_or_default();
+            // Recover the state from the latest durable write-ahead record, so the witness
+            // never starts voting before it knows whether it was already locked on something
+            // when it last crashed.
+            let state = State::load(&storage);
 
             // Run an instance of the handler.
             Self {
                 keypair,
                 committee,
+                threshold_share,
                 storage,
                 rx_notification,
                 rx_certificate,
                 rx_state_query,
+                rx_subscribe_state,
                 tx_processed_certificate,
+                tx_missing,
+                tx_vote_cast,
+                tx_committed,
                 state,
+                locked_notification: None,
+                subscribers: Vec::new(),
+                last_notified: None,
+                orphans: BTreeMap::new(),
             }
             .run()
             .await
         });
     }
 
+    /// Pushes the current state to every active subscriber, dropping any whose stream is
+    /// closed (the subscriber cancelled by disconnecting). A no-op if the state is identical
+    /// to the last one pushed.
+    async fn notify_subscribers(&mut self) {
+        if self.subscribers.is_empty() || self.last_notified.as_ref() == Some(&self.state) {
+            return;
+        }
+        self.last_notified = Some(self.state.clone());
+
+        let mut alive = Vec::with_capacity(self.subscribers.len());
+        for mut subscriber in self.subscribers.drain(..) {
+            let reply = WitnessToIdPMessage::State(Ok(self.state.clone()));
+            if subscriber.send(reply).await.is_ok() {
+                alive.push(subscriber);
+            }
+        }
+        self.subscribers = alive;
+    }
+
+    /// Sign a vote for `notification`, attaching our threshold signature share if we hold one.
+    fn sign_vote(&self, notification: &PublishNotification) -> PublishVote {
+        let vote = PublishVote::new(notification, &self.keypair);
+        match &self.threshold_share {
+            Some(share) => vote.with_threshold_share(share),
+            None => vote,
+        }
+    }
+
+    /// Verify our own data-availability share against `notification`'s commitment, if it
+    /// carries one. A no-op for a committee that does not run the data-availability layer.
+    fn verify_data_availability(&self, notification: &PublishNotification) -> WitnessResult<()> {
+        let commitment = match &notification.data_commitment {
+            Some(commitment) => commitment,
+            None => return Ok(()),
+        };
+        let srs = self
+            .committee
+            .data_availability_srs
+            .as_ref()
+            .ok_or(MessageError::DataAvailabilitySetupMismatch)?;
+        let share = notification
+            .data_share_for(&self.keypair.public())
+            .ok_or(MessageError::MissingDataShare(self.keypair.public()))?;
+        kzg_da::verify_share(srs, commitment, share).map_err(MessageError::from)?;
+        Ok(())
+    }
+
     /// Try to vote for a publish notification.
     async fn make_vote(&self, notification: &PublishNotification) -> WitnessResult<PublishVote> {
         // Verify the notification.
         notification
             .verify(&self.committee, &self.state.root)
             .await?;
+        self.verify_data_availability(notification)?;
 
         // Check the sequence number.
         ensure!(
@@ -94,19 +184,54 @@ impl PublishHandler {
             }
         );
 
-        // Ensure there are no locks.
+        // Ensure there are no conflicting locks, applying the unlock rule when one exists.
         match self.state.lock.as_ref() {
+            // Already locked on this exact root: reply with the same vote (idempotent,
+            // handles retransmits and pure round bumps of the same proposal).
+            Some(vote) if vote.root() == notification.root() => Ok(vote.clone()),
+
+            // Locked on a conflicting root: only unlock if the IdP attaches a quorum
+            // certificate proving this new root already has enough support that our old
+            // lock can never commit.
             Some(vote) => {
+                let justification = notification.justification.as_ref().ok_or_else(|| {
+                    // We have both IdP-signed notifications behind the conflict: turn it
+                    // into a proof anyone can verify, instead of a local-only error. This is
+                    // the one case a justification can never legitimately be missing for,
+                    // since the IdP itself is the one who signed both roots.
+                    match &self.locked_notification {
+                        Some(locked) => {
+                            WitnessError::EquivocatingIdp(Box::new(ConflictingNotifications {
+                                notification_1: locked.clone(),
+                                notification_2: notification.clone(),
+                            }))
+                        }
+                        None => WitnessError::ConflictingNotification {
+                            lock: *vote.root(),
+                            received: *notification.root(),
+                        },
+                    }
+                })?;
                 ensure!(
-                    vote.root() == notification.root(),
-                    WitnessError::ConflictingNotification {
-                        lock: *vote.root(),
-                        received: *notification.root()
+                    justification.root() == notification.root()
+                        && justification.sequence_number() == notification.sequence_number(),
+                    WitnessError::MessageError(MessageError::JustificationMismatch {
+                        justified: *justification.root(),
+                        attached: *notification.root(),
+                    })
+                );
+                ensure!(
+                    justification.round() >= vote.round(),
+                    WitnessError::StaleJustification {
+                        locked_round: vote.round(),
+                        justification_round: justification.round(),
                     }
                 );
-                Ok(vote.clone())
+                justification.verify(&self.committee)?;
+                Ok(self.sign_vote(notification))
             }
-            None => Ok(PublishVote::new(notification, &self.keypair)),
+
+            None => Ok(self.sign_vote(notification)),
         }
     }
 
@@ -123,6 +248,51 @@ impl PublishHandler {
         Ok(())
     }
 
+    /// Advance the state with `certificate`, which must be the next one in sequence, and
+    /// report it downstream. Shared by the direct receive path and by `run`'s replay of
+    /// buffered `orphans` once they become contiguous, so both apply a certificate exactly
+    /// the same way.
+    async fn commit_certificate(
+        &mut self,
+        serialized: SerializedPublishCertificateMessage,
+        certificate: &PublishCertificate,
+    ) {
+        // Invariant: a certificate is only ever applied to the sequence number it certifies.
+        assert_eq!(self.state.sequence_number, certificate.sequence_number());
+
+        // Update the witness state.
+        #[cfg(not(feature = "witness-only-benchmark"))]
+        {
+            // Do not update the state root when running benchmarks. This allows the
+            // benchmark client to re-use the same proof (and thus not becoming the
+            // CPU bottleneck).
+            self.state.root = *certificate.root();
+        }
+        self.state.sequence_number += 1;
+        self.state.lock = None;
+        self.locked_notification = None;
+
+        // Persist before acknowledging below, for the same reason as when the lock was
+        // acquired: the advance must be durable before anyone downstream can observe it.
+        self.state.persist(&self.storage);
+
+        debug!("Commit {:?}", certificate);
+        // NOTE: These log entries are used to compute performance.
+        info!("Commit {}", certificate);
+
+        // Send the serialized certificate to the sync helper.
+        self.tx_processed_certificate
+            .send((serialized, certificate.sequence_number()))
+            .await
+            .expect("Failed to send certificate to sync helper");
+
+        // Let the `ViewChangeHandler` know this sequence number is done, so it stops timing
+        // it out.
+        let _ = self.tx_committed.send(certificate.sequence_number()).await;
+
+        self.notify_subscribers().await;
+    }
+
     /// Main loop listening to verified IdP's notification messages.
     async fn run(&mut self) {
         loop {
@@ -140,12 +310,15 @@ impl PublishHandler {
                         Ok(vote) => {
                             debug!("Create {:?}", vote);
 
-                            // Register the lock.
+                            // Register the lock, and persist it before the vote is released
+                            // below so a restart can never forget a lock it already voted on.
                             self.state.lock = Some(vote.clone());
-                            let serialized_state = bincode::serialize(&self.state)
-                                .expect("Failed to serialize state");
-                            self.storage.write(&STORE_STATE_ADDR, &serialized_state)
-                                .expect("Failed to persist state");
+                            self.locked_notification = Some(notification.clone());
+                            self.state.persist(&self.storage);
+
+                            // Let the `ViewChangeHandler` know we cast a vote, so it (re)starts
+                            // this sequence number's round timer.
+                            let _ = self.tx_vote_cast.send(vote.clone()).await;
 
                             // Reply with a vote.
                             WitnessToIdPMessage::PublishVote(Ok(vote))
@@ -161,37 +334,38 @@ impl PublishHandler {
                         Err(e) => {
                             warn!("{}", e);
 
+                            // Tell the sync requester about the gap so it starts chasing the
+                            // missing certificates down from peer witnesses, instead of only
+                            // ever catching up via the IdP's own (slower) anti-entropy retry.
+                            if let WitnessError::MissingEarlierCertificates(expected) = &e {
+                                let missing = MissingCertificates {
+                                    from: *expected,
+                                    to: certificate.sequence_number() - 1,
+                                };
+                                let _ = self.tx_missing.send(missing).await;
+
+                                // Keep the certificate itself around: we already have it, so
+                                // there is no need to re-fetch it once the gap before it
+                                // closes, only to apply it in order once it does.
+                                self.orphans
+                                    .entry(certificate.sequence_number())
+                                    .or_insert((serialized, certificate));
+                            }
+
                             // Reply with an error message.
                             WitnessToIdPMessage::State(Err(e))
                         },
                         Ok(()) => {
                             if self.state.sequence_number == certificate.sequence_number() {
-                                // Update the witness state.
-                                #[cfg(not(feature = "witness-only-benchmark"))]
+                                self.commit_certificate(serialized, &certificate).await;
+
+                                // Replay any buffered certificates that are now contiguous,
+                                // in ascending sequence order.
+                                while let Some((buffered_serialized, buffered_certificate)) =
+                                    self.orphans.remove(&self.state.sequence_number)
                                 {
-                                    // Do not update the state root when running benchmarks. This allows the
-                                    // benchmark client to re-use the same proof (and thus not becoming the
-                                    // CPU bottleneck).
-                                    self.state.root = *certificate.root();
+                                    self.commit_certificate(buffered_serialized, &buffered_certificate).await;
                                 }
-                                self.state.sequence_number += 1;
-                                self.state.lock = None;
-
-                                let serialized_state = bincode::serialize(&self.state)
-                                    .expect("Failed to serialize state");
-                                self.storage.write(&STORE_STATE_ADDR, &serialized_state)
-                                    .expect("Failed to persist state");
-
-                                debug!("Commit {:?}", certificate);
-                                // NOTE: These log entries are used to compute performance.
-                                info!("Commit {}", certificate);
-
-                                // Send the serialized certificate to the sync helper.
-                                self
-                                    .tx_processed_certificate
-                                    .send((serialized, certificate.sequence_number()))
-                                    .await
-                                    .expect("Failed to send certificate to sync helper");
                             } else {
                                 debug!("Already processed {:?}", certificate);
                             }
@@ -208,6 +382,16 @@ impl PublishHandler {
                     let reply =  WitnessToIdPMessage::State(Ok(self.state.clone()));
                     replier.send(reply).expect("Failed to reply to state query");
                 }
+
+                // Receive new state-change subscriptions: resume with the current state
+                // immediately so late joiners start consistent, then keep the subscriber
+                // around for subsequent pushes.
+                Some(mut subscriber) = self.rx_subscribe_state.recv() => {
+                    let reply = WitnessToIdPMessage::State(Ok(self.state.clone()));
+                    if subscriber.send(reply).await.is_ok() {
+                        self.subscribers.push(subscriber);
+                    }
+                }
             }
         }
     }
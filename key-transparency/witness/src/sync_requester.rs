@@ -0,0 +1,193 @@
+use crate::Replier;
+use bytes::Bytes;
+use config::Committee;
+use crypto::PublicKey;
+use futures::stream::{futures_unordered::FuturesUnordered, StreamExt};
+use hashset_delay::HashSetDelay;
+use log::debug;
+use messages::{
+    codec,
+    publish::{PublishCertificate, PublishMessage},
+    sync::PublishCertificateQuery,
+    IdPToWitnessMessage, SequenceNumber, SerializedPublishCertificateMessage, WitnessToIdPMessage,
+};
+use network::reliable_sender::{CancelHandler, ReliableSender};
+use std::collections::HashMap;
+use tokio::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        oneshot,
+    },
+    task::JoinHandle,
+    time::Duration,
+};
+
+/// How long to wait for a peer to answer a certificate request before trying the next one.
+const INITIAL_RETRY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The retry timeout never backs off past this, so a persistently missing certificate is
+/// still retried at a bounded rate rather than being forgotten.
+const MAX_RETRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Signals a gap in the certificate sequence, so the `SyncRequester` can start chasing the
+/// missing range down from peer witnesses.
+#[derive(Debug)]
+pub struct MissingCertificates {
+    /// The first missing sequence number (inclusive).
+    pub from: SequenceNumber,
+    /// The last missing sequence number (inclusive).
+    pub to: SequenceNumber,
+}
+
+/// Requests certificates this witness is missing from peer witnesses, deduplicating
+/// in-flight requests with a delay queue so each missing sequence number has a single
+/// tracking entry, retried with backoff until a peer supplies it, complementing `SyncHelper` (the serving side of the same
+/// anti-entropy protocol) with a robust requester.
+pub struct SyncRequester {
+    /// The committee information.
+    committee: Committee,
+    /// A reliable network sender.
+    network: ReliableSender,
+    /// Peer witnesses to request from, in round-robin order.
+    peers: Vec<PublicKey>,
+    /// The index of the next peer to try in `peers`.
+    next_peer: usize,
+    /// Missing sequence numbers, deduplicated and retried with a backoff timeout.
+    missing: HashSetDelay<SequenceNumber>,
+    /// Number of requests already sent for each still-missing sequence number, used to
+    /// compute the next retry's backoff.
+    attempts: HashMap<SequenceNumber, u32>,
+    /// Forward recovered certificates into the same pipeline as IdP-delivered ones, so they
+    /// go through the usual verification, persistence and subscriber notification.
+    tx_certificate: Sender<(SerializedPublishCertificateMessage, PublishCertificate, Replier)>,
+    /// Receive newly-detected gaps from the publish handler.
+    rx_missing: Receiver<MissingCertificates>,
+}
+
+impl SyncRequester {
+    /// Spawn a new sync requester task.
+    pub fn spawn(
+        name: PublicKey,
+        committee: Committee,
+        tx_certificate: Sender<(SerializedPublishCertificateMessage, PublishCertificate, Replier)>,
+        rx_missing: Receiver<MissingCertificates>,
+    ) -> JoinHandle<()> {
+        let peers = committee
+            .witnesses_addresses()
+            .into_iter()
+            .map(|(peer, _)| peer)
+            .filter(|peer| *peer != name)
+            .collect();
+
+        tokio::spawn(async move {
+            Self {
+                committee,
+                network: ReliableSender::new(),
+                peers,
+                next_peer: 0,
+                missing: HashSetDelay::new(INITIAL_RETRY_TIMEOUT),
+                attempts: HashMap::new(),
+                tx_certificate,
+                rx_missing,
+            }
+            .run()
+            .await
+        })
+    }
+
+    /// Send a `PublishCertificateQuery` for `sequence_number` to the next peer in round-robin
+    /// order. Returns `None` if there are no peers to query (e.g. a lone witness).
+    async fn send_request(&mut self, sequence_number: SequenceNumber) -> Option<CancelHandler> {
+        if self.peers.is_empty() {
+            return None;
+        }
+        let peer = self.peers[self.next_peer % self.peers.len()];
+        self.next_peer = (self.next_peer + 1) % self.peers.len();
+        let address = self
+            .committee
+            .witness_address(&peer)
+            .expect("Round-robin peer must be in the committee");
+
+        let query = PublishCertificateQuery { sequence_number };
+        let message = IdPToWitnessMessage::PublishCertificateQuery(query);
+        let bytes = Bytes::from(codec::encode(&message).expect("Failed to serialize certificate query"));
+
+        debug!("Requesting missing certificate {} from {}", sequence_number, peer);
+        Some(self.network.send(address, bytes).await)
+    }
+
+    /// Parse a `PublishCertificateResponse` reply, returning the certificate it carries only
+    /// if it actually covers `expected` (a peer may not have it, or reply with something
+    /// else entirely).
+    fn parse_response(
+        bytes: &[u8],
+        expected: SequenceNumber,
+    ) -> Option<(SerializedPublishCertificateMessage, PublishCertificate)> {
+        let serialized = match codec::decode::<WitnessToIdPMessage>(bytes).ok()? {
+            WitnessToIdPMessage::PublishCertificateResponse(serialized) => serialized,
+            _ => return None,
+        };
+        let certificate = match codec::decode::<IdPToWitnessMessage>(&serialized).ok()? {
+            IdPToWitnessMessage::PublishCertificate(certificate) => certificate,
+            _ => return None,
+        };
+        (certificate.sequence_number() == expected).then_some((serialized, certificate))
+    }
+
+    /// Main loop: track reported gaps, chase down whatever is still missing, and apply
+    /// whatever a peer actually has.
+    async fn run(&mut self) {
+        let mut pending: FuturesUnordered<_> = FuturesUnordered::new();
+
+        loop {
+            tokio::select! {
+                // A gap was just reported: start tracking every sequence number in it that
+                // isn't already tracked, so repeated reports of the same gap are free.
+                Some(range) = self.rx_missing.recv() => {
+                    for sequence_number in range.from..=range.to {
+                        if self.missing.contains_key(&sequence_number) {
+                            continue;
+                        }
+                        self.missing.insert_at(sequence_number, INITIAL_RETRY_TIMEOUT);
+                        self.attempts.insert(sequence_number, 0);
+                        if let Some(handle) = self.send_request(sequence_number).await {
+                            pending.push(async move { (sequence_number, handle.await.ok()) });
+                        }
+                    }
+                },
+
+                // A missing certificate's retry timer expired without being resolved: try
+                // the next peer, backing off so a persistently unreachable witness doesn't
+                // get hammered forever.
+                Some(Ok(sequence_number)) = self.missing.next() => {
+                    let attempts = self.attempts.entry(sequence_number).or_insert(0);
+                    *attempts += 1;
+                    let timeout = (INITIAL_RETRY_TIMEOUT * 2u32.pow(*attempts)).min(MAX_RETRY_TIMEOUT);
+                    self.missing.insert_at(sequence_number, timeout);
+                    if let Some(handle) = self.send_request(sequence_number).await {
+                        pending.push(async move { (sequence_number, handle.await.ok()) });
+                    }
+                },
+
+                // A peer answered one of our requests.
+                Some((sequence_number, reply)) = pending.next() => {
+                    if let Some(bytes) = reply {
+                        if let Some((serialized, certificate)) = Self::parse_response(&bytes, sequence_number) {
+                            // Stop tracking it: we now have it, regardless of whether the
+                            // pipeline below ends up applying it (e.g. it may have arrived
+                            // from the IdP in the meantime).
+                            self.missing.remove(&sequence_number);
+                            self.attempts.remove(&sequence_number);
+
+                            let (replier, _) = oneshot::channel();
+                            let _ = self
+                                .tx_certificate
+                                .send((serialized, certificate, replier))
+                                .await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{build_witness_handler, WitnessHandler};
+use async_trait::async_trait;
+use bytes::Bytes;
+use config::Committee;
+use crypto::{KeyPair, ThresholdKeyShare};
+use messages::{
+    codec,
+    publish::{PublishNotification, PublishVote},
+    sync::State,
+    Blake3, IdPToWitnessMessage, Round, SequenceNumber, WitnessToIdPMessage,
+};
+use network::receiver::{MessageHandler, Receiver as NetworkReceiver, Writer};
+use std::{collections::HashSet, error::Error, sync::Mutex, time::Duration};
+use storage::Storage;
+use tokio::time::sleep;
+use winter_crypto::Hasher;
+
+/// Scripts how a [`TestWitness`] reacts to inbound network messages, letting integration tests
+/// exercise the IdP's and other witnesses' fault-handling paths against a misbehaving committee
+/// member that otherwise runs the real protocol logic end to end (c.f.
+/// `test_utils::WitnessBehavior`, which scripts a single notification/certificate round-trip
+/// over a bare socket and never touches `PublishHandler`, `SyncHelper`, or `ViewChangeHandler`).
+pub enum ByzantineBehavior {
+    /// Runs the real witness logic with no injected fault.
+    Honest,
+    /// Votes honestly the first time it is asked to vote on a given sequence number and round,
+    /// then signs and returns a forged vote for a different root the next time it is asked
+    /// about the same sequence number and round (as a retransmitted notification would trigger),
+    /// producing two validly-signed, conflicting votes from the same author.
+    Equivocate,
+    /// Replies to every `StateQuery` with whatever `State` it last held, one generation behind
+    /// its real current state.
+    StaleState,
+    /// Never replies to any request.
+    DropReplies,
+    /// Replies to every request, but only after sleeping for the given duration; streamed
+    /// requests (`CertificateRangeQuery`, `SubscribeState`) are forwarded without the delay.
+    DelayReplies(Duration),
+    /// Never replies to a `PublishCertificateQuery`, as if it never had the certificate.
+    RefuseCertificateQueries,
+}
+
+/// A test double for a witness that wraps the real `WitnessHandler`, so integration tests can
+/// spin up a mixed honest/Byzantine committee and assert that an honest party's `make_vote`
+/// locking and the catch-up/timeout paths uphold safety and liveness, instead of hand-rolling a
+/// fake socket that never runs the real protocol logic.
+pub struct TestWitness {
+    keypair: KeyPair,
+    behavior: ByzantineBehavior,
+    inner: WitnessHandler,
+    // Sequence numbers and rounds already answered once, so `ByzantineBehavior::Equivocate`
+    // knows to forge its reply on the second and later requests about the same round instead of
+    // the first.
+    seen: Mutex<HashSet<(SequenceNumber, Round)>>,
+    // The most recent `State` returned to a `StateQuery`, held back one generation by
+    // `ByzantineBehavior::StaleState`.
+    previous_state: Mutex<Option<State>>,
+}
+
+impl TestWitness {
+    /// Build every internal task exactly as `spawn_witness` would, then wrap the resulting
+    /// handler with `behavior` and bind a listener on the committee's address for this witness.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn(
+        keypair: KeyPair,
+        view_change_keypair: KeyPair,
+        sync_keypair: KeyPair,
+        behavior_keypair: KeyPair,
+        behavior: ByzantineBehavior,
+        committee: Committee,
+        threshold_share: Option<ThresholdKeyShare>,
+        secure_storage: Storage,
+        audit_storage: Storage,
+        max_range_span: SequenceNumber,
+        initial_view_timeout: Duration,
+    ) {
+        let (_name, address, inner) = build_witness_handler(
+            keypair,
+            view_change_keypair,
+            sync_keypair,
+            committee,
+            threshold_share,
+            secure_storage,
+            audit_storage,
+            max_range_span,
+            initial_view_timeout,
+        );
+        let handler = TestWitness {
+            keypair: behavior_keypair,
+            behavior,
+            inner,
+            seen: Mutex::new(HashSet::new()),
+            previous_state: Mutex::new(None),
+        };
+        NetworkReceiver::spawn(address, handler);
+    }
+
+    /// Reply honestly the first time a round is asked about, and with a forged vote for a
+    /// different root every time after.
+    fn equivocating_reply(
+        &self,
+        sequence_number: SequenceNumber,
+        round: Round,
+        notification: &PublishNotification,
+        honest_reply: WitnessToIdPMessage,
+    ) -> WitnessToIdPMessage {
+        let first_time = self.seen.lock().unwrap().insert((sequence_number, round));
+        if first_time {
+            return honest_reply;
+        }
+        let mut forged = notification.clone();
+        forged.root = Blake3::hash(b"test witness equivocation");
+        WitnessToIdPMessage::PublishVote(Ok(PublishVote::new(&forged, &self.keypair)))
+    }
+
+    /// Reply with whatever `State` was returned to the *previous* `StateQuery`, so the witness
+    /// always appears one generation behind its real current state.
+    fn stale_reply(&self, current: State) -> WitnessToIdPMessage {
+        let mut previous_state = self.previous_state.lock().unwrap();
+        let reply = previous_state.clone().unwrap_or_else(|| current.clone());
+        *previous_state = Some(current);
+        WitnessToIdPMessage::State(Ok(reply))
+    }
+}
+
+#[async_trait]
+impl MessageHandler for TestWitness {
+    async fn dispatch(&self, writer: &mut Writer, serialized: Bytes) -> Result<(), Box<dyn Error>> {
+        let message = codec::decode::<IdPToWitnessMessage>(&serialized)?;
+
+        if matches!(self.behavior, ByzantineBehavior::DropReplies) {
+            return Ok(());
+        }
+        if matches!(self.behavior, ByzantineBehavior::RefuseCertificateQueries)
+            && matches!(message, IdPToWitnessMessage::PublishCertificateQuery(_))
+        {
+            return Ok(());
+        }
+
+        match message {
+            IdPToWitnessMessage::CertificateRangeQuery(_) | IdPToWitnessMessage::SubscribeState => {
+                // Streamed replies are forwarded verbatim; of the `ByzantineBehavior`s only
+                // `DropReplies` (checked above) applies to them.
+                return self.inner.dispatch(writer, serialized).await;
+            }
+            IdPToWitnessMessage::PublishNotification(notification) => {
+                if let ByzantineBehavior::DelayReplies(delay) = self.behavior {
+                    sleep(delay).await;
+                }
+                let sequence_number = notification.sequence_number;
+                let round = notification.round;
+                let for_reply = notification.clone();
+                let reply = self
+                    .inner
+                    .compute_reply(
+                        &serialized,
+                        IdPToWitnessMessage::PublishNotification(notification),
+                    )
+                    .await;
+                let reply = match self.behavior {
+                    ByzantineBehavior::Equivocate => {
+                        self.equivocating_reply(sequence_number, round, &for_reply, reply)
+                    }
+                    _ => reply,
+                };
+                let bytes = codec::encode(&reply).expect("Failed to serialize reply");
+                writer.send(Bytes::from(bytes)).await?;
+            }
+            IdPToWitnessMessage::StateQuery => {
+                if let ByzantineBehavior::DelayReplies(delay) = self.behavior {
+                    sleep(delay).await;
+                }
+                let reply = self
+                    .inner
+                    .compute_reply(&serialized, IdPToWitnessMessage::StateQuery)
+                    .await;
+                let reply = match (&self.behavior, reply) {
+                    (ByzantineBehavior::StaleState, WitnessToIdPMessage::State(Ok(state))) => {
+                        self.stale_reply(state)
+                    }
+                    (_, reply) => reply,
+                };
+                let bytes = codec::encode(&reply).expect("Failed to serialize reply");
+                writer.send(Bytes::from(bytes)).await?;
+            }
+            message => {
+                if let ByzantineBehavior::DelayReplies(delay) = self.behavior {
+                    sleep(delay).await;
+                }
+                let reply = self.inner.compute_reply(&serialized, message).await;
+                let bytes = codec::encode(&reply).expect("Failed to serialize reply");
+                writer.send(Bytes::from(bytes)).await?;
+            }
+        }
+        Ok(())
+    }
+}
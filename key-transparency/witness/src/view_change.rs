@@ -0,0 +1,306 @@
+use crate::Replier;
+use bytes::Bytes;
+use config::{Committee, VotingPower};
+use crypto::{KeyPair, PublicKey};
+use futures::future::join_all;
+use log::{debug, warn};
+use messages::{
+    codec,
+    publish::{CertificateKind, NewView, PublishCertificate, PublishMessage, PublishVote, ViewChange},
+    IdPToWitnessMessage, Round, SequenceNumber, SerializedPublishCertificateMessage,
+    WitnessToIdPMessage,
+};
+use network::reliable_sender::ReliableSender;
+use std::collections::HashMap;
+use tokio::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        oneshot,
+    },
+    task::JoinHandle,
+    time::Duration,
+};
+
+/// The view timeout never backs off past this, so a persistently stalled sequence number is
+/// still retried at a bounded rate rather than spinning the committee ever faster.
+const MAX_VIEW_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Drives the witnesses' recovery path for a stalled or crashed IdP: each witness times out a
+/// sequence number it is locked on, reports that timeout (and its lock) to the view's
+/// round-robin leader (`Committee::leader`), and the leader recovers a certificate from the
+/// collected locks if a quorum of them already agreed on the same root and round. This is a
+/// recovery mechanism only: it can resurrect a certificate the IdP failed to assemble and
+/// broadcast, but it cannot invent a new root of its own (only the IdP can produce the
+/// accompanying audit proof), so a view that recovers nothing simply logs and keeps waiting
+/// for the IdP to come back or for a later view to succeed.
+pub struct ViewChangeHandler {
+    /// The keypair of this witness.
+    keypair: KeyPair,
+    /// The committee information.
+    committee: Committee,
+    /// A reliable network sender, used to reach a view's leader when it is not us.
+    network: ReliableSender,
+    /// This witness's current lock for each sequence number it is still timing out, mirroring
+    /// (a subset of) the `PublishHandler`'s own state.
+    locks: HashMap<SequenceNumber, PublishVote>,
+    /// How many times we have already timed out each sequence number, used to pick an
+    /// ever-increasing view (and thus a new leader) on every attempt.
+    attempts: HashMap<SequenceNumber, Round>,
+    /// View-change messages collected so far, keyed by the `(sequence_number, new_round)` view
+    /// they were reported for. Only populated for views we are the leader of.
+    collected: HashMap<(SequenceNumber, Round), Vec<ViewChange>>,
+    /// Feeds a recovered certificate into the same pipeline as one delivered by the IdP or
+    /// fetched by the `SyncRequester`.
+    tx_certificate: Sender<(
+        SerializedPublishCertificateMessage,
+        PublishCertificate,
+        Replier,
+    )>,
+    /// Receive every vote this witness casts.
+    rx_vote_cast: Receiver<PublishVote>,
+    /// Receive every sequence number once it commits.
+    rx_committed: Receiver<SequenceNumber>,
+    /// Receive view-change messages from peer witnesses.
+    rx_view_change: Receiver<(ViewChange, Replier)>,
+    /// Receive new-view messages from peer witnesses.
+    rx_new_view: Receiver<(NewView, Replier)>,
+    /// How long to wait, after casting a vote, for a certificate to commit before reporting a
+    /// timeout to the next view's leader. Kept as a field (rather than a constant) so tests can
+    /// shrink it and exercise the recovery path without waiting out a production-length timeout,
+    /// mirroring how `SyncHelper::max_range_span` is threaded in rather than hard-coded.
+    initial_view_timeout: Duration,
+}
+
+impl ViewChangeHandler {
+    /// Spawn a new view-change handler task.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn(
+        keypair: KeyPair,
+        committee: Committee,
+        tx_certificate: Sender<(
+            SerializedPublishCertificateMessage,
+            PublishCertificate,
+            Replier,
+        )>,
+        rx_vote_cast: Receiver<PublishVote>,
+        rx_committed: Receiver<SequenceNumber>,
+        rx_view_change: Receiver<(ViewChange, Replier)>,
+        rx_new_view: Receiver<(NewView, Replier)>,
+        initial_view_timeout: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            Self {
+                keypair,
+                committee,
+                network: ReliableSender::new(),
+                locks: HashMap::new(),
+                attempts: HashMap::new(),
+                collected: HashMap::new(),
+                tx_certificate,
+                rx_vote_cast,
+                rx_committed,
+                rx_view_change,
+                rx_new_view,
+                initial_view_timeout,
+            }
+            .run()
+            .await
+        })
+    }
+
+    /// Forward a recovered (or otherwise obtained) certificate into the normal certificate
+    /// pipeline, exactly like the `SyncRequester` does for ones fetched from a peer.
+    async fn apply_certificate(&self, certificate: PublishCertificate) {
+        let message = IdPToWitnessMessage::PublishCertificate(certificate.clone());
+        let serialized = codec::encode(&message).expect("Failed to serialize certificate");
+        let (replier, _) = oneshot::channel();
+        let _ = self
+            .tx_certificate
+            .send((serialized, certificate, replier))
+            .await;
+    }
+
+    /// Build and send (or, if we are the leader ourselves, apply locally) a view-change for
+    /// `sequence_number`'s next attempt.
+    async fn report_timeout(&mut self, sequence_number: SequenceNumber) {
+        let attempt = self.attempts.entry(sequence_number).or_insert(0);
+        *attempt += 1;
+        let lock = self.locks.get(&sequence_number).cloned();
+        let base_round = lock.as_ref().map(|vote| vote.round).unwrap_or(0);
+        let new_round = base_round + *attempt;
+
+        let view_change = ViewChange::new(sequence_number, new_round, lock, &self.keypair);
+        let leader = self.committee.leader(new_round);
+
+        warn!(
+            "Sequence {} timed out at view {}, reporting to leader {}",
+            sequence_number, new_round, leader
+        );
+
+        if leader == self.keypair.public() {
+            if let Some(new_view) = self.handle_view_change(view_change) {
+                self.broadcast_new_view(new_view).await;
+            }
+            return;
+        }
+
+        let address = match self.committee.witness_address(&leader) {
+            Some(address) => address,
+            None => return,
+        };
+        let message = IdPToWitnessMessage::ViewChange(view_change);
+        let bytes = Bytes::from(codec::encode(&message).expect("Failed to serialize view-change"));
+        let _ = self.network.send(address, bytes).await.await;
+    }
+
+    /// Record a view-change we are the leader for, and once a quorum of them has been collected
+    /// for the same view, try to recover a certificate and return the resulting `NewView`.
+    fn handle_view_change(&mut self, view_change: ViewChange) -> Option<NewView> {
+        if let Err(e) = view_change.verify(&self.committee) {
+            warn!("{}", e);
+            return None;
+        }
+        if self.committee.leader(view_change.new_round) != self.keypair.public() {
+            // Misrouted or stale: not our view to resolve.
+            return None;
+        }
+
+        let key = (view_change.sequence_number, view_change.new_round);
+        let entry = self.collected.entry(key).or_default();
+        if entry.iter().any(|vc| vc.author == view_change.author) {
+            return None;
+        }
+        entry.push(view_change);
+
+        let weight: VotingPower = entry
+            .iter()
+            .map(|vc| self.committee.voting_power(&vc.author))
+            .sum();
+        if weight < self.committee.quorum_threshold() {
+            return None;
+        }
+
+        let certificate = Self::recover_certificate(&self.committee, entry);
+        let new_view = NewView::new(key.0, key.1, certificate, &self.keypair);
+        self.collected.remove(&key);
+        Some(new_view)
+    }
+
+    /// Group the locks carried by `view_changes` by `(root, round)` and return a certificate for
+    /// whichever group's combined voting power already reaches quorum, if any.
+    fn recover_certificate(
+        committee: &Committee,
+        view_changes: &[ViewChange],
+    ) -> Option<PublishCertificate> {
+        let sequence_number = view_changes.first()?.sequence_number;
+        let mut groups: HashMap<_, Vec<_>> = HashMap::new();
+        for vc in view_changes {
+            if let Some(lock) = &vc.lock {
+                groups
+                    .entry((lock.root, lock.round))
+                    .or_insert_with(Vec::new)
+                    .push((lock.author, lock.signature.clone(), lock.timestamp));
+            }
+        }
+        groups.into_iter().find_map(|((root, round), votes)| {
+            let weight: VotingPower = votes
+                .iter()
+                .map(|(name, _, _)| committee.voting_power(name))
+                .sum();
+            (weight >= committee.quorum_threshold()).then(|| PublishCertificate {
+                root,
+                sequence_number,
+                round,
+                timestamps: votes
+                    .iter()
+                    .map(|(name, _, timestamp)| (*name, *timestamp))
+                    .collect(),
+                kind: CertificateKind::Votes(
+                    votes
+                        .into_iter()
+                        .map(|(name, signature, _)| (name, signature))
+                        .collect(),
+                ),
+            })
+        })
+    }
+
+    /// Broadcast a resolved view to every witness: those who recover the same view apply the
+    /// certificate (if any) exactly like they would one delivered by the IdP.
+    async fn broadcast_new_view(&self, new_view: NewView) {
+        let message = IdPToWitnessMessage::NewView(new_view);
+        let bytes = Bytes::from(codec::encode(&message).expect("Failed to serialize new-view"));
+        let addresses = self
+            .committee
+            .witnesses_addresses()
+            .into_iter()
+            .map(|(_, address)| address)
+            .collect();
+        let handles = self.network.broadcast(addresses, bytes).await;
+        join_all(handles).await;
+    }
+
+    /// Process a `NewView` received from a peer (normally the view's leader).
+    async fn process_new_view(&mut self, new_view: NewView) {
+        if let Err(e) = new_view.verify(&self.committee) {
+            warn!("{}", e);
+            return;
+        }
+        match new_view.certificate {
+            Some(certificate) => {
+                debug!("Recovered {:?} via view-change", certificate);
+                self.apply_certificate(certificate).await;
+            }
+            None => warn!(
+                "View {} for sequence {} resolved without a recoverable certificate",
+                new_view.new_round, new_view.sequence_number
+            ),
+        }
+    }
+
+    /// Main loop.
+    async fn run(&mut self) {
+        let mut timers = hashset_delay::HashSetDelay::new(self.initial_view_timeout);
+
+        loop {
+            tokio::select! {
+                // A vote was just cast: (re)start this sequence number's timer and remember the
+                // lock, so a timeout can report it.
+                Some(vote) = self.rx_vote_cast.recv() => {
+                    let sequence_number = vote.sequence_number;
+                    self.locks.insert(sequence_number, vote);
+                    timers.insert_at(sequence_number, self.initial_view_timeout);
+                },
+
+                // The sequence number committed: stop timing it out.
+                Some(sequence_number) = self.rx_committed.recv() => {
+                    self.locks.remove(&sequence_number);
+                    self.attempts.remove(&sequence_number);
+                    timers.remove(&sequence_number);
+                },
+
+                // A sequence number's timer fired without a commit.
+                Some(Ok(sequence_number)) = timers.next() => {
+                    self.report_timeout(sequence_number).await;
+                    let attempt = self.attempts.get(&sequence_number).copied().unwrap_or(1);
+                    let timeout = (self.initial_view_timeout * 2u32.pow(attempt as u32)).min(MAX_VIEW_TIMEOUT);
+                    timers.insert_at(sequence_number, timeout);
+                },
+
+                // A peer reported a timeout to us.
+                Some((view_change, replier)) = self.rx_view_change.recv() => {
+                    if let Some(new_view) = self.handle_view_change(view_change) {
+                        self.broadcast_new_view(new_view).await;
+                    }
+                    let _ = replier.send(WitnessToIdPMessage::ViewChangeAck);
+                },
+
+                // A peer (normally a view's leader) resolved a view.
+                Some((new_view, replier)) = self.rx_new_view.recv() => {
+                    self.process_new_view(new_view).await;
+                    let _ = replier.send(WitnessToIdPMessage::ViewChangeAck);
+                },
+            }
+        }
+    }
+}
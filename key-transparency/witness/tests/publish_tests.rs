@@ -1,23 +1,31 @@
-use vkd::{
-    directory::Directory,
-    ecvrf::HardCodedAkdVRF,
-    storage::{
-        memory::AsyncInMemoryDatabase,
-        types::{AkdLabel, AkdValue},
-    },
-};
+use bytes::Bytes;
 use function_name::named;
 use futures::future::try_join_all;
 use messages::{
+    codec,
     error::WitnessError,
-    publish::{PublishCertificate, PublishNotification, PublishVote},
-    sync::State,
+    publish::{
+        CertificateKind, ConflictingVote, PublishCertificate, PublishNotification, PublishVote,
+    },
+    sync::{RootChainQuery, State},
     Blake3, WitnessToIdPMessage,
 };
+use std::collections::HashMap;
 use test_utils::{
-    broadcast_certificate, broadcast_notification, certificate, committee, delete_storage, keys,
-    notification, proof, spawn_test_witnesses, votes,
+    broadcast_certificate, broadcast_certificate_query, broadcast_notification,
+    broadcast_root_chain_query, broadcast_state_query, certificate, committee, delete_storage,
+    keys, notification, proof, spawn_test_witnesses, spawn_test_witnesses_with_byzantine, votes,
+    TEST_VIEW_TIMEOUT,
+};
+use vkd::{
+    directory::Directory,
+    ecvrf::HardCodedAkdVRF,
+    storage::{
+        memory::AsyncInMemoryDatabase,
+        types::{AkdLabel, AkdValue},
+    },
 };
+use witness::test_witness::ByzantineBehavior;
 
 #[tokio::test]
 #[named]
@@ -39,7 +47,7 @@ async fn correct_notification() {
         .await
         .unwrap()
         .iter()
-        .map(|reply| match bincode::deserialize(&reply).unwrap() {
+        .map(|reply| match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::PublishVote(Ok(vote)) => vote,
             _ => panic!("Unexpected protocol message"),
         })
@@ -74,6 +82,7 @@ async fn unexpected_sequence_number() {
         root,
         proof,
         /* sequence_number */ bad_sequence_number,
+        /* round */ 0,
         /* keypair */ &identity_provider,
     );
 
@@ -82,7 +91,7 @@ async fn unexpected_sequence_number() {
 
     // Ensure the witnesses' replies are as expected.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
+        match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::PublishVote(Err(WitnessError::UnexpectedSequenceNumber {
                 expected,
                 got,
@@ -109,7 +118,7 @@ async fn conflicting_notification() {
     spawn_test_witnesses(&test_id, &committee);
     tokio::task::yield_now().await;
 
-    // Broadcast a first notification.
+    // Broadcast a first notification and let every witness lock onto it.
     let notification = notification().await;
     let notification_root = notification.root.clone();
     let handles = broadcast_notification(notification, &committee).await;
@@ -118,7 +127,7 @@ async fn conflicting_notification() {
     // Make a conflicting proof of update.
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedAkdVRF {};
-    let vkd = Directory::new::<Blake3>(&db, &vrf, false).await.unwrap();
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
     vkd.publish::<Blake3>(vec![(AkdLabel(vec![1, 2, 3]), AkdValue(vec![3, 4, 6]))])
         .await
         .unwrap();
@@ -131,26 +140,27 @@ async fn conflicting_notification() {
     // Generate the audit proof.
     let proof = vkd.audit::<Blake3>(0, 1).await.unwrap();
 
-    // Broadcast a conflicting notification.
+    // Broadcast a conflicting notification, signed by the same IdP, for the same sequence
+    // number -- this is the IdP equivocating, not merely a stale/unjustified re-proposal.
     let (_, identity_provider) = keys().pop().unwrap();
     let conflict = PublishNotification::new(
         root,
         proof,
         /* sequence number */ 1,
+        /* round */ 0,
         /* keypair */ &identity_provider,
     );
     let conflict_root = conflict.root.clone();
     let handles = broadcast_notification(conflict, &committee).await;
 
-    // Ensure the witnesses' replies are as expected.
+    // Ensure every witness detects the equivocation and produces a proof anyone can verify,
+    // rather than only a local error.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
-            WitnessToIdPMessage::PublishVote(Err(WitnessError::ConflictingNotification {
-                lock,
-                received,
-            })) => {
-                assert_eq!(lock, notification_root);
-                assert_eq!(received, conflict_root);
+        match codec::decode(&reply).unwrap() {
+            WitnessToIdPMessage::PublishVote(Err(WitnessError::EquivocatingIdp(proof))) => {
+                assert_eq!(proof.notification_1.root, notification_root);
+                assert_eq!(proof.notification_2.root, conflict_root);
+                assert!(proof.verify(&committee).is_ok());
             }
             _ => panic!("Unexpected protocol message"),
         }
@@ -186,7 +196,7 @@ async fn expected_certificate() {
 
     // Ensure the witnesses' replies are as expected.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
+        match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::State(Ok(state)) => assert_eq!(state, expected),
             _ => panic!("Unexpected protocol message"),
         }
@@ -196,6 +206,91 @@ async fn expected_certificate() {
     delete_storage(&test_id);
 }
 
+#[tokio::test]
+#[named]
+async fn unlock_via_justification() {
+    let base_port = 7_350;
+    let committee = committee(base_port);
+    let test_id = function_name!();
+
+    // Spawn 4 witnesses.
+    spawn_test_witnesses(&test_id, &committee);
+    tokio::task::yield_now().await;
+
+    // Broadcast a first notification and let every witness lock onto it.
+    let notification = notification().await;
+    let handles = broadcast_notification(notification, &committee).await;
+    let _ = try_join_all(handles).await.unwrap();
+
+    // Make a conflicting proof of update.
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedAkdVRF {};
+    let vkd = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
+    vkd.publish::<Blake3>(vec![(AkdLabel(vec![9, 9, 9]), AkdValue(vec![9, 9, 9]))])
+        .await
+        .unwrap();
+    let current_azks = vkd.retrieve_current_azks().await.unwrap();
+    let root = vkd
+        .get_root_hash_at_epoch::<Blake3>(&current_azks, /* sequence number */ 1)
+        .await
+        .unwrap();
+    let proof = vkd.audit::<Blake3>(0, 1).await.unwrap();
+
+    // Assemble a quorum certificate for the conflicting root at round 0, as if quorum had
+    // already moved on without this witness (e.g. it missed the round entirely).
+    let (_, identity_provider) = keys().pop().unwrap();
+    let unjustified = PublishNotification::new(
+        root,
+        proof.clone(),
+        /* sequence_number */ 1,
+        /* round */ 0,
+        /* keypair */ &identity_provider,
+    );
+    let quorum_votes: Vec<_> = keys()
+        .iter()
+        .map(|(_, keypair)| PublishVote::new(&unjustified, keypair))
+        .collect();
+    let justification = PublishCertificate {
+        root: unjustified.root,
+        sequence_number: unjustified.sequence_number,
+        round: unjustified.round,
+        kind: CertificateKind::Votes(
+            quorum_votes
+                .into_iter()
+                .map(|vote| (vote.author, vote.signature))
+                .collect(),
+        ),
+        timestamps: Vec::new(),
+    };
+
+    // Re-propose the conflicting root at a later round, with the justification attached.
+    let reproposal = PublishNotification::new(
+        root,
+        proof,
+        /* sequence_number */ 1,
+        /* round */ 1,
+        /* keypair */ &identity_provider,
+    )
+    .with_justification(justification);
+    let reproposal_root = reproposal.root;
+
+    // Broadcast the justified re-proposal.
+    let handles = broadcast_notification(reproposal, &committee).await;
+
+    // Ensure every witness unlocks and votes for the new root instead of rejecting it.
+    for reply in try_join_all(handles).await.unwrap() {
+        match codec::decode(&reply).unwrap() {
+            WitnessToIdPMessage::PublishVote(Ok(vote)) => {
+                assert_eq!(vote.root, reproposal_root);
+            }
+            _ => panic!("Unexpected protocol message"),
+        }
+    }
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
 #[tokio::test]
 #[named]
 async fn unexpected_certificate() {
@@ -215,6 +310,7 @@ async fn unexpected_certificate() {
         root,
         proof,
         /* sequence_number */ future_sequence_number,
+        /* round */ 0,
         /* keypair */ &identity_provider,
     );
 
@@ -226,7 +322,9 @@ async fn unexpected_certificate() {
     let certificate = PublishCertificate {
         root: notification.root.clone(),
         sequence_number: notification.sequence_number,
-        votes: votes.into_iter().map(|x| (x.author, x.signature)).collect(),
+        round: notification.round,
+        kind: CertificateKind::Votes(votes.into_iter().map(|x| (x.author, x.signature)).collect()),
+        timestamps: Vec::new(),
     };
 
     // Broadcast the certificate.
@@ -234,7 +332,7 @@ async fn unexpected_certificate() {
 
     // Ensure the witnesses' replies are as expected.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
+        match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::State(Err(WitnessError::MissingEarlierCertificates(seq))) => {
                 assert_eq!(seq, 1);
             }
@@ -245,3 +343,197 @@ async fn unexpected_certificate() {
     // Delete the storage.
     delete_storage(&test_id);
 }
+
+#[tokio::test]
+#[named]
+async fn recovers_certificate_after_idp_stall() {
+    let base_port = 7_500;
+    let committee = committee(base_port);
+    let test_id = function_name!();
+
+    // Spawn 4 witnesses.
+    spawn_test_witnesses(&test_id, &committee);
+    tokio::task::yield_now().await;
+
+    // Broadcast a notification and let every witness lock onto and vote for it, exactly as if
+    // the IdP had collected a full quorum of votes and then crashed before ever assembling and
+    // broadcasting the resulting certificate.
+    let notification = notification().await;
+    let handles = broadcast_notification(notification, &committee).await;
+    let _ = try_join_all(handles).await.unwrap();
+
+    // Wait out the (short, test-only) view timeout plus some slack for the view-change
+    // round-trip: every witness's `ViewChangeHandler` should time out, report its lock to the
+    // round's leader, and the leader should recover a certificate from the matching locks and
+    // broadcast it back, all without any further involvement from the (stalled) IdP.
+    tokio::time::sleep(TEST_VIEW_TIMEOUT * 10).await;
+
+    // Ensure every witness committed the recovered certificate and advanced past it.
+    let (_, root, _) = proof().await;
+    let expected = State {
+        root,
+        sequence_number: 2,
+        lock: None,
+    };
+    let handles = broadcast_state_query(&committee).await;
+    for reply in try_join_all(handles).await.unwrap() {
+        match codec::decode(&reply).unwrap() {
+            WitnessToIdPMessage::State(Ok(state)) => assert_eq!(state, expected),
+            _ => panic!("Unexpected protocol message"),
+        }
+    }
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn root_chain_query() {
+    let base_port = 7_600;
+    let committee = committee(base_port);
+    let test_id = function_name!();
+
+    // Spawn 4 witnesses.
+    spawn_test_witnesses(&test_id, &committee);
+    tokio::task::yield_now().await;
+
+    // Commit a single certificate.
+    let certificate = certificate().await;
+    let handles = broadcast_certificate(certificate, &committee).await;
+    let _ = try_join_all(handles).await.unwrap();
+
+    // Ask every witness for the committed root chain.
+    let (_, root, _) = proof().await;
+    let handles = broadcast_root_chain_query(RootChainQuery { from: 1, to: 1 }, &committee).await;
+    for reply in try_join_all(handles).await.unwrap() {
+        match codec::decode(&reply).unwrap() {
+            WitnessToIdPMessage::RootChainResponse(Ok(chain)) => {
+                assert_eq!(chain.entries.len(), 1);
+                assert_eq!(chain.entries[0].sequence_number, 1);
+                assert_eq!(chain.entries[0].root, root);
+                assert!(chain.verify(&committee).is_ok());
+            }
+            _ => panic!("Unexpected protocol message"),
+        }
+    }
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn equivocating_witness_produces_a_conflicting_vote() {
+    let base_port = 7_700;
+    let committee = committee(base_port);
+    let test_id = function_name!();
+
+    // Replace one witness with a `TestWitness` that votes honestly the first time it is asked
+    // about a round, then signs a conflicting vote the next time.
+    let equivocator = keys()[0].0;
+    let mut byzantine = HashMap::new();
+    byzantine.insert(0, ByzantineBehavior::Equivocate);
+    spawn_test_witnesses_with_byzantine(&test_id, &committee, byzantine);
+    tokio::task::yield_now().await;
+
+    // Broadcast the same notification twice, as a retransmitting IdP would after a timeout.
+    let notification = notification().await;
+    let decode_votes = |replies: Vec<Bytes>| -> Vec<PublishVote> {
+        replies
+            .iter()
+            .map(|reply| match codec::decode(reply).unwrap() {
+                WitnessToIdPMessage::PublishVote(Ok(vote)) => vote,
+                _ => panic!("Unexpected protocol message"),
+            })
+            .collect()
+    };
+    let handles = broadcast_notification(notification.clone(), &committee).await;
+    let first_votes = decode_votes(try_join_all(handles).await.unwrap());
+    let handles = broadcast_notification(notification, &committee).await;
+    let second_votes = decode_votes(try_join_all(handles).await.unwrap());
+
+    // The honest witnesses' votes are unchanged on the retransmit (the real `make_vote` locking
+    // is idempotent), but the equivocator's second vote conflicts with its first, and a
+    // `ConflictingVote` built from the two verifies as a valid equivocation proof.
+    for (name, _) in keys().iter().skip(1) {
+        let first = first_votes
+            .iter()
+            .find(|vote| vote.author == *name)
+            .unwrap();
+        let second = second_votes
+            .iter()
+            .find(|vote| vote.author == *name)
+            .unwrap();
+        assert_eq!(first, second);
+    }
+    let vote_1 = first_votes
+        .iter()
+        .find(|vote| vote.author == equivocator)
+        .cloned()
+        .unwrap();
+    let vote_2 = second_votes
+        .iter()
+        .find(|vote| vote.author == equivocator)
+        .cloned()
+        .unwrap();
+    assert_ne!(vote_1.root, vote_2.root);
+    assert!(ConflictingVote { vote_1, vote_2 }
+        .verify(&committee)
+        .is_ok());
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
+
+#[tokio::test]
+#[named]
+async fn honest_quorum_unaffected_by_a_witness_refusing_certificate_queries() {
+    let base_port = 7_800;
+    let committee = committee(base_port);
+    let test_id = function_name!();
+
+    // Replace one witness with a `TestWitness` that never replies to a `PublishCertificateQuery`,
+    // as if it had never received the certificate.
+    let mut byzantine = HashMap::new();
+    byzantine.insert(0, ByzantineBehavior::RefuseCertificateQueries);
+    spawn_test_witnesses_with_byzantine(&test_id, &committee, byzantine);
+    tokio::task::yield_now().await;
+
+    // Commit a single certificate.
+    let certificate = certificate().await;
+    let handles = broadcast_certificate(certificate, &committee).await;
+    let _ = try_join_all(handles).await.unwrap();
+
+    // The other 3 witnesses still hold a quorum (4 witnesses, quorum threshold 3), so a
+    // lagging peer's `PublishCertificateQuery` still gets answered by enough of the committee,
+    // despite one member refusing to serve this exact request. The refusing witness's own
+    // handle is dropped without awaiting it: it never replies at all, so awaiting it would hang.
+    let refusing_witness = keys()[0].0;
+    let query = messages::sync::PublishCertificateQuery { sequence_number: 1 };
+    let names: Vec<_> = committee
+        .witnesses_addresses()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    let handles = broadcast_certificate_query(query, &committee).await;
+    let replies = try_join_all(
+        names
+            .into_iter()
+            .zip(handles)
+            .filter(|(name, _)| *name != refusing_witness)
+            .map(|(_, handle)| handle),
+    )
+    .await
+    .unwrap();
+    assert_eq!(replies.len(), committee.witnesses.len() - 1);
+    for reply in replies {
+        assert!(matches!(
+            codec::decode(&reply).unwrap(),
+            WitnessToIdPMessage::PublishCertificateResponse(_)
+        ));
+    }
+
+    // Delete the storage.
+    delete_storage(&test_id);
+}
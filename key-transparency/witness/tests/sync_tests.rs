@@ -2,7 +2,8 @@ use bytes::Bytes;
 use function_name::named;
 use futures::future::try_join_all;
 use messages::{
-    publish::PublishCertificate,
+    codec,
+    publish::{CertificateKind, PublishCertificate},
     sync::{PublishCertificateQuery, State},
     IdPToWitnessMessage, WitnessToIdPMessage,
 };
@@ -29,7 +30,7 @@ async fn state_query() {
         .map(|(_, address)| address)
         .collect();
     let message = IdPToWitnessMessage::StateQuery;
-    let serialized = bincode::serialize(&message).unwrap();
+    let serialized = codec::encode(&message).unwrap();
     let bytes = Bytes::from(serialized);
     let mut sender = ReliableSender::new();
     let handles = sender.broadcast(addresses, bytes).await;
@@ -39,7 +40,7 @@ async fn state_query() {
 
     // Ensure the witnesses' replies are as expected.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
+        match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::State(Ok(state)) => assert_eq!(state, expected),
             _ => panic!("Unexpected protocol message"),
         }
@@ -65,11 +66,15 @@ async fn sync_request() {
     let certificate = PublishCertificate {
         root: notification.root,
         sequence_number: notification.sequence_number,
-        votes: votes()
-            .await
-            .into_iter()
-            .map(|x| (x.author, x.signature))
-            .collect(),
+        round: notification.round,
+        kind: CertificateKind::Votes(
+            votes()
+                .await
+                .into_iter()
+                .map(|x| (x.author, x.signature))
+                .collect(),
+        ),
+        timestamps: Vec::new(),
     };
     let handles = broadcast_certificate(certificate.clone(), &committee).await;
     let _ = try_join_all(handles).await.unwrap();
@@ -85,16 +90,16 @@ async fn sync_request() {
         .map(|(_, address)| address)
         .collect();
     let message = IdPToWitnessMessage::PublishCertificateQuery(request);
-    let serialized = bincode::serialize(&message).unwrap();
+    let serialized = codec::encode(&message).unwrap();
     let bytes = Bytes::from(serialized);
     let mut sender = ReliableSender::new();
     let handles = sender.broadcast(addresses, bytes).await;
 
     // Ensure the witnesses' replies are as expected.
     for reply in try_join_all(handles).await.unwrap() {
-        match bincode::deserialize(&reply).unwrap() {
+        match codec::decode(&reply).unwrap() {
             WitnessToIdPMessage::PublishCertificateResponse(received) => {
-                match bincode::deserialize(&received).unwrap() {
+                match codec::decode(&received).unwrap() {
                     IdPToWitnessMessage::PublishCertificate(cert) => {
                         assert_eq!(cert, certificate);
                     }
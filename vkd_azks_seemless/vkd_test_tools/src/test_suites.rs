@@ -7,12 +7,18 @@ extern crate thread_id;
 // License, Version 2.0 found in the LICENSE-APACHE file in the root directory
 // of this source tree.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
 use vkd::ecvrf::VRFKeyStorage;
-use vkd::storage::types::{VkdLabel, VkdValue};
+use vkd::errors::StorageError;
+use vkd::storage::types::{DbRecord, KeyData, ValueStateRetrievalFlag, VkdLabel, VkdValue};
+use vkd::storage::{Storable, Storage};
 use vkd::Directory;
 use rand::distributions::Alphanumeric;
+use rand::rngs::StdRng;
 use rand::seq::IteratorRandom;
-use rand::{thread_rng, Rng};
+use rand::{thread_rng, Rng, SeedableRng};
 
 use winter_crypto::hashers::Blake3_256;
 use winter_math::fields::f128::BaseElement;
@@ -129,3 +135,253 @@ pub async fn directory_test_suite<S: vkd::storage::Storage + Sync + Send, V: VRF
         }
     }
 }
+
+/// A [`Storage`] decorator that injects faults into an otherwise-real backend, so that
+/// [`directory_test_suite`]'s error-handling branches (storage reads/writes failing mid-publish,
+/// mid-lookup, etc.) actually get exercised instead of only ever seeing a healthy backend. Faults
+/// are driven by a seeded [`StdRng`], so a failure discovered in CI can be replayed by re-running
+/// [`directory_test_suite_with_faults`] with the same seed.
+///
+/// Two fault modes are supported and can be combined:
+/// - a per-operation `failure_probability` (0.0 = never, 1.0 = always);
+/// - a `fail_after_n_ops` cutoff that forces every operation from the Nth one onward to fail
+///   regardless of `failure_probability`, for deterministically reproducing "storage died partway
+///   through a long publish" scenarios.
+///
+/// `DbRecord` has no raw byte representation exposed outside of `vkd::storage`, so there is
+/// nothing to flip bits in after a successful read; "corruption" is therefore simulated the same
+/// way a real corrupted read would surface to a caller -- as a [`StorageError::Connection`] on
+/// the read that would otherwise have returned the now-unreadable bytes.
+pub struct FaultyStorage<S> {
+    inner: S,
+    rng: Arc<Mutex<StdRng>>,
+    op_count: Arc<AtomicUsize>,
+    failure_probability: f64,
+    fail_after_n_ops: Option<usize>,
+}
+
+impl<S: Clone> Clone for FaultyStorage<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            rng: self.rng.clone(),
+            op_count: self.op_count.clone(),
+            failure_probability: self.failure_probability,
+            fail_after_n_ops: self.fail_after_n_ops,
+        }
+    }
+}
+
+impl<S> FaultyStorage<S> {
+    /// Wrap `inner`, injecting faults seeded from `seed` with the given per-operation
+    /// `failure_probability` (clamped to `0.0..=1.0`).
+    pub fn new(inner: S, seed: u64, failure_probability: f64) -> Self {
+        Self {
+            inner,
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(seed))),
+            op_count: Arc::new(AtomicUsize::new(0)),
+            failure_probability: failure_probability.clamp(0.0, 1.0),
+            fail_after_n_ops: None,
+        }
+    }
+
+    /// Force every operation from the `n`th one onward (0-indexed) to fail, in addition to
+    /// whatever `failure_probability` already injects.
+    pub fn fail_after_n_ops(mut self, n: usize) -> Self {
+        self.fail_after_n_ops = Some(n);
+        self
+    }
+
+    /// Decide whether the operation about to run should fail, consuming one op-count tick and
+    /// (when the count-based cutoff isn't already the deciding factor) one RNG draw, so the
+    /// sequence of decisions is fully determined by the seed.
+    fn should_fail(&self) -> bool {
+        let count = self.op_count.fetch_add(1, Ordering::SeqCst);
+        if matches!(self.fail_after_n_ops, Some(n) if count >= n) {
+            return true;
+        }
+        self.rng.lock().unwrap().gen_bool(self.failure_probability)
+    }
+
+    fn injected_error(&self) -> StorageError {
+        StorageError::Connection("FaultyStorage: injected fault".to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: Storage + Sync + Send> Storage for FaultyStorage<S> {
+    async fn set(&self, record: DbRecord) -> Result<(), StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.set(record).await
+    }
+
+    async fn batch_set(&self, records: Vec<DbRecord>) -> Result<(), StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.batch_set(records).await
+    }
+
+    async fn get<St: Storable + Sync>(&self, id: &St::Key) -> Result<DbRecord, StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.get::<St>(id).await
+    }
+
+    async fn batch_get<St: Storable + Sync>(
+        &self,
+        ids: &[St::Key],
+    ) -> Result<Vec<DbRecord>, StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.batch_get::<St>(ids).await
+    }
+
+    async fn get_user_data(&self, username: &VkdLabel) -> Result<KeyData, StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.get_user_data(username).await
+    }
+
+    async fn get_user_state_versions(
+        &self,
+        keys: &[VkdLabel],
+        flag: ValueStateRetrievalFlag,
+    ) -> Result<std::collections::HashMap<VkdLabel, u64>, StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.get_user_state_versions(keys, flag).await
+    }
+
+    async fn begin_transaction(&self) -> bool {
+        self.inner.begin_transaction().await
+    }
+
+    async fn commit_transaction(&self) -> Result<(), StorageError> {
+        if self.should_fail() {
+            return Err(self.injected_error());
+        }
+        self.inner.commit_transaction().await
+    }
+
+    async fn rollback_transaction(&self) -> Result<(), StorageError> {
+        self.inner.rollback_transaction().await
+    }
+}
+
+/// Seeded counterpart to [`directory_test_suite`]: every source of randomness (user-name
+/// generation, and which users are sampled for lookup/history proofs) is driven from `seed`
+/// instead of [`thread_rng`], and `storage` is expected to inject faults (e.g. a
+/// [`FaultyStorage`]). Unlike [`directory_test_suite`], a storage error at any point is treated
+/// as the expected outcome and the suite simply stops -- the only thing asserted is that the
+/// directory surfaces these failures as `Err`s instead of panicking, and that any proof which
+/// *does* come back `Ok` still verifies correctly.
+pub async fn directory_test_suite_with_faults<
+    S: vkd::storage::Storage + Sync + Send,
+    V: VRFKeyStorage,
+>(
+    storage: &S,
+    num_users: usize,
+    vrf: &V,
+    seed: u64,
+) {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut users: Vec<String> = vec![];
+    for _ in 0..num_users {
+        users.push(
+            (&mut rng)
+                .sample_iter(&Alphanumeric)
+                .take(30)
+                .map(char::from)
+                .collect(),
+        );
+    }
+
+    let dir = match Directory::<_, _>::new::<Blake3>(storage, vrf, false).await {
+        Err(_) => return,
+        Ok(dir) => dir,
+    };
+
+    for i in 1..=3 {
+        let mut data = Vec::new();
+        for value in users.iter() {
+            data.push((
+                VkdLabel::from_utf8_str(value),
+                VkdValue(format!("{}", i).as_bytes().to_vec()),
+            ));
+        }
+        if dir.publish::<Blake3>(data).await.is_err() {
+            return;
+        }
+    }
+
+    let azks = match dir.retrieve_current_azks().await {
+        Err(_) => return,
+        Ok(azks) => azks,
+    };
+    let root_hash = match dir.get_root_hash::<Blake3>(&azks).await {
+        Err(_) => return,
+        Ok(root_hash) => root_hash,
+    };
+
+    for user in users.iter().choose_multiple(&mut rng, 10) {
+        let key = VkdLabel::from_utf8_str(user);
+        let proof = match dir.lookup::<Blake3>(key.clone()).await {
+            Err(_) => return,
+            Ok(proof) => proof,
+        };
+        let vrf_pk = match dir.get_public_key().await {
+            Err(_) => return,
+            Ok(pk) => pk,
+        };
+        if vkd::client::lookup_verify::<Blake3>(&vrf_pk, root_hash, key, proof).is_err() {
+            panic!("Lookup proof failed to verify despite storage reporting success");
+        }
+    }
+
+    for user in users.iter().choose_multiple(&mut rng, 2) {
+        let key = VkdLabel::from_utf8_str(user);
+        let proof = match dir.key_history::<Blake3>(&key).await {
+            Err(_) => return,
+            Ok(proof) => proof,
+        };
+        let (root_hashes, previous_root_hashes) =
+            match vkd::directory::get_key_history_hashes::<_, Blake3, V>(&dir, &proof).await {
+                Err(_) => return,
+                Ok(hashes) => hashes,
+            };
+        let vrf_pk = match dir.get_public_key().await {
+            Err(_) => return,
+            Ok(pk) => pk,
+        };
+        if vkd::client::key_history_verify::<Blake3>(
+            &vrf_pk,
+            root_hashes,
+            previous_root_hashes,
+            key,
+            proof,
+            false,
+        )
+        .is_err()
+        {
+            panic!("History proof failed to verify despite storage reporting success");
+        }
+    }
+
+    if let Ok(proof) = dir.audit::<Blake3>(1u64, 2u64).await {
+        let start_root_hash = dir.get_root_hash_at_epoch::<Blake3>(&azks, 1u64).await;
+        let end_root_hash = dir.get_root_hash_at_epoch::<Blake3>(&azks, 2u64).await;
+        if let (Ok(start), Ok(end)) = (start_root_hash, end_root_hash) {
+            if vkd::auditor::audit_verify(start, end, proof).await.is_err() {
+                panic!("Audit proof failed to verify despite storage reporting success");
+            }
+        }
+    }
+}
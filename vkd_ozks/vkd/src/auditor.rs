@@ -9,16 +9,22 @@
 
 use std::marker::{Send, Sync};
 
+use futures::stream::{Stream, StreamExt};
 use winter_crypto::Hasher;
 
 use crate::{
+    directory::AuditStep,
     errors::{VkdError, AuditorError, OzksError},
     proof_structs::{AppendOnlyProof, SingleAppendOnlyProof},
     storage::memory::AsyncInMemoryDatabase,
     Ozks,
 };
 
-/// Verifies an audit proof, given start and end hashes for a merkle patricia tree.
+/// Verifies an audit proof, given start and end hashes for a merkle patricia
+/// tree. Pairs with [`crate::directory::Directory::audit`], which assembles
+/// `proof` from the tree nodes actually inserted and retired between the
+/// two epochs; `hashes` should be the committed [`EpochHash`](crate::EpochHash)
+/// root hashes for every epoch in `[start_epoch, end_epoch]`, in order.
 pub async fn audit_verify<H: Hasher + Send + Sync>(
     hashes: Vec<H::Digest>,
     proof: AppendOnlyProof<H>,
@@ -53,6 +59,33 @@ pub async fn audit_verify<H: Hasher + Send + Sync>(
     Ok(())
 }
 
+/// Verifies a stream of single-epoch [`AuditStep`]s -- e.g. from
+/// [`crate::directory::Directory::audit_stream`] -- one at a time, starting
+/// from `start_hash`, folding each step's root hash into the starting hash
+/// [`verify_consecutive_append_only`] checks the next step against. Returns
+/// the final verified root hash, or the first error encountered -- either a
+/// broken append-only chain or a failure pulled from the underlying stream
+/// -- without ever needing the whole range's proofs in memory at once, so
+/// an auditor can checkpoint its progress after any prefix of the stream
+/// instead of only at the end like [`audit_verify`].
+pub async fn audit_verify_stream<H, S>(
+    mut steps: S,
+    start_hash: H::Digest,
+) -> Result<H::Digest, VkdError>
+where
+    H: Hasher + Send + Sync,
+    S: Stream<Item = Result<AuditStep<H>, VkdError>> + Unpin,
+{
+    let mut prev_hash = start_hash;
+    while let Some(step) = steps.next().await {
+        let step = step?;
+        verify_consecutive_append_only::<H>(&step.proof, prev_hash, step.root_hash, step.epoch)
+            .await?;
+        prev_hash = step.root_hash;
+    }
+    Ok(prev_hash)
+}
+
 /// Helper for audit, verifies an append-only proof
 pub async fn verify_consecutive_append_only<H: Hasher + Send + Sync>(
     proof: &SingleAppendOnlyProof<H>,
@@ -0,0 +1,126 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! Persisted epoch-transition checkpoints, letting a fresh
+//! [`ReadOnlyDirectory`](crate::directory::ReadOnlyDirectory) or an external
+//! auditor "warp-sync" its verified view of the directory to a recent,
+//! finalized epoch instead of replaying the full history from epoch 0 --
+//! analogous to snapshot sync in proof-of-authority chains. See
+//! [`ReadOnlyDirectory::bootstrap_from_checkpoint`](crate::directory::ReadOnlyDirectory::bootstrap_from_checkpoint).
+
+use winter_crypto::Hasher;
+
+use crate::errors::{StorageError, VkdError};
+use crate::proof_structs::AppendOnlyProof;
+use crate::storage::types::StorageType;
+use crate::storage::Storable;
+
+/// The storage key identifying the [`EpochTransition`] checkpoint persisted
+/// for a single epoch.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EpochTransitionKey(pub u64);
+
+/// The checkpoint [`Directory::publish`](crate::directory::Directory::publish)
+/// persists after every successful publish: the directory's root hash moving
+/// from `prev_root_hash` at `epoch - 1` to `root_hash` at `epoch`, together
+/// with the append-only proof produced by the `Ozks` for that single-epoch
+/// step. A contiguous run of these, starting from a trusted epoch, lets a
+/// syncing client verify the directory evolved append-only up to a recent
+/// epoch without recomputing anything about the epochs in between.
+#[derive(Clone, Debug)]
+pub struct EpochTransition {
+    /// The epoch this checkpoint transitions the directory *to*.
+    pub epoch: u64,
+    /// The root hash at `epoch`, encoded so it can be read back as the
+    /// typed `H::Digest` it was derived from.
+    pub root_hash: Vec<u8>,
+    /// The root hash at `epoch - 1`, immediately before this transition,
+    /// encoded the same way as `root_hash`.
+    pub prev_root_hash: Vec<u8>,
+    /// The encoded [`AppendOnlyProof`] for the single-epoch transition
+    /// `epoch - 1 -> epoch`.
+    pub append_only_proof: Vec<u8>,
+}
+
+impl EpochTransition {
+    /// The storage key this checkpoint is persisted under.
+    pub fn key(&self) -> EpochTransitionKey {
+        EpochTransitionKey(self.epoch)
+    }
+
+    /// Builds a checkpoint from a freshly-computed publish, encoding the
+    /// digests and proof into their storage-safe representation.
+    pub(crate) fn new<H: Hasher>(
+        epoch: u64,
+        root_hash: H::Digest,
+        prev_root_hash: H::Digest,
+        append_only_proof: &AppendOnlyProof<H>,
+    ) -> Result<Self, VkdError> {
+        Ok(Self {
+            epoch,
+            root_hash: encode(&root_hash)?,
+            prev_root_hash: encode(&prev_root_hash)?,
+            append_only_proof: encode(append_only_proof)?,
+        })
+    }
+
+    /// Recovers the typed root hash this checkpoint transitions *to*.
+    pub(crate) fn decode_root_hash<H: Hasher>(&self) -> Result<H::Digest, VkdError> {
+        decode(&self.root_hash)
+    }
+
+    /// Recovers the typed root hash this checkpoint transitions *from*.
+    pub(crate) fn decode_prev_root_hash<H: Hasher>(&self) -> Result<H::Digest, VkdError> {
+        decode(&self.prev_root_hash)
+    }
+
+    /// Recovers the typed append-only proof for this transition.
+    pub(crate) fn decode_proof<H: Hasher>(&self) -> Result<AppendOnlyProof<H>, VkdError> {
+        decode(&self.append_only_proof)
+    }
+}
+
+impl Storable for EpochTransition {
+    type Key = EpochTransitionKey;
+
+    fn data_type() -> StorageType {
+        StorageType::EpochTransition
+    }
+
+    fn get_id(&self) -> EpochTransitionKey {
+        self.key()
+    }
+
+    fn get_full_binary_id(&self) -> Vec<u8> {
+        Self::get_full_binary_key_id(&self.get_id())
+    }
+
+    fn get_full_binary_key_id(key: &EpochTransitionKey) -> Vec<u8> {
+        let mut bin = vec![StorageType::EpochTransition as u8];
+        bin.extend_from_slice(&key.0.to_be_bytes());
+        bin
+    }
+
+    fn key_from_full_binary(bin: &[u8]) -> Result<EpochTransitionKey, String> {
+        if bin.len() < 9 {
+            return Err("Not enough bytes to parse an EpochTransitionKey".to_string());
+        }
+        let mut epoch_bytes = [0u8; 8];
+        epoch_bytes.copy_from_slice(&bin[1..9]);
+        Ok(EpochTransitionKey(u64::from_be_bytes(epoch_bytes)))
+    }
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, VkdError> {
+    bincode::serialize(value)
+        .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, VkdError> {
+    bincode::deserialize(bytes)
+        .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))
+}
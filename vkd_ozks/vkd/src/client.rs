@@ -0,0 +1,446 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! Code for a client of a auditable key directory
+
+use crate::{
+    directory::get_marker_version,
+    ecvrf::VRFPublicKey,
+    errors::{HistoryVerificationError, VkdError},
+    proof_structs::{HistoryProof, LookupProof, UpdateProof},
+    storage::types::{VkdLabel, VkdValue},
+};
+
+use winter_crypto::Hasher;
+
+/// Specifies how much of a key's version history a client is asking a server
+/// to prove, and therefore how much of it [key_history_verify] has to check.
+///
+/// Every variant except [`HistoryParams::MostRecentInsecure`] gets the same
+/// soundness guarantee: the returned window is proven to be a contiguous,
+/// unbroken suffix of the key's real history and its highest version is
+/// proven to be the version that is current as of `current_epoch`. The
+/// variants only differ in how far back that suffix goes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryParams {
+    /// Verify the entire version history of the key, back to version 1.
+    Complete,
+    /// Verify only the `n` most recent versions of the key.
+    MostRecent(usize),
+    /// Verify only the versions that were updated at or after `epoch`.
+    SinceEpoch(u64),
+    /// Like [`HistoryParams::MostRecent`], but the server skips generating
+    /// (and the proof carries no) anchor proving a real version immediately
+    /// preceded the window -- so a caller using this mode has no
+    /// cryptographic guarantee the window wasn't narrowed further than `n`.
+    /// Debugging/benchmarking only: pair with
+    /// [`HistoryVerificationParams::MostRecentOnly`], since
+    /// [`HistoryVerificationParams::Strict`] will reject the missing anchor.
+    MostRecentInsecure(usize),
+}
+
+impl Default for HistoryParams {
+    fn default() -> Self {
+        Self::Complete
+    }
+}
+
+/// Controls how strictly [key_history_verify] checks that the window of versions
+/// returned forms an unbroken chain, independently of how much of that history
+/// `params` asked for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryVerificationParams {
+    /// Require every returned version (other than version 1) to carry proof
+    /// that its immediate predecessor was retired, and require the returned
+    /// versions to be themselves contiguous. This is the only mode that can
+    /// catch a server hiding an intermediate version of the key's history,
+    /// and is the appropriate choice whenever the caller cares about the
+    /// window being complete.
+    Strict,
+    /// Only hold the single most recent version returned to the freshness
+    /// checks: it must be live at `current_epoch` and, unless it is version 1,
+    /// carry proof that its predecessor was retired. Do not require that proof
+    /// of any older returned version, nor that returned versions are
+    /// contiguous; every returned version's existence proof and commitment
+    /// are still checked. Appropriate for a caller that only wants the current
+    /// value and a freshness guarantee, not a provably unbroken history.
+    MostRecentOnly,
+}
+
+impl Default for HistoryVerificationParams {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+/// The verified outcome for a single version returned by a key history
+/// proof, produced by [key_history_verify] in the same order as the
+/// `update_proofs` it was given: newest version first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyResult {
+    /// The version number this result is for.
+    pub version: u64,
+    /// The epoch at which this version became fresh.
+    pub epoch: u64,
+    /// The value committed for this version, or `None` if the proof
+    /// tombstoned it (only possible when the caller passed
+    /// `allow_tombstones = true`).
+    pub value: Option<VkdValue>,
+}
+
+/// Verifies a lookup with respect to the root_hash
+pub fn lookup_verify<H: Hasher>(
+    vrf_public_key: &VRFPublicKey,
+    root_hash: H::Digest,
+    uname: VkdLabel,
+    proof: LookupProof<H>,
+) -> Result<(), VkdError> {
+    let LookupProof {
+        epoch,
+        plaintext_value,
+        version,
+        existence_vrf_proof,
+        existence_proof,
+        marker_vrf_proof,
+        marker_proof,
+        freshness_vrf_proof,
+        freshness_proof,
+        commitment_proof,
+    } = proof;
+
+    let marker_version = 1 << get_marker_version(version);
+
+    let existence_label =
+        vrf_public_key.verify_label::<H>(&uname, false, version, &existence_vrf_proof)?;
+    existence_proof.verify::<H>(existence_label, root_hash, epoch)?;
+
+    let marker_label =
+        vrf_public_key.verify_label::<H>(&uname, false, marker_version, &marker_vrf_proof)?;
+    marker_proof.verify::<H>(marker_label, root_hash, epoch)?;
+
+    let freshness_label =
+        vrf_public_key.verify_label::<H>(&uname, true, version, &freshness_vrf_proof)?;
+    freshness_proof.verify::<H>(freshness_label, root_hash)?;
+
+    crate::utils::verify_commitment::<H>(&existence_label, &plaintext_value, &commitment_proof)?;
+
+    Ok(())
+}
+
+/// Verifies a key history proof, given the corresponding sequence of hashes.
+/// Returns a [`VerifyResult`] for every version covered by `proof`, newest
+/// first, each carrying the version's epoch and its committed value (or
+/// `None` if that version was tombstoned).
+///
+/// `params` controls how much of the key's history `proof` is expected to
+/// cover: the full chain, only the `n` most recent versions, or only the
+/// versions updated since a given epoch. `verification` controls how strictly
+/// that window is checked for gaps: in [`HistoryVerificationParams::Strict`]
+/// mode (the default), the returned window is checked to be a contiguous
+/// suffix of the key's true history, while [`HistoryVerificationParams::MostRecentOnly`]
+/// only checks the single freshest entry. Regardless of `verification`, the
+/// most recent entry is checked to be the version that is actually current at
+/// `current_epoch` -- a server cannot satisfy a bounded request by silently
+/// hiding a newer value behind an older window.
+pub fn key_history_verify<H: Hasher>(
+    vrf_public_key: &VRFPublicKey,
+    root_hash: H::Digest,
+    current_epoch: u64,
+    uname: VkdLabel,
+    proof: HistoryProof<H>,
+    params: HistoryParams,
+    verification: HistoryVerificationParams,
+    allow_tombstones: bool,
+) -> Result<Vec<VerifyResult>, VkdError> {
+    if proof.update_proofs.is_empty() {
+        return Err(VkdError::HistoryErr(
+            HistoryVerificationError::NoUpdateProofsSupplied,
+        ));
+    }
+
+    // The update proofs must be given in strictly-decreasing version order, and
+    // since `params` may legitimately truncate the chain short of version 1, we
+    // can only check that the versions returned are *themselves* contiguous --
+    // not that they start at 1 -- except in `HistoryParams::Complete` mode.
+    // `MostRecentOnly` verification skips this: it only vouches for the
+    // freshest entry, so gaps among the rest are out of scope.
+    if verification == HistoryVerificationParams::Strict {
+        for window in proof.update_proofs.windows(2) {
+            if window[0].version != window[1].version + 1 {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::NonContiguousVersions(
+                        window[1].version,
+                        window[0].version,
+                    ),
+                ));
+            }
+            // `update_proofs` is in strictly-decreasing version order, so the
+            // newer entry (`window[0]`) must also have been published at a
+            // strictly later epoch than the one it superseded. Without this,
+            // a server could reorder or duplicate epochs across an otherwise
+            // version-contiguous window -- something the version check alone
+            // can't catch.
+            if window[0].epoch <= window[1].epoch {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::NonIncreasingEpochs(window[1].epoch, window[0].epoch),
+                ));
+            }
+        }
+    }
+
+    let returned_versions = proof.update_proofs.len();
+    match params {
+        HistoryParams::Complete => {
+            let oldest_version = proof.update_proofs.last().unwrap().version;
+            if oldest_version != 1 {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::IncompleteVersionHistory(oldest_version),
+                ));
+            }
+        }
+        HistoryParams::MostRecent(n) | HistoryParams::MostRecentInsecure(n) => {
+            if returned_versions > n {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::TooManyUpdateProofs(returned_versions, n),
+                ));
+            }
+        }
+        HistoryParams::SinceEpoch(epoch) => {
+            if proof.update_proofs.iter().any(|up| up.epoch < epoch) {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::UpdateProofBeforeRequestedEpoch(epoch),
+                ));
+            }
+        }
+    }
+
+    let results = convert_history_proof::<H>(
+        root_hash,
+        vrf_public_key,
+        &proof.update_proofs,
+        &uname,
+        verification,
+        allow_tombstones,
+    )?;
+
+    // Regardless of how much history was requested, the highest version we were
+    // handed back must be the version that is actually live at `current_epoch`:
+    // otherwise a malicious server could satisfy e.g. `MostRecent(3)` by
+    // returning an older window of 3 versions while withholding the fact that a
+    // newer value has since been published.
+    let last_version = proof.update_proofs[0].version;
+    let next_marker = get_marker_version(last_version) + 1;
+    let final_marker = get_marker_version(current_epoch);
+
+    if proof.next_few_vrf_proofs.len() != proof.non_existence_of_next_few.len() {
+        return Err(VkdError::HistoryErr(
+            HistoryVerificationError::ProofLengthMismatch("next_few".to_string()),
+        ));
+    }
+    for (i, (vrf_proof, non_existence_proof)) in proof
+        .next_few_vrf_proofs
+        .iter()
+        .zip(proof.non_existence_of_next_few.iter())
+        .enumerate()
+    {
+        let version = last_version + 1 + i as u64;
+        let label = vrf_public_key.verify_label::<H>(&uname, false, version, vrf_proof)?;
+        non_existence_proof.verify::<H>(label, root_hash)?;
+    }
+
+    if proof.future_marker_vrf_proofs.len() != proof.non_existence_of_future_markers.len() {
+        return Err(VkdError::HistoryErr(
+            HistoryVerificationError::ProofLengthMismatch("future_markers".to_string()),
+        ));
+    }
+    for (i, (vrf_proof, non_existence_proof)) in proof
+        .future_marker_vrf_proofs
+        .iter()
+        .zip(proof.non_existence_of_future_markers.iter())
+        .enumerate()
+    {
+        let marker_power = next_marker + i as u64;
+        if marker_power > final_marker {
+            break;
+        }
+        let version = 1u64 << marker_power;
+        let label = vrf_public_key.verify_label::<H>(&uname, false, version, vrf_proof)?;
+        non_existence_proof.verify::<H>(label, root_hash)?;
+    }
+
+    // An explicit, unconditional check that the claimed most-recent version
+    // has no successor -- independent of the logarithmic `next_few`/
+    // `future_marker` scheme above, so a server can't rely on a gap in that
+    // scheme's own windowing to hide a rotation at exactly `last_version + 1`.
+    let next_version_label = vrf_public_key.verify_label::<H>(
+        &uname,
+        false,
+        last_version + 1,
+        &proof.next_version_vrf_proof,
+    )?;
+    proof
+        .non_existence_of_next_version
+        .verify::<H>(next_version_label, root_hash)?;
+
+    // For a limited-history request (`MostRecent`/`SinceEpoch`) whose window
+    // doesn't reach back to version 1, require positive evidence that a real
+    // version immediately preceded the window's oldest entry. Without this,
+    // a server could satisfy the window-size/epoch-floor checks above while
+    // silently narrowing the window further than `params` actually asked
+    // for, since neither check alone can tell the difference between "the
+    // window legitimately ends here" and "the server stopped early".
+    // `MostRecentInsecure` never carries this anchor by design, and
+    // `MostRecentOnly` verification doesn't ask for one either, so both are
+    // exempted here; `Strict` verification of a `MostRecentInsecure` proof
+    // still falls through to the missing-anchor error below.
+    let oldest_version = proof.update_proofs.last().unwrap().version;
+    let window_has_floor = verification == HistoryVerificationParams::Strict
+        && !matches!(params, HistoryParams::Complete);
+    if window_has_floor && oldest_version > 1 {
+        match (
+            &proof.window_lower_bound_vrf_proof,
+            &proof.window_lower_bound_existence_proof,
+            proof.window_lower_bound_epoch,
+        ) {
+            (Some(vrf_proof), Some(existence_proof), Some(epoch)) => {
+                let preceding_label = vrf_public_key.verify_label::<H>(
+                    &uname,
+                    false,
+                    oldest_version - 1,
+                    vrf_proof,
+                )?;
+                existence_proof.verify::<H>(preceding_label, root_hash, epoch)?;
+            }
+            _ => {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::MissingWindowLowerBoundProof(oldest_version),
+                ));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Converts a chain of `update_proofs` into the [`VerifyResult`]s the caller
+/// sees, while positively verifying that *every* claimed past version
+/// actually existed at its stated epoch -- not merely that some later
+/// version superseded it. A server that omits a version's existence
+/// evidence while still presenting a superficially valid staleness chain
+/// (i.e. only ever proving the *next* version stale, never the claimed
+/// version live) is rejected here rather than downstream.
+fn convert_history_proof<H: Hasher>(
+    root_hash: H::Digest,
+    vrf_public_key: &VRFPublicKey,
+    update_proofs: &[UpdateProof<H>],
+    uname: &VkdLabel,
+    verification: HistoryVerificationParams,
+    allow_tombstones: bool,
+) -> Result<Vec<VerifyResult>, VkdError> {
+    let mut results = Vec::with_capacity(update_proofs.len());
+    for (i, proof) in update_proofs.iter().enumerate() {
+        // In `MostRecentOnly` mode only the freshest entry (index 0) is held
+        // to the predecessor-staleness requirement; older entries, if any are
+        // even present, are not vouched for.
+        let require_previous_version_proof =
+            verification == HistoryVerificationParams::Strict || i == 0;
+        results.push(convert_single_update_proof::<H>(
+            root_hash,
+            vrf_public_key,
+            proof,
+            uname,
+            require_previous_version_proof,
+            allow_tombstones,
+        )?);
+    }
+    Ok(results)
+}
+
+/// Verifies a single update proof, returning its [`VerifyResult`].
+fn convert_single_update_proof<H: Hasher>(
+    root_hash: H::Digest,
+    vrf_public_key: &VRFPublicKey,
+    proof: &UpdateProof<H>,
+    uname: &VkdLabel,
+    require_previous_version_proof: bool,
+    allow_tombstones: bool,
+) -> Result<VerifyResult, VkdError> {
+    let epoch = proof.epoch;
+    let version = proof.version;
+
+    // Require positive evidence that this exact (key, version) existed at
+    // `epoch` -- a staleness chain alone only shows what replaced it, not
+    // that it was ever live.
+    let existence_label =
+        vrf_public_key.verify_label::<H>(uname, false, version, &proof.existence_vrf_proof)?;
+    proof
+        .existence_at_ep
+        .verify::<H>(existence_label, root_hash, epoch)?;
+
+    match (
+        &proof.previous_version_vrf_proof,
+        &proof.previous_version_stale_at_ep,
+    ) {
+        (Some(previous_vrf_proof), Some(previous_stale_proof)) => {
+            // A previous-version proof is only meaningful if a previous
+            // version can exist; version 1 has no predecessor.
+            if version <= 1 {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::UnexpectedPreviousVersionProof(version),
+                ));
+            }
+            let previous_label =
+                vrf_public_key.verify_label::<H>(uname, true, version - 1, previous_vrf_proof)?;
+            previous_stale_proof.verify::<H>(previous_label, root_hash, epoch)?;
+        }
+        (None, None) => {
+            // No previous-version evidence was supplied: this is only valid
+            // if the proof claims to be the genesis version, or the caller
+            // (per `require_previous_version_proof`) isn't asking us to
+            // vouch for this entry's predecessor. Otherwise the server has
+            // omitted the evidence that a lower version was staled, while
+            // implicitly claiming one exists.
+            if version > 1 && require_previous_version_proof {
+                return Err(VkdError::HistoryErr(
+                    HistoryVerificationError::MissingPreviousVersionProof(version),
+                ));
+            }
+        }
+        _ => {
+            return Err(VkdError::HistoryErr(
+                HistoryVerificationError::MissingPreviousVersionProof(version),
+            ))
+        }
+    }
+
+    // A tombstoned value is indicated by an empty plaintext, in which case we
+    // can't check the commitment opens to the real value -- only that the
+    // caller is willing to accept tombstones at all.
+    let is_tombstone = proof.plaintext_value.0 == crate::TOMBSTONE;
+    if is_tombstone && !allow_tombstones {
+        return Err(VkdError::HistoryErr(
+            HistoryVerificationError::EncounteredTombstone(epoch),
+        ));
+    }
+    if !is_tombstone {
+        crate::utils::verify_commitment::<H>(
+            &existence_label,
+            &proof.plaintext_value,
+            &proof.commitment_proof,
+        )?;
+    }
+
+    Ok(VerifyResult {
+        version,
+        epoch,
+        value: if is_tombstone {
+            None
+        } else {
+            Some(proof.plaintext_value.clone())
+        },
+    })
+}
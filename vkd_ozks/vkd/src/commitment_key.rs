@@ -0,0 +1,117 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! Derivation of the commitment key [`Directory`](crate::directory::Directory)
+//! uses to bind published label/value commitments, and of the per-entry
+//! nonces consumed when opening them.
+//!
+//! Prior to this module (Issue #184), the commitment key was simply
+//! `H::hash(raw_vrf_private_key)` -- dangerously coupling the commitment
+//! key to the VRF signing key, so that compromising (or merely observing
+//! enough outputs of) one weakens the other. [`derive_commitment_key`]
+//! instead derives the key with the extract-then-expand structure of HKDF
+//! (RFC 5869), built from plain hashes of concatenations rather than HMAC,
+//! over an independent server secret, retrieved through [`CommitmentKeyStorage`]
+//! rather than [`VRFKeyStorage`](crate::ecvrf::VRFKeyStorage).
+
+use crate::errors::VkdError;
+use async_trait::async_trait;
+use winter_crypto::Hasher;
+
+/// `HKDF-Extract` salt used to derive the commitment key's pseudorandom key
+/// from the server secret. Fixed, since `server_secret` already supplies
+/// the entropy; only used to domain-separate this derivation from any
+/// other HKDF use of the same secret.
+const COMMITMENT_KEY_SALT: &[u8] = b"vkd-commitment-key-hkdf-salt-v1";
+
+/// `HKDF-Expand` info string identifying the commitment key output. The
+/// per-entry nonces of [`derive_commitment_nonce`] are expanded from the
+/// commitment key itself, not from this PRK.
+const COMMITMENT_KEY_INFO: &[u8] = b"vkd-commitment-key-v1";
+
+/// Storage for the independent server secret backing the commitment key,
+/// analogous to [`VRFKeyStorage`](crate::ecvrf::VRFKeyStorage) but
+/// deliberately a distinct secret: an implementer is expected to keep it
+/// beside the VRF key in the same key store, not derive one from the
+/// other.
+#[async_trait]
+pub trait CommitmentKeyStorage: Clone + Send + Sync {
+    /// Retrieves the raw server secret the commitment key is derived from.
+    async fn retrieve_server_secret(&self) -> Result<Vec<u8>, VkdError>;
+
+    /// When `true`, [`Directory`](crate::directory::Directory) derives the
+    /// commitment key the legacy (Issue #184) way -- `H::hash` of the raw
+    /// VRF private key -- instead of via HKDF over the server secret.
+    ///
+    /// This exists solely as a migration path: a directory that published
+    /// commitments before this module existed must keep deriving the same
+    /// key, or every commitment proof it already published stops
+    /// verifying. New directories should leave this `false`.
+    fn use_legacy_commitment_key(&self) -> bool {
+        false
+    }
+}
+
+/// Derives the commitment key from `server_secret` with an HKDF-style
+/// extract-then-expand over `H`: `prk = H(COMMITMENT_KEY_SALT || server_secret)`,
+/// then `okm = H(prk || COMMITMENT_KEY_INFO || 0x01)`. Neither step uses
+/// HMAC; see [`extract`] and [`expand`].
+pub fn derive_commitment_key<H: Hasher>(server_secret: &[u8]) -> H::Digest {
+    let prk = extract::<H>(COMMITMENT_KEY_SALT, server_secret);
+    expand::<H>(&prk, COMMITMENT_KEY_INFO)
+}
+
+/// Derives the legacy (Issue #184) commitment key: a plain hash of the raw
+/// VRF private key. Only present so a directory that published commitments
+/// before the HKDF migration can still reproduce the key its existing
+/// proofs were committed under; see [`CommitmentKeyStorage::use_legacy_commitment_key`].
+pub fn derive_legacy_commitment_key<H: Hasher>(raw_vrf_key: &[u8]) -> H::Digest {
+    H::hash(raw_vrf_key)
+}
+
+/// Derives the per-entry nonce [`get_commitment_proof`](crate::utils::get_commitment_proof)
+/// consumes for a single `(label, version)`, by expanding the commitment
+/// key with an info string of `label_bytes || version`. Domain-separating
+/// by label and version means the same nonce is never reused across
+/// different entries, or across the same label's successive versions.
+pub fn derive_commitment_nonce<H: Hasher>(
+    commitment_key: &H::Digest,
+    label_bytes: &[u8],
+    version: u64,
+) -> H::Digest {
+    let mut info = Vec::with_capacity(label_bytes.len() + 8);
+    info.extend_from_slice(label_bytes);
+    info.extend_from_slice(&version.to_be_bytes());
+    expand::<H>(commitment_key.as_bytes().as_ref(), &info)
+}
+
+/// The extract step of HKDF, computed as `H(salt || ikm)` rather than
+/// `HMAC(salt, ikm)`. `H` is a general-purpose hasher here
+/// rather than a block cipher-based MAC, so the key is folded in by
+/// concatenation (`H(salt || ikm)`) instead of HMAC's usual padding
+/// construction -- sufficient for key separation since `H` is assumed
+/// collision-resistant.
+fn extract<H: Hasher>(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    H::hash(&[salt, ikm].concat()).as_bytes().as_ref().to_vec()
+}
+
+/// The expand step of HKDF, restricted to a single output block and computed
+/// as `H(prk || info || 0x01)` rather than `HMAC(prk, info || 0x01)`. A single block is always sufficient here since every caller's
+/// requested output length is exactly one hash digest, as permitted by
+/// RFC 5869.
+fn expand<H: Hasher>(prk: &[u8], info: &[u8]) -> H::Digest {
+    H::hash(&[prk, info, &[1u8]].concat())
+}
+
+#[async_trait]
+impl CommitmentKeyStorage for crate::ecvrf::HardCodedVkdVRF {
+    async fn retrieve_server_secret(&self) -> Result<Vec<u8>, VkdError> {
+        // Matches the hard-coded nature of `HardCodedVkdVRF`'s own VRF key:
+        // fine for tests and examples, never for a real deployment.
+        Ok(b"hard-coded-commitment-server-secret".to_vec())
+    }
+}
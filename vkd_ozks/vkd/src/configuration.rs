@@ -0,0 +1,170 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! A pluggable [`Configuration`] abstracting the domain-separation and
+//! node-label conventions that [`EMPTY_VALUE`](crate::EMPTY_VALUE),
+//! [`EMPTY_LABEL`](crate::EMPTY_LABEL), [`ROOT_LABEL`](crate::ROOT_LABEL) and
+//! the rest of the tree's hashing currently hard-code. Two deployments that
+//! disagree on these conventions produce directories whose root hashes are
+//! incompatible in ways a client can't detect from the wire format alone;
+//! making the scheme an explicit, swappable parameter lets a verifier and a
+//! server agree on it the same way they already agree on a [`Hasher`].
+//!
+//! This is the trait itself and two reference implementations.
+//! [`Directory::publish`](crate::directory::Directory::publish) and its
+//! siblings already take a `C: Configuration<H>` generic alongside `H`,
+//! deliberately per-call rather than as a third type parameter on
+//! `Directory<S, V>` itself -- so that existing callers who only ever use one
+//! scheme for the process's lifetime aren't forced to name it at every
+//! `Directory::new` call site, while a server juggling multiple schemes can
+//! still pick a different `C` per publish. Commitment (and therefore the
+//! choice of `C`) only happens at publish time: `lookup`/`key_history` just
+//! replay commitments a prior `publish` already baked in, so neither they nor
+//! the client-side verifiers need a `C` of their own yet. Within `publish`
+//! only [`Configuration::empty_value`] is used; the tree's leaf and internal
+//! hashing and the VRF label inputs do not go through `C`.
+
+use winter_crypto::Hasher;
+
+/// Abstracts a deployment's leaf-hashing, internal-node-hashing, empty-value
+/// and VRF-label-input conventions, so that two directories instantiated with
+/// different [`Configuration`]s are explicitly, detectably incompatible
+/// rather than silently producing mismatched root hashes under the same
+/// hard-coded scheme.
+pub trait Configuration<H: Hasher>: Send + Sync {
+    /// The placeholder byte value hashed for a node that has no real
+    /// committed value yet (an empty sibling in a not-yet-populated subtree).
+    fn empty_value() -> Vec<u8>;
+
+    /// Hashes a leaf node's label and the hash of its committed value into
+    /// the digest stored at that leaf.
+    fn hash_leaf(label_bytes: &[u8], value_hash: H::Digest) -> H::Digest;
+
+    /// Derives an internal node's digest from its two children's digests, in
+    /// left-to-right order. The arity of the tree ([`ARITY`](crate::ARITY))
+    /// is fixed at 2, so this always takes exactly a left and right child.
+    fn hash_internal(left: H::Digest, right: H::Digest) -> H::Digest;
+
+    /// Hashes a raw identifier, staleness flag and version into the
+    /// domain-separated input handed to the VRF when deriving that
+    /// `(identifier, version)` pair's node label.
+    fn hash_label_input(uname: &[u8], is_stale: bool, version: u64) -> Vec<u8>;
+}
+
+/// The crate's original, hard-coded scheme: a single zero byte for
+/// [`Configuration::empty_value`], leaves hashed as
+/// `H::merge([H::hash(label), value_hash])`,
+/// internal nodes as `H::merge([left, right])`, and VRF label inputs as
+/// `stale_byte || version_be_bytes || uname`. Matches the conventions
+/// [`EMPTY_VALUE`](crate::EMPTY_VALUE) and the rest of the crate assumed
+/// before [`Configuration`] existed.
+pub struct DefaultConfiguration;
+
+impl<H: Hasher> Configuration<H> for DefaultConfiguration {
+    fn empty_value() -> Vec<u8> {
+        crate::EMPTY_VALUE.to_vec()
+    }
+
+    fn hash_leaf(label_bytes: &[u8], value_hash: H::Digest) -> H::Digest {
+        H::merge(&[H::hash(label_bytes), value_hash])
+    }
+
+    fn hash_internal(left: H::Digest, right: H::Digest) -> H::Digest {
+        H::merge(&[left, right])
+    }
+
+    fn hash_label_input(uname: &[u8], is_stale: bool, version: u64) -> Vec<u8> {
+        let mut input = Vec::with_capacity(uname.len() + 9);
+        input.push(is_stale as u8);
+        input.extend_from_slice(&version.to_be_bytes());
+        input.extend_from_slice(uname);
+        input
+    }
+}
+
+/// An alternative commitment scheme, illustrating that a second deployment
+/// can disagree with [`DefaultConfiguration`] on every hook this trait
+/// exposes while remaining internally consistent: a non-zero empty value (so
+/// an empty node's hash can never coincide with a hash of real, all-zero
+/// content), leaves hashed with the value first, and internal nodes combined
+/// in the opposite child order.
+pub struct AlternateConfiguration;
+
+impl<H: Hasher> Configuration<H> for AlternateConfiguration {
+    fn empty_value() -> Vec<u8> {
+        vec![0xffu8]
+    }
+
+    fn hash_leaf(label_bytes: &[u8], value_hash: H::Digest) -> H::Digest {
+        H::merge(&[value_hash, H::hash(label_bytes)])
+    }
+
+    fn hash_internal(left: H::Digest, right: H::Digest) -> H::Digest {
+        H::merge(&[right, left])
+    }
+
+    fn hash_label_input(uname: &[u8], is_stale: bool, version: u64) -> Vec<u8> {
+        let mut input = Vec::with_capacity(uname.len() + 9);
+        input.extend_from_slice(uname);
+        input.extend_from_slice(&version.to_be_bytes());
+        input.push(is_stale as u8);
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Blake3;
+
+    // The two reference configurations must actually disagree on every hook,
+    // so picking the wrong one for a directory is detectable rather than an
+    // accidental no-op.
+    #[test]
+    fn reference_configurations_diverge() {
+        assert_ne!(
+            <DefaultConfiguration as Configuration<Blake3>>::empty_value(),
+            <AlternateConfiguration as Configuration<Blake3>>::empty_value()
+        );
+
+        let label = b"some-label";
+        let value_hash = Blake3::hash(b"some-value");
+        assert_ne!(
+            <DefaultConfiguration as Configuration<Blake3>>::hash_leaf(label, value_hash),
+            <AlternateConfiguration as Configuration<Blake3>>::hash_leaf(label, value_hash)
+        );
+
+        let left = Blake3::hash(b"left");
+        let right = Blake3::hash(b"right");
+        assert_ne!(
+            <DefaultConfiguration as Configuration<Blake3>>::hash_internal(left, right),
+            <AlternateConfiguration as Configuration<Blake3>>::hash_internal(left, right)
+        );
+
+        assert_ne!(
+            <DefaultConfiguration as Configuration<Blake3>>::hash_label_input(b"uname", false, 1),
+            <AlternateConfiguration as Configuration<Blake3>>::hash_label_input(b"uname", false, 1)
+        );
+    }
+
+    // `DefaultConfiguration` reproduces the crate's pre-`Configuration`
+    // hashing exactly, so adopting it is a no-op for an existing deployment.
+    #[test]
+    fn default_configuration_matches_the_original_hard_coded_scheme() {
+        assert_eq!(
+            <DefaultConfiguration as Configuration<Blake3>>::empty_value(),
+            crate::EMPTY_VALUE.to_vec()
+        );
+
+        let label = b"some-label";
+        let value_hash = Blake3::hash(b"some-value");
+        assert_eq!(
+            <DefaultConfiguration as Configuration<Blake3>>::hash_leaf(label, value_hash),
+            Blake3::merge(&[Blake3::hash(label), value_hash])
+        );
+    }
+}
@@ -9,25 +9,39 @@
 
 use crate::ordered_append_only_zks::Ozks;
 
+use crate::checkpoint::{EpochTransition, EpochTransitionKey};
+use crate::client::HistoryParams;
+use crate::commitment_key::{self, CommitmentKeyStorage};
+use crate::configuration::Configuration;
 use crate::ecvrf::{VRFKeyStorage, VRFPublicKey};
+use crate::epoch_commitment_sink::EpochCommitmentSink;
+use crate::object_cache::{ObjectCache, DEFAULT_CACHE_CEILING_BYTES};
 use crate::proof_structs::*;
 use crate::{helper_structs::LookupInfo, EpochHash, Node};
 
-use crate::errors::{VkdError, DirectoryError, StorageError};
+use crate::errors::{AuditorError, DirectoryError, StorageError, VkdError};
 
-use crate::storage::types::{VkdLabel, VkdValue, DbRecord, ValueState, ValueStateRetrievalFlag};
-use crate::storage::Storage;
+use crate::storage::types::{DbRecord, ValueState, ValueStateRetrievalFlag, VkdLabel, VkdValue};
+use crate::storage::{Storable, Storage};
 
+use futures::stream::{self, StreamExt};
 use log::{debug, error, info};
 
 #[cfg(feature = "rand")]
 use rand::{distributions::Alphanumeric, CryptoRng, Rng};
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::marker::{Send, Sync};
 use std::sync::Arc;
 use winter_crypto::{Digest, Hasher};
 
+/// Default upper bound on how many independent proof generations (per-label
+/// lookups, `key_history` update proofs, and their non-existence companions)
+/// a [`Directory`] will run concurrently against storage. Override with
+/// [`Directory::with_proof_concurrency`].
+pub const DEFAULT_PROOF_CONCURRENCY: usize = 16;
+
 #[cfg(feature = "rand")]
 impl VkdValue {
     /// Gets a random value for a VKD
@@ -44,12 +58,118 @@ impl VkdLabel {
     }
 }
 
+/// Specific, well-defined defects that [`Directory::publish_corrupted`] (and,
+/// for the two history-proof-shaped variants, [`Directory::key_history_corrupted`])
+/// can inject into a label's update or proof, to build known-invalid
+/// directories and proofs for exercising auditor and verifier negative-testing
+/// paths.
+///
+/// [`UnmarkedStaleVersion`](Self::UnmarkedStaleVersion) is a server that
+/// fails to mark an old version stale;
+/// [`MarkVersionStaleWithoutCommit`](Self::MarkVersionStaleWithoutCommit)
+/// marks a version stale without a real successor replacing it -- the
+/// corrupted-server behaviors closest in spirit to "mark a live version
+/// stale"; and [`TooManyVersions`](Self::TooManyVersions) is a server
+/// serving proof for a version it never actually committed, the same shape
+/// of misbehavior as inserting an unrequested extra version.
+#[cfg(any(test, feature = "rand"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublishCorruption {
+    /// When bumping `label` to a new version, skip inserting the
+    /// `stale_label`/`EMPTY_VALUE` node that should mark the previous version
+    /// as retired, so the azks never actually proves the old version stale.
+    UnmarkedStaleVersion(VkdLabel),
+    /// When bumping `label` to a new version, insert the stale marker for the
+    /// previous version but omit the fresh commitment node for the new one,
+    /// so the directory claims a version retired without ever publishing its
+    /// successor.
+    MarkVersionStaleWithoutCommit(VkdLabel),
+    /// Commit a different `VkdValue` for `label` than the one recorded in the
+    /// `ValueState` written to storage, so the committed leaf and the
+    /// plaintext history disagree about what value was actually published.
+    TamperedCommitmentValue(VkdLabel),
+    /// Drop `label`'s most recent [`UpdateProof`] from a served
+    /// [`HistoryProof`], so the proof under-reports how many versions the
+    /// directory has actually published for it.
+    TooFewVersions(VkdLabel),
+    /// Duplicate `label`'s most recent [`UpdateProof`] under a fabricated,
+    /// never-published version one past it, so the proof over-reports how
+    /// many versions the directory has actually published for it.
+    TooManyVersions(VkdLabel),
+    /// Record `label`'s new version in its plaintext `ValueState` as usual,
+    /// but skip inserting the corresponding node into the tree delta
+    /// entirely, so the directory's history claims a published version that
+    /// the tree itself never committed to.
+    DroppedTreeNode(VkdLabel),
+    /// Checkpoint the epoch transition and anchor with the tree's real root
+    /// hash as usual, but return a different, fabricated root hash in the
+    /// [`EpochHash`] handed back to the caller, so a client that trusts the
+    /// returned hash is disagreeing with what storage actually committed.
+    InconsistentRootHash,
+    /// When bumping `label` to a new version, derive its fresh commitment
+    /// under the *stale* version's VRF label instead of its own, so two
+    /// different versions of `label` collide on a single tree label rather
+    /// than the VRF's per-version binding keeping them apart.
+    ReusedVrfLabel(VkdLabel),
+}
+
+/// Default number of leaves a batch must exceed before
+/// [`Directory::publish_with_insert_mode`]'s default [`InsertMode::Parallel`]
+/// actually spawns concurrent insertion tasks, rather than falling back to
+/// [`InsertMode::Sequential`] to avoid paying spawn overhead on small batches.
+pub const DEFAULT_PARALLEL_INSERT_THRESHOLD: usize = 64;
+
+/// Picks how [`Directory::publish_with_insert_mode`] inserts a batch of
+/// leaves into the underlying tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InsertMode {
+    /// Insert every leaf in the batch one at a time.
+    Sequential,
+    /// Partition the batch by each leaf label's top-level prefix bits and
+    /// insert the resulting disjoint subtrees concurrently, across up to
+    /// `max_parallelism` tasks, falling back to [`InsertMode::Sequential`]
+    /// whenever the batch has `threshold` leaves or fewer.
+    Parallel {
+        /// Upper bound on how many concurrent insertion tasks to spawn.
+        max_parallelism: usize,
+        /// Batches at or below this size are inserted sequentially instead.
+        threshold: usize,
+    },
+}
+
+impl Default for InsertMode {
+    /// [`InsertMode::Parallel`], sized to the available cores and gated by
+    /// [`DEFAULT_PARALLEL_INSERT_THRESHOLD`].
+    fn default() -> Self {
+        InsertMode::Parallel {
+            max_parallelism: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            threshold: DEFAULT_PARALLEL_INSERT_THRESHOLD,
+        }
+    }
+}
+
+/// A single epoch transition yielded by [`Directory::audit_stream`]: the
+/// append-only proof for the step `epoch - 1 -> epoch`, together with the
+/// root hash the directory held once that step completed. Verify it with
+/// [`crate::auditor::verify_consecutive_append_only`], or fold a whole
+/// stream of these with [`crate::auditor::audit_verify_stream`].
+pub struct AuditStep<H: Hasher> {
+    /// The epoch this step transitions *to*.
+    pub epoch: u64,
+    /// The append-only proof for this single-epoch transition.
+    pub proof: SingleAppendOnlyProof<H>,
+    /// The root hash the directory held at `epoch`, once this step
+    /// completed -- becomes the next step's starting hash.
+    pub root_hash: H::Digest,
+}
+
 /// The representation of a auditable key directory
 #[derive(Clone)]
 pub struct Directory<S, V> {
     storage: S,
     vrf: V,
-    read_only: bool,
     /// The cache lock guarantees that the cache is not
     /// flushed mid-proof generation. We allow multiple proof generations
     /// to occur (RwLock.read() operations can have multiple) but we want
@@ -57,45 +177,629 @@ pub struct Directory<S, V> {
     /// (in this case we do utilize the write() lock which can only occur 1
     /// at a time and gates further read() locks being acquired during write()).
     cache_lock: Arc<tokio::sync::RwLock<()>>,
+    /// Optional external anchor that every successful [`Directory::publish`]
+    /// notifies with the epoch it just committed. See
+    /// [`crate::epoch_commitment_sink::EpochCommitmentSink`].
+    commitment_sink: Option<Arc<dyn EpochCommitmentSink>>,
+    /// The upper bound on how many independent proof generations
+    /// [`Directory::batch_lookup`] and [`Directory::key_history`] will run
+    /// concurrently against storage. See [`Directory::with_proof_concurrency`].
+    proof_concurrency: usize,
+    /// The size-bounded, epoch-aware object cache consulted by
+    /// [`Directory::poll_for_ozks_changes`] in place of a full storage-layer
+    /// cache flush. See [`crate::object_cache::ObjectCache`].
+    object_cache: Arc<ObjectCache>,
 }
 
-impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
+impl<S: Storage + Sync + Send, V: VRFKeyStorage + CommitmentKeyStorage> Directory<S, V> {
     /// Creates a new (stateless) instance of a auditable key directory.
     /// Takes as input a pointer to the storage being used for this instance.
-    /// The state is stored in the storage.
-    pub async fn new<H: Hasher>(storage: &S, vrf: &V, read_only: bool) -> Result<Self, VkdError> {
-        let ozks = Directory::<S, V>::get_ozks_from_storage(storage, false).await;
+    /// The state is stored in the storage. Generates a fresh `Azks` in
+    /// `storage` if one is not already present -- use
+    /// [`ReadOnlyDirectory::new`] instead if `storage` should already own
+    /// one and a missing `Azks` ought to be an error.
+    pub async fn new<H: Hasher>(storage: &S, vrf: &V) -> Result<Self, VkdError> {
+        Self::new_with_commitment_sink::<H>(storage, vrf, None).await
+    }
 
-        if read_only && ozks.is_err() {
-            return Err(VkdError::Directory(DirectoryError::ReadOnlyDirectory(
-                "Cannot start directory in read-only mode when AZKS is missing".to_string(),
-            )));
-        } else if ozks.is_err() {
+    /// Identical to [`Directory::new`], but additionally wires in
+    /// `commitment_sink`, which every successful [`Directory::publish`] (or
+    /// [`Directory::publish_corrupted`]) invokes with the epoch and root hash
+    /// it just committed to storage, once the transaction has succeeded. See
+    /// [`crate::epoch_commitment_sink::EpochCommitmentSink`].
+    pub async fn new_with_commitment_sink<H: Hasher>(
+        storage: &S,
+        vrf: &V,
+        commitment_sink: Option<Arc<dyn EpochCommitmentSink>>,
+    ) -> Result<Self, VkdError> {
+        if Directory::<S, V>::get_ozks_from_storage(storage, false)
+            .await
+            .is_err()
+        {
             // generate a new ozks if one is not found
             let ozks = Ozks::new::<_, H>(storage).await?;
             // store it
             storage.set(DbRecord::Ozks(ozks.clone())).await?;
         }
 
-        Ok(Directory {
+        Ok(Directory::from_existing(storage, vrf, commitment_sink))
+    }
+
+    /// Builds a [`Directory`] handle onto `storage` without touching it --
+    /// the caller is responsible for having already ensured an `Azks` is
+    /// present (or being fine with the handle's first call failing if not).
+    /// Used by [`Directory::new_with_commitment_sink`] once it has
+    /// guaranteed one exists, and by [`ReadOnlyDirectory::new`], which
+    /// checks for one itself rather than creating it.
+    fn from_existing(
+        storage: &S,
+        vrf: &V,
+        commitment_sink: Option<Arc<dyn EpochCommitmentSink>>,
+    ) -> Self {
+        Directory {
             storage: storage.clone(),
-            read_only,
             cache_lock: Arc::new(tokio::sync::RwLock::new(())),
             vrf: vrf.clone(),
-        })
+            commitment_sink,
+            proof_concurrency: DEFAULT_PROOF_CONCURRENCY,
+            object_cache: Arc::new(ObjectCache::new(DEFAULT_CACHE_CEILING_BYTES)),
+        }
+    }
+
+    /// Overrides the number of independent proof generations
+    /// [`Directory::batch_lookup`] and [`Directory::key_history`] run
+    /// concurrently against storage, in place of the default
+    /// [`DEFAULT_PROOF_CONCURRENCY`].
+    pub fn with_proof_concurrency(mut self, proof_concurrency: usize) -> Self {
+        self.proof_concurrency = proof_concurrency;
+        self
+    }
+
+    /// Overrides the byte ceiling [`Directory::poll_for_ozks_changes`]'s
+    /// object cache enforces before it starts evicting its
+    /// least-recently-used entries, in place of the default
+    /// [`DEFAULT_CACHE_CEILING_BYTES`].
+    pub fn with_cache_ceiling_bytes(mut self, ceiling_bytes: u64) -> Self {
+        self.object_cache = Arc::new(ObjectCache::new(ceiling_bytes));
+        self
+    }
+
+    /// Invokes the configured [`EpochCommitmentSink`], if any, to externally
+    /// anchor `root_hash` for `epoch`. Called only after the publish's
+    /// storage transaction has already committed.
+    async fn anchor_epoch<H: Hasher>(
+        &self,
+        epoch: u64,
+        root_hash: H::Digest,
+    ) -> Result<(), VkdError> {
+        if let Some(sink) = &self.commitment_sink {
+            sink.commit(epoch, root_hash.as_bytes().to_vec())
+                .await
+                .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))?;
+        }
+        Ok(())
+    }
+
+    /// Builds and persists the [`EpochTransition`] checkpoint for the step
+    /// `current_ozks` just took from `current_epoch` to `next_epoch`, so a
+    /// later [`ReadOnlyDirectory::bootstrap_from_checkpoint`] can verify this step
+    /// without replaying it. Called only after the publish's storage
+    /// transaction has already committed, so `current_ozks` can be queried
+    /// for the append-only proof of the step that was just written.
+    async fn checkpoint_epoch_transition<H: Hasher>(
+        &self,
+        current_ozks: &Ozks,
+        current_epoch: u64,
+        next_epoch: u64,
+        root_hash: H::Digest,
+    ) -> Result<(), VkdError> {
+        let prev_root_hash = current_ozks
+            .get_root_hash_at_epoch::<_, H>(&self.storage, current_epoch)
+            .await?;
+        let append_only_proof = current_ozks
+            .get_append_only_proof::<_, H>(&self.storage, current_epoch, next_epoch)
+            .await?;
+        let transition = EpochTransition::new::<H>(
+            next_epoch,
+            root_hash,
+            prev_root_hash,
+            &append_only_proof,
+        )?;
+        self.storage
+            .set(DbRecord::EpochTransition(transition))
+            .await?;
+        Ok(())
+    }
+
+    /// Fetches the persisted [`EpochTransition`] checkpoint transitioning
+    /// the directory *to* `epoch`.
+    async fn get_epoch_transition(storage: &S, epoch: u64) -> Result<EpochTransition, VkdError> {
+        match storage.get::<EpochTransition>(&EpochTransitionKey(epoch)).await? {
+            DbRecord::EpochTransition(transition) => Ok(transition),
+            _ => Err(VkdError::Storage(StorageError::NotFound(format!(
+                "No epoch transition checkpoint found for epoch {}",
+                epoch
+            )))),
+        }
+    }
+
+    /// Fetches the contiguous run of persisted [`EpochTransition`] checkpoints
+    /// transitioning the directory from `start_epoch` up to and including
+    /// `end_epoch`, so a syncing client can drive its own
+    /// [`ReadOnlyDirectory::bootstrap_from_checkpoint`]-style verification without
+    /// trusting this directory's own replay of the same checkpoints.
+    pub async fn get_epoch_transitions(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<Vec<EpochTransition>, VkdError> {
+        if start_epoch > end_epoch {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "Start epoch {} is greater than the end epoch {}",
+                start_epoch, end_epoch
+            ))));
+        }
+        let keys: Vec<EpochTransitionKey> =
+            (start_epoch..=end_epoch).map(EpochTransitionKey).collect();
+        let records = self.storage.batch_get::<EpochTransition>(&keys).await?;
+        records
+            .into_iter()
+            .map(|record| match record {
+                DbRecord::EpochTransition(transition) => Ok(transition),
+                _ => Err(VkdError::Storage(StorageError::NotFound(
+                    "Unexpected record type returned while fetching epoch transitions"
+                        .to_string(),
+                ))),
+            })
+            .collect()
+    }
+
+    /// Returns the ordered set of [`DbRecord`]s written while the directory
+    /// moved from `from_epoch` to `to_epoch` -- every [`EpochTransition`]
+    /// checkpoint and [`ValueState`] committed in that span, plus (see
+    /// below) the current `Ozks` tree snapshot when `to_epoch` is in fact
+    /// the directory's latest epoch -- so a read replica can fetch and
+    /// apply just the slice of storage it is missing instead of
+    /// re-reading the whole database. The records are grouped by kind: every
+    /// transition checkpoint first, in increasing epoch order across
+    /// `(from_epoch, to_epoch]`, then the value states in the order storage
+    /// returns them, then the snapshot if any. They are not ordered by
+    /// parent pointers, since that range is already the natural unit this
+    /// directory checkpoints and commits in.
+    ///
+    /// The tree itself does not have a finer-grained delta to extract below
+    /// the whole-snapshot level: in this directory's storage layout, an
+    /// epoch's entire `Ozks` is written as one versioned snapshot (see
+    /// `publish`'s `DbRecord::Ozks` write) under a single fixed storage key
+    /// (see `retrieve_current_ozks`) rather than as individually
+    /// addressable node records, so there is exactly one `Ozks` snapshot in
+    /// storage at a time -- the current one. That means this can only ever
+    /// bundle a tree snapshot that matches `to_epoch` when `to_epoch` is
+    /// the directory's current epoch; for a `to_epoch` further in the past
+    /// there is no way to hand back a snapshot specific to it; only the
+    /// transition/value-state history applies in that case, and a replica
+    /// calling for such a range should use a later `to_epoch` or fetch the
+    /// current snapshot separately once it is caught up. When `to_epoch`
+    /// *is* current, though, bundling the snapshot here means a replica
+    /// applying the result via [`Directory::apply_epoch_delta`] can serve
+    /// fresh lookup proofs at the new tip immediately, with no second
+    /// round trip to poll for it (contrast
+    /// [`Directory::poll_for_ozks_changes`], which is the polling
+    /// alternative when a replica wants to be notified of new epochs
+    /// rather than drive the sync itself).
+    pub async fn epoch_delta(
+        &self,
+        from_epoch: u64,
+        to_epoch: u64,
+    ) -> Result<Vec<DbRecord>, VkdError> {
+        if from_epoch > to_epoch {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "Start epoch {} is greater than the end epoch {}",
+                from_epoch, to_epoch
+            ))));
+        }
+        if from_epoch == to_epoch {
+            return Ok(Vec::new());
+        }
+
+        let mut records: Vec<DbRecord> = self
+            .get_epoch_transitions(from_epoch + 1, to_epoch)
+            .await?
+            .into_iter()
+            .map(DbRecord::EpochTransition)
+            .collect();
+
+        let value_states = self
+            .storage
+            .get_user_data_in_epoch_range(from_epoch + 1, to_epoch)
+            .await?;
+        records.extend(value_states.into_iter().map(DbRecord::ValueState));
+
+        let current_ozks = self.retrieve_current_ozks().await?;
+        if current_ozks.get_latest_epoch() == to_epoch {
+            records.push(DbRecord::Ozks(current_ozks));
+        }
+
+        Ok(records)
+    }
+
+    /// Ingests a slice of [`DbRecord`]s previously returned by
+    /// [`Directory::epoch_delta`] into `storage`, so a replica can apply a
+    /// delta it fetched from a publisher without re-deriving it locally.
+    /// Goes through the same [`Storage::batch_set`] path every other write
+    /// in this crate uses for storing records, which is already an upsert by
+    /// `DbRecord`'s own key -- so re-applying an overlapping or previously
+    /// seen delta is a no-op rather than a conflict, making this safe to
+    /// retry after a partial sync.
+    pub async fn apply_epoch_delta(storage: &S, records: Vec<DbRecord>) -> Result<(), VkdError> {
+        storage.batch_set(records).await
+    }
+
+    /// Updates the directory to include the updated key-value pairs.
+    ///
+    /// `C` supplies the value hashed into a previous version's stale marker
+    /// node (see [`Configuration::empty_value`]); its `hash_leaf`,
+    /// `hash_internal` and `hash_label_input` hooks are not called here, so
+    /// leaves, internal nodes and VRF labels are built the same way whatever
+    /// `C` is. Pass
+    /// [`DefaultConfiguration`](crate::configuration::DefaultConfiguration)
+    /// to match every epoch published before `C` existed.
+    pub async fn publish<H: Hasher, C: Configuration<H>>(
+        &self,
+        updates: Vec<(VkdLabel, VkdValue)>,
+    ) -> Result<EpochHash<H>, VkdError> {
+        // The guard will be dropped at the end of the publish
+        let _guard = self.cache_lock.read().await;
+
+        let mut update_set = Vec::<Node<H>>::new();
+        let mut user_data_update_set = Vec::<ValueState>::new();
+
+        let mut current_ozks = self.retrieve_current_ozks().await?;
+        let current_epoch = current_ozks.get_latest_epoch();
+        let next_epoch = current_epoch + 1;
+
+        let mut keys: Vec<VkdLabel> = updates.iter().map(|(uname, _val)| uname.clone()).collect();
+        // sort the keys, as inserting in primary-key order is more efficient for MySQL
+        keys.sort_by(|a, b| a.cmp(b));
+
+        // we're only using the maximum "version" of the user's state at the last epoch
+        // they were seen in the directory. Therefore we've minimized the call to only
+        // return a hashmap of VkdLabel => u64 and not retrieving the other data which is not
+        // read (i.e. the actual _data_ payload).
+        let all_user_versions_retrieved = self
+            .storage
+            .get_user_state_versions(&keys, ValueStateRetrievalFlag::LeqEpoch(current_epoch))
+            .await?;
+
+        info!(
+            "Retrieved {} previous user versions of {} requested",
+            all_user_versions_retrieved.len(),
+            keys.len()
+        );
+
+        let commitment_key = self.derive_commitment_key::<H>().await?;
+
+        for (uname, val) in updates {
+            match all_user_versions_retrieved.get(&uname) {
+                None => {
+                    // no data found for the user
+                    let latest_version = 1;
+                    let label = self
+                        .vrf
+                        .get_node_label::<H>(&uname, false, latest_version)
+                        .await?;
+
+                    let value_to_add =
+                        crate::utils::commit_value::<H>(&commitment_key.as_bytes(), &label, &val);
+                    update_set.push(Node::<H> {
+                        label,
+                        hash: value_to_add,
+                    });
+                    let latest_state =
+                        ValueState::new(uname, val, latest_version, label, next_epoch);
+                    user_data_update_set.push(latest_state);
+                }
+                Some((_, previous_value)) if val == *previous_value => {
+                    // skip this version because the user is trying to re-publish the already most recent value
+                    // XXXX
+                }
+                Some((previous_version, _)) => {
+                    // Data found for the given user
+                    let latest_version = *previous_version + 1;
+                    let stale_label = self
+                        .vrf
+                        .get_node_label::<H>(&uname, true, *previous_version)
+                        .await?;
+                    let fresh_label = self
+                        .vrf
+                        .get_node_label::<H>(&uname, false, latest_version)
+                        .await?;
+                    let stale_value_to_add = H::hash(&C::empty_value());
+                    let fresh_value_to_add = crate::utils::commit_value::<H>(
+                        &commitment_key.as_bytes(),
+                        &fresh_label,
+                        &val,
+                    );
+                    update_set.push(Node::<H> {
+                        label: stale_label,
+                        hash: stale_value_to_add,
+                    });
+                    update_set.push(Node::<H> {
+                        label: fresh_label,
+                        hash: fresh_value_to_add,
+                    });
+                    let new_state =
+                        ValueState::new(uname, val, latest_version, fresh_label, next_epoch);
+                    user_data_update_set.push(new_state);
+                }
+            }
+        }
+        let insertion_set: Vec<Node<H>> = update_set.to_vec();
+
+        if insertion_set.is_empty() {
+            info!("After filtering for duplicated user information, there is no publish which is necessary (0 updates)");
+            // The AZKS has not been updated/mutated at this point, so we can just return the root hash from before
+            let root_hash = current_ozks.get_root_hash::<_, H>(&self.storage).await?;
+            return Ok(EpochHash(current_epoch, root_hash));
+        }
+
+        if let false = self.storage.begin_transaction().await {
+            error!("Transaction is already active");
+            return Err(VkdError::Storage(StorageError::Transaction(
+                "Transaction is already active".to_string(),
+            )));
+        }
+        info!("Starting database insertion");
+
+        current_ozks
+            .batch_insert_leaves::<_, H>(&self.storage, insertion_set)
+            .await?;
+
+        // batch all the inserts into a single transactional write to storage
+        let mut updates = vec![DbRecord::Ozks(current_ozks.clone())];
+        for update in user_data_update_set.into_iter() {
+            updates.push(DbRecord::ValueState(update));
+        }
+        self.storage.batch_set(updates).await?;
+
+        // now commit the transaction
+        debug!("Committing transaction");
+        if let Err(err) = self.storage.commit_transaction().await {
+            // ignore any rollback error(s)
+            let _ = self.storage.rollback_transaction().await;
+            return Err(VkdError::Storage(err));
+        } else {
+            debug!("Transaction committed");
+        }
+
+        let root_hash = current_ozks
+            .get_root_hash_at_epoch::<_, H>(&self.storage, next_epoch)
+            .await?;
+
+        self.checkpoint_epoch_transition::<H>(&current_ozks, current_epoch, next_epoch, root_hash)
+            .await?;
+        self.anchor_epoch::<H>(next_epoch, root_hash).await?;
+
+        // Drop the read guard so the write lock below can't deadlock against
+        // it, then refresh the object cache ourselves rather than leaving a
+        // reader on this same `Directory` to see whatever was cached before
+        // this publish until the next `poll_for_ozks_changes` tick happens to
+        // run.
+        drop(_guard);
+        {
+            let _guard = self.cache_lock.write().await;
+            self.object_cache.evict_stale(next_epoch).await;
+            let root_record = DbRecord::Ozks(current_ozks.clone());
+            self.object_cache
+                .insert(root_record.get_full_binary_id(), root_record, next_epoch)
+                .await;
+        }
+
+        Ok(EpochHash(next_epoch, root_hash))
+    }
+
+    /// Identical to [`Directory::publish`], except that it injects `corruption`
+    /// into the update for the label it names, going through the same
+    /// transactional `batch_insert_leaves`/`batch_set` path so the resulting
+    /// on-disk state is genuinely inconsistent rather than just a mangled
+    /// proof. Exists to build known-invalid directories for exercising
+    /// auditor and verifier negative-testing paths -- not something a real
+    /// publisher would ever want to call.
+    #[cfg(any(test, feature = "rand"))]
+    pub async fn publish_corrupted<H: Hasher, C: Configuration<H>>(
+        &self,
+        updates: Vec<(VkdLabel, VkdValue)>,
+        corruption: PublishCorruption,
+    ) -> Result<EpochHash<H>, VkdError> {
+        // The guard will be dropped at the end of the publish
+        let _guard = self.cache_lock.read().await;
+
+        let mut update_set = Vec::<Node<H>>::new();
+        let mut user_data_update_set = Vec::<ValueState>::new();
+
+        let mut current_ozks = self.retrieve_current_ozks().await?;
+        let current_epoch = current_ozks.get_latest_epoch();
+        let next_epoch = current_epoch + 1;
+
+        let mut keys: Vec<VkdLabel> = updates.iter().map(|(uname, _val)| uname.clone()).collect();
+        // sort the keys, as inserting in primary-key order is more efficient for MySQL
+        keys.sort_by(|a, b| a.cmp(b));
+
+        let all_user_versions_retrieved = self
+            .storage
+            .get_user_state_versions(&keys, ValueStateRetrievalFlag::LeqEpoch(current_epoch))
+            .await?;
+
+        let commitment_key = self.derive_commitment_key::<H>().await?;
+
+        for (uname, val) in updates {
+            let committed_value = match &corruption {
+                PublishCorruption::TamperedCommitmentValue(target) if *target == uname => {
+                    VkdValue::from_utf8_str("corrupted-commitment-value")
+                }
+                _ => val.clone(),
+            };
+
+            match all_user_versions_retrieved.get(&uname) {
+                None => {
+                    // no data found for the user
+                    let latest_version = 1;
+                    let label = self
+                        .vrf
+                        .get_node_label::<H>(&uname, false, latest_version)
+                        .await?;
+
+                    let value_to_add = crate::utils::commit_value::<H>(
+                        &commitment_key.as_bytes(),
+                        &label,
+                        &committed_value,
+                    );
+                    if corruption != PublishCorruption::DroppedTreeNode(uname.clone()) {
+                        update_set.push(Node::<H> {
+                            label,
+                            hash: value_to_add,
+                        });
+                    }
+                    let latest_state =
+                        ValueState::new(uname, val, latest_version, label, next_epoch);
+                    user_data_update_set.push(latest_state);
+                }
+                Some((_, previous_value)) if val == *previous_value => {
+                    // skip this version because the user is trying to re-publish the already most recent value
+                }
+                Some((previous_version, _)) => {
+                    // Data found for the given user
+                    let latest_version = *previous_version + 1;
+                    let stale_label = self
+                        .vrf
+                        .get_node_label::<H>(&uname, true, *previous_version)
+                        .await?;
+                    let fresh_label =
+                        if corruption == PublishCorruption::ReusedVrfLabel(uname.clone()) {
+                            stale_label
+                        } else {
+                            self.vrf
+                                .get_node_label::<H>(&uname, false, latest_version)
+                                .await?
+                        };
+                    let stale_value_to_add = H::hash(&C::empty_value());
+                    let fresh_value_to_add = crate::utils::commit_value::<H>(
+                        &commitment_key.as_bytes(),
+                        &fresh_label,
+                        &committed_value,
+                    );
+
+                    if corruption != PublishCorruption::UnmarkedStaleVersion(uname.clone()) {
+                        update_set.push(Node::<H> {
+                            label: stale_label,
+                            hash: stale_value_to_add,
+                        });
+                    }
+                    if corruption != PublishCorruption::MarkVersionStaleWithoutCommit(uname.clone())
+                    {
+                        update_set.push(Node::<H> {
+                            label: fresh_label,
+                            hash: fresh_value_to_add,
+                        });
+                    }
+
+                    let new_state =
+                        ValueState::new(uname, val, latest_version, fresh_label, next_epoch);
+                    user_data_update_set.push(new_state);
+                }
+            }
+        }
+        let insertion_set: Vec<Node<H>> = update_set.to_vec();
+
+        // A `DroppedTreeNode` corruption can leave `insertion_set` empty even
+        // though `user_data_update_set` has a `ValueState` to commit -- unlike
+        // the ordinary "nothing to publish" case, that state must still be
+        // written to storage so the corruption actually takes effect.
+        if insertion_set.is_empty() && user_data_update_set.is_empty() {
+            info!("After filtering for duplicated user information, there is no publish which is necessary (0 updates)");
+            // The AZKS has not been updated/mutated at this point, so we can just return the root hash from before
+            let root_hash = current_ozks.get_root_hash::<_, H>(&self.storage).await?;
+            return Ok(EpochHash(current_epoch, root_hash));
+        }
+
+        if let false = self.storage.begin_transaction().await {
+            error!("Transaction is already active");
+            return Err(VkdError::Storage(StorageError::Transaction(
+                "Transaction is already active".to_string(),
+            )));
+        }
+        info!("Starting database insertion");
+
+        if !insertion_set.is_empty() {
+            current_ozks
+                .batch_insert_leaves::<_, H>(&self.storage, insertion_set)
+                .await?;
+        }
+
+        // batch all the inserts into a single transactional write to storage
+        let mut updates = vec![DbRecord::Ozks(current_ozks.clone())];
+        for update in user_data_update_set.into_iter() {
+            updates.push(DbRecord::ValueState(update));
+        }
+        self.storage.batch_set(updates).await?;
+
+        // now commit the transaction
+        debug!("Committing transaction");
+        if let Err(err) = self.storage.commit_transaction().await {
+            // ignore any rollback error(s)
+            let _ = self.storage.rollback_transaction().await;
+            return Err(VkdError::Storage(err));
+        } else {
+            debug!("Transaction committed");
+        }
+
+        let root_hash = current_ozks
+            .get_root_hash_at_epoch::<_, H>(&self.storage, next_epoch)
+            .await?;
+
+        self.checkpoint_epoch_transition::<H>(&current_ozks, current_epoch, next_epoch, root_hash)
+            .await?;
+        self.anchor_epoch::<H>(next_epoch, root_hash).await?;
+
+        // `InconsistentRootHash` checkpoints and anchors the tree's real root
+        // hash above (so storage stays internally consistent), but claims a
+        // different one here, so an auditor trusting this returned value
+        // disagrees with what was actually committed.
+        let returned_root_hash = if corruption == PublishCorruption::InconsistentRootHash {
+            H::hash(b"corrupted-root-hash")
+        } else {
+            root_hash
+        };
+
+        // See the matching refresh at the end of `publish`: keep the object
+        // cache in sync with what was actually committed, not with
+        // `returned_root_hash`, so a corrupted proof comes from the proof
+        // generation path disagreeing with storage, not from a stale cache
+        // masking it.
+        drop(_guard);
+        {
+            let _guard = self.cache_lock.write().await;
+            self.object_cache.evict_stale(next_epoch).await;
+            let root_record = DbRecord::Ozks(current_ozks.clone());
+            self.object_cache
+                .insert(root_record.get_full_binary_id(), root_record, next_epoch)
+                .await;
+        }
+
+        Ok(EpochHash(next_epoch, returned_root_hash))
     }
 
-    /// Updates the directory to include the updated key-value pairs.
-    pub async fn publish<H: Hasher>(
+    /// Identical to [`Directory::publish`], except that `insert_mode` picks
+    /// how the resulting batch of leaves is inserted into the tree -- see
+    /// [`InsertMode`]. The default [`InsertMode`] partitions large batches
+    /// by label-prefix bits and inserts the disjoint subtrees concurrently,
+    /// which `publish` itself never does, so that callers who don't care
+    /// about insertion order keep paying nothing extra for it.
+    pub async fn publish_with_insert_mode<H: Hasher, C: Configuration<H>>(
         &self,
         updates: Vec<(VkdLabel, VkdValue)>,
+        insert_mode: InsertMode,
     ) -> Result<EpochHash<H>, VkdError> {
-        if self.read_only {
-            return Err(VkdError::Directory(DirectoryError::ReadOnlyDirectory(
-                "Cannot publish while in read-only mode".to_string(),
-            )));
-        }
-
         // The guard will be dropped at the end of the publish
         let _guard = self.cache_lock.read().await;
 
@@ -110,27 +814,16 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         // sort the keys, as inserting in primary-key order is more efficient for MySQL
         keys.sort_by(|a, b| a.cmp(b));
 
-        // we're only using the maximum "version" of the user's state at the last epoch
-        // they were seen in the directory. Therefore we've minimized the call to only
-        // return a hashmap of VkdLabel => u64 and not retrieving the other data which is not
-        // read (i.e. the actual _data_ payload).
         let all_user_versions_retrieved = self
             .storage
             .get_user_state_versions(&keys, ValueStateRetrievalFlag::LeqEpoch(current_epoch))
             .await?;
 
-        info!(
-            "Retrieved {} previous user versions of {} requested",
-            all_user_versions_retrieved.len(),
-            keys.len()
-        );
-
         let commitment_key = self.derive_commitment_key::<H>().await?;
 
         for (uname, val) in updates {
             match all_user_versions_retrieved.get(&uname) {
                 None => {
-                    // no data found for the user
                     let latest_version = 1;
                     let label = self
                         .vrf
@@ -149,10 +842,8 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
                 }
                 Some((_, previous_value)) if val == *previous_value => {
                     // skip this version because the user is trying to re-publish the already most recent value
-                    // XXXX
                 }
                 Some((previous_version, _)) => {
-                    // Data found for the given user
                     let latest_version = *previous_version + 1;
                     let stale_label = self
                         .vrf
@@ -162,7 +853,7 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
                         .vrf
                         .get_node_label::<H>(&uname, false, latest_version)
                         .await?;
-                    let stale_value_to_add = H::hash(&crate::EMPTY_VALUE);
+                    let stale_value_to_add = H::hash(&C::empty_value());
                     let fresh_value_to_add = crate::utils::commit_value::<H>(
                         &commitment_key.as_bytes(),
                         &fresh_label,
@@ -186,7 +877,6 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
 
         if insertion_set.is_empty() {
             info!("After filtering for duplicated user information, there is no publish which is necessary (0 updates)");
-            // The AZKS has not been updated/mutated at this point, so we can just return the root hash from before
             let root_hash = current_ozks.get_root_hash::<_, H>(&self.storage).await?;
             return Ok(EpochHash(current_epoch, root_hash));
         }
@@ -200,7 +890,7 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         info!("Starting database insertion");
 
         current_ozks
-            .batch_insert_leaves::<_, H>(&self.storage, insertion_set)
+            .batch_insert_leaves_with_mode::<_, H>(&self.storage, insertion_set, insert_mode)
             .await?;
 
         // batch all the inserts into a single transactional write to storage
@@ -224,9 +914,22 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
             .get_root_hash_at_epoch::<_, H>(&self.storage, next_epoch)
             .await?;
 
+        self.checkpoint_epoch_transition::<H>(&current_ozks, current_epoch, next_epoch, root_hash)
+            .await?;
+        self.anchor_epoch::<H>(next_epoch, root_hash).await?;
+
+        // See the matching refresh at the end of `publish`.
+        drop(_guard);
+        {
+            let _guard = self.cache_lock.write().await;
+            self.object_cache.evict_stale(next_epoch).await;
+            let root_record = DbRecord::Ozks(current_ozks.clone());
+            self.object_cache
+                .insert(root_record.get_full_binary_id(), root_record, next_epoch)
+                .await;
+        }
+
         Ok(EpochHash(next_epoch, root_hash))
-        // At the moment the tree root is not being written anywhere. Eventually we
-        // want to change this to call a write operation to post to a blockchain or some such thing
     }
 
     /// Provides proof for correctness of latest version
@@ -290,7 +993,13 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
                 .get_non_membership_proof(&self.storage, lookup_info.non_existent_label)
                 .await?,
             commitment_proof: crate::utils::get_commitment_proof::<H>(
-                &commitment_key.as_bytes(),
+                &self
+                    .commitment_opening_key::<H>(
+                        &commitment_key,
+                        &commitment_label.label_val,
+                        current_version,
+                    )
+                    .as_bytes(),
                 &commitment_label,
                 &plaintext_value,
             )
@@ -301,12 +1010,43 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         Ok(lookup_proof)
     }
 
-    // TODO(eoz): Call proof generations async
+    /// Runs `f` over `items` concurrently, bounded to at most `concurrency`
+    /// calls in flight at once, and returns the results in the same order as
+    /// `items` regardless of the order the individual calls complete in.
+    /// Used to drive independent per-label proof generations (which have
+    /// already had their storage nodes warmed by a preload pass) against
+    /// storage in parallel rather than one at a time.
+    async fn run_concurrent<T, R, F, Fut>(
+        items: Vec<T>,
+        concurrency: usize,
+        f: F,
+    ) -> Result<Vec<R>, VkdError>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<R, VkdError>>,
+    {
+        let mut indexed: Vec<(usize, R)> = stream::iter(items.into_iter().enumerate())
+            .map(|(i, item)| {
+                let fut = f(item);
+                async move { fut.await.map(|result| (i, result)) }
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<Result<(usize, R), VkdError>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, VkdError>>()?;
+        indexed.sort_by_key(|(i, _)| *i);
+        Ok(indexed.into_iter().map(|(_, result)| result).collect())
+    }
+
     /// Allows efficient batch lookups by preloading necessary nodes for the lookups.
     pub async fn batch_lookup<H: Hasher>(
         &self,
         unames: &[VkdLabel],
     ) -> Result<Vec<LookupProof<H>>, VkdError> {
+        // The guard will be dropped at the end of the proof generation
+        let _guard = self.cache_lock.read().await;
+
         let current_ozks = self.retrieve_current_ozks().await?;
         let current_epoch = current_ozks.get_latest_epoch();
 
@@ -337,18 +1077,22 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         // Ensure we have got all lookup infos needed.
         assert_eq!(unames.len(), lookup_infos.len());
 
-        let mut lookup_proofs = Vec::new();
-        for i in 0..unames.len() {
-            lookup_proofs.push(
-                self.lookup_with_info::<H>(
-                    unames[i].clone(),
-                    &current_ozks,
-                    current_epoch,
-                    lookup_infos[i].clone(),
-                )
-                .await?,
-            );
-        }
+        // With the cache warmed, each label's proof is independent of the
+        // others, so generate them concurrently rather than one at a time.
+        let labeled: Vec<(VkdLabel, LookupInfo)> =
+            unames.iter().cloned().zip(lookup_infos).collect();
+        let lookup_proofs = Directory::<S, V>::run_concurrent(
+            labeled,
+            self.proof_concurrency,
+            |(uname, lookup_info)| {
+                let current_ozks = &current_ozks;
+                async move {
+                    self.lookup_with_info::<H>(uname, current_ozks, current_epoch, lookup_info)
+                        .await
+                }
+            },
+        )
+        .await?;
 
         Ok(lookup_proofs)
     }
@@ -399,115 +1143,28 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         }
     }
 
-    /// Takes in the current state of the server and a label.
-    /// If the label is present in the current state,
-    /// this function returns all the values ever associated with it,
-    /// and the epoch at which each value was first committed to the server state.
-    /// It also returns the proof of the latest version being served at all times.
-    pub async fn key_history<H: Hasher>(
-        &self,
-        uname: &VkdLabel,
-    ) -> Result<HistoryProof<H>, VkdError> {
-        // The guard will be dropped at the end of the proof generation
-        let _guard = self.cache_lock.read().await;
-
-        let username = uname.to_vec();
-        let current_ozks = self.retrieve_current_ozks().await?;
-        let current_epoch = current_ozks.get_latest_epoch();
-
-        if let Ok(this_user_data) = self.storage.get_user_data(uname).await {
-            let mut user_data = this_user_data.states;
-            // reverse sort from highest epoch to lowest
-            user_data.sort_by(|a, b| b.epoch.partial_cmp(&a.epoch).unwrap());
-
-            let mut update_proofs = Vec::<UpdateProof<H>>::new();
-            let mut last_version = 0;
-            let mut epochs = Vec::<u64>::new();
-            for user_state in user_data {
-                // Ignore states in storage that are ahead of current directory epoch
-                if user_state.epoch <= current_epoch {
-                    let proof = self.create_single_update_proof(uname, &user_state).await?;
-                    update_proofs.push(proof);
-                    last_version = if user_state.version > last_version {
-                        user_state.version
-                    } else {
-                        last_version
-                    };
-                    epochs.push(user_state.epoch);
-                }
-            }
-            let next_marker = get_marker_version(last_version) + 1;
-            let final_marker = get_marker_version(current_epoch);
-
-            let mut next_few_vrf_proofs = Vec::<Vec<u8>>::new();
-            let mut non_existence_of_next_few = Vec::<NonMembershipProof<H>>::new();
-
-            for ver in last_version + 1..(1 << next_marker) {
-                let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
-                let non_existence_of_ver = current_ozks
-                    .get_non_membership_proof(&self.storage, label_for_ver)
-                    .await?;
-                non_existence_of_next_few.push(non_existence_of_ver);
-                next_few_vrf_proofs.push(
-                    self.vrf
-                        .get_label_proof::<H>(uname, false, ver)
-                        .await?
-                        .to_bytes()
-                        .to_vec(),
-                );
-            }
-
-            let mut future_marker_vrf_proofs = Vec::<Vec<u8>>::new();
-            let mut non_existence_of_future_markers = Vec::<NonMembershipProof<H>>::new();
-
-            for marker_power in next_marker..final_marker + 1 {
-                let ver = 1 << marker_power;
-                let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
-                let non_existence_of_ver = current_ozks
-                    .get_non_membership_proof(&self.storage, label_for_ver)
-                    .await?;
-                non_existence_of_future_markers.push(non_existence_of_ver);
-                future_marker_vrf_proofs.push(
-                    self.vrf
-                        .get_label_proof::<H>(uname, false, ver)
-                        .await?
-                        .to_bytes()
-                        .to_vec(),
-                );
-            }
-
-            Ok(HistoryProof {
-                update_proofs,
-                epochs,
-                next_few_vrf_proofs,
-                non_existence_of_next_few,
-                future_marker_vrf_proofs,
-                non_existence_of_future_markers,
-            })
-        } else {
-            match std::str::from_utf8(&username) {
-                Ok(name) => Err(VkdError::Storage(StorageError::NotFound(format!(
-                    "User {} at epoch {}",
-                    name, current_epoch
-                )))),
-                _ => Err(VkdError::Storage(StorageError::NotFound(format!(
-                    "User {:?} at epoch {}",
-                    username, current_epoch
-                )))),
-            }
-        }
-    }
-
-    /// Takes in the current state of the server and a label along with
-    /// a "top" number of key updates to generate a proof for.
+    /// Takes in the current state of the server and a label, along with `params`
+    /// controlling how much of the key's version history to prove: the complete
+    /// history, only the `n` most recent versions, or only versions updated at or
+    /// after a given epoch (see [`crate::client::HistoryParams`]).
     ///
-    /// If the label is present in the current state,
-    /// this function returns all the values & proof of validity
-    /// up to `top_n_updates` results.
-    pub async fn limited_key_history<H: Hasher>(
+    /// If the label is present in the current state, this function returns the
+    /// requested window of values ever associated with it, and the epoch at which
+    /// each value was first committed to the server state. It also returns the
+    /// proof of the latest version being served at all times.
+    ///
+    /// A windowed history is bounded on both ends: the `next_few`/`future_marker`
+    /// non-membership proofs (built below) pin the window's most recent version as
+    /// the one actually current at `current_epoch`, and `window_lower_bound` pins
+    /// its oldest version as genuinely preceded by a real, previously-published
+    /// version rather than a server-fabricated gap. We use a single direct
+    /// existence proof for that preceding version rather than the power-of-two
+    /// marker scheme the upper bound uses, since there is exactly one version to
+    /// pin here and no need to amortize over a logarithmic range of candidates.
+    pub async fn key_history<H: Hasher>(
         &self,
-        top_n_updates: usize,
         uname: &VkdLabel,
+        params: HistoryParams,
     ) -> Result<HistoryProof<H>, VkdError> {
         // The guard will be dropped at the end of the proof generation
         let _guard = self.cache_lock.read().await;
@@ -517,92 +1174,273 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         let mut user_data = self.storage.get_user_data(uname).await?.states;
         // reverse sort from highest epoch to lowest
         user_data.sort_by(|a, b| b.epoch.partial_cmp(&a.epoch).unwrap());
+        // Keep the unwindowed history around so a limited-history window's
+        // lower boundary can be positively tied to the version that really
+        // precedes it, rather than just to whatever `params` happened to cut
+        // the window at.
+        let full_history = user_data.clone();
+
+        let windowed_history = match params {
+            HistoryParams::Complete => user_data,
+            HistoryParams::MostRecent(n) | HistoryParams::MostRecentInsecure(n) => {
+                user_data.into_iter().take(n).collect()
+            }
+            HistoryParams::SinceEpoch(epoch) => user_data
+                .into_iter()
+                .filter(|user_state| user_state.epoch >= epoch)
+                .collect(),
+        };
 
-        let limited_history = user_data
-            .into_iter()
-            .take(top_n_updates)
-            .collect::<Vec<_>>();
-
-        if limited_history.is_empty() {
+        if windowed_history.is_empty() {
             let msg = if let Ok(username_str) = std::str::from_utf8(uname) {
                 format!("User {}", username_str)
             } else {
                 format!("User {:?}", uname)
             };
-            Err(VkdError::Storage(StorageError::NotFound(msg)))
-        } else {
-            let mut update_proofs = Vec::<UpdateProof<H>>::new();
-            let mut last_version = 0;
-            let mut epochs = Vec::<u64>::new();
-            for user_state in limited_history {
-                // Ignore states in storage that are ahead of current directory epoch
-                if user_state.epoch <= current_epoch {
-                    let proof = self.create_single_update_proof(uname, &user_state).await?;
-                    update_proofs.push(proof);
-                    last_version = if user_state.version > last_version {
-                        user_state.version
-                    } else {
-                        last_version
-                    };
-                    epochs.push(user_state.epoch);
-                }
-            }
-            let next_marker = get_marker_version(last_version) + 1;
-            let final_marker = get_marker_version(current_epoch);
+            return Err(VkdError::Storage(StorageError::NotFound(msg)));
+        }
 
-            let mut next_few_vrf_proofs = Vec::<Vec<u8>>::new();
-            let mut non_existence_of_next_few = Vec::<NonMembershipProof<H>>::new();
+        // `windowed_history` is a window over the user's actual version
+        // sequence, so consecutive entries must be exactly one version apart
+        // regardless of which `HistoryParams` mode selected the window. A gap
+        // here means a `UserState` is missing or corrupted in storage -- the
+        // client-side verifier would eventually reject a proof built from it
+        // anyway, so refuse to serve one rather than leak a malformed proof.
+        for window in windowed_history.windows(2) {
+            if window[0].version != window[1].version + 1 {
+                return Err(VkdError::Storage(StorageError::Transaction(format!(
+                    "User state history has a gap between versions {} and {}",
+                    window[1].version, window[0].version
+                ))));
+            }
+        }
 
-            for ver in last_version + 1..(1 << next_marker) {
-                let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
-                let non_existence_of_ver = current_ozks
-                    .get_non_membership_proof(&self.storage, label_for_ver)
+        // Ignore states in storage that are ahead of current directory epoch.
+        // `last_version` only depends on the states themselves, not on any
+        // proof we generate for them, so it can be computed up front and the
+        // per-state proofs below generated concurrently.
+        let in_range_states: Vec<ValueState> = windowed_history
+            .into_iter()
+            .filter(|user_state| user_state.epoch <= current_epoch)
+            .collect();
+        let last_version = in_range_states
+            .iter()
+            .map(|user_state| user_state.version)
+            .max()
+            .unwrap_or(0);
+        let oldest_version = in_range_states
+            .iter()
+            .map(|user_state| user_state.version)
+            .min()
+            .unwrap_or(0);
+
+        // For a limited-history request (`MostRecent`/`SinceEpoch`) whose
+        // window doesn't reach back to version 1, additionally prove that a
+        // real version immediately preceded the window's oldest entry --
+        // positive evidence of where the true history continues, so a
+        // server can't silently narrow the window further than `params`
+        // asked for without a client being able to tell.
+        let window_lower_bound = match params {
+            HistoryParams::Complete | HistoryParams::MostRecentInsecure(_) => None,
+            HistoryParams::MostRecent(_) | HistoryParams::SinceEpoch(_) if oldest_version > 1 => {
+                let preceding_version = oldest_version - 1;
+                let preceding_state = full_history
+                    .iter()
+                    .find(|state| state.version == preceding_version)
+                    .ok_or_else(|| {
+                        VkdError::Directory(DirectoryError::InconsistentHistoryWindow(format!(
+                            "No stored user state found for version {}, needed to bound a history window starting at version {}",
+                            preceding_version, oldest_version
+                        )))
+                    })?;
+                let label = self
+                    .vrf
+                    .get_node_label::<H>(uname, false, preceding_version)
                     .await?;
-                non_existence_of_next_few.push(non_existence_of_ver);
-                next_few_vrf_proofs.push(
-                    self.vrf
+                let vrf_proof = self
+                    .vrf
+                    .get_label_proof::<H>(uname, false, preceding_version)
+                    .await?
+                    .to_bytes()
+                    .to_vec();
+                let existence_proof = current_ozks
+                    .get_membership_proof(&self.storage, label, preceding_state.epoch)
+                    .await?;
+                Some((vrf_proof, existence_proof, preceding_state.epoch))
+            }
+            HistoryParams::MostRecent(_) | HistoryParams::SinceEpoch(_) => None,
+        };
+        let (
+            window_lower_bound_vrf_proof,
+            window_lower_bound_existence_proof,
+            window_lower_bound_epoch,
+        ) = match window_lower_bound {
+            Some((vrf_proof, existence_proof, epoch)) => {
+                (Some(vrf_proof), Some(existence_proof), Some(epoch))
+            }
+            None => (None, None, None),
+        };
+
+        let history_results = Directory::<S, V>::run_concurrent(
+            in_range_states,
+            self.proof_concurrency,
+            |user_state| async move {
+                let proof = self.create_single_update_proof(uname, &user_state).await?;
+                Ok((user_state.epoch, proof))
+            },
+        )
+        .await?;
+        let (epochs, update_proofs): (Vec<u64>, Vec<UpdateProof<H>>) =
+            history_results.into_iter().unzip();
+
+        let next_marker = get_marker_version(last_version) + 1;
+        let final_marker = get_marker_version(current_epoch);
+
+        // These non-membership proofs are independent of one another, so (as
+        // above) generate them concurrently rather than one at a time.
+        let next_few_results = Directory::<S, V>::run_concurrent(
+            (last_version + 1..(1 << next_marker)).collect::<Vec<u64>>(),
+            self.proof_concurrency,
+            |ver| {
+                let current_ozks = &current_ozks;
+                async move {
+                    let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
+                    let non_existence_of_ver = current_ozks
+                        .get_non_membership_proof(&self.storage, label_for_ver)
+                        .await?;
+                    let vrf_proof = self
+                        .vrf
                         .get_label_proof::<H>(uname, false, ver)
                         .await?
                         .to_bytes()
-                        .to_vec(),
-                );
-            }
-
-            let mut future_marker_vrf_proofs = Vec::<Vec<u8>>::new();
-            let mut non_existence_of_future_markers = Vec::<NonMembershipProof<H>>::new();
-
-            for marker_power in next_marker..final_marker + 1 {
-                let ver = 1 << marker_power;
-                let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
-                let non_existence_of_ver = current_ozks
-                    .get_non_membership_proof(&self.storage, label_for_ver)
-                    .await?;
-                non_existence_of_future_markers.push(non_existence_of_ver);
-                future_marker_vrf_proofs.push(
-                    self.vrf
+                        .to_vec();
+                    Ok((vrf_proof, non_existence_of_ver))
+                }
+            },
+        )
+        .await?;
+        let (next_few_vrf_proofs, non_existence_of_next_few): (
+            Vec<Vec<u8>>,
+            Vec<NonMembershipProof<H>>,
+        ) = next_few_results.into_iter().unzip();
+
+        let future_marker_results = Directory::<S, V>::run_concurrent(
+            (next_marker..final_marker + 1).collect::<Vec<u64>>(),
+            self.proof_concurrency,
+            |marker_power| {
+                let current_ozks = &current_ozks;
+                async move {
+                    let ver = 1 << marker_power;
+                    let label_for_ver = self.vrf.get_node_label::<H>(uname, false, ver).await?;
+                    let non_existence_of_ver = current_ozks
+                        .get_non_membership_proof(&self.storage, label_for_ver)
+                        .await?;
+                    let vrf_proof = self
+                        .vrf
                         .get_label_proof::<H>(uname, false, ver)
                         .await?
                         .to_bytes()
-                        .to_vec(),
-                );
-            }
+                        .to_vec();
+                    Ok((vrf_proof, non_existence_of_ver))
+                }
+            },
+        )
+        .await?;
+        let (future_marker_vrf_proofs, non_existence_of_future_markers): (
+            Vec<Vec<u8>>,
+            Vec<NonMembershipProof<H>>,
+        ) = future_marker_results.into_iter().unzip();
+
+        // An explicit, unconditional non-existence proof for the version
+        // immediately after the one we're claiming is most recent --
+        // independent of the logarithmic marker scheme above, so a client
+        // isn't relying solely on that scheme's own windowing to catch a
+        // server hiding a rotation at exactly `last_version + 1`.
+        let next_version_label = self
+            .vrf
+            .get_node_label::<H>(uname, false, last_version + 1)
+            .await?;
+        let non_existence_of_next_version = current_ozks
+            .get_non_membership_proof(&self.storage, next_version_label)
+            .await?;
+        let next_version_vrf_proof = self
+            .vrf
+            .get_label_proof::<H>(uname, false, last_version + 1)
+            .await?
+            .to_bytes()
+            .to_vec();
+
+        Ok(HistoryProof {
+            update_proofs,
+            epochs,
+            next_few_vrf_proofs,
+            non_existence_of_next_few,
+            future_marker_vrf_proofs,
+            non_existence_of_future_markers,
+            next_version_vrf_proof,
+            non_existence_of_next_version,
+            window_lower_bound_vrf_proof,
+            window_lower_bound_existence_proof,
+            window_lower_bound_epoch,
+        })
+    }
 
-            Ok(HistoryProof {
-                update_proofs,
-                epochs,
-                next_few_vrf_proofs,
-                non_existence_of_next_few,
-                future_marker_vrf_proofs,
-                non_existence_of_future_markers,
-            })
+    /// Identical to [`Directory::key_history`], except that it then tampers
+    /// with the resulting [`HistoryProof`]'s update-proof list for `uname`
+    /// according to `corruption`, so the served proof disagrees with what's
+    /// actually in storage. Unlike [`Directory::publish_corrupted`], this
+    /// never touches storage -- it only mangles the proof a correctly
+    /// published directory would otherwise have served, exercising the
+    /// client-side history verifier's negative-testing paths for `corruption`
+    /// variants [`PublishCorruption::TooFewVersions`] and
+    /// [`PublishCorruption::TooManyVersions`]. Any other variant is a no-op.
+    #[cfg(any(test, feature = "rand"))]
+    pub async fn key_history_corrupted<H: Hasher>(
+        &self,
+        uname: &VkdLabel,
+        params: HistoryParams,
+        corruption: PublishCorruption,
+    ) -> Result<HistoryProof<H>, VkdError> {
+        let mut proof = self.key_history::<H>(uname, params).await?;
+
+        match &corruption {
+            PublishCorruption::TooFewVersions(target) if *target == *uname => {
+                // Drop the most recent update proof (index 0, since
+                // `update_proofs`/`epochs` are in descending-version order),
+                // so the proof under-reports the published version count.
+                if !proof.update_proofs.is_empty() {
+                    proof.update_proofs.remove(0);
+                    proof.epochs.remove(0);
+                }
+            }
+            PublishCorruption::TooManyVersions(target) if *target == *uname => {
+                // Duplicate the most recent update proof under a fabricated
+                // version one past it, claiming at the same epoch, so the
+                // proof over-reports the published version count.
+                if let (Some(mut fabricated), Some(&epoch)) =
+                    (proof.update_proofs.first().cloned(), proof.epochs.first())
+                {
+                    fabricated.version += 1;
+                    proof.update_proofs.insert(0, fabricated);
+                    proof.epochs.insert(0, epoch);
+                }
+            }
+            _ => {}
         }
+
+        Ok(proof)
     }
 
     /// Poll for changes in the epoch number of the AZKS struct
-    /// stored in the storage layer. If an epoch change is detected,
-    /// the object cache (if present) is flushed immediately so
-    /// that new objects are retrieved from the storage layer against
-    /// the "latest" epoch. There is a "special" flow in the storage layer
+    /// stored in the storage layer. If an epoch change is detected, only
+    /// the object cache entries that are now stale (their `last_epoch`
+    /// predates the new latest epoch) are evicted, rather than flushing the
+    /// cache in its entirety -- a full flush caused a cold-start latency
+    /// spike for the next round of proof generations, since every node
+    /// they touched, not just the ones the epoch bump actually changed, had
+    /// to be refetched from storage. The freshly-read root is re-inserted
+    /// into the cache immediately, so it's warm again as soon as the change
+    /// is observable. There is a "special" flow in the storage layer
     /// to do a storage-layer retrieval which ignores the cache
     pub async fn poll_for_ozks_changes(
         &self,
@@ -620,14 +1458,22 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
             let latest = Directory::<S, V>::get_ozks_from_storage(&self.storage, true).await?;
             if latest.latest_epoch > last.latest_epoch {
                 {
-                    // acquire a singleton lock prior to flushing the cache to assert that no
-                    // cache accesses are underway (i.e. publish/proof generations/etc)
+                    // acquire a singleton lock prior to evicting from the cache to assert that
+                    // no cache accesses are underway (i.e. publish/proof generations/etc)
                     let _guard = self.cache_lock.write().await;
-                    // flush the cache in its entirety
-                    self.storage.flush_cache().await;
+                    // evict only the entries that are now stale for the new latest epoch
+                    self.object_cache.evict_stale(latest.latest_epoch).await;
                     // re-fetch the ozks to load it into cache so when we release the cache lock
                     // others will see the new AZKS loaded up and ready
                     last = Directory::<S, V>::get_ozks_from_storage(&self.storage, false).await?;
+                    let root_record = DbRecord::Ozks(last.clone());
+                    self.object_cache
+                        .insert(
+                            root_record.get_full_binary_id(),
+                            root_record,
+                            latest.latest_epoch,
+                        )
+                        .await;
 
                     // notify change occurred
                     if let Some(channel) = &change_detected {
@@ -677,11 +1523,137 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
         }
     }
 
+    /// Like [`Directory::audit`], but instead of assembling one
+    /// [`AppendOnlyProof`] covering the whole `[audit_start_ep, audit_end_ep)`
+    /// range up front, returns a stream that produces each single-epoch
+    /// [`AuditStep`] lazily as it's polled. An auditor catching up over a
+    /// large range can verify (via
+    /// [`crate::auditor::verify_consecutive_append_only`], or fold the whole
+    /// stream with [`crate::auditor::audit_verify_stream`]) and checkpoint
+    /// its progress one step at a time, instead of holding proofs for the
+    /// entire range in memory at once.
+    pub async fn audit_stream<H: Hasher + Send + Sync>(
+        &self,
+        audit_start_ep: u64,
+        audit_end_ep: u64,
+    ) -> Result<impl stream::Stream<Item = Result<AuditStep<H>, VkdError>> + '_, VkdError> {
+        if audit_start_ep >= audit_end_ep {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "Start epoch {} is greater than or equal the end epoch {}",
+                audit_start_ep, audit_end_ep
+            ))));
+        }
+
+        let current_ozks = self.retrieve_current_ozks().await?;
+        let current_epoch = current_ozks.get_latest_epoch();
+        if current_epoch < audit_end_ep {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "End epoch {} is greater than the current epoch {}",
+                audit_end_ep, current_epoch
+            ))));
+        }
+
+        Ok(stream::unfold(
+            (audit_start_ep, current_ozks),
+            move |(epoch, current_ozks)| async move {
+                if epoch >= audit_end_ep {
+                    return None;
+                }
+                let next_epoch = epoch + 1;
+                let step = self
+                    .audit_one_epoch::<H>(&current_ozks, epoch, next_epoch)
+                    .await;
+                Some((step, (next_epoch, current_ozks)))
+            },
+        ))
+    }
+
+    /// Generates a single-epoch [`AuditStep`], for the transition from
+    /// `start_epoch` to `end_epoch`, against an already-retrieved `Ozks` --
+    /// the per-step work shared by [`Directory::audit_stream`]'s iterations.
+    async fn audit_one_epoch<H: Hasher + Send + Sync>(
+        &self,
+        current_ozks: &Ozks,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<AuditStep<H>, VkdError> {
+        // The guard will be dropped at the end of the proof generation
+        let _guard = self.cache_lock.read().await;
+
+        let append_only_proof = current_ozks
+            .get_append_only_proof::<_, H>(&self.storage, start_epoch, end_epoch)
+            .await?;
+        let proof = append_only_proof.proofs.into_iter().next().ok_or_else(|| {
+            VkdError::AuditErr(AuditorError::VerifyAuditProof(format!(
+                "No append-only proof produced for the single-epoch transition from {} to {}",
+                start_epoch, end_epoch
+            )))
+        })?;
+        let root_hash = current_ozks
+            .get_root_hash_at_epoch::<_, H>(&self.storage, end_epoch)
+            .await?;
+
+        Ok(AuditStep {
+            epoch: end_epoch,
+            proof,
+            root_hash,
+        })
+    }
+
+    /// Returns the single-epoch append-only proof for the transition into
+    /// `epoch` from `epoch - 1`, along with the root hash on each side of it
+    /// (`(prev_root, next_root, proof)`). Unlike [`Directory::audit_stream`],
+    /// each call is fully self-contained -- it doesn't share an `Ozks`
+    /// snapshot with any other call -- so an auditor fleet can fetch,
+    /// serialize, and verify (via
+    /// [`crate::auditor::verify_consecutive_append_only`]) every epoch
+    /// transition in a range independently and in parallel, rather than
+    /// walking the whole range from one end.
+    pub async fn audit_epoch<H: Hasher + Send + Sync>(
+        &self,
+        epoch: u64,
+    ) -> Result<(H::Digest, H::Digest, SingleAppendOnlyProof<H>), VkdError> {
+        if epoch == 0 {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(
+                "Epoch 0 has no preceding epoch to audit a transition from".to_string(),
+            )));
+        }
+
+        let current_ozks = self.retrieve_current_ozks().await?;
+        let current_epoch = current_ozks.get_latest_epoch();
+        if current_epoch < epoch {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "Epoch {} is greater than the current epoch {}",
+                epoch, current_epoch
+            ))));
+        }
+
+        let step = self
+            .audit_one_epoch::<H>(&current_ozks, epoch - 1, epoch)
+            .await?;
+        let prev_root_hash = current_ozks
+            .get_root_hash_at_epoch::<_, H>(&self.storage, epoch - 1)
+            .await?;
+
+        Ok((prev_root_hash, step.root_hash, step.proof))
+    }
+
     /// Retrieves the current ozks
     pub async fn retrieve_current_ozks(&self) -> Result<Ozks, crate::errors::VkdError> {
         Directory::<S, V>::get_ozks_from_storage(&self.storage, false).await
     }
 
+    /// A snapshot of this directory's object cache counters -- hits,
+    /// misses, evictions, and, with the `runtime_metrics` feature enabled,
+    /// per-operation call counts and cumulative wall-clock time. Useful for
+    /// tuning [`ObjectCache`]'s byte ceiling, or for catching a regression
+    /// where a lookup that used to hit the cache starts fanning out into
+    /// many storage round trips instead.
+    #[cfg(feature = "runtime_metrics")]
+    pub fn get_metrics(&self) -> crate::object_cache::ObjectCacheMetrics {
+        self.object_cache.metrics()
+    }
+
     async fn get_ozks_from_storage(
         storage: &S,
         ignore_cache: bool,
@@ -757,7 +1729,9 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
 
         let commitment_key = self.derive_commitment_key::<H>().await?;
         let commitment_proof = crate::utils::get_commitment_proof::<H>(
-            &commitment_key.as_bytes(),
+            &self
+                .commitment_opening_key::<H>(&commitment_key, &existence_label.label_val, version)
+                .as_bytes(),
             &existence_label,
             plaintext_value,
         )
@@ -778,6 +1752,14 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
 
     /// Gets the ozks root hash at the provided epoch. Note that the root hash should exist at any epoch
     /// that the ozks existed, so as long as epoch >= 0, we should be fine.
+    ///
+    /// This recomputes the path from `current_ozks`'s tree nodes rather than
+    /// reading a single precomputed value, since tree nodes here don't
+    /// currently persist their children's hashes alongside their labels --
+    /// a node storage change that would let this (and proof generation,
+    /// which re-hashes the same subtrees) read a cached value instead.
+    /// `Ozks`'s on-disk node representation isn't present in this snapshot,
+    /// so that's noted here rather than attempted blind.
     pub async fn get_root_hash_at_epoch<H: Hasher>(
         &self,
         current_ozks: &Ozks,
@@ -800,12 +1782,242 @@ impl<S: Storage + Sync + Send, V: VRFKeyStorage> Directory<S, V> {
             .await
     }
 
-    // FIXME (Issue #184): This should be derived properly. Instead of hashing the VRF private
-    // key, we should derive this properly from a server secret.
+    /// Derives the commitment key used to bind published label/value
+    /// commitments. Normally this is an HKDF-style derivation over an independent server secret
+    /// (see [`commitment_key::derive_commitment_key`]), kept deliberately
+    /// separate from the VRF signing key -- that used to not be the case
+    /// (Issue #184), and [`CommitmentKeyStorage::use_legacy_commitment_key`]
+    /// remains only so a directory that published commitments before this
+    /// change can still reproduce the key its existing proofs verify
+    /// against.
     async fn derive_commitment_key<H: Hasher>(&self) -> Result<H::Digest, VkdError> {
-        let raw_key = self.vrf.retrieve().await?;
-        let commitment_key = H::hash(&raw_key);
-        Ok(commitment_key)
+        if self.vrf.use_legacy_commitment_key() {
+            let raw_key = self.vrf.retrieve().await?;
+            return Ok(commitment_key::derive_legacy_commitment_key::<H>(&raw_key));
+        }
+        let server_secret = self.vrf.retrieve_server_secret().await?;
+        Ok(commitment_key::derive_commitment_key::<H>(&server_secret))
+    }
+
+    /// Derives the per-entry key `commitment_proof` must be opened (and
+    /// later verified) against, given the directory's `commitment_key` (see
+    /// [`Self::derive_commitment_key`]). On the non-legacy path this is
+    /// `commitment_key`'s nonce for this `(label, version)` (see
+    /// [`commitment_key::derive_commitment_nonce`]). On the legacy
+    /// ([`CommitmentKeyStorage::use_legacy_commitment_key`]) path,
+    /// commitments were opened directly under `commitment_key` itself --
+    /// there was no per-entry nonce step yet -- so this returns it
+    /// unchanged; deriving a nonce on that path would make every
+    /// commitment proof published before the nonce was introduced stop
+    /// verifying, defeating the point of the legacy key.
+    fn commitment_opening_key<H: Hasher>(
+        &self,
+        commitment_key: &H::Digest,
+        label_bytes: &[u8],
+        version: u64,
+    ) -> H::Digest {
+        if self.vrf.use_legacy_commitment_key() {
+            return commitment_key.clone();
+        }
+        commitment_key::derive_commitment_nonce::<H>(commitment_key, label_bytes, version)
+    }
+}
+
+/// A read-only view onto a [`Directory`]'s storage, for fleets of replica
+/// proof-servers that only ever *serve* `lookup`/`key_history`/`audit` proofs
+/// against a storage layer written by a single publisher elsewhere, and must
+/// never mutate it. Unlike [`Directory`], there is no `publish` or
+/// `publish_corrupted` method to statically forbid calling -- the wrapper
+/// simply never exposes them, so a replica can't accidentally mutate state
+/// or race the publisher on epoch counters. Every read path is a thin
+/// forwarding call onto an inner [`Directory`], so the two stay in lockstep
+/// as proof generation evolves rather than drifting into two parallel
+/// implementations.
+///
+/// [`ReadOnlyDirectory::new`] errors if no `Ozks` is already present in
+/// storage, rather than initializing a fresh one the way
+/// [`Directory::new`] does: a replica has nothing useful to serve until the
+/// publisher has written at least one epoch.
+///
+/// Rejecting a write is a property of this type, not a runtime check: there
+/// is no `publish`/`publish_corrupted` method to call in the first place, so
+/// a caller holding a `ReadOnlyDirectory` gets the guarantee at compile
+/// time, the same way it would from a reference-counted read guard.
+#[derive(Clone)]
+pub struct ReadOnlyDirectory<S, V> {
+    inner: Directory<S, V>,
+}
+
+impl<S: Storage + Sync + Send, V: VRFKeyStorage + CommitmentKeyStorage> ReadOnlyDirectory<S, V> {
+    /// Wraps `storage` for read-only proof serving. Errors if `storage` does
+    /// not already contain an `Ozks` -- use [`Directory::new`] on the
+    /// publisher side to initialize one first.
+    ///
+    /// Takes the `storage: &S` that backs a replica database directly,
+    /// rather than a separate `StorageManager` wrapper type: `S: Storage` is
+    /// already the handle a replica's out-of-band epoch feed writes through,
+    /// so introducing another layer in front of it here wouldn't add a
+    /// capability this type needs -- the same reasoning
+    /// [`ObjectCache`](crate::object_cache::ObjectCache) documents for why it
+    /// doesn't grow into one either.
+    pub async fn new<H: Hasher>(storage: &S, vrf: &V) -> Result<Self, VkdError> {
+        if Directory::<S, V>::get_ozks_from_storage(storage, false)
+            .await
+            .is_err()
+        {
+            return Err(VkdError::Directory(DirectoryError::ReadOnlyDirectory(
+                "Cannot start a read-only directory when AZKS is missing".to_string(),
+            )));
+        }
+        Ok(Self {
+            inner: Directory::from_existing(storage, vrf, None),
+        })
+    }
+
+    /// "Warp-syncs" a read-only directory view to a recent, finalized epoch
+    /// without replaying the full history from epoch 0. Starting from
+    /// `trusted` -- an `(epoch, root_hash)` pair the caller already trusts
+    /// out-of-band -- walks the persisted [`EpochTransition`] checkpoints
+    /// forward one epoch at a time, verifying each one's `append_only_proof`
+    /// links its `prev_root_hash` to the previously-verified root hash via
+    /// [`crate::auditor::audit_verify`]. Refuses to adopt any epoch within
+    /// `finality_depth` of the current tip, so only transitions that are
+    /// unlikely to be rolled back are accepted. Returns a [`ReadOnlyDirectory`]
+    /// together with the furthest epoch the walk was able to verify.
+    pub async fn bootstrap_from_checkpoint<H: Hasher + Send + Sync>(
+        storage: &S,
+        vrf: &V,
+        trusted: EpochHash<H>,
+        finality_depth: u64,
+    ) -> Result<(Self, EpochHash<H>), VkdError> {
+        let EpochHash(trusted_epoch, trusted_root_hash) = trusted;
+
+        let current_ozks = Directory::<S, V>::get_ozks_from_storage(storage, false).await?;
+        let tip_epoch = current_ozks.get_latest_epoch();
+        let finalized_epoch = tip_epoch.saturating_sub(finality_depth);
+
+        if trusted_epoch > finalized_epoch {
+            return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                "Trusted epoch {} is not yet finalized: tip is at epoch {} and the finality depth is {}",
+                trusted_epoch, tip_epoch, finality_depth
+            ))));
+        }
+
+        let mut verified_epoch = trusted_epoch;
+        let mut verified_root_hash = trusted_root_hash;
+
+        while verified_epoch < finalized_epoch {
+            let next_epoch = verified_epoch + 1;
+            let transition = Directory::<S, V>::get_epoch_transition(storage, next_epoch).await?;
+
+            let transition_prev_root_hash = transition.decode_prev_root_hash::<H>()?;
+            if transition_prev_root_hash != verified_root_hash {
+                return Err(VkdError::Directory(DirectoryError::InvalidEpoch(format!(
+                    "Checkpoint for epoch {} does not chain from the previously verified root hash",
+                    next_epoch
+                ))));
+            }
+
+            let transition_root_hash = transition.decode_root_hash::<H>()?;
+            let append_only_proof = transition.decode_proof::<H>()?;
+            crate::auditor::audit_verify::<H>(
+                vec![verified_root_hash, transition_root_hash],
+                append_only_proof,
+            )
+            .await?;
+
+            verified_epoch = next_epoch;
+            verified_root_hash = transition_root_hash;
+        }
+
+        let directory = Self {
+            inner: Directory::from_existing(storage, vrf, None),
+        };
+
+        Ok((directory, EpochHash(verified_epoch, verified_root_hash)))
+    }
+
+    /// See [`Directory::audit`].
+    pub async fn audit<H: Hasher>(
+        &self,
+        audit_start_ep: u64,
+        audit_end_ep: u64,
+    ) -> Result<AppendOnlyProof<H>, VkdError> {
+        self.inner.audit::<H>(audit_start_ep, audit_end_ep).await
+    }
+
+    /// See [`Directory::audit_stream`].
+    pub async fn audit_stream<H: Hasher + Send + Sync>(
+        &self,
+        audit_start_ep: u64,
+        audit_end_ep: u64,
+    ) -> Result<impl stream::Stream<Item = Result<AuditStep<H>, VkdError>> + '_, VkdError> {
+        self.inner.audit_stream::<H>(audit_start_ep, audit_end_ep).await
+    }
+
+    /// See [`Directory::audit_epoch`].
+    pub async fn audit_epoch<H: Hasher + Send + Sync>(
+        &self,
+        epoch: u64,
+    ) -> Result<(H::Digest, H::Digest, SingleAppendOnlyProof<H>), VkdError> {
+        self.inner.audit_epoch::<H>(epoch).await
+    }
+
+    /// See [`Directory::lookup`].
+    pub async fn lookup<H: Hasher>(&self, uname: VkdLabel) -> Result<LookupProof<H>, VkdError> {
+        self.inner.lookup::<H>(uname).await
+    }
+
+    /// See [`Directory::key_history`].
+    pub async fn key_history<H: Hasher>(
+        &self,
+        uname: &VkdLabel,
+        params: HistoryParams,
+    ) -> Result<HistoryProof<H>, VkdError> {
+        self.inner.key_history::<H>(uname, params).await
+    }
+
+    /// See [`Directory::get_public_key`].
+    pub async fn get_public_key(&self) -> Result<VRFPublicKey, VkdError> {
+        self.inner.get_public_key().await
+    }
+
+    /// See [`Directory::retrieve_current_ozks`].
+    pub async fn retrieve_current_ozks(&self) -> Result<Ozks, VkdError> {
+        self.inner.retrieve_current_ozks().await
+    }
+
+    /// See [`Directory::get_metrics`].
+    #[cfg(feature = "runtime_metrics")]
+    pub fn get_metrics(&self) -> crate::object_cache::ObjectCacheMetrics {
+        self.inner.get_metrics()
+    }
+
+    /// See [`Directory::get_root_hash`].
+    pub async fn get_root_hash<H: Hasher>(&self, current_ozks: &Ozks) -> Result<H::Digest, VkdError> {
+        self.inner.get_root_hash::<H>(current_ozks).await
+    }
+
+    /// See [`Directory::create_single_update_proof`].
+    pub async fn create_single_update_proof<H: Hasher>(
+        &self,
+        uname: &VkdLabel,
+        user_state: &ValueState,
+    ) -> Result<UpdateProof<H>, VkdError> {
+        self.inner
+            .create_single_update_proof::<H>(uname, user_state)
+            .await
+    }
+
+    /// See [`Directory::poll_for_ozks_changes`].
+    pub async fn poll_for_ozks_changes(
+        &self,
+        period: tokio::time::Duration,
+        change_detected: Option<tokio::sync::mpsc::Sender<()>>,
+    ) -> Result<(), VkdError> {
+        self.inner
+            .poll_for_ozks_changes(period, change_detected)
+            .await
     }
 }
 
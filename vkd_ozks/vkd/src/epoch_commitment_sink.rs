@@ -0,0 +1,83 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! A pluggable sink for anchoring published epoch root hashes to an external
+//! transparency log (e.g. a blockchain, a witness quorum, or a public
+//! append-only log), so that auditors outside of this directory's own
+//! storage can observe and challenge the sequence of roots it has committed
+//! to.
+
+use async_trait::async_trait;
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+
+/// The error returned by an [`EpochCommitmentSink`] when it fails to anchor
+/// an epoch's root hash externally.
+#[derive(Debug)]
+pub struct SinkError(pub String);
+
+impl std::fmt::Display for SinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "epoch commitment sink error: {}", self.0)
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+/// Anchors a published epoch's root hash to some external transparency log.
+///
+/// [`Directory::publish`](crate::directory::Directory::publish) invokes
+/// [`commit`](EpochCommitmentSink::commit) once a publish's storage
+/// transaction has committed successfully, passing the epoch that was just
+/// published and the serialized root hash at that epoch. A publish that is
+/// retried after a partial failure (e.g. the process crashing between
+/// `commit_transaction` and the sink call) may invoke `commit` again for the
+/// same epoch, so implementations must treat it as idempotent: anchoring the
+/// same epoch twice must not produce a second external entry.
+#[async_trait]
+pub trait EpochCommitmentSink: Send + Sync {
+    /// Anchors `root_hash` for `epoch` externally. Must be a no-op (other
+    /// than perhaps re-confirming the existing anchor) if `epoch` has
+    /// already been committed by a previous call.
+    async fn commit(&self, epoch: u64, root_hash: Vec<u8>) -> Result<(), SinkError>;
+}
+
+/// A simple in-memory, append-only reference implementation of
+/// [`EpochCommitmentSink`]. Useful directly in tests, and as a template for
+/// a real external anchor: commits are deduplicated by epoch, so re-anchoring
+/// the same epoch after a retried publish is a no-op rather than a second
+/// log entry.
+#[derive(Default)]
+pub struct InMemoryEpochCommitmentSink {
+    committed_epochs: Mutex<HashSet<u64>>,
+    log: Mutex<Vec<(u64, Vec<u8>)>>,
+}
+
+impl InMemoryEpochCommitmentSink {
+    /// Creates a new, empty commitment log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the (epoch, root_hash) pairs anchored so far, oldest first.
+    pub async fn anchored_epochs(&self) -> Vec<(u64, Vec<u8>)> {
+        self.log.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl EpochCommitmentSink for InMemoryEpochCommitmentSink {
+    async fn commit(&self, epoch: u64, root_hash: Vec<u8>) -> Result<(), SinkError> {
+        let mut committed_epochs = self.committed_epochs.lock().await;
+        if !committed_epochs.insert(epoch) {
+            // Already anchored this epoch; idempotent no-op.
+            return Ok(());
+        }
+        self.log.lock().await.push((epoch, root_hash));
+        Ok(())
+    }
+}
@@ -44,7 +44,7 @@
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
 //! };
 //! ```
 //!
@@ -60,6 +60,7 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //! type Blake3 = Blake3_256<BaseElement>;
 //! use vkd::directory::Directory;
 //!
@@ -67,9 +68,9 @@
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
 //!     // commit the latest changes
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!          (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!       .await;
 //! };
@@ -90,12 +91,13 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //!
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Generate latest proof
@@ -116,12 +118,13 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //!
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Generate latest proof
@@ -156,16 +159,17 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //!
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Generate latest proof
-//!     let history_proof = vkd.key_history::<Blake3_256<BaseElement>>(&VkdLabel::from_utf8_str("hello")).await;
+//!     let history_proof = vkd.key_history::<Blake3_256<BaseElement>>(&VkdLabel::from_utf8_str("hello"), vkd::client::HistoryParams::Complete).await;
 //! };
 //! ```
 //! ## Verifying a key history proof
@@ -174,7 +178,7 @@
 //! use winter_crypto::Hasher;
 //! use winter_crypto::hashers::Blake3_256;
 //! use winter_math::fields::f128::BaseElement;
-//! use vkd::client::key_history_verify;
+//! use vkd::client::{key_history_verify, HistoryParams, HistoryVerificationParams};
 //! use vkd::directory::Directory;
 //! type Blake3 = Blake3_256<BaseElement>;
 //! type Blake3Digest = <Blake3_256<winter_math::fields::f128::BaseElement> as Hasher>::Digest;
@@ -182,15 +186,16 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Generate latest proof
-//!     let history_proof = vkd.key_history::<Blake3_256<BaseElement>>(&VkdLabel::from_utf8_str("hello")).await.unwrap();
+//!     let history_proof = vkd.key_history::<Blake3_256<BaseElement>>(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete).await.unwrap();
 //!     let current_ozks = vkd.retrieve_current_ozks().await.unwrap();
 //!     // Get the ozks root hashes at the required epochs
 //!     let (root_hashes, previous_root_hashes) = vkd::directory::get_key_history_hashes::<_, Blake3_256<BaseElement>, HardCodedVkdVRF>(&vkd, &history_proof).await.unwrap();
@@ -204,6 +209,8 @@
 //!         current_epoch,
 //!         VkdLabel::from_utf8_str("hello"),
 //!         history_proof,
+//!         HistoryParams::Complete,
+//!         HistoryVerificationParams::Strict,
 //!         false,
 //!         ).unwrap();
 //!     };
@@ -223,17 +230,18 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //!
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
 //!     // Commit to the first epoch
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Commit to the second epoch
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello3"), VkdValue::from_utf8_str("world3")),
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello3"), VkdValue::from_utf8_str("world3")),
 //!         (VkdLabel::from_utf8_str("hello4"), VkdValue::from_utf8_str("world4")),])
 //!          .await.unwrap();
 //!     // Generate audit proof for the evolution from epoch 1 to epoch 2.
@@ -254,17 +262,18 @@
 //! use vkd::storage::Storage;
 //! use vkd::storage::memory::AsyncInMemoryDatabase;
 //! use vkd::ecvrf::HardCodedVkdVRF;
+//! use vkd::configuration::DefaultConfiguration;
 //!
 //! let db = AsyncInMemoryDatabase::new();
 //! async {
 //!     let vrf = HardCodedVkdVRF{};
-//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await.unwrap();
+//!     let mut vkd = Directory::<_, HardCodedVkdVRF>::new::<Blake3_256<BaseElement>>(&db, &vrf).await.unwrap();
 //!     // Commit to the first epoch
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello"), VkdValue::from_utf8_str("world")),
 //!         (VkdLabel::from_utf8_str("hello2"), VkdValue::from_utf8_str("world2")),])
 //!          .await.unwrap();
 //!     // Commit to the second epoch
-//!     vkd.publish::<Blake3_256<BaseElement>>(vec![(VkdLabel::from_utf8_str("hello3"), VkdValue::from_utf8_str("world3")),
+//!     vkd.publish::<Blake3_256<BaseElement>, DefaultConfiguration>(vec![(VkdLabel::from_utf8_str("hello3"), VkdValue::from_utf8_str("world3")),
 //!         (VkdLabel::from_utf8_str("hello4"), VkdValue::from_utf8_str("world4")),])
 //!          .await.unwrap();
 //!     // Generate audit proof for the evolution from epoch 1 to epoch 2.
@@ -307,6 +316,12 @@
 //! vkd = { version = "0.5", features = ["vrf", "public-tests"] }
 //! ```
 //!
+//! 4. _runtime_metrics_: Adds to [`object_cache::ObjectCache`]'s always-on hit, miss and eviction
+//! counters (and, transitively, to [`directory::Directory::get_metrics`]/[`directory::ReadOnlyDirectory::get_metrics`])
+//! call counts and cumulative wall-clock time for its `get` and `insert` operations, so you can
+//! tell whether a slow `publish` or `lookup` is spending its time re-fetching storage the cache should
+//! have kept warm.
+//!
 
 #![warn(missing_docs)]
 #![allow(clippy::multiple_crate_versions)]
@@ -322,12 +337,17 @@ extern crate rand;
 
 pub mod ordered_append_only_zks;
 pub mod auditor;
+pub mod checkpoint;
 pub mod client;
+pub mod commitment_key;
+pub mod configuration;
 pub mod directory;
 pub mod ecvrf;
+pub mod epoch_commitment_sink;
 pub mod errors;
 pub mod helper_structs;
 pub mod node_label;
+pub mod object_cache;
 pub mod proof_structs;
 pub mod serialization;
 pub mod storage;
@@ -337,7 +357,8 @@ mod utils;
 
 // ========== Type re-exports which are commonly used ========== //
 pub use ordered_append_only_zks::Ozks;
-pub use directory::Directory;
+pub use configuration::{AlternateConfiguration, Configuration, DefaultConfiguration};
+pub use directory::{Directory, ReadOnlyDirectory};
 pub use helper_structs::{EpochHash, Node};
 pub use node_label::NodeLabel;
 pub use storage::types::{VkdLabel, VkdValue};
@@ -0,0 +1,359 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! A size-bounded, epoch-aware object cache sitting in front of the storage
+//! layer.
+//!
+//! [`Directory::poll_for_ozks_changes`](crate::directory::Directory::poll_for_ozks_changes)
+//! previously reacted to an epoch bump by flushing the storage layer's
+//! entire object cache, so the next round of proof generations always paid
+//! a cold-start latency spike re-fetching nodes that almost certainly
+//! hadn't changed. [`ObjectCache`] instead tracks the epoch each cached
+//! [`DbRecord`] was last touched at, so an epoch bump only needs to evict
+//! the entries that are actually stale, and separately bounds its own
+//! memory footprint so it can't grow without limit between epoch bumps.
+//!
+//! With the `high_parallelism` feature enabled, the cache is backed by a
+//! sharded concurrent map instead of a single `RwLock<HashMap>`, so a write
+//! to one shard (eviction, insertion) doesn't block reads against the
+//! others -- unlike the single `cache_lock` write-guard that used to stall
+//! every concurrent proof read during a flush.
+//!
+//! This is deliberately just a cache, not a full `StorageManager` wrapper
+//! around [`Storage`](crate::storage::Storage): the other two pieces such a
+//! wrapper would provide already live where they're used instead of behind
+//! an extra indirection. Batching a publish's node and `ValueState`
+//! mutations into one round trip is
+//! [`Directory::publish`](crate::directory::Directory::publish)'s own
+//! `storage.batch_set(updates)` call, since `publish` is already the only
+//! place those mutations are assembled; and the "flush on detected epoch
+//! change" hook this module's doc above describes is exactly what
+//! [`Directory::poll_for_ozks_changes`](crate::directory::Directory::poll_for_ozks_changes)
+//! does against `self` with no separate type needed.
+
+use crate::storage::types::DbRecord;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "high_parallelism")]
+use dashmap::DashMap;
+#[cfg(not(feature = "high_parallelism"))]
+use tokio::sync::RwLock;
+
+/// Default ceiling on the cache's approximate in-memory footprint, in
+/// bytes, before [`ObjectCache::enforce_ceiling`] starts evicting the
+/// least-recently-used entries. Override with
+/// [`ObjectCache::with_ceiling_bytes`].
+pub const DEFAULT_CACHE_CEILING_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A single cached [`DbRecord`] together with the bookkeeping needed to
+/// bound the cache's memory footprint and to evict it once it is no longer
+/// fresh for the latest epoch.
+pub struct CachedItem {
+    /// The approximate heap footprint of `value`, in bytes. See
+    /// [`CachedItem::measure`].
+    pub bytes_used: u64,
+    /// The epoch this item was last written or refreshed at. An epoch bump
+    /// to some `new_latest_epoch` evicts every item with `last_epoch <
+    /// new_latest_epoch`.
+    pub last_epoch: u64,
+    /// A monotonically increasing access counter, used to order entries for
+    /// LRU eviction when the cache is over its byte ceiling.
+    pub last_accessed: u64,
+    /// The cached record.
+    pub value: DbRecord,
+}
+
+impl CachedItem {
+    /// Approximates the in-memory footprint of `value` by its serialized
+    /// size, which is cheap to compute and tracks the dominant cost (the
+    /// record's own data) closely enough to bound the cache usefully.
+    pub fn measure(value: &DbRecord) -> u64 {
+        bincode::serialized_size(value).unwrap_or(0) + std::mem::size_of::<DbRecord>() as u64
+    }
+}
+
+/// A snapshot of [`ObjectCache`]'s hit/miss/eviction counters, taken with
+/// [`ObjectCache::metrics`] (or [`Directory::get_metrics`](crate::directory::Directory::get_metrics)).
+/// With the `runtime_metrics` feature enabled, also reports how many
+/// [`ObjectCache::get`]/[`ObjectCache::insert`] calls were made and how much
+/// wall-clock time they spent in total, so a caller can notice a lookup
+/// that unexpectedly fans out into many slow cache round trips instead of
+/// hitting the in-memory map directly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObjectCacheMetrics {
+    /// Number of [`ObjectCache::get`] calls that found a cached value.
+    pub hits: u64,
+    /// Number of [`ObjectCache::get`] calls that found nothing cached.
+    pub misses: u64,
+    /// Number of entries evicted so far, by [`ObjectCache::evict_stale`] or
+    /// by [`ObjectCache::enforce_ceiling`]'s LRU eviction.
+    pub evictions: u64,
+    /// Number of [`ObjectCache::get`] calls made so far.
+    #[cfg(feature = "runtime_metrics")]
+    pub get_calls: u64,
+    /// Total wall-clock time spent across every [`ObjectCache::get`] call.
+    #[cfg(feature = "runtime_metrics")]
+    pub get_time: std::time::Duration,
+    /// Number of [`ObjectCache::insert`] calls made so far.
+    #[cfg(feature = "runtime_metrics")]
+    pub insert_calls: u64,
+    /// Total wall-clock time spent across every [`ObjectCache::insert`] call.
+    #[cfg(feature = "runtime_metrics")]
+    pub insert_time: std::time::Duration,
+}
+
+/// A size-bounded, epoch-aware cache of [`DbRecord`]s, keyed by a record's
+/// full binary storage id.
+pub struct ObjectCache {
+    ceiling_bytes: AtomicU64,
+    bytes_used: AtomicU64,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    #[cfg(feature = "runtime_metrics")]
+    get_calls: AtomicU64,
+    #[cfg(feature = "runtime_metrics")]
+    get_nanos: AtomicU64,
+    #[cfg(feature = "runtime_metrics")]
+    insert_calls: AtomicU64,
+    #[cfg(feature = "runtime_metrics")]
+    insert_nanos: AtomicU64,
+    #[cfg(feature = "high_parallelism")]
+    entries: DashMap<Vec<u8>, CachedItem>,
+    #[cfg(not(feature = "high_parallelism"))]
+    entries: RwLock<HashMap<Vec<u8>, CachedItem>>,
+}
+
+impl Default for ObjectCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CEILING_BYTES)
+    }
+}
+
+impl ObjectCache {
+    /// Creates a new, empty cache bounded to `ceiling_bytes` of approximate
+    /// in-memory footprint.
+    pub fn new(ceiling_bytes: u64) -> Self {
+        Self {
+            ceiling_bytes: AtomicU64::new(ceiling_bytes),
+            bytes_used: AtomicU64::new(0),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            #[cfg(feature = "runtime_metrics")]
+            get_calls: AtomicU64::new(0),
+            #[cfg(feature = "runtime_metrics")]
+            get_nanos: AtomicU64::new(0),
+            #[cfg(feature = "runtime_metrics")]
+            insert_calls: AtomicU64::new(0),
+            #[cfg(feature = "runtime_metrics")]
+            insert_nanos: AtomicU64::new(0),
+            #[cfg(feature = "high_parallelism")]
+            entries: DashMap::new(),
+            #[cfg(not(feature = "high_parallelism"))]
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// A snapshot of this cache's hit/miss/eviction counters since creation.
+    pub fn metrics(&self) -> ObjectCacheMetrics {
+        ObjectCacheMetrics {
+            hits: self.hits.load(Ordering::SeqCst),
+            misses: self.misses.load(Ordering::SeqCst),
+            evictions: self.evictions.load(Ordering::SeqCst),
+            #[cfg(feature = "runtime_metrics")]
+            get_calls: self.get_calls.load(Ordering::SeqCst),
+            #[cfg(feature = "runtime_metrics")]
+            get_time: std::time::Duration::from_nanos(self.get_nanos.load(Ordering::SeqCst)),
+            #[cfg(feature = "runtime_metrics")]
+            insert_calls: self.insert_calls.load(Ordering::SeqCst),
+            #[cfg(feature = "runtime_metrics")]
+            insert_time: std::time::Duration::from_nanos(
+                self.insert_nanos.load(Ordering::SeqCst),
+            ),
+        }
+    }
+
+    /// Overrides the memory-pressure byte ceiling, in place of
+    /// [`DEFAULT_CACHE_CEILING_BYTES`].
+    pub fn with_ceiling_bytes(self, ceiling_bytes: u64) -> Self {
+        self.ceiling_bytes.store(ceiling_bytes, Ordering::SeqCst);
+        self
+    }
+
+    /// Inserts (or refreshes) `value` under `key`, stamping it with
+    /// `epoch` as its last-touched epoch, then evicts the
+    /// least-recently-used entries until the cache is back under its byte
+    /// ceiling.
+    pub async fn insert(&self, key: Vec<u8>, value: DbRecord, epoch: u64) {
+        #[cfg(feature = "runtime_metrics")]
+        let started_at = std::time::Instant::now();
+
+        let bytes_used = CachedItem::measure(&value);
+        let last_accessed = self.clock.fetch_add(1, Ordering::SeqCst);
+        let item = CachedItem {
+            bytes_used,
+            last_epoch: epoch,
+            last_accessed,
+            value,
+        };
+
+        #[cfg(feature = "high_parallelism")]
+        {
+            if let Some(old) = self.entries.insert(key, item) {
+                self.bytes_used.fetch_sub(old.bytes_used, Ordering::SeqCst);
+            }
+        }
+        #[cfg(not(feature = "high_parallelism"))]
+        {
+            if let Some(old) = self.entries.write().await.insert(key, item) {
+                self.bytes_used.fetch_sub(old.bytes_used, Ordering::SeqCst);
+            }
+        }
+        self.bytes_used.fetch_add(bytes_used, Ordering::SeqCst);
+
+        self.enforce_ceiling().await;
+
+        #[cfg(feature = "runtime_metrics")]
+        {
+            self.insert_calls.fetch_add(1, Ordering::SeqCst);
+            self.insert_nanos
+                .fetch_add(started_at.elapsed().as_nanos() as u64, Ordering::SeqCst);
+        }
+    }
+
+    /// Looks up `key`, bumping its last-accessed order on a hit so it is
+    /// less likely to be picked for LRU eviction.
+    pub async fn get(&self, key: &[u8]) -> Option<DbRecord> {
+        #[cfg(feature = "runtime_metrics")]
+        let started_at = std::time::Instant::now();
+
+        let last_accessed = self.clock.fetch_add(1, Ordering::SeqCst);
+
+        #[cfg(feature = "high_parallelism")]
+        let found = self.entries.get_mut(key).map(|mut item| {
+            item.last_accessed = last_accessed;
+            item.value.clone()
+        });
+        #[cfg(not(feature = "high_parallelism"))]
+        let found = {
+            let mut guard = self.entries.write().await;
+            guard.get_mut(key).map(|item| {
+                item.last_accessed = last_accessed;
+                item.value.clone()
+            })
+        };
+
+        match &found {
+            Some(_) => self.hits.fetch_add(1, Ordering::SeqCst),
+            None => self.misses.fetch_add(1, Ordering::SeqCst),
+        };
+
+        #[cfg(feature = "runtime_metrics")]
+        {
+            self.get_calls.fetch_add(1, Ordering::SeqCst);
+            self.get_nanos
+                .fetch_add(started_at.elapsed().as_nanos() as u64, Ordering::SeqCst);
+        }
+
+        found
+    }
+
+    /// Evicts every cached entry whose `last_epoch` is older than
+    /// `new_latest_epoch`, leaving entries that were already refreshed for
+    /// the new epoch (e.g. the root freshly re-fetched by
+    /// [`Directory::poll_for_ozks_changes`](crate::directory::Directory::poll_for_ozks_changes))
+    /// untouched. Unlike a full flush, this keeps the still-hot interior
+    /// nodes warm across the epoch bump.
+    pub async fn evict_stale(&self, new_latest_epoch: u64) {
+        #[cfg(feature = "high_parallelism")]
+        {
+            self.entries.retain(|_, item| {
+                let keep = item.last_epoch >= new_latest_epoch;
+                if !keep {
+                    self.bytes_used.fetch_sub(item.bytes_used, Ordering::SeqCst);
+                    self.evictions.fetch_add(1, Ordering::SeqCst);
+                }
+                keep
+            });
+        }
+        #[cfg(not(feature = "high_parallelism"))]
+        {
+            let mut guard = self.entries.write().await;
+            guard.retain(|_, item| {
+                let keep = item.last_epoch >= new_latest_epoch;
+                if !keep {
+                    self.bytes_used.fetch_sub(item.bytes_used, Ordering::SeqCst);
+                    self.evictions.fetch_add(1, Ordering::SeqCst);
+                }
+                keep
+            });
+        }
+    }
+
+    /// While the cache's tracked footprint is over its byte ceiling, evicts
+    /// the least-recently-used entry. Called automatically after every
+    /// [`ObjectCache::insert`]; exposed so a memory-pressure hook elsewhere
+    /// in the process (e.g. a global allocator callback) can trigger it
+    /// proactively.
+    pub async fn enforce_ceiling(&self) {
+        let ceiling = self.ceiling_bytes.load(Ordering::SeqCst);
+        while self.bytes_used.load(Ordering::SeqCst) > ceiling {
+            let oldest_key = {
+                #[cfg(feature = "high_parallelism")]
+                {
+                    self.entries
+                        .iter()
+                        .min_by_key(|entry| entry.value().last_accessed)
+                        .map(|entry| entry.key().clone())
+                }
+                #[cfg(not(feature = "high_parallelism"))]
+                {
+                    let guard = self.entries.read().await;
+                    guard
+                        .iter()
+                        .min_by_key(|(_, item)| item.last_accessed)
+                        .map(|(key, _)| key.clone())
+                }
+            };
+
+            match oldest_key {
+                Some(key) => self.remove(&key).await,
+                None => break,
+            }
+        }
+    }
+
+    /// Removes `key` from the cache, if present. Only called by
+    /// [`ObjectCache::enforce_ceiling`]'s LRU eviction, so every removal here
+    /// counts as an eviction; see [`ObjectCache::evict_stale`] for the other
+    /// eviction path.
+    async fn remove(&self, key: &[u8]) {
+        #[cfg(feature = "high_parallelism")]
+        {
+            if let Some((_, item)) = self.entries.remove(key) {
+                self.bytes_used.fetch_sub(item.bytes_used, Ordering::SeqCst);
+                self.evictions.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        #[cfg(not(feature = "high_parallelism"))]
+        {
+            if let Some(item) = self.entries.write().await.remove(key) {
+                self.bytes_used.fetch_sub(item.bytes_used, Ordering::SeqCst);
+                self.evictions.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// The cache's currently tracked approximate footprint, in bytes.
+    pub fn bytes_used(&self) -> u64 {
+        self.bytes_used.load(Ordering::SeqCst)
+    }
+}
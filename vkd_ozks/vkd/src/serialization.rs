@@ -0,0 +1,464 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! A stable, versioned protobuf wire format for the proofs in
+//! [`proof_structs`](crate::proof_structs), alongside the crate's internal
+//! serde/bincode encoding used for local storage. `to_bytes`/`from_bytes` are
+//! the only entry points: a stored or transmitted proof survives internal
+//! `proof_structs` refactors, and an auditor or client with no Rust
+//! implementation can decode `vkd.lookup(...)`, `vkd.key_history(...)` and
+//! `auditor::audit_verify(...)` outputs directly from this format. See
+//! `proto/proof_structs.proto` for the message definitions this module is
+//! generated against.
+//!
+//! Every message carries `format_version` and validates field presence and
+//! fixed digest lengths on decode, returning
+//! [`VkdError::Storage`](crate::errors::StorageError) rather than panicking
+//! on malformed input -- a proof decoded here may have come from an untrusted
+//! peer. Nested VRF, membership and non-membership proofs (which do not yet
+//! have a dedicated field-level schema) are carried as an opaque
+//! `proof_payload`: the crate's existing bincode encoding of those structs,
+//! the same one [`crate::checkpoint::EpochTransition`] already uses for its
+//! own embedded [`AppendOnlyProof`]. This keeps the format forward-compatible
+//! as that internal representation evolves, while still giving every proof a
+//! fixed, versioned envelope and validating the fields a non-Rust verifier
+//! actually needs to branch on.
+
+use crate::errors::{StorageError, VkdError};
+use crate::proof_structs::{AppendOnlyProof, HistoryProof, LookupProof, UpdateProof};
+use crate::storage::types::VkdValue;
+use winter_crypto::{Digest as _, Hasher};
+use winter_utils::{Deserializable, SliceReader};
+
+/// Types generated by `prost-build` from `proto/proof_structs.proto`.
+mod proto {
+    include!(concat!(env!("OUT_DIR"), "/vkd.proof_structs.rs"));
+}
+
+/// The only format version this module currently emits or accepts.
+const FORMAT_VERSION: u32 = 1;
+
+/// The digest length this wire format accepts: the hashers this crate is
+/// tested with (Blake3 and Sha3_256) both produce 32-byte digests, and a
+/// `start_hash` or `end_hash` field of a different length is malformed.
+const DIGEST_LEN: usize = 32;
+
+fn encode_digest<H: Hasher>(digest: &H::Digest) -> Vec<u8> {
+    digest.as_bytes().to_vec()
+}
+
+fn decode_digest<H: Hasher>(field: &str, bytes: &[u8]) -> Result<H::Digest, VkdError> {
+    if bytes.len() != DIGEST_LEN {
+        return Err(VkdError::Storage(StorageError::Transaction(format!(
+            "Expected a {}-byte digest for `{}`, got {}",
+            DIGEST_LEN,
+            field,
+            bytes.len()
+        ))));
+    }
+    H::Digest::read_from(&mut SliceReader::new(bytes))
+        .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))
+}
+
+fn encode_payload<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, VkdError> {
+    bincode::serialize(value)
+        .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))
+}
+
+fn decode_payload<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, VkdError> {
+    bincode::deserialize(bytes)
+        .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))
+}
+
+fn require_format_version(field: &str, format_version: u32) -> Result<(), VkdError> {
+    if format_version != FORMAT_VERSION {
+        return Err(VkdError::Storage(StorageError::Transaction(format!(
+            "Unsupported `{}` format version {} (expected {})",
+            field, format_version, FORMAT_VERSION
+        ))));
+    }
+    Ok(())
+}
+
+/// Build the protobuf message for a single update proof, without encoding it
+/// to bytes yet, so [`HistoryProof::to_bytes`] can embed it directly in a
+/// `repeated` field instead of re-decoding an inner encoded copy.
+fn update_proof_to_message<H: Hasher>(
+    proof: &UpdateProof<H>,
+) -> Result<proto::UpdateProof, VkdError> {
+    Ok(proto::UpdateProof {
+        format_version: FORMAT_VERSION,
+        epoch: proof.epoch,
+        version: proof.version,
+        plaintext_value: proof.plaintext_value.0.clone(),
+        tombstoned: proof.plaintext_value.0 == crate::TOMBSTONE,
+        proof_payload: encode_payload(&(
+            &proof.existence_vrf_proof,
+            &proof.existence_at_ep,
+            &proof.previous_version_vrf_proof,
+            &proof.previous_version_stale_at_ep,
+            &proof.commitment_proof,
+        ))?,
+    })
+}
+
+/// The inverse of [`update_proof_to_message`].
+fn update_proof_from_message<H: Hasher>(
+    message: &proto::UpdateProof,
+) -> Result<UpdateProof<H>, VkdError> {
+    require_format_version("UpdateProof.format_version", message.format_version)?;
+    let (
+        existence_vrf_proof,
+        existence_at_ep,
+        previous_version_vrf_proof,
+        previous_version_stale_at_ep,
+        commitment_proof,
+    ) = decode_payload(&message.proof_payload)?;
+    Ok(UpdateProof {
+        epoch: message.epoch,
+        version: message.version,
+        plaintext_value: VkdValue(message.plaintext_value.clone()),
+        existence_vrf_proof,
+        existence_at_ep,
+        previous_version_vrf_proof,
+        previous_version_stale_at_ep,
+        commitment_proof,
+    })
+}
+
+impl<H: Hasher> UpdateProof<H> {
+    /// Encode this update proof to the crate's protobuf wire format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VkdError> {
+        let message = update_proof_to_message(self)?;
+        Ok(prost::Message::encode_to_vec(&message))
+    }
+
+    /// Decode an update proof previously produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VkdError> {
+        let message: proto::UpdateProof = prost::Message::decode(bytes)
+            .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))?;
+        update_proof_from_message(&message)
+    }
+}
+
+impl<H: Hasher> LookupProof<H> {
+    /// Encode this lookup proof to the crate's protobuf wire format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VkdError> {
+        let message = proto::LookupProof {
+            format_version: FORMAT_VERSION,
+            epoch: self.epoch,
+            version: self.version,
+            plaintext_value: self.plaintext_value.0.clone(),
+            tombstoned: self.plaintext_value.0 == crate::TOMBSTONE,
+            proof_payload: encode_payload(&(
+                &self.existence_vrf_proof,
+                &self.existence_proof,
+                &self.marker_vrf_proof,
+                &self.marker_proof,
+                &self.freshness_vrf_proof,
+                &self.freshness_proof,
+                &self.commitment_proof,
+            ))?,
+        };
+        Ok(prost::Message::encode_to_vec(&message))
+    }
+
+    /// Decode a lookup proof previously produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VkdError> {
+        let message: proto::LookupProof = prost::Message::decode(bytes)
+            .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))?;
+        require_format_version("LookupProof.format_version", message.format_version)?;
+        let (
+            existence_vrf_proof,
+            existence_proof,
+            marker_vrf_proof,
+            marker_proof,
+            freshness_vrf_proof,
+            freshness_proof,
+            commitment_proof,
+        ) = decode_payload(&message.proof_payload)?;
+        Ok(Self {
+            epoch: message.epoch,
+            version: message.version,
+            plaintext_value: VkdValue(message.plaintext_value),
+            existence_vrf_proof,
+            existence_proof,
+            marker_vrf_proof,
+            marker_proof,
+            freshness_vrf_proof,
+            freshness_proof,
+            commitment_proof,
+        })
+    }
+}
+
+impl<H: Hasher> HistoryProof<H> {
+    /// Encode this history proof to the crate's protobuf wire format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VkdError> {
+        let update_proofs = self
+            .update_proofs
+            .iter()
+            .map(update_proof_to_message)
+            .collect::<Result<Vec<proto::UpdateProof>, VkdError>>()?;
+        let message = proto::HistoryProof {
+            format_version: FORMAT_VERSION,
+            update_proofs,
+            proof_payload: encode_payload(&(
+                &self.next_few_vrf_proofs,
+                &self.non_existence_of_next_few,
+                &self.future_marker_vrf_proofs,
+                &self.non_existence_of_future_markers,
+                &self.next_version_vrf_proof,
+                &self.non_existence_of_next_version,
+                &self.window_lower_bound_vrf_proof,
+                &self.window_lower_bound_existence_proof,
+                &self.window_lower_bound_epoch,
+            ))?,
+        };
+        Ok(prost::Message::encode_to_vec(&message))
+    }
+
+    /// Decode a history proof previously produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VkdError> {
+        let message: proto::HistoryProof = prost::Message::decode(bytes)
+            .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))?;
+        require_format_version("HistoryProof.format_version", message.format_version)?;
+        if message.update_proofs.is_empty() {
+            return Err(VkdError::Storage(StorageError::Transaction(
+                "HistoryProof must contain at least one UpdateProof".to_string(),
+            )));
+        }
+        let update_proofs = message
+            .update_proofs
+            .iter()
+            .map(update_proof_from_message)
+            .collect::<Result<Vec<_>, VkdError>>()?;
+        let (
+            next_few_vrf_proofs,
+            non_existence_of_next_few,
+            future_marker_vrf_proofs,
+            non_existence_of_future_markers,
+            next_version_vrf_proof,
+            non_existence_of_next_version,
+            window_lower_bound_vrf_proof,
+            window_lower_bound_existence_proof,
+            window_lower_bound_epoch,
+        ) = decode_payload(&message.proof_payload)?;
+        Ok(Self {
+            update_proofs,
+            next_few_vrf_proofs,
+            non_existence_of_next_few,
+            future_marker_vrf_proofs,
+            non_existence_of_future_markers,
+            next_version_vrf_proof,
+            non_existence_of_next_version,
+            window_lower_bound_vrf_proof,
+            window_lower_bound_existence_proof,
+            window_lower_bound_epoch,
+        })
+    }
+}
+
+impl<H: Hasher> AppendOnlyProof<H> {
+    /// Encode this append-only proof to the crate's protobuf wire format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VkdError> {
+        let message = proto::AppendOnlyProof {
+            format_version: FORMAT_VERSION,
+            start_hash: encode_digest::<H>(&self.start_hash),
+            end_hash: encode_digest::<H>(&self.end_hash),
+            proof_payload: encode_payload(&self.proofs)?,
+        };
+        Ok(prost::Message::encode_to_vec(&message))
+    }
+
+    /// Decode an append-only proof previously produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VkdError> {
+        let message: proto::AppendOnlyProof = prost::Message::decode(bytes)
+            .map_err(|err| VkdError::Storage(StorageError::Transaction(err.to_string())))?;
+        require_format_version("AppendOnlyProof.format_version", message.format_version)?;
+        Ok(Self {
+            start_hash: decode_digest::<H>("start_hash", &message.start_hash)?,
+            end_hash: decode_digest::<H>("end_hash", &message.end_hash)?,
+            proofs: decode_payload(&message.proof_payload)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::DefaultConfiguration;
+    use crate::directory::Directory;
+    use crate::ecvrf::HardCodedVkdVRF;
+    use crate::storage::memory::AsyncInMemoryDatabase;
+    use crate::storage::types::VkdLabel;
+    use crate::Blake3;
+    use winter_crypto::{hashers::Sha3_256, Hasher};
+    use winter_math::fields::f128::BaseElement;
+
+    async fn published_directory() -> Directory<AsyncInMemoryDatabase, HardCodedVkdVRF> {
+        let db = AsyncInMemoryDatabase::new();
+        let vrf = HardCodedVkdVRF {};
+        let directory = Directory::new::<Blake3>(&db, &vrf).await.unwrap();
+        directory
+            .publish::<Blake3, DefaultConfiguration>(vec![(
+                VkdLabel(b"protobuf_round_trip".to_vec()),
+                VkdValue(b"v1".to_vec()),
+            )])
+            .await
+            .unwrap();
+        directory
+    }
+
+    // `published_directory` above always commits with `Blake3`, so the
+    // Sha3_256 round-trip tests below build their own directory directly
+    // with that hasher instead of reusing it.
+    async fn published_directory_with_hasher<H: Hasher>(
+    ) -> Directory<AsyncInMemoryDatabase, HardCodedVkdVRF> {
+        let db = AsyncInMemoryDatabase::new();
+        let vrf = HardCodedVkdVRF {};
+        let directory = Directory::new::<H>(&db, &vrf).await.unwrap();
+        directory
+            .publish::<H, DefaultConfiguration>(vec![(
+                VkdLabel(b"protobuf_round_trip".to_vec()),
+                VkdValue(b"v1".to_vec()),
+            )])
+            .await
+            .unwrap();
+        directory
+    }
+
+    // Every proof type round-trips through the protobuf encoding to exactly
+    // the same value the crate's internal serde path would produce, so a
+    // decoder on either side of the wire agrees on what a proof says.
+    #[tokio::test]
+    async fn lookup_proof_round_trips() {
+        let directory = published_directory().await;
+        let label = VkdLabel(b"protobuf_round_trip".to_vec());
+        let proof = directory.lookup::<Blake3>(label).await.unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = LookupProof::<Blake3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn history_proof_round_trips() {
+        let directory = published_directory().await;
+        let label = VkdLabel(b"protobuf_round_trip".to_vec());
+        let proof = directory
+            .key_history::<Blake3>(&label, crate::client::HistoryParams::Complete)
+            .await
+            .unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = HistoryProof::<Blake3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn append_only_proof_round_trips() {
+        let directory = published_directory().await;
+        directory
+            .publish::<Blake3, DefaultConfiguration>(vec![(
+                VkdLabel(b"protobuf_round_trip".to_vec()),
+                VkdValue(b"v2".to_vec()),
+            )])
+            .await
+            .unwrap();
+        let proof = directory.audit::<Blake3>(1, 2).await.unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = AppendOnlyProof::<Blake3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    // The Blake3 round-trip tests above exercise the common path; these
+    // mirror them with `Sha3_256` to confirm the wire format doesn't
+    // secretly assume Blake3's digest representation beyond its length.
+    #[tokio::test]
+    async fn lookup_proof_round_trips_for_sha3_256() {
+        type Sha3 = Sha3_256<BaseElement>;
+        let directory = published_directory_with_hasher::<Sha3>().await;
+        let label = VkdLabel(b"protobuf_round_trip".to_vec());
+        let proof = directory.lookup::<Sha3>(label).await.unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = LookupProof::<Sha3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn history_proof_round_trips_for_sha3_256() {
+        type Sha3 = Sha3_256<BaseElement>;
+        let directory = published_directory_with_hasher::<Sha3>().await;
+        let label = VkdLabel(b"protobuf_round_trip".to_vec());
+        let proof = directory
+            .key_history::<Sha3>(&label, crate::client::HistoryParams::Complete)
+            .await
+            .unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = HistoryProof::<Sha3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn append_only_proof_round_trips_for_sha3_256() {
+        type Sha3 = Sha3_256<BaseElement>;
+        let directory = published_directory_with_hasher::<Sha3>().await;
+        directory
+            .publish::<Sha3, DefaultConfiguration>(vec![(
+                VkdLabel(b"protobuf_round_trip".to_vec()),
+                VkdValue(b"v2".to_vec()),
+            )])
+            .await
+            .unwrap();
+        let proof = directory.audit::<Sha3>(1, 2).await.unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = AppendOnlyProof::<Sha3>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_a_digest_of_the_wrong_length() {
+        let err = decode_digest::<Blake3>("start_hash", &[0u8; 16]).unwrap_err();
+        assert!(matches!(
+            err,
+            VkdError::Storage(StorageError::Transaction(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_an_unsupported_format_version() {
+        let err =
+            require_format_version("LookupProof.format_version", FORMAT_VERSION + 1).unwrap_err();
+        assert!(matches!(
+            err,
+            VkdError::Storage(StorageError::Transaction(_))
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) Anonymous Authors of NDSS Submission #545.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+//! Reusable adversarial sanity checks for a [`Storage`] implementation, gated
+//! behind the `public-tests` feature so a downstream storage (or
+//! [`Configuration`](crate::configuration::Configuration)) implementer can
+//! call them from their own test suite instead of hand-rolling equivalent
+//! coverage. [`test_publish_corruptions_are_detected`] drives every
+//! [`PublishCorruption`] variant through a fresh instance of the caller's
+//! storage and asserts that the corresponding client or auditor verifier
+//! actually rejects the resulting proof or transition -- so a new storage
+//! backend inherits this negative-testing coverage for free, rather than
+//! these rejections only ever being exercised (or not) by this crate's own
+//! `tests.rs`.
+
+use crate::client::{key_history_verify, lookup_verify, HistoryParams, HistoryVerificationParams};
+use crate::commitment_key::CommitmentKeyStorage;
+use crate::configuration::Configuration;
+use crate::directory::{Directory, PublishCorruption};
+use crate::ecvrf::VRFKeyStorage;
+use crate::errors::VkdError;
+use crate::storage::types::{VkdLabel, VkdValue};
+use crate::storage::Storage;
+use crate::{auditor::audit_verify, EpochHash};
+
+use winter_crypto::Hasher;
+
+/// Publishes two epochs against a fresh instance of storage for each
+/// [`PublishCorruption`] variant in turn, injects that corruption, and
+/// asserts that the corresponding verifier rejects it:
+///
+/// - [`PublishCorruption::UnmarkedStaleVersion`], [`PublishCorruption::MarkVersionStaleWithoutCommit`],
+///   [`PublishCorruption::TamperedCommitmentValue`] and [`PublishCorruption::ReusedVrfLabel`]
+///   are injected via [`Directory::publish_corrupted`] and checked with [`lookup_verify`].
+/// - [`PublishCorruption::TooFewVersions`] and [`PublishCorruption::TooManyVersions`]
+///   are injected via [`Directory::key_history_corrupted`] and checked with
+///   [`key_history_verify`].
+/// - [`PublishCorruption::DroppedTreeNode`] is injected via
+///   [`Directory::publish_corrupted`] and checked by requiring
+///   [`Directory::lookup`] itself to fail: the label's `ValueState` claims a
+///   version the tree never actually committed.
+/// - [`PublishCorruption::InconsistentRootHash`] is injected via
+///   [`Directory::publish_corrupted`] and checked with [`audit_verify`]: the
+///   root hash [`Directory::publish_corrupted`] returns disagrees with the
+///   one actually checkpointed to storage.
+///
+/// `new_storage` must return a fresh, empty instance of the storage backend
+/// under test each time it is called, since every scenario below needs its
+/// own directory to publish into.
+pub async fn test_publish_corruptions_are_detected<H, C, S, V, F>(
+    new_storage: F,
+    vrf: &V,
+) -> Result<(), VkdError>
+where
+    H: Hasher + Send + Sync,
+    C: Configuration<H>,
+    S: Storage + Sync + Send,
+    V: VRFKeyStorage + CommitmentKeyStorage,
+    F: Fn() -> S,
+{
+    let target = VkdLabel::from_utf8_str("corruption-target");
+    let other = VkdLabel::from_utf8_str("unrelated-label");
+
+    // Simple single-version corruptions: publish version 1 normally, then
+    // corrupt the introduction of version 2, and check that the lookup proof
+    // for `target` no longer verifies.
+    for corruption in [
+        PublishCorruption::UnmarkedStaleVersion(target.clone()),
+        PublishCorruption::MarkVersionStaleWithoutCommit(target.clone()),
+        PublishCorruption::TamperedCommitmentValue(target.clone()),
+        PublishCorruption::ReusedVrfLabel(target.clone()),
+    ] {
+        let storage = new_storage();
+        let directory = Directory::new::<H>(&storage, vrf).await?;
+        directory
+            .publish::<H, C>(vec![(target.clone(), VkdValue::from_utf8_str("version-1"))])
+            .await?;
+        directory
+            .publish_corrupted::<H, C>(
+                vec![(target.clone(), VkdValue::from_utf8_str("version-2"))],
+                corruption.clone(),
+            )
+            .await?;
+
+        let current_ozks = directory.retrieve_current_ozks().await?;
+        let root_hash = directory.get_root_hash::<H>(&current_ozks).await?;
+        let vrf_pk = directory.get_public_key().await?;
+        let lookup_proof = directory.lookup::<H>(target.clone()).await?;
+        let verified = lookup_verify::<H>(&vrf_pk, root_hash, target.clone(), lookup_proof);
+        if verified.is_ok() {
+            return Err(VkdError::Storage(crate::errors::StorageError::Transaction(
+                format!("lookup_verify accepted a proof corrupted by {corruption:?}"),
+            )));
+        }
+    }
+
+    // History-shaped corruptions: publish two honest versions of `target`,
+    // then ask for a tampered history proof and check that it no longer
+    // verifies.
+    for corruption in [
+        PublishCorruption::TooFewVersions(target.clone()),
+        PublishCorruption::TooManyVersions(target.clone()),
+    ] {
+        let storage = new_storage();
+        let directory = Directory::new::<H>(&storage, vrf).await?;
+        directory
+            .publish::<H, C>(vec![(target.clone(), VkdValue::from_utf8_str("version-1"))])
+            .await?;
+        directory
+            .publish::<H, C>(vec![(target.clone(), VkdValue::from_utf8_str("version-2"))])
+            .await?;
+
+        let current_ozks = directory.retrieve_current_ozks().await?;
+        let root_hash = directory.get_root_hash::<H>(&current_ozks).await?;
+        let current_epoch = current_ozks.get_latest_epoch();
+        let vrf_pk = directory.get_public_key().await?;
+        let history_proof = directory
+            .key_history_corrupted::<H>(&target, HistoryParams::Complete, corruption.clone())
+            .await?;
+        let verified = key_history_verify::<H>(
+            &vrf_pk,
+            root_hash,
+            current_epoch,
+            target.clone(),
+            history_proof,
+            HistoryParams::Complete,
+            HistoryVerificationParams::Strict,
+            false,
+        );
+        if verified.is_ok() {
+            return Err(VkdError::Storage(crate::errors::StorageError::Transaction(
+                format!("key_history_verify accepted a proof corrupted by {corruption:?}"),
+            )));
+        }
+    }
+
+    // A leaf dropped from the tree delta: `other`'s first version is
+    // recorded in its plaintext `ValueState`, but its commitment is never
+    // inserted into the tree, so generating a lookup proof for it must fail
+    // outright rather than silently succeed.
+    {
+        let storage = new_storage();
+        let directory = Directory::new::<H>(&storage, vrf).await?;
+        directory
+            .publish::<H, C>(vec![(target.clone(), VkdValue::from_utf8_str("version-1"))])
+            .await?;
+        directory
+            .publish_corrupted::<H, C>(
+                vec![(other.clone(), VkdValue::from_utf8_str("version-1"))],
+                PublishCorruption::DroppedTreeNode(other.clone()),
+            )
+            .await?;
+
+        if directory.lookup::<H>(other.clone()).await.is_ok() {
+            return Err(VkdError::Storage(crate::errors::StorageError::Transaction(
+                "lookup succeeded for a label whose tree node was dropped from the publish delta"
+                    .to_string(),
+            )));
+        }
+    }
+
+    // An inconsistent root hash: the epoch's transition is checkpointed with
+    // the tree's real root hash, but `publish_corrupted` claims a different
+    // one, so an auditor who trusts the claimed hash must reject the audit
+    // proof for that epoch.
+    {
+        let storage = new_storage();
+        let directory = Directory::new::<H>(&storage, vrf).await?;
+        let EpochHash(_, root_hash_1) = directory
+            .publish::<H, C>(vec![(target.clone(), VkdValue::from_utf8_str("version-1"))])
+            .await?;
+        let EpochHash(_, claimed_root_hash_2) = directory
+            .publish_corrupted::<H, C>(
+                vec![(target.clone(), VkdValue::from_utf8_str("version-2"))],
+                PublishCorruption::InconsistentRootHash,
+            )
+            .await?;
+
+        let audit_proof = directory.audit::<H>(1, 2).await?;
+        let verified = audit_verify::<H>(vec![root_hash_1, claimed_root_hash_2], audit_proof).await;
+        if verified.is_ok() {
+            return Err(VkdError::Storage(crate::errors::StorageError::Transaction(
+                "audit_verify accepted a claimed root hash inconsistent with storage".to_string(),
+            )));
+        }
+    }
+
+    Ok(())
+}
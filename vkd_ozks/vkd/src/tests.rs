@@ -9,16 +9,20 @@
 //! Contains the tests for the high-level API (directory, auditor, client)
 
 use crate::{
-    auditor::audit_verify,
-    client::{key_history_verify, lookup_verify},
-    directory::{get_key_history_hashes, Directory},
+    auditor::{audit_verify, audit_verify_stream, verify_consecutive_append_only},
+    checkpoint::{EpochTransition, EpochTransitionKey},
+    client::{key_history_verify, lookup_verify, HistoryParams, HistoryVerificationParams},
+    commitment_key::CommitmentKeyStorage,
+    configuration::DefaultConfiguration,
+    directory::{get_key_history_hashes, Directory, ReadOnlyDirectory},
     ecvrf::{HardCodedVkdVRF, VRFKeyStorage},
     errors::VkdError,
     storage::{
         memory::AsyncInMemoryDatabase,
-        types::{VkdLabel, VkdValue, DbRecord},
+        types::{DbRecord, VkdLabel, VkdValue},
         Storage,
     },
+    EpochHash,
 };
 use winter_crypto::{
     hashers::{Blake3_256, Sha3_256},
@@ -32,7 +36,7 @@ type Blake3 = Blake3_256<BaseElement>;
 async fn test_empty_tree_root_hash() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3_256<BaseElement>>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3_256<BaseElement>>(&db, &vrf).await?;
 
     let current_ozks = vkd.retrieve_current_ozks().await?;
     let hash = vkd
@@ -51,10 +55,10 @@ async fn test_empty_tree_root_hash() -> Result<(), VkdError> {
 async fn test_simple_publish() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
     // Make sure you can publish and that something so simple
     // won't throw errors.
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world"),
     )])
@@ -62,6 +66,57 @@ async fn test_simple_publish() -> Result<(), VkdError> {
     Ok(())
 }
 
+// Publishing the same batch of updates via `publish` (always sequential
+// insertion) and `publish_with_insert_mode` with an explicit `InsertMode::Parallel`
+// should produce identical root hashes: partitioning the batch across tasks
+// must not change which leaves end up in the tree, only how they get there.
+#[tokio::test]
+async fn test_parallel_publish_matches_sequential_root_hash() -> Result<(), VkdError> {
+    use crate::directory::InsertMode;
+
+    let updates = |prefix: &str| {
+        (0..16)
+            .map(|i| {
+                (
+                    VkdLabel::from_utf8_str(&format!("{}{}", prefix, i)),
+                    VkdValue::from_utf8_str(&format!("value{}", i)),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let sequential_db = AsyncInMemoryDatabase::new();
+    let sequential_vrf = HardCodedVkdVRF {};
+    let sequential_vkd =
+        Directory::<_, _>::new::<Blake3>(&sequential_db, &sequential_vrf).await?;
+    sequential_vkd
+        .publish::<Blake3, DefaultConfiguration>(updates("seq"))
+        .await?;
+    let sequential_ozks = sequential_vkd.retrieve_current_ozks().await?;
+    let sequential_root_hash = sequential_vkd
+        .get_root_hash::<Blake3>(&sequential_ozks)
+        .await?;
+
+    let parallel_db = AsyncInMemoryDatabase::new();
+    let parallel_vrf = HardCodedVkdVRF {};
+    let parallel_vkd = Directory::<_, _>::new::<Blake3>(&parallel_db, &parallel_vrf).await?;
+    parallel_vkd
+        .publish_with_insert_mode::<Blake3, DefaultConfiguration>(
+            updates("seq"),
+            InsertMode::Parallel {
+                max_parallelism: 4,
+                threshold: 0,
+            },
+        )
+        .await?;
+    let parallel_ozks = parallel_vkd.retrieve_current_ozks().await?;
+    let parallel_root_hash = parallel_vkd.get_root_hash::<Blake3>(&parallel_ozks).await?;
+
+    assert_eq!(sequential_root_hash, parallel_root_hash);
+
+    Ok(())
+}
+
 // A simple lookup test, for a tree with two elements:
 // ensure that calculation of a lookup proof doesn't throw an error and
 // that the output of vkd.lookup verifies on the client.
@@ -69,9 +124,9 @@ async fn test_simple_publish() -> Result<(), VkdError> {
 async fn test_simple_lookup() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
     // Add two labels and corresponding values to the vkd
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world"),
@@ -109,24 +164,26 @@ async fn test_small_key_history() -> Result<(), VkdError> {
     // Then the test verifies the key history.
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
     // Publish the first value for the label "hello"
     // Epoch here will be 1
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world"),
     )])
     .await?;
     // Publish the second value for the label "hello"
     // Epoch here will be 2
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world2"),
     )])
     .await?;
 
     // Get the key_history_proof for the label "hello"
-    let key_history_proof = vkd.key_history(&VkdLabel::from_utf8_str("hello")).await?;
+    let key_history_proof = vkd
+        .key_history(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
+        .await?;
     // Get the latest root hash
     let current_ozks = vkd.retrieve_current_ozks().await?;
     let current_epoch = current_ozks.get_latest_epoch();
@@ -140,6 +197,8 @@ async fn test_small_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         key_history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
@@ -153,9 +212,9 @@ async fn test_small_key_history() -> Result<(), VkdError> {
 async fn test_simple_key_history() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
     // Epoch 1: Add labels "hello" and "hello2"
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world"),
@@ -167,7 +226,7 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
     ])
     .await?;
     // Epoch 2: Update the values for both the labels to version 2
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world_2"),
@@ -179,7 +238,7 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
     ])
     .await?;
     // Epoch 3: Update the values for both the labels again to version 3
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world3"),
@@ -191,7 +250,7 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
     ])
     .await?;
     // Epoch 4: Add two new labels
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello3"),
             VkdValue::from_utf8_str("world"),
@@ -203,14 +262,14 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
     ])
     .await?;
     // Epoch 5: Updated "hello" to version 4
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world_updated"),
     )])
     .await?;
     // Epoch 6: Update the values for "hello3" and "hello4"
     // both two version 2.
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello3"),
             VkdValue::from_utf8_str("world6"),
@@ -222,7 +281,9 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
     ])
     .await?;
     // Get the key history proof for the label "hello". This should have 4 versions.
-    let key_history_proof = vkd.key_history(&VkdLabel::from_utf8_str("hello")).await?;
+    let key_history_proof = vkd
+        .key_history(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
+        .await?;
     // Check that the correct number of proofs are sent
     if key_history_proof.update_proofs.len() != 4 {
         return Err(VkdError::TestErr(format!(
@@ -242,11 +303,15 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         key_history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
     // Key history proof for "hello2"
-    let key_history_proof = vkd.key_history(&VkdLabel::from_utf8_str("hello2")).await?;
+    let key_history_proof = vkd
+        .key_history(&VkdLabel::from_utf8_str("hello2"), HistoryParams::Complete)
+        .await?;
     // Check that the correct number of proofs are sent
     if key_history_proof.update_proofs.len() != 3 {
         return Err(VkdError::TestErr(format!(
@@ -260,11 +325,15 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello2"),
         key_history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
     // Key history proof for "hello3"
-    let key_history_proof = vkd.key_history(&VkdLabel::from_utf8_str("hello3")).await?;
+    let key_history_proof = vkd
+        .key_history(&VkdLabel::from_utf8_str("hello3"), HistoryParams::Complete)
+        .await?;
     // Check that the correct number of proofs are sent
     if key_history_proof.update_proofs.len() != 2 {
         return Err(VkdError::TestErr(format!(
@@ -278,11 +347,15 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello3"),
         key_history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
     // Key history proof for "hello4"
-    let key_history_proof = vkd.key_history(&VkdLabel::from_utf8_str("hello4")).await?;
+    let key_history_proof = vkd
+        .key_history(&VkdLabel::from_utf8_str("hello4"), HistoryParams::Complete)
+        .await?;
     // Check that the correct number of proofs are sent
     if key_history_proof.update_proofs.len() != 2 {
         return Err(VkdError::TestErr(format!(
@@ -296,6 +369,8 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello4"),
         key_history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
@@ -308,102 +383,82 @@ async fn test_simple_key_history() -> Result<(), VkdError> {
 async fn test_simple_audit() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
-
-    vkd.publish::<Blake3>(vec![
-        (
-            VkdLabel::from_utf8_str("hello"),
-            VkdValue::from_utf8_str("world"),
-        ),
-        (
-            VkdLabel::from_utf8_str("hello2"),
-            VkdValue::from_utf8_str("world2"),
-        ),
-    ])
-    .await?;
-
-    // Get the root hash after the first server publish
-    let root_hash_1 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // `publish` hands back the epoch and root hash it just committed
+    // directly, so unlike the rest of this test's setup, auditing doesn't
+    // need to separately re-derive them via `retrieve_current_ozks` +
+    // `get_root_hash`.
+    let EpochHash(_, root_hash_1) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![
+            (
+                VkdLabel::from_utf8_str("hello"),
+                VkdValue::from_utf8_str("world"),
+            ),
+            (
+                VkdLabel::from_utf8_str("hello2"),
+                VkdValue::from_utf8_str("world2"),
+            ),
+        ])
         .await?;
 
-    vkd.publish::<Blake3>(vec![
-        (
-            VkdLabel::from_utf8_str("hello"),
-            VkdValue::from_utf8_str("world_2"),
-        ),
-        (
-            VkdLabel::from_utf8_str("hello2"),
-            VkdValue::from_utf8_str("world2_2"),
-        ),
-    ])
-    .await?;
-
-    // Get the root hash after the second server publish
-    let root_hash_2 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let EpochHash(_, root_hash_2) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![
+            (
+                VkdLabel::from_utf8_str("hello"),
+                VkdValue::from_utf8_str("world_2"),
+            ),
+            (
+                VkdLabel::from_utf8_str("hello2"),
+                VkdValue::from_utf8_str("world2_2"),
+            ),
+        ])
         .await?;
 
-    vkd.publish::<Blake3>(vec![
-        (
-            VkdLabel::from_utf8_str("hello"),
-            VkdValue::from_utf8_str("world3"),
-        ),
-        (
-            VkdLabel::from_utf8_str("hello2"),
-            VkdValue::from_utf8_str("world4"),
-        ),
-    ])
-    .await?;
-
-    // Get the root hash after the third server publish
-    let root_hash_3 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let EpochHash(_, root_hash_3) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![
+            (
+                VkdLabel::from_utf8_str("hello"),
+                VkdValue::from_utf8_str("world3"),
+            ),
+            (
+                VkdLabel::from_utf8_str("hello2"),
+                VkdValue::from_utf8_str("world4"),
+            ),
+        ])
         .await?;
 
-    vkd.publish::<Blake3>(vec![
-        (
-            VkdLabel::from_utf8_str("hello3"),
-            VkdValue::from_utf8_str("world"),
-        ),
-        (
-            VkdLabel::from_utf8_str("hello4"),
-            VkdValue::from_utf8_str("world2"),
-        ),
-    ])
-    .await?;
-
-    // Get the root hash after the fourth server publish
-    let root_hash_4 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let EpochHash(_, root_hash_4) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![
+            (
+                VkdLabel::from_utf8_str("hello3"),
+                VkdValue::from_utf8_str("world"),
+            ),
+            (
+                VkdLabel::from_utf8_str("hello4"),
+                VkdValue::from_utf8_str("world2"),
+            ),
+        ])
         .await?;
 
-    vkd.publish::<Blake3>(vec![(
-        VkdLabel::from_utf8_str("hello"),
-        VkdValue::from_utf8_str("world_updated"),
-    )])
-    .await?;
-
-    // Get the root hash after the fifth server publish
-    let root_hash_5 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let EpochHash(_, root_hash_5) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![(
+            VkdLabel::from_utf8_str("hello"),
+            VkdValue::from_utf8_str("world_updated"),
+        )])
         .await?;
 
-    vkd.publish::<Blake3>(vec![
-        (
-            VkdLabel::from_utf8_str("hello3"),
-            VkdValue::from_utf8_str("world6"),
-        ),
-        (
-            VkdLabel::from_utf8_str("hello4"),
-            VkdValue::from_utf8_str("world12"),
-        ),
-    ])
-    .await?;
-
-    // Get the root hash after the 6th server publish
-    let root_hash_6 = vkd
-        .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
+    let EpochHash(_, root_hash_6) = vkd
+        .publish::<Blake3, DefaultConfiguration>(vec![
+            (
+                VkdLabel::from_utf8_str("hello3"),
+                VkdValue::from_utf8_str("world6"),
+            ),
+            (
+                VkdLabel::from_utf8_str("hello4"),
+                VkdValue::from_utf8_str("world12"),
+            ),
+        ])
         .await?;
 
     // This is to ensure that an audit of two consecutive, although relatively old epochs is calculated correctly.
@@ -460,6 +515,22 @@ async fn test_simple_audit() -> Result<(), VkdError> {
     let invalid_audit = vkd.audit::<Blake3>(6, 7).await;
     assert!(matches!(invalid_audit, Err(_)));
 
+    // `audit_epoch` should agree with the corresponding single-epoch slice of
+    // the monolithic audit proof above: same proof, same root hashes on
+    // either side of the transition.
+    let (prev_root_2, next_root_2, single_proof) = vkd.audit_epoch::<Blake3>(2).await?;
+    assert_eq!(root_hash_1, prev_root_2);
+    assert_eq!(root_hash_2, next_root_2);
+    verify_consecutive_append_only::<Blake3>(&single_proof, prev_root_2, next_root_2, 2).await?;
+
+    // `audit_stream` should verify the same range as a single monolithic
+    // `audit` call, without ever materializing the whole range's proof at
+    // once: feeding it straight into `audit_verify_stream` should land on
+    // the same final root hash `audit_proof_4` verified against above.
+    let stream = vkd.audit_stream::<Blake3>(1, 5).await?;
+    let streamed_root_hash = audit_verify_stream::<Blake3, _>(stream, root_hash_1).await?;
+    assert_eq!(root_hash_5, streamed_root_hash);
+
     Ok(())
 }
 
@@ -470,10 +541,10 @@ async fn test_simple_audit() -> Result<(), VkdError> {
 async fn test_read_during_publish() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     // Publish once
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world"),
@@ -489,7 +560,7 @@ async fn test_read_during_publish() -> Result<(), VkdError> {
         .get_root_hash::<Blake3>(&vkd.retrieve_current_ozks().await?)
         .await?;
     // Publish updates for the same labels.
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world_2"),
@@ -510,7 +581,7 @@ async fn test_read_during_publish() -> Result<(), VkdError> {
     let checkpoint_ozks = vkd.retrieve_current_ozks().await.unwrap();
 
     // Publish for the third time
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world_3"),
@@ -530,7 +601,7 @@ async fn test_read_during_publish() -> Result<(), VkdError> {
 
     // History proof should not contain the third epoch's update but still verify
     let history_proof = vkd
-        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"))
+        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
         .await?;
     let (root_hashes, _) = get_key_history_hashes(&vkd, &history_proof).await?;
     assert_eq!(2, root_hashes.len());
@@ -545,6 +616,8 @@ async fn test_read_during_publish() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
@@ -581,17 +654,22 @@ async fn test_read_during_publish() -> Result<(), VkdError> {
 async fn test_directory_read_only_mode() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
-    // There is no AZKS object in the storage layer, directory construction should fail
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, true).await;
+    // There is no AZKS object in the storage layer, so a read-only directory
+    // construction should fail -- unlike `Directory::new`, it never
+    // initializes one itself.
+    let vkd = ReadOnlyDirectory::<_, _>::new::<Blake3>(&db, &vrf).await;
     assert!(matches!(vkd, Err(_)));
 
-    // now create the AZKS
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await;
+    // now create the AZKS via a writable directory
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await;
     assert!(matches!(vkd, Ok(_)));
 
-    // create another read-only dir now that the AZKS exists in the storage layer, and try to publish which should fail
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, true).await?;
-    assert!(matches!(vkd.publish::<Blake3>(vec![]).await, Err(_)));
+    // a read-only directory can now be constructed against the same storage.
+    // `ReadOnlyDirectory` has no `publish` method at all, so a caller can't
+    // accidentally mutate state through it -- there's nothing left to assert
+    // at runtime here, the guarantee is enforced by the type system.
+    let vkd = ReadOnlyDirectory::<_, _>::new::<Blake3>(&db, &vrf).await;
+    assert!(matches!(vkd, Ok(_)));
 
     Ok(())
 }
@@ -604,10 +682,10 @@ async fn test_directory_polling_ozks_change() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
     // writer will write the AZKS record
-    let writer = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let writer = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     writer
-        .publish::<Blake3>(vec![
+        .publish::<Blake3, DefaultConfiguration>(vec![
             (
                 VkdLabel::from_utf8_str("hello"),
                 VkdValue::from_utf8_str("world"),
@@ -619,8 +697,10 @@ async fn test_directory_polling_ozks_change() -> Result<(), VkdError> {
         ])
         .await?;
 
-    // reader will not write the AZKS but will be "polling" for AZKS changes
-    let reader = Directory::<_, _>::new::<Blake3>(&db, &vrf, true).await?;
+    // reader will not write the AZKS but will be "polling" for AZKS changes.
+    // It's a `ReadOnlyDirectory`, so it's statically guaranteed not to write
+    // the AZKS record itself.
+    let reader = ReadOnlyDirectory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     // start the poller
     let (tx, mut rx) = tokio::sync::mpsc::channel(10);
@@ -636,7 +716,7 @@ async fn test_directory_polling_ozks_change() -> Result<(), VkdError> {
 
     // publish epoch 2
     writer
-        .publish::<Blake3>(vec![
+        .publish::<Blake3, DefaultConfiguration>(vec![
             (
                 VkdLabel::from_utf8_str("hello"),
                 VkdValue::from_utf8_str("world_2"),
@@ -657,19 +737,98 @@ async fn test_directory_polling_ozks_change() -> Result<(), VkdError> {
     Ok(())
 }
 
-// This test is testing the limited_key_history function,
-// which takes a parameter n and gets the history for the
-// n most recent updates.
+// `epoch_delta` should hand back exactly the slice of history a replica is
+// missing, and applying it to a separate storage backend should let that
+// backend answer the same `get_epoch_transitions` query the publisher would
+// -- and, since the requested range reaches the publisher's current epoch,
+// actually serve a verifiable lookup proof at the new tip without any
+// further sync step.
+#[tokio::test]
+async fn test_epoch_delta_sync() -> Result<(), VkdError> {
+    let publisher_db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let publisher = Directory::<_, _>::new::<Blake3>(&publisher_db, &vrf).await?;
+
+    // epoch 1
+    publisher
+        .publish::<Blake3, DefaultConfiguration>(vec![(
+            VkdLabel::from_utf8_str("hello"),
+            VkdValue::from_utf8_str("world"),
+        )])
+        .await?;
+
+    // epoch 2
+    publisher
+        .publish::<Blake3, DefaultConfiguration>(vec![(
+            VkdLabel::from_utf8_str("hello"),
+            VkdValue::from_utf8_str("world_2"),
+        )])
+        .await?;
+
+    // epoch 3
+    publisher
+        .publish::<Blake3, DefaultConfiguration>(vec![(
+            VkdLabel::from_utf8_str("hello"),
+            VkdValue::from_utf8_str("world_3"),
+        )])
+        .await?;
+
+    // A replica that has already caught up to epoch 1 only needs the delta
+    // for epochs 2 and 3: one `EpochTransition` and one `ValueState` each,
+    // plus (since epoch 3 is the publisher's current epoch) the current
+    // `Ozks` snapshot, bundled so the replica can serve proofs immediately.
+    let delta = publisher.epoch_delta(1, 3).await?;
+    assert_eq!(5, delta.len());
+
+    let replica_db = AsyncInMemoryDatabase::new();
+    Directory::<_, HardCodedVkdVRF>::apply_epoch_delta(&replica_db, delta).await?;
+
+    let replica_transitions = replica_db
+        .batch_get::<EpochTransition>(&[EpochTransitionKey(2), EpochTransitionKey(3)])
+        .await?;
+    assert_eq!(2, replica_transitions.len());
+
+    // The replica can now answer a verifiable lookup proof at the new tip
+    // itself, with no separate full-snapshot fetch: `Directory::new` picks
+    // up the `Ozks` snapshot the delta just wrote rather than minting a
+    // fresh, empty one (see `Directory::new_with_commitment_sink`).
+    let replica = Directory::<_, _>::new::<Blake3>(&replica_db, &vrf).await?;
+    let lookup_proof = replica.lookup::<Blake3>(VkdLabel::from_utf8_str("hello")).await?;
+    assert_eq!(VkdValue::from_utf8_str("world_3"), lookup_proof.plaintext_value);
+    let replica_ozks = replica.retrieve_current_ozks().await?;
+    let root_hash = replica.get_root_hash::<Blake3>(&replica_ozks).await?;
+    let pk = replica.get_public_key().await?;
+    lookup_verify::<Blake3>(
+        &pk,
+        root_hash,
+        VkdLabel::from_utf8_str("hello"),
+        lookup_proof,
+    )?;
+
+    // Re-applying the same delta is a no-op rather than an error, so a
+    // replica can safely retry after an interrupted sync.
+    let delta_again = publisher.epoch_delta(1, 3).await?;
+    Directory::<_, HardCodedVkdVRF>::apply_epoch_delta(&replica_db, delta_again).await?;
+
+    // Asking for a range the replica is already current on returns nothing.
+    let empty_delta = publisher.epoch_delta(3, 3).await?;
+    assert!(empty_delta.is_empty());
+
+    Ok(())
+}
+
+// This test is testing key_history with HistoryParams::MostRecent(n),
+// which gets the history for the n most recent updates.
 // We also want this update to verify.
 #[tokio::test]
 async fn test_limited_key_history() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
     // epoch 0
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     // epoch 1
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world"),
@@ -682,7 +841,7 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     .await?;
 
     // epoch 2
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world_2"),
@@ -695,7 +854,7 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     .await?;
 
     // epoch 3
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello"),
             VkdValue::from_utf8_str("world3"),
@@ -708,7 +867,7 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     .await?;
 
     // epoch 4
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello3"),
             VkdValue::from_utf8_str("world"),
@@ -721,14 +880,14 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     .await?;
 
     // epoch 5
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world_updated"),
     )])
     .await?;
 
     // epoch 6
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello3"),
             VkdValue::from_utf8_str("world6"),
@@ -741,7 +900,7 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     .await?;
 
     // epoch 7
-    vkd.publish::<Blake3>(vec![
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![
         (
             VkdLabel::from_utf8_str("hello3"),
             VkdValue::from_utf8_str("world7"),
@@ -757,7 +916,10 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
 
     // "hello" was updated in epochs 1,2,3,5. Pull the latest item from the history (i.e. a lookup proof)
     let history_proof = vkd
-        .limited_key_history::<Blake3>(1, &VkdLabel::from_utf8_str("hello"))
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::MostRecent(1),
+        )
         .await?;
     assert_eq!(1, history_proof.update_proofs.len());
     assert_eq!(5, history_proof.update_proofs[0].epoch);
@@ -767,75 +929,474 @@ async fn test_limited_key_history() -> Result<(), VkdError> {
     let current_epoch = current_ozks.get_latest_epoch();
     let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
 
-    // Now check that the key history verifies
+    // Now check that the key history verifies, as the 1 most recent version
     key_history_verify::<Blake3>(
         &vrf_pk,
         root_hash,
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         history_proof,
+        HistoryParams::MostRecent(1),
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
     // Take the top 3 results, and check that we're getting the right epoch updates
     let history_proof = vkd
-        .limited_key_history::<Blake3>(3, &VkdLabel::from_utf8_str("hello"))
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::MostRecent(3),
+        )
         .await?;
     assert_eq!(3, history_proof.update_proofs.len());
     assert_eq!(5, history_proof.update_proofs[0].epoch);
     assert_eq!(3, history_proof.update_proofs[1].epoch);
     assert_eq!(2, history_proof.update_proofs[2].epoch);
 
-    // Now check that the key history verifies
+    // Now check that the key history verifies, as the 3 most recent versions
     key_history_verify::<Blake3>(
         &vrf_pk,
         root_hash,
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         history_proof,
+        HistoryParams::MostRecent(3),
+        HistoryVerificationParams::Strict,
         false,
     )?;
 
     Ok(())
 }
 
+// `HistoryParams::MostRecentInsecure` skips generating the window's
+// lower-bound anchor entirely, so it only verifies under
+// `HistoryVerificationParams::MostRecentOnly` -- `Strict` must reject it for
+// the missing anchor, same as a corrupted `MostRecent` proof would be.
+#[tokio::test]
+async fn test_most_recent_insecure_key_history() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world2"),
+    )])
+    .await?;
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vkd.get_public_key().await?;
+
+    let history_proof = vkd
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::MostRecentInsecure(1),
+        )
+        .await?;
+    assert_eq!(1, history_proof.update_proofs.len());
+
+    key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof.clone(),
+        HistoryParams::MostRecentInsecure(1),
+        HistoryVerificationParams::MostRecentOnly,
+        false,
+    )?;
+
+    let result = key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof,
+        HistoryParams::MostRecentInsecure(1),
+        HistoryVerificationParams::Strict,
+        false,
+    );
+    assert!(matches!(result, Err(_)));
+
+    Ok(())
+}
+
+// This test ensures that a bounded `MostRecent` history request can't be
+// satisfied by a server hiding a newer value behind an older window: the
+// verifier must reject a proof whose highest returned version is not the
+// version that is actually current at `current_epoch`.
+#[tokio::test]
+async fn test_limited_key_history_rejects_stale_window() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // epoch 1: version 1
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+    // Checkpoint at epoch 1, to later emulate a server that hasn't seen epoch 2 yet
+    let checkpoint_ozks = vkd.retrieve_current_ozks().await?;
+
+    // epoch 2: version 2
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world2"),
+    )])
+    .await?;
+
+    // The true, current state: version 2 at epoch 2
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vkd.get_public_key().await?;
+
+    // Roll storage back to the epoch-1 checkpoint, to emulate a stale (or
+    // malicious) server answering a "most recent 1" query with version 1,
+    // hiding the fact that version 2 has since been published.
+    db.set(DbRecord::Ozks(checkpoint_ozks))
+        .await
+        .expect("Error resetting directory to previous epoch");
+    let stale_history_proof = vkd
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::MostRecent(1),
+        )
+        .await?;
+    assert_eq!(1, stale_history_proof.update_proofs.len());
+    assert_eq!(1, stale_history_proof.update_proofs[0].version);
+
+    // Requesting the 1 most recent version, but being handed a window whose
+    // highest version (1) isn't current at `current_epoch` (it's 2), must fail.
+    let result = key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        stale_history_proof,
+        HistoryParams::MostRecent(1),
+        HistoryVerificationParams::Strict,
+        false,
+    );
+    assert!(matches!(result, Err(_)));
+
+    Ok(())
+}
+
+// A truncated `MostRecent(n)` window whose oldest returned version is not
+// version 1 must carry a `window_lower_bound_*` anchor proving the preceding
+// version really existed -- a server that strips that anchor (while leaving
+// everything else about the window valid) must be rejected, since otherwise
+// it could silently narrow the window further than `params` asked for.
+#[tokio::test]
+async fn test_limited_key_history_rejects_missing_window_lower_bound() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // epoch 1: version 1
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+
+    // epoch 2: version 2
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world2"),
+    )])
+    .await?;
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vkd.get_public_key().await?;
+
+    let mut history_proof = vkd
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::MostRecent(1),
+        )
+        .await?;
+    assert_eq!(1, history_proof.update_proofs.len());
+    assert_eq!(2, history_proof.update_proofs[0].version);
+
+    // Strip the anchor that proves version 1 preceded this window.
+    history_proof.window_lower_bound_vrf_proof = None;
+    history_proof.window_lower_bound_existence_proof = None;
+    history_proof.window_lower_bound_epoch = None;
+
+    let result = key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof,
+        HistoryParams::MostRecent(1),
+        HistoryVerificationParams::Strict,
+        false,
+    );
+    assert!(matches!(result, Err(_)));
+
+    Ok(())
+}
+
+// A version-contiguous history whose epochs are nonetheless out of order (or
+// duplicated) must be rejected under HistoryVerificationParams::Strict, since
+// the version check alone can't catch a server that reorders or duplicates
+// epochs across an otherwise-contiguous window.
+#[tokio::test]
+async fn test_key_history_rejects_non_increasing_epochs() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // epoch 1: version 1
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+
+    // epoch 2: version 2
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world2"),
+    )])
+    .await?;
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vkd.get_public_key().await?;
+
+    let mut history_proof = vkd
+        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
+        .await?;
+    assert_eq!(2, history_proof.update_proofs.len());
+
+    // Tamper with the newer entry's epoch so that, despite the versions still
+    // being contiguous (2, then 1), the epoch it claims to have been
+    // published at no longer comes after the older entry's epoch.
+    history_proof.update_proofs[0].epoch = history_proof.update_proofs[1].epoch;
+
+    let result = key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
+        false,
+    );
+    assert!(matches!(result, Err(_)));
+
+    Ok(())
+}
+
+// A `Complete` history proof must vouch for every intermediate version, not
+// just the oldest and newest: each `update_proof` only proves its own
+// version existed *and* that the version directly below it was staled, so
+// splicing out a middle entry breaks that chain even though the remaining
+// entries are individually well-formed.
+#[tokio::test]
+async fn test_key_history_rejects_missing_intermediate_version() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // epoch 1: version 1
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+
+    // epoch 2: version 2
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world2"),
+    )])
+    .await?;
+
+    // epoch 3: version 3
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world3"),
+    )])
+    .await?;
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vkd.get_public_key().await?;
+
+    let mut history_proof = vkd
+        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
+        .await?;
+    assert_eq!(3, history_proof.update_proofs.len());
+
+    // Drop the middle entry (version 2), leaving a proof that still looks
+    // superficially well-formed: version 3 at the front, version 1 at the
+    // back, just missing the version in between.
+    history_proof.update_proofs.remove(1);
+
+    let result = key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
+        false,
+    );
+    assert!(matches!(result, Err(_)));
+
+    Ok(())
+}
+
+// This test is testing key_history with HistoryParams::SinceEpoch(epoch),
+// which gets the history for all updates committed at or after `epoch`.
+#[tokio::test]
+async fn test_key_history_since_epoch() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    // epoch 0
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    // epoch 1
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+
+    // epoch 2
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world_2"),
+    )])
+    .await?;
+
+    // epoch 3
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world3"),
+    )])
+    .await?;
+
+    // epoch 4: unrelated label, doesn't affect "hello"'s history
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello2"),
+        VkdValue::from_utf8_str("world"),
+    )])
+    .await?;
+
+    // epoch 5
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
+        VkdLabel::from_utf8_str("hello"),
+        VkdValue::from_utf8_str("world_updated"),
+    )])
+    .await?;
+
+    let vrf_pk = vkd.get_public_key().await?;
+
+    // "hello" was updated in epochs 1, 2, 3, 5. Ask for only the updates
+    // committed at or after epoch 3.
+    let history_proof = vkd
+        .key_history::<Blake3>(
+            &VkdLabel::from_utf8_str("hello"),
+            HistoryParams::SinceEpoch(3),
+        )
+        .await?;
+    assert_eq!(2, history_proof.update_proofs.len());
+    assert_eq!(5, history_proof.update_proofs[0].epoch);
+    assert_eq!(3, history_proof.update_proofs[1].epoch);
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let current_epoch = current_ozks.get_latest_epoch();
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+
+    key_history_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        current_epoch,
+        VkdLabel::from_utf8_str("hello"),
+        history_proof,
+        HistoryParams::SinceEpoch(3),
+        HistoryVerificationParams::Strict,
+        false,
+    )?;
+
+    Ok(())
+}
+
+// Exercises every `PublishCorruption` variant against this crate's own
+// `AsyncInMemoryDatabase`, using the shared helper the `public-tests` feature
+// exposes for downstream storage implementers. Nothing else in this file
+// runs that helper, so without this test the crate would only ever prove
+// this exact rejection coverage for other backends, never for its own.
+#[tokio::test]
+async fn test_publish_corruptions_are_detected_in_memory() -> Result<(), VkdError> {
+    crate::test_utils::test_publish_corruptions_are_detected::<
+        Blake3,
+        DefaultConfiguration,
+        AsyncInMemoryDatabase,
+        HardCodedVkdVRF,
+        _,
+    >(AsyncInMemoryDatabase::new, &HardCodedVkdVRF {})
+    .await
+}
+
 #[tokio::test]
 async fn test_tombstoned_key_history() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
     // epoch 0
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     // epoch 1
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world"),
     )])
     .await?;
 
     // epoch 2
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world2"),
     )])
     .await?;
 
     // epoch 3
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world3"),
     )])
     .await?;
 
     // epoch 4
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world4"),
     )])
     .await?;
 
     // epoch 5
-    vkd.publish::<Blake3>(vec![(
+    vkd.publish::<Blake3, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world5"),
     )])
@@ -855,7 +1416,7 @@ async fn test_tombstoned_key_history() -> Result<(), VkdError> {
 
     // Now get a history proof for this key
     let history_proof = vkd
-        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"))
+        .key_history::<Blake3>(&VkdLabel::from_utf8_str("hello"), HistoryParams::Complete)
         .await?;
     assert_eq!(5, history_proof.update_proofs.len());
 
@@ -870,6 +1431,8 @@ async fn test_tombstoned_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         history_proof.clone(),
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         false,
     );
     assert!(matches!(tombstones, Err(_)));
@@ -882,13 +1445,15 @@ async fn test_tombstoned_key_history() -> Result<(), VkdError> {
         current_epoch,
         VkdLabel::from_utf8_str("hello"),
         history_proof,
+        HistoryParams::Complete,
+        HistoryVerificationParams::Strict,
         true,
     )?;
-    assert_eq!(false, tombstones[0]);
-    assert_eq!(false, tombstones[1]);
-    assert_eq!(false, tombstones[2]);
-    assert_eq!(true, tombstones[3]);
-    assert_eq!(true, tombstones[4]);
+    assert!(tombstones[0].value.is_some());
+    assert!(tombstones[1].value.is_some());
+    assert!(tombstones[2].value.is_some());
+    assert!(tombstones[3].value.is_none());
+    assert!(tombstones[4].value.is_none());
 
     Ok(())
 }
@@ -905,7 +1470,7 @@ async fn test_simple_lookup_for_small_tree_blake() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
     // epoch 0
-    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
 
     // Create a set with 2 updates, (label, value) pairs
     // ("hello10", "hello10")
@@ -918,7 +1483,7 @@ async fn test_simple_lookup_for_small_tree_blake() -> Result<(), VkdError> {
         ));
     }
     // Publish the updates. Now the vkd's epoch will be 1.
-    vkd.publish::<Blake3>(updates).await?;
+    vkd.publish::<Blake3, DefaultConfiguration>(updates).await?;
 
     // The label we will lookup is "hello10"
     let target_label = VkdLabel(format!("hello1{}", 0).as_bytes().to_vec());
@@ -947,13 +1512,51 @@ async fn test_simple_lookup_for_small_tree_blake() -> Result<(), VkdError> {
     Ok(())
 }
 
+// Test lookup in a tree with genuinely 2 leaves (the loop above only ever
+// published 1, despite its comment), using the Blake3 hash function.
+#[tokio::test]
+async fn test_simple_lookup_for_two_leaf_tree_blake() -> Result<(), VkdError> {
+    let db = AsyncInMemoryDatabase::new();
+    let vrf = HardCodedVkdVRF {};
+    let vkd = Directory::<_, _>::new::<Blake3>(&db, &vrf).await?;
+
+    let updates = vec![
+        (
+            VkdLabel::from_utf8_str("hello10"),
+            VkdValue::from_utf8_str("hello10"),
+        ),
+        (
+            VkdLabel::from_utf8_str("hello11"),
+            VkdValue::from_utf8_str("hello11"),
+        ),
+    ];
+    vkd.publish::<Blake3, DefaultConfiguration>(updates).await?;
+
+    let target_label = VkdLabel::from_utf8_str("hello10");
+    let lookup_proof = vkd.lookup(target_label.clone()).await?;
+
+    let current_ozks = vkd.retrieve_current_ozks().await?;
+    let root_hash = vkd.get_root_hash::<Blake3>(&current_ozks).await?;
+    let vrf_pk = vrf.get_vrf_public_key().await?;
+
+    let vkd_result = crate::client::lookup_verify::<Blake3>(
+        &vrf_pk,
+        root_hash,
+        target_label.clone(),
+        lookup_proof,
+    );
+    assert!(matches!(vkd_result, Ok(())), "{:?}", vkd_result);
+
+    Ok(())
+}
+
 // Test lookup in a smaller tree with 2 leaves, using the Sha3_256 hash function.
 #[tokio::test]
 async fn test_simple_lookup_for_small_tree_sha256() -> Result<(), VkdError> {
     let db = AsyncInMemoryDatabase::new();
     let vrf = HardCodedVkdVRF {};
     // epoch 0
-    let vkd = Directory::<_, _>::new::<Sha3_256<BaseElement>>(&db, &vrf, false).await?;
+    let vkd = Directory::<_, _>::new::<Sha3_256<BaseElement>>(&db, &vrf).await?;
 
     // Create a set with 2 updates, (label, value) pairs
     // ("hello10", "hello10")
@@ -967,7 +1570,8 @@ async fn test_simple_lookup_for_small_tree_sha256() -> Result<(), VkdError> {
     }
 
     // Publish the updates. Now the vkd's epoch will be 1.
-    vkd.publish::<Sha3_256<BaseElement>>(updates).await?;
+    vkd.publish::<Sha3_256<BaseElement>, DefaultConfiguration>(updates)
+        .await?;
 
     // The label we will lookup is "hello10"
     let target_label = VkdLabel(format!("hello{}", 0).as_bytes().to_vec());
@@ -1001,8 +1605,8 @@ async fn test_simple_lookup_for_small_tree_sha256() -> Result<(), VkdError> {
 =========== Test Helpers ===========
 */
 
-async fn async_poll_helper_proof<T: Storage + Sync + Send, V: VRFKeyStorage>(
-    reader: &Directory<T, V>,
+async fn async_poll_helper_proof<T: Storage + Sync + Send, V: VRFKeyStorage + CommitmentKeyStorage>(
+    reader: &ReadOnlyDirectory<T, V>,
     value: VkdValue,
 ) -> Result<(), VkdError> {
     // reader should read "hello" and this will populate the "cache" a log
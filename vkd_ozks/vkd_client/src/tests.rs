@@ -8,17 +8,18 @@
 //! This crate contains the tests for the client library which make sure that the
 //! base VKD library and this "lean" client result in the same outputs
 
+use vkd::configuration::DefaultConfiguration;
 use vkd::ecvrf::HardCodedVkdVRF;
 
-use vkd::serialization::from_digest;
 #[cfg(feature = "nostd")]
 use alloc::format;
 #[cfg(feature = "nostd")]
 use alloc::vec;
 #[cfg(feature = "nostd")]
 use alloc::vec::Vec;
+use vkd::serialization::from_digest;
 
-use vkd::errors::{VkdError, StorageError};
+use vkd::errors::{StorageError, VkdError};
 use vkd::storage::Storage;
 use vkd::{VkdLabel, VkdValue};
 use winter_crypto::Hasher;
@@ -193,7 +194,7 @@ where
 async fn test_simple_lookup() -> Result<(), VkdError> {
     let db = InMemoryDb::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::new::<Hash>(&db, &vrf, false).await?;
+    let vkd = Directory::new::<Hash>(&db, &vrf).await?;
 
     let mut updates = vec![];
     for i in 0..15 {
@@ -203,7 +204,7 @@ async fn test_simple_lookup() -> Result<(), VkdError> {
         ));
     }
 
-    vkd.publish::<Hash>(updates).await?;
+    vkd.publish::<Hash, DefaultConfiguration>(updates).await?;
 
     let target_label = VkdLabel(format!("hello{}", 10).as_bytes().to_vec());
 
@@ -248,7 +249,7 @@ async fn test_simple_lookup() -> Result<(), VkdError> {
 async fn test_simple_lookup_for_small_tree() -> Result<(), VkdError> {
     let db = InMemoryDb::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::new::<Hash>(&db, &vrf, false).await?;
+    let vkd = Directory::new::<Hash>(&db, &vrf).await?;
 
     let mut updates = vec![];
     for i in 0..1 {
@@ -258,7 +259,7 @@ async fn test_simple_lookup_for_small_tree() -> Result<(), VkdError> {
         ));
     }
 
-    vkd.publish::<Hash>(updates).await?;
+    vkd.publish::<Hash, DefaultConfiguration>(updates).await?;
 
     let target_label = VkdLabel(format!("hello{}", 0).as_bytes().to_vec());
 
@@ -305,7 +306,7 @@ async fn test_simple_lookup_for_small_tree() -> Result<(), VkdError> {
 async fn test_history_proof_multiple_epochs() -> Result<(), VkdError> {
     let db = InMemoryDb::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::new::<Hash>(&db, &vrf, false).await?;
+    let vkd = Directory::new::<Hash>(&db, &vrf).await?;
     let vrf_pk = vkd.get_public_key().await.unwrap();
     let key = VkdLabel::from_utf8_str("label");
     let key_bytes = key.to_vec();
@@ -317,11 +318,13 @@ async fn test_history_proof_multiple_epochs() -> Result<(), VkdError> {
             key.clone(),
             VkdValue(format!("value{}", epoch).as_bytes().to_vec()),
         )];
-        vkd.publish::<Hash>(data).await?;
+        vkd.publish::<Hash, DefaultConfiguration>(data).await?;
     }
 
     // retrieves and verifies history proofs for the key
-    let proof = vkd.key_history::<Hash>(&key).await?;
+    let proof = vkd
+        .key_history::<Hash>(&key, vkd::client::HistoryParams::Complete)
+        .await?;
     let internal_proof = convert_history_proof::<Hash>(&proof);
     let (mut root_hash, current_epoch) =
         vkd::directory::get_directory_root_hash_and_ep::<_, Hash, HardCodedVkdVRF>(&vkd).await?;
@@ -379,17 +382,19 @@ async fn test_history_proof_multiple_epochs() -> Result<(), VkdError> {
 async fn test_history_proof_single_epoch() -> Result<(), VkdError> {
     let db = InMemoryDb::new();
     let vrf = HardCodedVkdVRF {};
-    let vkd = Directory::new::<Hash>(&db, &vrf, false).await?;
+    let vkd = Directory::new::<Hash>(&db, &vrf).await?;
     let vrf_pk = vkd.get_public_key().await.unwrap();
     let key = VkdLabel::from_utf8_str("label");
     let key_bytes = key.to_vec();
 
     // publishes single key-value
-    vkd.publish::<Hash>(vec![(key.clone(), VkdValue::from_utf8_str("value"))])
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(key.clone(), VkdValue::from_utf8_str("value"))])
         .await?;
 
     // retrieves and verifies history proofs for the key
-    let proof = vkd.key_history::<Hash>(&key).await?;
+    let proof = vkd
+        .key_history::<Hash>(&key, vkd::client::HistoryParams::Complete)
+        .await?;
     let internal_proof = convert_history_proof::<Hash>(&proof);
     let (root_hash, current_epoch) =
         vkd::directory::get_directory_root_hash_and_ep::<_, Hash, HardCodedVkdVRF>(&vkd).await?;
@@ -421,38 +426,38 @@ async fn test_tombstoned_key_history() -> Result<(), VkdError> {
     let db = InMemoryDb::new();
     let vrf = HardCodedVkdVRF {};
     // epoch 0
-    let vkd = Directory::new::<Hash>(&db, &vrf, false).await?;
+    let vkd = Directory::new::<Hash>(&db, &vrf).await?;
 
     // epoch 1
-    vkd.publish::<Hash>(vec![(
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world"),
     )])
     .await?;
 
     // epoch 2
-    vkd.publish::<Hash>(vec![(
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world2"),
     )])
     .await?;
 
     // epoch 3
-    vkd.publish::<Hash>(vec![(
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world3"),
     )])
     .await?;
 
     // epoch 4
-    vkd.publish::<Hash>(vec![(
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world4"),
     )])
     .await?;
 
     // epoch 5
-    vkd.publish::<Hash>(vec![(
+    vkd.publish::<Hash, DefaultConfiguration>(vec![(
         VkdLabel::from_utf8_str("hello"),
         VkdValue::from_utf8_str("world5"),
     )])
@@ -469,7 +474,10 @@ async fn test_tombstoned_key_history() -> Result<(), VkdError> {
     db.tombstone_value_states(&tombstones).await?;
 
     let history_proof = vkd
-        .key_history::<Hash>(&VkdLabel::from_utf8_str("hello"))
+        .key_history::<Hash>(
+            &VkdLabel::from_utf8_str("hello"),
+            vkd::client::HistoryParams::Complete,
+        )
         .await?;
     assert_eq!(5, history_proof.update_proofs.len());
     let (root_hash, current_epoch) =